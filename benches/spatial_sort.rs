@@ -0,0 +1,63 @@
+//! Compares writing a LAZ file as-is versus sorted along a Morton or
+//! Hilbert curve via [`las::WriterOptions::with_space_filling_curve`].
+//!
+//! The compressed size of each variant is printed once before timing,
+//! since better compression is the main reason to sort.
+//!
+//! The default input is `tests/data/autzen.las`, which is small. Point to
+//! a larger file with `LAS_BENCH_FILE`:
+//!
+//! ```sh
+//! LAS_BENCH_FILE=/path/to/large.las cargo bench --bench spatial_sort --features laz
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::{Builder, PointData, Reader, SpaceFillingCurve, Writer, WriterOptions};
+use std::{env, hint::black_box, io::Cursor};
+
+fn bench_path() -> String {
+    env::var("LAS_BENCH_FILE").unwrap_or_else(|_| "tests/data/autzen.las".to_string())
+}
+
+fn write(points: &PointData, header: &las::Header, curve: Option<SpaceFillingCurve>) -> usize {
+    let mut builder = Builder::from(header.clone());
+    builder.point_format.is_compressed = true;
+    let mut options = WriterOptions::default();
+    if let Some(curve) = curve {
+        options = options.with_space_filling_curve(curve);
+    }
+    let mut writer = Writer::with_options(
+        Cursor::new(Vec::new()),
+        builder.into_header().unwrap(),
+        options,
+    )
+    .unwrap();
+    writer.write_points(points).unwrap();
+    writer.into_inner().unwrap().into_inner().len()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut reader = Reader::from_path(bench_path()).unwrap();
+    let header = reader.header().clone();
+    let points = reader.read_all().unwrap();
+    let variants = [
+        ("unsorted", None),
+        ("morton", Some(SpaceFillingCurve::Morton)),
+        ("hilbert", Some(SpaceFillingCurve::Hilbert)),
+    ];
+    for (name, curve) in variants {
+        println!("{name}: {} bytes", write(&points, &header, curve));
+    }
+
+    let mut group = c.benchmark_group("spatial_sort");
+    group.sample_size(10);
+    for (name, curve) in variants {
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(write(&points, &header, curve)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,1042 @@
+//! Convert point data to and from [Apache Arrow](https://arrow.apache.org/) record batches.
+//!
+//! Each dimension becomes a typed column:
+//!
+//! | Column | Arrow type |
+//! | --- | --- |
+//! | `x`, `y`, `z` | `Float64`, or `Int32` with [ArrowOptions::with_raw_coordinates] |
+//! | `intensity`, `point_source_id`, `nir` | `UInt16` |
+//! | `return_number`, `number_of_returns`, `classification`, `scanner_channel`, `user_data` | `UInt8` |
+//! | `scan_direction_flag`, `edge_of_flight_line`, `synthetic`, `key_point`, `withheld`, `overlap` | `Boolean` |
+//! | `scan_angle` (degrees) | `Float32` |
+//! | `gps_time` | `Float64` |
+//! | `color` | `Struct` of `red`, `green` and `blue` |
+//! | `waveform` | `Struct` of the waveform packet fields |
+//!
+//! `scanner_channel` is only present for extended formats, and `gps_time`,
+//! `color`, `nir` and `waveform` only if the format has them. Extra bytes
+//! described by [extra bytes descriptors](crate::extra_bytes) become one
+//! column each, named after the descriptor, and any undescribed extra bytes
+//! become a `FixedSizeBinary` column named `extra_bytes`.
+//!
+//! The `x`, `y` and `z` fields carry the transforms and the CRS in their
+//! metadata, and the schema carries the point format, so a record batch
+//! can be converted back into the same point data.
+//!
+//! ```
+//! use las::{arrow::ArrowOptions, PointData, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let options = ArrowOptions::for_header(reader.header()).unwrap();
+//! let points = reader.read_all().unwrap();
+//! let batch = points.to_record_batch(&options).unwrap();
+//! assert_eq!(points.len(), batch.num_rows());
+//!
+//! let roundtrip = PointData::from_record_batch(&batch).unwrap();
+//! assert_eq!(points.raw_bytes(), roundtrip.raw_bytes());
+//! ```
+
+use crate::{
+    extra_bytes::{DataType as ExtraBytesType, Descriptor},
+    point::{Dimension, Format, ScanDirection},
+    raw,
+    raw::point::{Flags, Waveform},
+    Color, Error, Header, PointData, PointDataBuilder, Result, Transform, Vector,
+};
+use arrow_array::{
+    builder::FixedSizeBinaryBuilder,
+    cast::AsArray,
+    types::{
+        Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type,
+        UInt32Type, UInt64Type, UInt8Type,
+    },
+    Array, ArrayRef, ArrowPrimitiveType, BooleanArray, Float32Array, Float64Array, Int16Array,
+    Int32Array, Int64Array, Int8Array, PrimitiveArray, RecordBatch, StructArray, UInt16Array,
+    UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use num_traits::ToPrimitive;
+use std::{collections::HashMap, sync::Arc};
+
+/// Schema metadata key for the point format number.
+pub const POINT_FORMAT_KEY: &str = "las:point_format";
+
+/// Field metadata key for the scale of a coordinate or an extra dimension.
+pub const SCALE_KEY: &str = "las:scale";
+
+/// Field metadata key for the offset of a coordinate or an extra dimension.
+pub const OFFSET_KEY: &str = "las:offset";
+
+/// Field metadata key for the WKT CRS of a coordinate.
+pub const CRS_KEY: &str = "las:crs";
+
+/// Field metadata key for the extra bytes data type code of an extra dimension.
+pub const DATA_TYPE_KEY: &str = "las:data_type";
+
+const EXTRA_BYTES_COLUMN: &str = "extra_bytes";
+
+/// Options for converting point data to a record batch.
+#[derive(Clone, Debug, Default)]
+pub struct ArrowOptions {
+    raw_coordinates: bool,
+    crs: Option<String>,
+    extra_bytes: Vec<Descriptor>,
+}
+
+impl ArrowOptions {
+    /// Creates options with the CRS and extra bytes descriptors of a header.
+    ///
+    /// Only WKT CRSes are carried over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{arrow::ArrowOptions, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = ArrowOptions::for_header(reader.header()).unwrap();
+    /// ```
+    pub fn for_header(header: &Header) -> Result<ArrowOptions> {
+        Ok(ArrowOptions {
+            raw_coordinates: false,
+            crs: header.get_wkt_crs_bytes().map(|bytes| {
+                String::from_utf8_lossy(bytes)
+                    .trim_end_matches('\0')
+                    .to_string()
+            }),
+            extra_bytes: header.extra_bytes_descriptors()?,
+        })
+    }
+
+    /// Recovers the options a record batch's schema was written with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{arrow::ArrowOptions, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = ArrowOptions::default().with_crs("LOCAL_CS[\"test\"]");
+    /// let batch = reader.read_points(1).unwrap().to_record_batch(&options).unwrap();
+    /// let options = ArrowOptions::from_schema(&batch.schema()).unwrap();
+    /// assert_eq!(Some("LOCAL_CS[\"test\"]"), options.crs());
+    /// ```
+    pub fn from_schema(schema: &Schema) -> Result<ArrowOptions> {
+        let x = schema.field_with_name("x")?;
+        Ok(ArrowOptions {
+            raw_coordinates: x.data_type() == &DataType::Int32,
+            crs: x.metadata().get(CRS_KEY).cloned(),
+            extra_bytes: schema
+                .fields()
+                .iter()
+                .filter_map(|field| descriptor(field).transpose())
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Writes x, y and z as raw `Int32` values instead of scaled `Float64` values.
+    pub fn with_raw_coordinates(mut self, raw_coordinates: bool) -> ArrowOptions {
+        self.raw_coordinates = raw_coordinates;
+        self
+    }
+
+    /// Sets the WKT CRS carried in the coordinate fields' metadata.
+    pub fn with_crs(mut self, wkt: impl Into<String>) -> ArrowOptions {
+        self.crs = Some(wkt.into());
+        self
+    }
+
+    /// Sets the descriptors used to split the extra bytes into columns.
+    pub fn with_extra_bytes(mut self, extra_bytes: Vec<Descriptor>) -> ArrowOptions {
+        self.extra_bytes = extra_bytes;
+        self
+    }
+
+    /// Returns the WKT CRS, if there is one.
+    pub fn crs(&self) -> Option<&str> {
+        self.crs.as_deref()
+    }
+
+    /// Returns the extra bytes descriptors.
+    pub fn extra_bytes(&self) -> &[Descriptor] {
+        &self.extra_bytes
+    }
+}
+
+impl PointData {
+    /// Converts these points to an Arrow record batch.
+    ///
+    /// Extra bytes descriptors that don't fit in the format's extra bytes
+    /// are ignored, and descriptors named like another column are an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{arrow::ArrowOptions, Reader};
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let batch = points.to_record_batch(&ArrowOptions::default()).unwrap();
+    /// assert!(batch.column_by_name("gps_time").is_some());
+    /// ```
+    pub fn to_record_batch(&self, options: &ArrowOptions) -> Result<RecordBatch> {
+        let format = *self.format();
+        let mut columns = Columns::default();
+        for (name, transform, dimension) in [
+            ("x", self.transforms().x, Dimension::X),
+            ("y", self.transforms().y, Dimension::Y),
+            ("z", self.transforms().z, Dimension::Z),
+        ] {
+            let mut metadata = HashMap::from([
+                (SCALE_KEY.to_string(), transform.scale.to_string()),
+                (OFFSET_KEY.to_string(), transform.offset.to_string()),
+            ]);
+            if let Some(crs) = &options.crs {
+                let _ = metadata.insert(CRS_KEY.to_string(), crs.clone());
+            }
+            let raw: Box<dyn Iterator<Item = i32> + '_> = match dimension {
+                Dimension::X => Box::new(self.x_raw()),
+                Dimension::Y => Box::new(self.y_raw()),
+                Dimension::Z => Box::new(self.z_raw()),
+                _ => unreachable!("{dimension:?} is not a coordinate"),
+            };
+            let (data_type, array): (DataType, ArrayRef) = if options.raw_coordinates {
+                (DataType::Int32, Arc::new(Int32Array::from_iter_values(raw)))
+            } else {
+                (
+                    DataType::Float64,
+                    Arc::new(Float64Array::from_iter_values(
+                        raw.map(|n| transform.direct(n)),
+                    )),
+                )
+            };
+            columns.push(
+                Field::new(name, data_type, false).with_metadata(metadata),
+                array,
+            );
+        }
+        columns.primitive::<UInt16Type>("intensity", self.intensity());
+        columns.primitive::<UInt8Type>("return_number", self.return_number());
+        columns.primitive::<UInt8Type>("number_of_returns", self.number_of_returns());
+        columns.boolean(
+            "scan_direction_flag",
+            self.scan_direction()
+                .map(|d| d == ScanDirection::LeftToRight),
+        );
+        columns.boolean("edge_of_flight_line", self.is_edge_of_flight_line());
+        columns.primitive::<UInt8Type>("classification", self.classification());
+        columns.boolean("synthetic", self.is_synthetic());
+        columns.boolean("key_point", self.is_key_point());
+        columns.boolean("withheld", self.is_withheld());
+        columns.boolean("overlap", self.is_overlap());
+        if format.is_extended {
+            columns.primitive::<UInt8Type>("scanner_channel", self.scanner_channel());
+        }
+        columns.primitive::<Float32Type>("scan_angle", self.scan_angle_degrees());
+        columns.primitive::<UInt8Type>("user_data", self.user_data());
+        columns.primitive::<UInt16Type>("point_source_id", self.point_source_id());
+        if let Some(gps_time) = self.gps_time() {
+            columns.primitive::<Float64Type>("gps_time", gps_time);
+        }
+        if let Some(rgb) = self.rgb() {
+            let (mut red, mut green, mut blue) = (Vec::new(), Vec::new(), Vec::new());
+            for (r, g, b) in rgb {
+                red.push(r);
+                green.push(g);
+                blue.push(b);
+            }
+            let array = StructArray::from(vec![
+                member("red", Arc::new(UInt16Array::from(red))),
+                member("green", Arc::new(UInt16Array::from(green))),
+                member("blue", Arc::new(UInt16Array::from(blue))),
+            ]);
+            columns.push(
+                Field::new("color", array.data_type().clone(), false),
+                Arc::new(array),
+            );
+        }
+        if let Some(nir) = self.nir() {
+            columns.primitive::<UInt16Type>("nir", nir);
+        }
+        if let Some(waveform) = self.waveform() {
+            let waveforms: Vec<Waveform> = waveform.collect();
+            let array = StructArray::from(vec![
+                member(
+                    "wave_packet_descriptor_index",
+                    Arc::new(UInt8Array::from_iter_values(
+                        waveforms.iter().map(|w| w.wave_packet_descriptor_index),
+                    )),
+                ),
+                member(
+                    "byte_offset_to_waveform_data",
+                    Arc::new(UInt64Array::from_iter_values(
+                        waveforms.iter().map(|w| w.byte_offset_to_waveform_data),
+                    )),
+                ),
+                member(
+                    "waveform_packet_size_in_bytes",
+                    Arc::new(UInt32Array::from_iter_values(
+                        waveforms.iter().map(|w| w.waveform_packet_size_in_bytes),
+                    )),
+                ),
+                member(
+                    "return_point_waveform_location",
+                    Arc::new(Float32Array::from_iter_values(
+                        waveforms.iter().map(|w| w.return_point_waveform_location),
+                    )),
+                ),
+                member(
+                    "x_t",
+                    Arc::new(Float32Array::from_iter_values(
+                        waveforms.iter().map(|w| w.x_t),
+                    )),
+                ),
+                member(
+                    "y_t",
+                    Arc::new(Float32Array::from_iter_values(
+                        waveforms.iter().map(|w| w.y_t),
+                    )),
+                ),
+                member(
+                    "z_t",
+                    Arc::new(Float32Array::from_iter_values(
+                        waveforms.iter().map(|w| w.z_t),
+                    )),
+                ),
+            ]);
+            columns.push(
+                Field::new("waveform", array.data_type().clone(), false),
+                Arc::new(array),
+            );
+        }
+        let mut offset = 0;
+        for descriptor in &options.extra_bytes {
+            let len = usize::from(descriptor.data_type.len());
+            if offset + len > usize::from(format.extra_bytes) {
+                break;
+            }
+            columns.extra_bytes(self, descriptor, offset)?;
+            offset += len;
+        }
+        if offset < usize::from(format.extra_bytes) {
+            let descriptor = Descriptor::new(EXTRA_BYTES_COLUMN, ExtraBytesType::Undocumented(0));
+            columns.extra_bytes(self, &descriptor, offset)?;
+        }
+        let schema = Schema::new(columns.fields).with_metadata(HashMap::from([(
+            POINT_FORMAT_KEY.to_string(),
+            format.to_u8()?.to_string(),
+        )]));
+        RecordBatch::try_new(Arc::new(schema), columns.arrays).map_err(Error::from)
+    }
+
+    /// Converts an Arrow record batch back to point data.
+    ///
+    /// Only the `x`, `y` and `z` columns are required; other missing
+    /// columns are filled with zeros. The point format is read from the
+    /// schema metadata, defaulting to format 0, and the transforms are read
+    /// from the coordinate fields' metadata. Scaled extra bytes values that
+    /// don't fit in their data type are an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{arrow::ArrowOptions, PointData, Reader};
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let batch = points.to_record_batch(&ArrowOptions::default()).unwrap();
+    /// let points = PointData::from_record_batch(&batch).unwrap();
+    /// ```
+    pub fn from_record_batch(batch: &RecordBatch) -> Result<PointData> {
+        let schema = batch.schema();
+        let mut format =
+            match schema.metadata().get(POINT_FORMAT_KEY) {
+                Some(n) => Format::new(n.parse().map_err(|_| {
+                    Error::InvalidRecordBatch(format!("invalid point format: {n}"))
+                })?)?,
+                None => Format::default(),
+            };
+        let mut extra_columns = Vec::new();
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            if let Some(descriptor) = descriptor(field)? {
+                extra_columns.push((descriptor, column));
+            }
+        }
+        format.extra_bytes = extra_columns
+            .iter()
+            .map(|(descriptor, _)| u16::from(descriptor.data_type.len()))
+            .sum();
+
+        let mut transforms = Vector::<Transform>::default();
+        let x = coordinate(batch, "x", &mut transforms.x)?;
+        let y = coordinate(batch, "y", &mut transforms.y)?;
+        let z = coordinate(batch, "z", &mut transforms.z)?;
+        let intensity = primitive::<UInt16Type>(batch, "intensity")?;
+        let return_number = primitive::<UInt8Type>(batch, "return_number")?;
+        let number_of_returns = primitive::<UInt8Type>(batch, "number_of_returns")?;
+        let scan_direction = boolean(batch, "scan_direction_flag")?;
+        let edge_of_flight_line = boolean(batch, "edge_of_flight_line")?;
+        let classification = primitive::<UInt8Type>(batch, "classification")?;
+        let synthetic = boolean(batch, "synthetic")?;
+        let key_point = boolean(batch, "key_point")?;
+        let withheld = boolean(batch, "withheld")?;
+        let overlap = boolean(batch, "overlap")?;
+        let scanner_channel = primitive::<UInt8Type>(batch, "scanner_channel")?;
+        let scan_angle = primitive::<Float32Type>(batch, "scan_angle")?;
+        let user_data = primitive::<UInt8Type>(batch, "user_data")?;
+        let point_source_id = primitive::<UInt16Type>(batch, "point_source_id")?;
+        let gps_time = primitive::<Float64Type>(batch, "gps_time")?;
+        let color = structure(batch, "color")?;
+        let color = match color {
+            Some(color) => Some([
+                child::<UInt16Type>(color, "red")?,
+                child::<UInt16Type>(color, "green")?,
+                child::<UInt16Type>(color, "blue")?,
+            ]),
+            None => None,
+        };
+        let nir = primitive::<UInt16Type>(batch, "nir")?;
+        let waveform = structure(batch, "waveform")?
+            .map(WaveformColumns::new)
+            .transpose()?;
+
+        let flag = |array: Option<&BooleanArray>, i: usize| array.is_some_and(|a| a.value(i));
+        let mut bytes = Vec::with_capacity(batch.num_rows() * usize::from(format.len()));
+        let mut point = raw::Point {
+            extra_bytes: Vec::with_capacity(usize::from(format.extra_bytes)),
+            ..Default::default()
+        };
+        for i in 0..batch.num_rows() {
+            point.x = x(i)?;
+            point.y = y(i)?;
+            point.z = z(i)?;
+            point.intensity = intensity.map_or(0, |a| a.value(i));
+            point.flags = flags(
+                return_number.map_or(0, |a| a.value(i)),
+                number_of_returns.map_or(0, |a| a.value(i)),
+                scanner_channel.map_or(0, |a| a.value(i)),
+                [
+                    flag(synthetic, i),
+                    flag(key_point, i),
+                    flag(withheld, i),
+                    flag(overlap, i),
+                    flag(scan_direction, i),
+                    flag(edge_of_flight_line, i),
+                ],
+                classification.map_or(0, |a| a.value(i)),
+            )?;
+            point.scan_angle = scan_angle.map_or(0., |a| a.value(i)).into();
+            point.user_data = user_data.map_or(0, |a| a.value(i));
+            point.point_source_id = point_source_id.map_or(0, |a| a.value(i));
+            point.gps_time = format
+                .has_gps_time
+                .then(|| gps_time.map_or(0., |a| a.value(i)));
+            point.color = format.has_color.then(|| match color {
+                Some([red, green, blue]) => Color {
+                    red: red.value(i),
+                    green: green.value(i),
+                    blue: blue.value(i),
+                },
+                None => Color::default(),
+            });
+            point.waveform = format.has_waveform.then(|| {
+                waveform
+                    .as_ref()
+                    .map_or_else(Waveform::default, |w| w.value(i))
+            });
+            point.nir = format.has_nir.then(|| nir.map_or(0, |a| a.value(i)));
+            point.extra_bytes.clear();
+            for (descriptor, column) in &extra_columns {
+                encode(descriptor, column, i, &mut point.extra_bytes)?;
+            }
+            point.write_to(&mut bytes, &format)?;
+        }
+        PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(transforms)
+            .build_from_bytes(bytes)
+    }
+}
+
+/// The waveform struct's member columns, looked up once per batch.
+struct WaveformColumns<'a> {
+    wave_packet_descriptor_index: &'a UInt8Array,
+    byte_offset_to_waveform_data: &'a UInt64Array,
+    waveform_packet_size_in_bytes: &'a UInt32Array,
+    return_point_waveform_location: &'a Float32Array,
+    x_t: &'a Float32Array,
+    y_t: &'a Float32Array,
+    z_t: &'a Float32Array,
+}
+
+impl<'a> WaveformColumns<'a> {
+    fn new(array: &'a StructArray) -> Result<WaveformColumns<'a>> {
+        Ok(WaveformColumns {
+            wave_packet_descriptor_index: child::<UInt8Type>(
+                array,
+                "wave_packet_descriptor_index",
+            )?,
+            byte_offset_to_waveform_data: child::<UInt64Type>(
+                array,
+                "byte_offset_to_waveform_data",
+            )?,
+            waveform_packet_size_in_bytes: child::<UInt32Type>(
+                array,
+                "waveform_packet_size_in_bytes",
+            )?,
+            return_point_waveform_location: child::<Float32Type>(
+                array,
+                "return_point_waveform_location",
+            )?,
+            x_t: child::<Float32Type>(array, "x_t")?,
+            y_t: child::<Float32Type>(array, "y_t")?,
+            z_t: child::<Float32Type>(array, "z_t")?,
+        })
+    }
+
+    fn value(&self, i: usize) -> Waveform {
+        Waveform {
+            wave_packet_descriptor_index: self.wave_packet_descriptor_index.value(i),
+            byte_offset_to_waveform_data: self.byte_offset_to_waveform_data.value(i),
+            waveform_packet_size_in_bytes: self.waveform_packet_size_in_bytes.value(i),
+            return_point_waveform_location: self.return_point_waveform_location.value(i),
+            x_t: self.x_t.value(i),
+            y_t: self.y_t.value(i),
+            z_t: self.z_t.value(i),
+        }
+    }
+}
+
+/// Builds three byte flags, keeping the classification as is.
+///
+/// Unlike [Point::flags](crate::Point::flags), this lets extended formats keep
+/// classification 12 apart from the overlap flag. Legacy formats fold the
+/// overlap flag into classification 12 when the record is written.
+fn flags(
+    return_number: u8,
+    number_of_returns: u8,
+    scanner_channel: u8,
+    [synthetic, key_point, withheld, overlap, scan_direction, edge_of_flight_line]: [bool; 6],
+    classification: u8,
+) -> Result<Flags> {
+    if return_number > 15 {
+        return Err(Error::ReturnNumber {
+            return_number,
+            version: None,
+        });
+    } else if number_of_returns > 15 {
+        return Err(Error::ReturnNumber {
+            return_number: number_of_returns,
+            version: None,
+        });
+    } else if scanner_channel > 3 {
+        return Err(Error::InvalidScannerChannel(scanner_channel));
+    }
+    let mut b = scanner_channel << 4;
+    for (bit, set) in [
+        (0, synthetic),
+        (1, key_point),
+        (2, withheld),
+        (3, overlap),
+        (6, scan_direction),
+        (7, edge_of_flight_line),
+    ] {
+        if set {
+            b |= 1 << bit;
+        }
+    }
+    Ok(Flags::ThreeByte(
+        (number_of_returns << 4) | return_number,
+        b,
+        classification,
+    ))
+}
+
+#[derive(Default)]
+struct Columns {
+    fields: Vec<Field>,
+    arrays: Vec<ArrayRef>,
+}
+
+impl Columns {
+    fn push(&mut self, field: Field, array: ArrayRef) {
+        self.fields.push(field);
+        self.arrays.push(array);
+    }
+
+    fn primitive<T: ArrowPrimitiveType>(
+        &mut self,
+        name: &str,
+        values: impl Iterator<Item = T::Native>,
+    ) {
+        self.push(
+            Field::new(name, T::DATA_TYPE, false),
+            Arc::new(PrimitiveArray::<T>::from_iter_values(values)),
+        );
+    }
+
+    fn boolean(&mut self, name: &str, values: impl Iterator<Item = bool>) {
+        let values: Vec<bool> = values.collect();
+        self.push(
+            Field::new(name, DataType::Boolean, false),
+            Arc::new(BooleanArray::from(values)),
+        );
+    }
+
+    fn extra_bytes(
+        &mut self,
+        points: &PointData,
+        descriptor: &Descriptor,
+        offset: usize,
+    ) -> Result<()> {
+        if self
+            .fields
+            .iter()
+            .any(|field| field.name() == &descriptor.name)
+        {
+            return Err(Error::InvalidRecordBatch(format!(
+                "extra bytes column {} has the same name as another column",
+                descriptor.name
+            )));
+        }
+        let values = points.extra_bytes().map(|bytes| &bytes[offset..]);
+        let mut metadata = HashMap::from([(
+            DATA_TYPE_KEY.to_string(),
+            descriptor.data_type.code().to_string(),
+        )]);
+        if let Some(scale) = descriptor.scale {
+            let _ = metadata.insert(SCALE_KEY.to_string(), scale.to_string());
+        }
+        if let Some(offset) = descriptor.offset {
+            let _ = metadata.insert(OFFSET_KEY.to_string(), offset.to_string());
+        }
+        let array: ArrayRef = match descriptor.data_type {
+            ExtraBytesType::Undocumented(n) => {
+                let len = if n == 0 {
+                    usize::from(points.format().extra_bytes) - offset
+                } else {
+                    usize::from(n)
+                };
+                let mut builder = FixedSizeBinaryBuilder::with_capacity(points.len(), len as i32);
+                for value in values {
+                    builder.append_value(&value[..len])?;
+                }
+                Arc::new(builder.finish())
+            }
+            _ if descriptor.is_scaled() => Arc::new(Float64Array::from_iter_values(
+                values.map(|bytes| descriptor.decode(bytes).unwrap_or_default()),
+            )),
+            ExtraBytesType::U8 => Arc::new(UInt8Array::from_iter_values(values.map(|b| b[0]))),
+            ExtraBytesType::I8 => Arc::new(Int8Array::from_iter_values(values.map(|b| b[0] as i8))),
+            ExtraBytesType::U16 => Arc::new(UInt16Array::from_iter_values(
+                values.map(|b| u16::from_le_bytes([b[0], b[1]])),
+            )),
+            ExtraBytesType::I16 => Arc::new(Int16Array::from_iter_values(
+                values.map(|b| i16::from_le_bytes([b[0], b[1]])),
+            )),
+            ExtraBytesType::U32 => Arc::new(UInt32Array::from_iter_values(
+                values.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            )),
+            ExtraBytesType::I32 => Arc::new(Int32Array::from_iter_values(
+                values.map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            )),
+            ExtraBytesType::U64 => Arc::new(UInt64Array::from_iter_values(
+                values.map(|b| u64::from_le_bytes(eight(b))),
+            )),
+            ExtraBytesType::I64 => Arc::new(Int64Array::from_iter_values(
+                values.map(|b| i64::from_le_bytes(eight(b))),
+            )),
+            ExtraBytesType::F32 => Arc::new(Float32Array::from_iter_values(
+                values.map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            )),
+            ExtraBytesType::F64 => Arc::new(Float64Array::from_iter_values(
+                values.map(|b| f64::from_le_bytes(eight(b))),
+            )),
+        };
+        self.push(
+            Field::new(&descriptor.name, array.data_type().clone(), false).with_metadata(metadata),
+            array,
+        );
+        Ok(())
+    }
+}
+
+fn member(name: &str, array: ArrayRef) -> (Arc<Field>, ArrayRef) {
+    (
+        Arc::new(Field::new(name, array.data_type().clone(), false)),
+        array,
+    )
+}
+
+fn eight(bytes: &[u8]) -> [u8; 8] {
+    let mut array = [0; 8];
+    array.copy_from_slice(&bytes[..8]);
+    array
+}
+
+/// Returns the extra bytes descriptor of a field, if it's an extra dimension.
+fn descriptor(field: &Field) -> Result<Option<Descriptor>> {
+    let parse = |key: &str| -> Result<Option<f64>> {
+        field
+            .metadata()
+            .get(key)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    Error::InvalidRecordBatch(format!(
+                        "invalid {key} for {}: {value}",
+                        field.name()
+                    ))
+                })
+            })
+            .transpose()
+    };
+    let Some(code) = field.metadata().get(DATA_TYPE_KEY) else {
+        return Ok(None);
+    };
+    let code: u8 = code.parse().map_err(|_| {
+        Error::InvalidRecordBatch(format!("invalid data type for {}: {code}", field.name()))
+    })?;
+    let len = match field.data_type() {
+        DataType::FixedSizeBinary(n) => u8::try_from(*n)?,
+        _ => 0,
+    };
+    let mut descriptor = Descriptor::new(field.name(), ExtraBytesType::new(code, len)?);
+    descriptor.scale = parse(SCALE_KEY)?;
+    descriptor.offset = parse(OFFSET_KEY)?;
+    Ok(Some(descriptor))
+}
+
+fn encode(descriptor: &Descriptor, column: &ArrayRef, i: usize, bytes: &mut Vec<u8>) -> Result<()> {
+    let mismatch = || {
+        Error::InvalidRecordBatch(format!(
+            "column {} has type {}",
+            descriptor.name,
+            column.data_type()
+        ))
+    };
+    if let ExtraBytesType::Undocumented(_) = descriptor.data_type {
+        let array = column.as_fixed_size_binary_opt().ok_or_else(mismatch)?;
+        bytes.extend_from_slice(array.value(i));
+        return Ok(());
+    }
+    if descriptor.is_scaled() {
+        let value = column
+            .as_primitive_opt::<Float64Type>()
+            .ok_or_else(mismatch)?
+            .value(i);
+        let n =
+            ((value - descriptor.offset.unwrap_or(0.)) / descriptor.scale.unwrap_or(1.)).round();
+        let out_of_range = || {
+            Error::InvalidRecordBatch(format!(
+                "value {value} of {} doesn't fit in {:?}",
+                descriptor.name, descriptor.data_type
+            ))
+        };
+        if n.is_nan() {
+            return Err(out_of_range());
+        }
+        macro_rules! scaled {
+            ($to:ident) => {
+                bytes.extend_from_slice(&n.$to().ok_or_else(out_of_range)?.to_le_bytes())
+            };
+        }
+        match descriptor.data_type {
+            ExtraBytesType::U8 => scaled!(to_u8),
+            ExtraBytesType::I8 => scaled!(to_i8),
+            ExtraBytesType::U16 => scaled!(to_u16),
+            ExtraBytesType::I16 => scaled!(to_i16),
+            ExtraBytesType::U32 => scaled!(to_u32),
+            ExtraBytesType::I32 => scaled!(to_i32),
+            ExtraBytesType::U64 => scaled!(to_u64),
+            ExtraBytesType::I64 => scaled!(to_i64),
+            ExtraBytesType::F32 => scaled!(to_f32),
+            ExtraBytesType::F64 => bytes.extend_from_slice(&n.to_le_bytes()),
+            ExtraBytesType::Undocumented(_) => unreachable!(),
+        }
+        return Ok(());
+    }
+    macro_rules! native {
+        ($type:ty) => {
+            bytes.extend_from_slice(
+                &column
+                    .as_primitive_opt::<$type>()
+                    .ok_or_else(mismatch)?
+                    .value(i)
+                    .to_le_bytes(),
+            )
+        };
+    }
+    match descriptor.data_type {
+        ExtraBytesType::U8 => native!(UInt8Type),
+        ExtraBytesType::I8 => native!(Int8Type),
+        ExtraBytesType::U16 => native!(UInt16Type),
+        ExtraBytesType::I16 => native!(Int16Type),
+        ExtraBytesType::U32 => native!(UInt32Type),
+        ExtraBytesType::I32 => native!(Int32Type),
+        ExtraBytesType::U64 => native!(UInt64Type),
+        ExtraBytesType::I64 => native!(Int64Type),
+        ExtraBytesType::F32 => native!(Float32Type),
+        ExtraBytesType::F64 => native!(Float64Type),
+        ExtraBytesType::Undocumented(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+fn coordinate<'a>(
+    batch: &'a RecordBatch,
+    name: &str,
+    transform: &mut Transform,
+) -> Result<Box<dyn Fn(usize) -> Result<i32> + 'a>> {
+    let schema = batch.schema();
+    let field = schema.field_with_name(name)?;
+    for (key, value) in [
+        (SCALE_KEY, &mut transform.scale),
+        (OFFSET_KEY, &mut transform.offset),
+    ] {
+        if let Some(s) = field.metadata().get(key) {
+            *value = s
+                .parse()
+                .map_err(|_| Error::InvalidRecordBatch(format!("invalid {key} for {name}: {s}")))?;
+        }
+    }
+    let column = batch
+        .column_by_name(name)
+        .expect("the field was found in the schema");
+    let transform = *transform;
+    if let Some(array) = column.as_primitive_opt::<Float64Type>() {
+        Ok(Box::new(move |i| transform.inverse(array.value(i))))
+    } else if let Some(array) = column.as_primitive_opt::<Int32Type>() {
+        Ok(Box::new(move |i| Ok(array.value(i))))
+    } else {
+        Err(Error::InvalidRecordBatch(format!(
+            "column {name} has type {}",
+            column.data_type()
+        )))
+    }
+}
+
+fn primitive<'a, T: ArrowPrimitiveType>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<Option<&'a PrimitiveArray<T>>> {
+    batch
+        .column_by_name(name)
+        .map(|column| typed(column.as_primitive_opt::<T>(), name, column))
+        .transpose()
+}
+
+fn boolean<'a>(batch: &'a RecordBatch, name: &str) -> Result<Option<&'a BooleanArray>> {
+    batch
+        .column_by_name(name)
+        .map(|column| typed(column.as_boolean_opt(), name, column))
+        .transpose()
+}
+
+fn structure<'a>(batch: &'a RecordBatch, name: &str) -> Result<Option<&'a StructArray>> {
+    batch
+        .column_by_name(name)
+        .map(|column| typed(column.as_struct_opt(), name, column))
+        .transpose()
+}
+
+fn child<'a, T: ArrowPrimitiveType>(
+    array: &'a StructArray,
+    name: &str,
+) -> Result<&'a PrimitiveArray<T>> {
+    let column = array
+        .column_by_name(name)
+        .ok_or_else(|| Error::InvalidRecordBatch(format!("missing struct field {name}")))?;
+    typed(column.as_primitive_opt::<T>(), name, column)
+}
+
+fn typed<'a, T>(array: Option<&'a T>, name: &str, column: &ArrayRef) -> Result<&'a T> {
+    array.ok_or_else(|| {
+        Error::InvalidRecordBatch(format!("column {name} has type {}", column.data_type()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, Builder, Point, Reader};
+
+    fn roundtrip(points: &PointData, options: &ArrowOptions) -> (RecordBatch, PointData) {
+        let batch = points.to_record_batch(options).unwrap();
+        let roundtrip = PointData::from_record_batch(&batch).unwrap();
+        assert_eq!(points.format(), roundtrip.format());
+        assert_eq!(points.transforms(), roundtrip.transforms());
+        assert_eq!(points.raw_bytes(), roundtrip.raw_bytes());
+        (batch, roundtrip)
+    }
+
+    fn points(format: u8, extra_bytes: u16) -> PointData {
+        let mut format = Format::new(format).unwrap();
+        format.extra_bytes = extra_bytes;
+        let points = (0..10).map(|i| Point {
+            x: f64::from(i) * 1.5,
+            y: f64::from(i) * 2.25,
+            z: -f64::from(i),
+            intensity: i as u16,
+            return_number: 1,
+            number_of_returns: 2,
+            classification: Classification::Ground,
+            is_overlap: i % 3 == 0,
+            is_withheld: i % 2 == 0,
+            scanner_channel: if format.is_extended { 2 } else { 0 },
+            scan_angle: 12.,
+            gps_time: format.has_gps_time.then_some(f64::from(i)),
+            color: format.has_color.then_some(Color::new(1, 2, 3)),
+            nir: format.has_nir.then_some(4),
+            waveform: format.has_waveform.then(|| Waveform {
+                byte_offset_to_waveform_data: 42,
+                x_t: 1.5,
+                ..Default::default()
+            }),
+            extra_bytes: (0..extra_bytes).map(|n| (n + i as u16) as u8).collect(),
+            ..Default::default()
+        });
+        PointDataBuilder::new()
+            .with_format(format)
+            .build_from_points(points)
+            .unwrap()
+    }
+
+    #[test]
+    fn all_formats() {
+        for format in 0..=10 {
+            let _ = roundtrip(&points(format, 0), &ArrowOptions::default());
+        }
+    }
+
+    #[test]
+    fn overlap_classification() {
+        for format in [1, 6] {
+            let mut points = points(format, 0);
+            points.set_classification_at(1, 12).unwrap();
+            let (batch, _) = roundtrip(&points, &ArrowOptions::default());
+            let overlap = batch.column_by_name("overlap").unwrap().as_boolean();
+            // Legacy formats can only mark overlap with the classification
+            assert_eq!(format == 1, overlap.value(1));
+        }
+    }
+
+    #[test]
+    fn raw_coordinates_and_crs() {
+        let options = ArrowOptions::default()
+            .with_raw_coordinates(true)
+            .with_crs("GEOGCS[\"WGS 84\"]");
+        let (batch, _) = roundtrip(&points(6, 0), &options);
+        let x = batch.schema().field_with_name("x").unwrap().clone();
+        assert_eq!(&DataType::Int32, x.data_type());
+        assert_eq!("0.001", x.metadata()[SCALE_KEY]);
+        assert_eq!("GEOGCS[\"WGS 84\"]", x.metadata()[CRS_KEY]);
+        let recovered = ArrowOptions::from_schema(&batch.schema()).unwrap();
+        assert_eq!(Some("GEOGCS[\"WGS 84\"]"), recovered.crs());
+        assert!(recovered.raw_coordinates);
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut height = Descriptor::new("height", ExtraBytesType::I16);
+        height.scale = Some(0.01);
+        let descriptors = vec![
+            Descriptor::new("amplitude", ExtraBytesType::U16),
+            height,
+            Descriptor::new("blob", ExtraBytesType::Undocumented(2)),
+        ];
+        let options = ArrowOptions::default().with_extra_bytes(descriptors.clone());
+        let (batch, _) = roundtrip(&points(3, 8), &options);
+        let schema = batch.schema();
+        assert_eq!(
+            &DataType::UInt16,
+            schema.field_with_name("amplitude").unwrap().data_type()
+        );
+        assert_eq!(
+            &DataType::Float64,
+            schema.field_with_name("height").unwrap().data_type()
+        );
+        assert_eq!(
+            &DataType::FixedSizeBinary(2),
+            schema.field_with_name("blob").unwrap().data_type()
+        );
+        assert_eq!(
+            &DataType::FixedSizeBinary(2),
+            schema.field_with_name("extra_bytes").unwrap().data_type()
+        );
+        let recovered = ArrowOptions::from_schema(&schema).unwrap();
+        assert_eq!(&descriptors[..], &recovered.extra_bytes()[..3]);
+    }
+
+    #[test]
+    fn extra_bytes_out_of_range() {
+        let mut height = Descriptor::new("height", ExtraBytesType::U8);
+        height.scale = Some(0.1);
+        let options = ArrowOptions::default().with_extra_bytes(vec![height]);
+        let batch = points(0, 1).to_record_batch(&options).unwrap();
+        let height = batch.schema().index_of("height").unwrap();
+        for value in [25.6, -0.1, f64::NAN] {
+            let mut columns = batch.columns().to_vec();
+            columns[height] = Arc::new(Float64Array::from(vec![value; batch.num_rows()]));
+            let batch = RecordBatch::try_new(batch.schema(), columns).unwrap();
+            assert!(matches!(
+                PointData::from_record_batch(&batch),
+                Err(Error::InvalidRecordBatch(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn extra_bytes_name_collision() {
+        for name in ["intensity", "extra_bytes"] {
+            let options = ArrowOptions::default()
+                .with_extra_bytes(vec![Descriptor::new(name, ExtraBytesType::U8)]);
+            assert!(matches!(
+                points(0, 2).to_record_batch(&options),
+                Err(Error::InvalidRecordBatch(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn for_header() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 1;
+        builder
+            .vlrs
+            .push(Descriptor::to_vlr(&[Descriptor::new("flag", ExtraBytesType::U8)]).unwrap());
+        let mut header = builder.into_header().unwrap();
+        header
+            .set_wkt_crs(b"LOCAL_CS[\"test\"]\0".to_vec())
+            .unwrap();
+        let options = ArrowOptions::for_header(&header).unwrap();
+        assert_eq!(Some("LOCAL_CS[\"test\"]"), options.crs());
+        assert_eq!(1, options.extra_bytes().len());
+    }
+
+    #[test]
+    fn autzen() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let options = ArrowOptions::for_header(reader.header()).unwrap();
+        let _ = roundtrip(&reader.read_all().unwrap(), &options);
+    }
+
+    #[test]
+    fn minimal_batch() {
+        let schema = Schema::new(vec![
+            Field::new("x", DataType::Float64, false),
+            Field::new("y", DataType::Float64, false),
+            Field::new("z", DataType::Float64, false),
+        ]);
+        let column: ArrayRef = Arc::new(Float64Array::from(vec![1., 2.]));
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![column.clone(), column.clone(), column],
+        )
+        .unwrap();
+        let points = PointData::from_record_batch(&batch).unwrap();
+        assert_eq!(vec![1., 2.], points.x().collect::<Vec<_>>());
+        assert_eq!(&Format::default(), points.format());
+
+        let schema = Schema::new(vec![Field::new("x", DataType::Utf8, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(arrow_array::StringArray::from(vec!["a"]))],
+        )
+        .unwrap();
+        assert!(PointData::from_record_batch(&batch).is_err());
+    }
+}
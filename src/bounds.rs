@@ -3,11 +3,14 @@ use std::f64;
 
 /// Minimum and maximum bounds in three dimensions.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
     /// The minimum values.
+    #[cfg_attr(feature = "serde", serde(with = "serde_bounds::min"))]
     pub min: Vector<f64>,
 
     /// The maximum values.
+    #[cfg_attr(feature = "serde", serde(with = "serde_bounds::max"))]
     pub max: Vector<f64>,
 }
 
@@ -111,6 +114,69 @@ impl Default for Bounds {
     }
 }
 
+/// Empty bounds are infinite, which JSON can't represent, so non-finite values are (de)serialized as `None`.
+#[cfg(feature = "serde")]
+mod serde_bounds {
+    use crate::Vector;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    fn serialize<S: Serializer>(vector: &Vector<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        let finite = |n: f64| n.is_finite().then_some(n);
+        Vector {
+            x: finite(vector.x),
+            y: finite(vector.y),
+            z: finite(vector.z),
+        }
+        .serialize(serializer)
+    }
+
+    fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        empty: f64,
+    ) -> Result<Vector<f64>, D::Error> {
+        let vector = Vector::<Option<f64>>::deserialize(deserializer)?;
+        Ok(Vector {
+            x: vector.x.unwrap_or(empty),
+            y: vector.y.unwrap_or(empty),
+            z: vector.z.unwrap_or(empty),
+        })
+    }
+
+    pub(super) mod min {
+        use super::*;
+
+        pub(in crate::bounds) fn serialize<S: Serializer>(
+            vector: &Vector<f64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            super::serialize(vector, serializer)
+        }
+
+        pub(in crate::bounds) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vector<f64>, D::Error> {
+            super::deserialize(deserializer, f64::INFINITY)
+        }
+    }
+
+    pub(super) mod max {
+        use super::*;
+
+        pub(in crate::bounds) fn serialize<S: Serializer>(
+            vector: &Vector<f64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            super::serialize(vector, serializer)
+        }
+
+        pub(in crate::bounds) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vector<f64>, D::Error> {
+            super::deserialize(deserializer, f64::NEG_INFINITY)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,681 @@
+//! Convert point data between point formats and las versions.
+//!
+//! A [Converter] rewrites the records of a [PointData] from whatever format
+//! they were read in to a target [Format], and produces a matching
+//! [Builder] for the output header. Every conversion reports what
+//! couldn't be carried over in a [Loss]:
+//!
+//! ```
+//! use las::{convert::Converter, point::Format, Reader, Version, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let converter = Converter::new(Format::new(0).unwrap(), Version::new(1, 2)).unwrap();
+//! let header = converter.builder(reader.header()).into_header().unwrap();
+//!
+//! let (points, loss) = converter.convert(&reader.read_all().unwrap()).unwrap();
+//! assert!(loss.gps_time > 0);
+//!
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! writer.write_points(&points).unwrap();
+//! ```
+//!
+//! Going from a legacy format (0-5) to an extended format (6-10) is always
+//! lossless. Going the other way follows the usual rules:
+//!
+//! - The overlap bit is folded into classification 12.
+//! - Classifications above 31 can't be stored, and become `Unclassified`.
+//! - The scanner channel is dropped.
+//! - Scaled scan angles are rounded to whole degrees.
+//! - Return numbers are capped at seven, or at five if the target version
+//!   predates las 1.4 (the header of those versions only has five return
+//!   counts).
+
+use crate::{
+    extra_bytes::Descriptor,
+    point::{Classification, Format},
+    raw::{
+        self,
+        point::{Flags, ScanAngle},
+    },
+    Builder, Error, Header, PointData, PointDataBuilder, Result, Version, Vlr,
+};
+use std::{io::Cursor, ops::AddAssign};
+
+const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
+
+/// Converts point records to a target point format and las version.
+///
+/// # Examples
+///
+/// ```
+/// use las::{convert::Converter, point::Format, Reader};
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let converter = Converter::for_header(reader.header(), Format::new(6).unwrap());
+/// assert_eq!(converter.version(), las::Version::new(1, 4));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Converter {
+    format: Format,
+    version: Version,
+}
+
+/// The information lost while converting points.
+///
+/// Each field counts the points that had a non-default value for that
+/// attribute that could not be represented in the target format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Loss {
+    /// Points whose gps time was dropped.
+    pub gps_time: u64,
+
+    /// Points whose color was dropped.
+    pub color: u64,
+
+    /// Points whose near infrared value was dropped.
+    pub nir: u64,
+
+    /// Points whose waveform packet information was dropped.
+    pub waveform: u64,
+
+    /// Points whose extra bytes were truncated.
+    pub extra_bytes: u64,
+
+    /// Points whose scanner channel was dropped.
+    pub scanner_channel: u64,
+
+    /// Points whose classification was overwritten by the overlap class.
+    pub overlap: u64,
+
+    /// Points whose classification couldn't be represented and was set to
+    /// `Unclassified`.
+    pub classification: u64,
+
+    /// Points whose return number or number of returns was capped.
+    pub return_number: u64,
+
+    /// Points whose scan angle was rounded.
+    pub scan_angle: u64,
+}
+
+impl Converter {
+    /// Creates a new converter for the target format and version.
+    ///
+    /// Returns an error if the version doesn't support the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Version};
+    /// let converter = Converter::new(Format::new(3).unwrap(), Version::new(1, 2)).unwrap();
+    /// assert!(Converter::new(Format::new(6).unwrap(), Version::new(1, 2)).is_err());
+    /// ```
+    pub fn new(format: Format, version: Version) -> Result<Converter> {
+        if version.supports_point_format(format) {
+            Ok(Converter { format, version })
+        } else {
+            Err(Error::UnsupportedFormat { version, format })
+        }
+    }
+
+    /// Creates a converter for points described by this header.
+    ///
+    /// The target version is the header's version, upgraded to the oldest
+    /// version that supports the target format if necessary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Header, Version};
+    /// let header = Header::from((1, 2));
+    /// let converter = Converter::for_header(&header, Format::new(1).unwrap());
+    /// assert_eq!(Version::new(1, 2), converter.version());
+    /// let converter = Converter::for_header(&header, Format::new(7).unwrap());
+    /// assert_eq!(Version::new(1, 4), converter.version());
+    /// ```
+    pub fn for_header(header: &Header, format: Format) -> Converter {
+        let version = if header.version().supports_point_format(format) {
+            header.version()
+        } else {
+            minimum_version(format)
+        };
+        Converter { format, version }
+    }
+
+    /// Returns the target point format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the target las version.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns a [Builder] for the converted data, based on the source header.
+    ///
+    /// The builder has the target format and version. Header fields that
+    /// the target version doesn't support (file source id, gps standard
+    /// time, synthetic return numbers, the wkt bit) are reset, and the
+    /// point counts are folded to match the capped return numbers. If the
+    /// target has fewer extra bytes, only the extra bytes descriptors that
+    /// still fit are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Header, Version};
+    /// let header = Header::from((1, 4));
+    /// let converter = Converter::new(Format::new(1).unwrap(), Version::new(1, 2)).unwrap();
+    /// let builder = converter.builder(&header);
+    /// assert_eq!(Version::new(1, 2), builder.version);
+    /// let header = builder.into_header().unwrap();
+    /// ```
+    pub fn builder(&self, header: &Header) -> Builder {
+        use crate::feature::{
+            Evlrs, FileSourceId, GpsStandardTime, LargeFiles, SyntheticReturnNumbers,
+        };
+
+        let mut builder = Builder::from(header.clone());
+        let is_compressed = builder.point_format.is_compressed;
+        builder.point_format = self.format;
+        builder.point_format.is_compressed = is_compressed;
+        builder.version = self.version;
+        if !self.version.supports::<FileSourceId>() {
+            builder.file_source_id = 0;
+        }
+        if !self.version.supports::<GpsStandardTime>() {
+            builder.gps_time_type = Default::default();
+        }
+        if !self.version.supports::<SyntheticReturnNumbers>() {
+            builder.has_synthetic_return_numbers = false;
+        }
+        if !self.version.supports::<LargeFiles>() {
+            if builder.has_wkt_crs {
+                log::warn!(
+                    "las {} does not support the wkt bit, clearing it",
+                    self.version
+                );
+            }
+            builder.has_wkt_crs = false;
+        }
+        if !self.version.supports::<Evlrs>() {
+            builder.point_padding.clear();
+        }
+        builder.cap_return_numbers(self.max_return_number());
+        trim_extra_bytes_descriptors(&mut builder.vlrs, self.format.extra_bytes);
+        trim_extra_bytes_descriptors(&mut builder.evlrs, self.format.extra_bytes);
+        builder
+    }
+
+    /// Converts point data to the target format.
+    ///
+    /// The returned point data keeps the source transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Reader, Version};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.read_all().unwrap();
+    /// let converter = Converter::new(Format::new(7).unwrap(), Version::new(1, 4)).unwrap();
+    /// let (converted, loss) = converter.convert(&points).unwrap();
+    /// assert_eq!(points.len(), converted.len());
+    /// assert!(loss.is_lossless());
+    /// ```
+    pub fn convert(&self, points: &PointData) -> Result<(PointData, Loss)> {
+        let source = *points.format();
+        let mut target = self.format;
+        target.is_compressed = source.is_compressed;
+        let mut loss = Loss::default();
+        let mut cursor = Cursor::new(points.raw_bytes());
+        let mut bytes = Vec::with_capacity(points.len() * usize::from(target.len()));
+        for _ in 0..points.len() {
+            let mut point = raw::Point::read_from(&mut cursor, &source)?;
+            self.convert_raw_point(&mut point, &mut loss);
+            point.write_to(&mut bytes, &target)?;
+        }
+        let points = PointDataBuilder::new()
+            .with_format(target)
+            .with_transforms(*points.transforms())
+            .build_from_bytes(bytes)?;
+        Ok((points, loss))
+    }
+
+    fn convert_raw_point(&self, point: &mut raw::Point, loss: &mut Loss) {
+        let flags = FlagFields::new(point.flags);
+        point.flags = if self.format.is_extended {
+            flags.into_three_byte()
+        } else {
+            flags.into_two_byte(self.max_return_number(), loss)
+        };
+
+        let scan_angle = if self.format.is_extended {
+            ScanAngle::Scaled(point.scan_angle.into())
+        } else {
+            ScanAngle::Rank(point.scan_angle.into())
+        };
+        // Scaled angles can't hit every whole degree exactly, so only the
+        // rounding to a rank counts as a loss.
+        if !self.format.is_extended && f32::from(scan_angle) != f32::from(point.scan_angle) {
+            loss.scan_angle += 1;
+        }
+        point.scan_angle = scan_angle;
+
+        if self.format.has_gps_time {
+            point.gps_time = point.gps_time.or(Some(0.));
+        } else if point.gps_time.take().is_some_and(|t| t != 0.) {
+            loss.gps_time += 1;
+        }
+        if self.format.has_color {
+            point.color = point.color.or(Some(Default::default()));
+        } else if point.color.take().is_some_and(|c| c != Default::default()) {
+            loss.color += 1;
+        }
+        if self.format.has_waveform {
+            point.waveform = point.waveform.or(Some(Default::default()));
+        } else if point
+            .waveform
+            .take()
+            .is_some_and(|w| w != Default::default())
+        {
+            loss.waveform += 1;
+        }
+        if self.format.has_nir {
+            point.nir = point.nir.or(Some(0));
+        } else if point.nir.take().is_some() {
+            // A zero nir is read back as `None`, so any `Some` is a real value.
+            loss.nir += 1;
+        }
+        let extra_bytes = usize::from(self.format.extra_bytes);
+        if point.extra_bytes.len() > extra_bytes
+            && point.extra_bytes[extra_bytes..].iter().any(|&b| b != 0)
+        {
+            loss.extra_bytes += 1;
+        }
+        point.extra_bytes.resize(extra_bytes, 0);
+    }
+
+    fn max_return_number(&self) -> u8 {
+        use crate::feature::LargeFiles;
+
+        if !self.version.supports::<LargeFiles>() {
+            5
+        } else if self.format.is_extended {
+            15
+        } else {
+            7
+        }
+    }
+}
+
+/// Keeps the extra bytes descriptors that fit in `extra_bytes`, and drops the vlr if none do.
+fn trim_extra_bytes_descriptors(vlrs: &mut Vec<Vlr>, extra_bytes: u16) {
+    vlrs.retain_mut(|vlr| {
+        if !vlr.is_extra_bytes() {
+            return true;
+        }
+        let descriptors = match Descriptor::from_vlr(vlr) {
+            Ok(descriptors) => descriptors,
+            Err(err) => {
+                log::warn!("dropping an extra bytes vlr that can't be read: {err}");
+                return false;
+            }
+        };
+        let mut len = 0;
+        let fits = descriptors
+            .iter()
+            .take_while(|descriptor| {
+                len += u16::from(descriptor.data_type.len());
+                len <= extra_bytes
+            })
+            .count();
+        if fits == descriptors.len() {
+            true
+        } else if fits == 0 {
+            false
+        } else {
+            match Descriptor::to_vlr(&descriptors[..fits]) {
+                Ok(trimmed) => {
+                    vlr.data = trimmed.data;
+                    true
+                }
+                Err(err) => {
+                    log::warn!("dropping an extra bytes vlr that can't be trimmed: {err}");
+                    false
+                }
+            }
+        }
+    });
+}
+
+impl Loss {
+    /// Returns true if no information was lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::convert::Loss;
+    /// let mut loss = Loss::default();
+    /// assert!(loss.is_lossless());
+    /// loss.color = 1;
+    /// assert!(!loss.is_lossless());
+    /// ```
+    pub fn is_lossless(&self) -> bool {
+        *self == Loss::default()
+    }
+}
+
+impl AddAssign for Loss {
+    fn add_assign(&mut self, other: Loss) {
+        self.gps_time += other.gps_time;
+        self.color += other.color;
+        self.nir += other.nir;
+        self.waveform += other.waveform;
+        self.extra_bytes += other.extra_bytes;
+        self.scanner_channel += other.scanner_channel;
+        self.overlap += other.overlap;
+        self.classification += other.classification;
+        self.return_number += other.return_number;
+        self.scan_angle += other.scan_angle;
+    }
+}
+
+/// The individual fields of point flags, independent of their encoding.
+#[derive(Clone, Copy, Debug)]
+struct FlagFields {
+    return_number: u8,
+    number_of_returns: u8,
+    classification: u8,
+    is_synthetic: bool,
+    is_key_point: bool,
+    is_withheld: bool,
+    is_overlap: bool,
+    scanner_channel: u8,
+    is_left_to_right: bool,
+    is_edge_of_flight_line: bool,
+}
+
+impl FlagFields {
+    fn new(flags: Flags) -> FlagFields {
+        use crate::point::ScanDirection;
+
+        let (classification, is_overlap) = match flags {
+            Flags::TwoByte(_, b) => (b & 0b0001_1111, false),
+            Flags::ThreeByte(_, b, c) => (c, b & 0b0000_1000 != 0),
+        };
+        let (classification, is_overlap) = if classification == OVERLAP_CLASSIFICATION_CODE {
+            (u8::from(Classification::Unclassified), true)
+        } else {
+            (classification, is_overlap)
+        };
+        FlagFields {
+            return_number: flags.return_number(),
+            number_of_returns: flags.number_of_returns(),
+            classification,
+            is_synthetic: flags.is_synthetic(),
+            is_key_point: flags.is_key_point(),
+            is_withheld: flags.is_withheld(),
+            is_overlap,
+            scanner_channel: flags.scanner_channel(),
+            is_left_to_right: flags.scan_direction() == ScanDirection::LeftToRight,
+            is_edge_of_flight_line: flags.is_edge_of_flight_line(),
+        }
+    }
+
+    fn into_three_byte(self) -> Flags {
+        let a = (self.number_of_returns << 4) | self.return_number;
+        let b = u8::from(self.is_synthetic)
+            | u8::from(self.is_key_point) << 1
+            | u8::from(self.is_withheld) << 2
+            | u8::from(self.is_overlap) << 3
+            | self.scanner_channel << 4
+            | u8::from(self.is_left_to_right) << 6
+            | u8::from(self.is_edge_of_flight_line) << 7;
+        Flags::ThreeByte(a, b, self.classification)
+    }
+
+    fn into_two_byte(self, max_return_number: u8, loss: &mut Loss) -> Flags {
+        let return_number = self.return_number.min(max_return_number);
+        let number_of_returns = self.number_of_returns.min(max_return_number);
+        if return_number != self.return_number || number_of_returns != self.number_of_returns {
+            loss.return_number += 1;
+        }
+        if self.scanner_channel != 0 {
+            loss.scanner_channel += 1;
+        }
+        let classification = if self.is_overlap {
+            if self.classification != u8::from(Classification::Unclassified) {
+                loss.overlap += 1;
+            }
+            OVERLAP_CLASSIFICATION_CODE
+        } else if self.classification > 31 {
+            loss.classification += 1;
+            u8::from(Classification::Unclassified)
+        } else {
+            self.classification
+        };
+        let a = return_number
+            | number_of_returns << 3
+            | u8::from(self.is_left_to_right) << 6
+            | u8::from(self.is_edge_of_flight_line) << 7;
+        let b = classification
+            | u8::from(self.is_synthetic) << 5
+            | u8::from(self.is_key_point) << 6
+            | u8::from(self.is_withheld) << 7;
+        Flags::TwoByte(a, b)
+    }
+}
+
+fn minimum_version(format: Format) -> Version {
+    (0..=4)
+        .map(|minor| Version::new(1, minor))
+        .find(|version| version.supports_point_format(format))
+        .unwrap_or(Version::new(1, 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extra_bytes::DataType, point::ScanDirection, Color, Point, Reader, Transform, Vector,
+        Writer,
+    };
+
+    fn point_data(format: Format, points: Vec<Point>) -> PointData {
+        PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(Vector {
+                x: Transform::default(),
+                y: Transform::default(),
+                z: Transform::default(),
+            })
+            .build_from_points(points)
+            .unwrap()
+    }
+
+    fn points(points: &PointData) -> Vec<Point> {
+        points.points().map(|p| p.unwrap()).collect()
+    }
+
+    #[test]
+    fn format_1_to_6() {
+        let source = point_data(
+            Format::new(1).unwrap(),
+            vec![Point {
+                return_number: 2,
+                number_of_returns: 3,
+                scan_direction: ScanDirection::LeftToRight,
+                is_edge_of_flight_line: true,
+                classification: Classification::Ground,
+                is_synthetic: true,
+                is_withheld: true,
+                scan_angle: -12.,
+                gps_time: Some(42.),
+                ..Default::default()
+            }],
+        );
+        let converter = Converter::new(Format::new(6).unwrap(), Version::new(1, 4)).unwrap();
+        let (converted, loss) = converter.convert(&source).unwrap();
+        assert!(loss.is_lossless());
+        assert_eq!(points(&source), points(&converted));
+        assert_eq!(Format::new(6).unwrap(), *converted.format());
+    }
+
+    #[test]
+    fn format_7_to_3() {
+        let source = point_data(
+            Format::new(7).unwrap(),
+            vec![
+                Point {
+                    classification: Classification::Ground,
+                    is_overlap: true,
+                    scanner_channel: 2,
+                    scan_angle: 9.,
+                    gps_time: Some(1.),
+                    color: Some(Color::new(1, 2, 3)),
+                    ..Default::default()
+                },
+                Point {
+                    classification: Classification::UserDefinable(64),
+                    scan_angle: 0.5,
+                    gps_time: Some(1.),
+                    color: Some(Color::new(1, 2, 3)),
+                    ..Default::default()
+                },
+            ],
+        );
+        let converter = Converter::new(Format::new(3).unwrap(), Version::new(1, 2)).unwrap();
+        let (converted, loss) = converter.convert(&source).unwrap();
+        assert_eq!(
+            Loss {
+                scanner_channel: 1,
+                overlap: 1,
+                classification: 1,
+                scan_angle: 1,
+                ..Default::default()
+            },
+            loss
+        );
+        assert_eq!(vec![12, 1], converted.classification().collect::<Vec<_>>());
+        let converted = points(&converted);
+        assert!(converted[0].is_overlap);
+        assert_eq!(0, converted[0].scanner_channel);
+        assert_eq!(9., converted[0].scan_angle);
+        assert_eq!(Classification::Unclassified, converted[1].classification);
+        assert_eq!(Some(Color::new(1, 2, 3)), converted[1].color);
+    }
+
+    #[test]
+    fn format_8_to_2() {
+        let source = point_data(
+            Format::new(8).unwrap(),
+            vec![Point {
+                gps_time: Some(1.),
+                color: Some(Color::new(1, 2, 3)),
+                nir: Some(42),
+                ..Default::default()
+            }],
+        );
+        let converter = Converter::new(Format::new(2).unwrap(), Version::new(1, 2)).unwrap();
+        let (converted, loss) = converter.convert(&source).unwrap();
+        assert_eq!(1, loss.nir);
+        assert_eq!(1, loss.gps_time);
+        let converted = points(&converted);
+        assert_eq!(None, converted[0].nir);
+        assert_eq!(None, converted[0].gps_time);
+        assert_eq!(Some(Color::new(1, 2, 3)), converted[0].color);
+    }
+
+    #[test]
+    fn downgrade_caps_return_numbers() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut header = builder.into_header().unwrap();
+        let source = point_data(
+            Format::new(6).unwrap(),
+            (1..=7)
+                .map(|return_number| Point {
+                    return_number,
+                    number_of_returns: 7,
+                    gps_time: Some(0.),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        header.add_point_data(&source);
+
+        let converter = Converter::new(Format::new(1).unwrap(), Version::new(1, 2)).unwrap();
+        let builder = converter.builder(&header);
+        assert_eq!(Version::new(1, 2), builder.version);
+        let header = builder.into_header().unwrap();
+        assert_eq!(Some(3), header.number_of_points_by_return(5));
+        assert_eq!(None, header.number_of_points_by_return(6));
+        let raw_header = header.clone().into_raw().unwrap();
+        assert_eq!([1, 1, 1, 1, 3], raw_header.number_of_points_by_return);
+
+        let (converted, loss) = converter.convert(&source).unwrap();
+        assert_eq!(7, loss.return_number);
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 5, 5],
+            converted.return_number().collect::<Vec<_>>()
+        );
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.write_points(&converted).unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(Version::new(1, 2), reader.header().version());
+        assert_eq!(Some(3), reader.header().number_of_points_by_return(5));
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut source_format = Format::new(0).unwrap();
+        source_format.extra_bytes = 2;
+        let source = point_data(
+            source_format,
+            vec![Point {
+                extra_bytes: vec![1, 2],
+                ..Default::default()
+            }],
+        );
+        let mut target = Format::new(0).unwrap();
+        target.extra_bytes = 1;
+        let converter = Converter::new(target, Version::new(1, 4)).unwrap();
+        let (converted, loss) = converter.convert(&source).unwrap();
+        assert_eq!(1, loss.extra_bytes);
+        assert_eq!(vec![1], points(&converted)[0].extra_bytes);
+    }
+
+    #[test]
+    fn extra_bytes_descriptors() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 3;
+        builder.vlrs.push(
+            Descriptor::to_vlr(&[
+                Descriptor::new("a", DataType::U8),
+                Descriptor::new("b", DataType::U16),
+            ])
+            .unwrap(),
+        );
+        let header = builder.into_header().unwrap();
+        let descriptors = |extra_bytes| {
+            let mut format = Format::new(0).unwrap();
+            format.extra_bytes = extra_bytes;
+            let converter = Converter::new(format, Version::new(1, 4)).unwrap();
+            let header = converter.builder(&header).into_header().unwrap();
+            assert!(header.vlrs().iter().filter(|v| v.is_extra_bytes()).count() <= 1);
+            header
+                .extra_bytes_descriptors()
+                .unwrap()
+                .into_iter()
+                .map(|descriptor| descriptor.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["a", "b"], descriptors(4));
+        assert_eq!(vec!["a", "b"], descriptors(3));
+        assert_eq!(vec!["a"], descriptors(2));
+        assert!(descriptors(0).is_empty());
+    }
+}
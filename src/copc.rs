@@ -14,7 +14,7 @@ pub const USER_ID: &str = "copc";
 /// The description of the LasZip VLR header.
 pub const DESCRIPTION: &str = "https://copc.io";
 
-use crate::{Error, Header, Result, Vlr};
+use crate::{vlr::KnownVlr, Error, Header, Result, Vlr};
 
 /// The COPC Info Vlr.
 ///
@@ -26,6 +26,7 @@ use crate::{Error, Header, Result, Vlr};
 /// - The info VLR is 160 bytes described by the following structure. reserved
 ///   elements MUST be set to 0.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CopcInfoVlr {
     /// Actual (unscaled) X coordinate of center of octree
     pub center_x: f64,
@@ -107,6 +108,27 @@ impl TryFrom<&Vlr> for CopcInfoVlr {
     }
 }
 
+impl KnownVlr for CopcInfoVlr {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == USER_ID && record_id == CopcInfoVlr::RECORD_ID
+    }
+
+    fn decode(vlr: &Vlr) -> Result<CopcInfoVlr> {
+        vlr.try_into()
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(160);
+        self.write_to(&mut data)?;
+        Ok(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: CopcInfoVlr::RECORD_ID,
+            description: DESCRIPTION.to_string(),
+            data,
+        })
+    }
+}
+
 /// VoxelKey corresponds to the naming of EPT data files.
 ///
 /// See <https://entwine.io/en/latest/entwine-point-tile.html#ept-data> for more.
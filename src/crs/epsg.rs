@@ -0,0 +1,995 @@
+//! An embedded subset of the EPSG registry.
+//!
+//! Covers common geographic systems, UTM-style projected systems on those
+//! datums, a few state plane and national grids, and common vertical systems.
+//! Use [get] to look up a definition, or [to_wkt] and [to_geotiff] to produce
+//! CRS data for a header:
+//!
+//! ```
+//! use las::crs::{epsg, WktVersion};
+//! let definition = epsg::get(32610).unwrap();
+//! assert_eq!("WGS 84 / UTM zone 10N", definition.name);
+//! let wkt = epsg::to_wkt(32610, Some(5703), WktVersion::Wkt1).unwrap();
+//! assert!(wkt.starts_with("COMPD_CS[\"WGS 84 / UTM zone 10N + NAVD88 height\""));
+//! ```
+
+use super::{GeoTiffCrs, GeoTiffData, WktVersion};
+use crate::{Error, Result};
+
+const ANGULAR_UNITS_GEO_KEY: u16 = 2054;
+const DEGREE_GEOTIFF_CODE: u16 = 9102;
+
+/// A coordinate reference system definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition {
+    /// The EPSG code.
+    pub code: u16,
+
+    /// The EPSG name, e.g. `"WGS 84 / UTM zone 10N"`.
+    pub name: String,
+
+    /// What kind of system this is, with its parameters.
+    pub kind: Kind,
+}
+
+/// The kind of a coordinate reference system.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Latitude and longitude, in degrees, on a datum.
+    Geographic(Datum),
+
+    /// Easting and northing, projected from a geographic system.
+    Projected {
+        /// The EPSG code of the base geographic system.
+        base: u16,
+
+        /// The map projection.
+        projection: Projection,
+
+        /// The linear unit of the coordinates and of the projection's false easting and northing.
+        unit: Unit,
+    },
+
+    /// Heights relative to a vertical datum.
+    Vertical {
+        /// The vertical datum.
+        datum: VerticalDatum,
+
+        /// The unit of the heights.
+        unit: Unit,
+    },
+}
+
+/// A geodetic datum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Datum {
+    /// The EPSG code.
+    pub code: u16,
+
+    /// The EPSG name.
+    pub name: &'static str,
+
+    /// The name used in WKT1, e.g. `"WGS_1984"`.
+    pub wkt1_name: &'static str,
+
+    /// The reference ellipsoid.
+    pub ellipsoid: Ellipsoid,
+}
+
+/// A reference ellipsoid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ellipsoid {
+    /// The EPSG code.
+    pub code: u16,
+
+    /// The EPSG name.
+    pub name: &'static str,
+
+    /// The semi-major axis, in metres.
+    pub semi_major_axis: f64,
+
+    /// The inverse flattening.
+    pub inverse_flattening: f64,
+}
+
+/// A vertical datum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerticalDatum {
+    /// The EPSG code.
+    pub code: u16,
+
+    /// The EPSG name.
+    pub name: &'static str,
+}
+
+/// A linear unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unit {
+    /// The EPSG code.
+    pub code: u16,
+
+    /// The EPSG name.
+    pub name: &'static str,
+
+    /// The length of one unit, in metres.
+    pub metres: f64,
+}
+
+/// A map projection and its parameters.
+///
+/// Angles are in degrees, false eastings and northings are in the projected system's unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Transverse Mercator (EPSG method 9807).
+    TransverseMercator {
+        /// Latitude of natural origin.
+        latitude_of_origin: f64,
+
+        /// Longitude of natural origin.
+        central_meridian: f64,
+
+        /// Scale factor at natural origin.
+        scale_factor: f64,
+
+        /// False easting.
+        false_easting: f64,
+
+        /// False northing.
+        false_northing: f64,
+    },
+
+    /// Lambert Conic Conformal with two standard parallels (EPSG method 9802).
+    LambertConformalConic2Sp {
+        /// Latitude of the first standard parallel.
+        standard_parallel_1: f64,
+
+        /// Latitude of the second standard parallel.
+        standard_parallel_2: f64,
+
+        /// Latitude of false origin.
+        latitude_of_origin: f64,
+
+        /// Longitude of false origin.
+        central_meridian: f64,
+
+        /// Easting at false origin.
+        false_easting: f64,
+
+        /// Northing at false origin.
+        false_northing: f64,
+    },
+}
+
+const METRE: Unit = Unit {
+    code: 9001,
+    name: "metre",
+    metres: 1.,
+};
+const FOOT: Unit = Unit {
+    code: 9002,
+    name: "foot",
+    metres: 0.3048,
+};
+const US_SURVEY_FOOT: Unit = Unit {
+    code: 9003,
+    name: "US survey foot",
+    metres: 0.304800609601219,
+};
+
+const GRS_1980: Ellipsoid = Ellipsoid {
+    code: 7019,
+    name: "GRS 1980",
+    semi_major_axis: 6378137.,
+    inverse_flattening: 298.257222101,
+};
+
+const GEOGRAPHIC: &[(u16, &str, Datum)] = &[
+    (
+        4326,
+        "WGS 84",
+        Datum {
+            code: 6326,
+            name: "World Geodetic System 1984",
+            wkt1_name: "WGS_1984",
+            ellipsoid: Ellipsoid {
+                code: 7030,
+                name: "WGS 84",
+                semi_major_axis: 6378137.,
+                inverse_flattening: 298.257223563,
+            },
+        },
+    ),
+    (
+        4269,
+        "NAD83",
+        Datum {
+            code: 6269,
+            name: "North American Datum 1983",
+            wkt1_name: "North_American_Datum_1983",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        4152,
+        "NAD83(HARN)",
+        Datum {
+            code: 6152,
+            name: "NAD83 (High Accuracy Reference Network)",
+            wkt1_name: "NAD83_High_Accuracy_Reference_Network",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        6318,
+        "NAD83(2011)",
+        Datum {
+            code: 1116,
+            name: "NAD83 (National Spatial Reference System 2011)",
+            wkt1_name: "NAD83_National_Spatial_Reference_System_2011",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        4617,
+        "NAD83(CSRS)",
+        Datum {
+            code: 6140,
+            name: "NAD83 Canadian Spatial Reference System",
+            wkt1_name: "NAD83_Canadian_Spatial_Reference_System",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        4267,
+        "NAD27",
+        Datum {
+            code: 6267,
+            name: "North American Datum 1927",
+            wkt1_name: "North_American_Datum_1927",
+            ellipsoid: Ellipsoid {
+                code: 7008,
+                name: "Clarke 1866",
+                semi_major_axis: 6378206.4,
+                inverse_flattening: 294.978698213898,
+            },
+        },
+    ),
+    (
+        4258,
+        "ETRS89",
+        Datum {
+            code: 6258,
+            name: "European Terrestrial Reference System 1989",
+            wkt1_name: "European_Terrestrial_Reference_System_1989",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        4277,
+        "OSGB36",
+        Datum {
+            code: 6277,
+            name: "Ordnance Survey of Great Britain 1936",
+            wkt1_name: "OSGB_1936",
+            ellipsoid: Ellipsoid {
+                code: 7001,
+                name: "Airy 1830",
+                semi_major_axis: 6377563.396,
+                inverse_flattening: 299.3249646,
+            },
+        },
+    ),
+    (
+        4283,
+        "GDA94",
+        Datum {
+            code: 6283,
+            name: "Geocentric Datum of Australia 1994",
+            wkt1_name: "Geocentric_Datum_of_Australia_1994",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        7844,
+        "GDA2020",
+        Datum {
+            code: 1168,
+            name: "Geocentric Datum of Australia 2020",
+            wkt1_name: "Geocentric_Datum_of_Australia_2020",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        4167,
+        "NZGD2000",
+        Datum {
+            code: 6167,
+            name: "New Zealand Geodetic Datum 2000",
+            wkt1_name: "New_Zealand_Geodetic_Datum_2000",
+            ellipsoid: GRS_1980,
+        },
+    ),
+    (
+        6668,
+        "JGD2011",
+        Datum {
+            code: 1128,
+            name: "Japanese Geodetic Datum 2011",
+            wkt1_name: "Japanese_Geodetic_Datum_2011",
+            ellipsoid: GRS_1980,
+        },
+    ),
+];
+
+/// UTM zone ranges: (first code, first zone, last zone, base, name prefix, zone prefix, south).
+const UTM: &[(u16, u8, u8, u16, &str, &str, bool)] = &[
+    (32601, 1, 60, 4326, "WGS 84", "UTM zone ", false),
+    (32701, 1, 60, 4326, "WGS 84", "UTM zone ", true),
+    (26901, 1, 23, 4269, "NAD83", "UTM zone ", false),
+    (6330, 1, 19, 6318, "NAD83(2011)", "UTM zone ", false),
+    (6328, 59, 60, 6318, "NAD83(2011)", "UTM zone ", false),
+    (26701, 1, 22, 4267, "NAD27", "UTM zone ", false),
+    (25828, 28, 38, 4258, "ETRS89", "UTM zone ", false),
+    (28348, 48, 58, 4283, "GDA94", "MGA zone ", true),
+    (7846, 46, 59, 7844, "GDA2020", "MGA zone ", true),
+];
+
+const PROJECTED: &[(u16, &str, u16, Projection, Unit)] = &[
+    (
+        2992,
+        "NAD83 / Oregon GIC Lambert (ft)",
+        4269,
+        OREGON_LAMBERT_FT,
+        FOOT,
+    ),
+    (
+        2994,
+        "NAD83(HARN) / Oregon Lambert (ft)",
+        4152,
+        OREGON_LAMBERT_FT,
+        FOOT,
+    ),
+    (
+        27700,
+        "OSGB36 / British National Grid",
+        4277,
+        Projection::TransverseMercator {
+            latitude_of_origin: 49.,
+            central_meridian: -2.,
+            scale_factor: 0.9996012717,
+            false_easting: 400000.,
+            false_northing: -100000.,
+        },
+        METRE,
+    ),
+];
+
+const OREGON_LAMBERT_FT: Projection = Projection::LambertConformalConic2Sp {
+    standard_parallel_1: 43.,
+    standard_parallel_2: 45.5,
+    latitude_of_origin: 41.75,
+    central_meridian: -120.5,
+    false_easting: 1312335.958,
+    false_northing: 0.,
+};
+
+const NAVD88: VerticalDatum = VerticalDatum {
+    code: 5103,
+    name: "North American Vertical Datum 1988",
+};
+
+const VERTICAL: &[(u16, &str, VerticalDatum, Unit)] = &[
+    (5703, "NAVD88 height", NAVD88, METRE),
+    (6360, "NAVD88 height (ftUS)", NAVD88, US_SURVEY_FOOT),
+    (8228, "NAVD88 height (ft)", NAVD88, FOOT),
+    (
+        5713,
+        "CGVD28 height",
+        VerticalDatum {
+            code: 5114,
+            name: "Canadian Geodetic Vertical Datum of 1928",
+        },
+        METRE,
+    ),
+    (
+        6647,
+        "CGVD2013(CGG2013) height",
+        VerticalDatum {
+            code: 1127,
+            name: "Canadian Geodetic Vertical Datum of 2013 (CGG2013)",
+        },
+        METRE,
+    ),
+    (
+        5773,
+        "EGM96 height",
+        VerticalDatum {
+            code: 5171,
+            name: "EGM96 geoid",
+        },
+        METRE,
+    ),
+    (
+        3855,
+        "EGM2008 height",
+        VerticalDatum {
+            code: 1027,
+            name: "EGM2008 geoid",
+        },
+        METRE,
+    ),
+    (
+        5941,
+        "NN2000 height",
+        VerticalDatum {
+            code: 1096,
+            name: "Norway Normal Null 2000",
+        },
+        METRE,
+    ),
+    (
+        5701,
+        "ODN height",
+        VerticalDatum {
+            code: 5101,
+            name: "Ordnance Datum Newlyn",
+        },
+        METRE,
+    ),
+    (
+        7837,
+        "DHHN2016 height",
+        VerticalDatum {
+            code: 1170,
+            name: "Deutsches Haupthoehennetz 2016",
+        },
+        METRE,
+    ),
+    (
+        5711,
+        "AHD height",
+        VerticalDatum {
+            code: 5111,
+            name: "Australian Height Datum",
+        },
+        METRE,
+    ),
+];
+
+/// Looks up a coordinate reference system by EPSG code.
+///
+/// Returns `None` if the code isn't in the embedded subset.
+///
+/// # Examples
+///
+/// ```
+/// use las::crs::epsg::{self, Kind};
+/// let definition = epsg::get(5941).unwrap();
+/// assert!(matches!(definition.kind, Kind::Vertical { .. }));
+/// assert!(epsg::get(1).is_none());
+/// ```
+pub fn get(code: u16) -> Option<Definition> {
+    if let Some(&(_, name, datum)) = GEOGRAPHIC.iter().find(|g| g.0 == code) {
+        return Some(Definition {
+            code,
+            name: name.to_string(),
+            kind: Kind::Geographic(datum),
+        });
+    }
+    if let Some(&(_, name, base, projection, unit)) = PROJECTED.iter().find(|p| p.0 == code) {
+        return Some(Definition {
+            code,
+            name: name.to_string(),
+            kind: Kind::Projected {
+                base,
+                projection,
+                unit,
+            },
+        });
+    }
+    if let Some(&(_, name, datum, unit)) = VERTICAL.iter().find(|v| v.0 == code) {
+        return Some(Definition {
+            code,
+            name: name.to_string(),
+            kind: Kind::Vertical { datum, unit },
+        });
+    }
+    UTM.iter().find_map(
+        |&(first, first_zone, last_zone, base, prefix, zone_prefix, south)| {
+            let zone = code.checked_sub(first)? + u16::from(first_zone);
+            if zone > u16::from(last_zone) {
+                return None;
+            }
+            let hemisphere = match (south, zone_prefix) {
+                (_, "MGA zone ") => "",
+                (true, _) => "S",
+                (false, _) => "N",
+            };
+            Some(Definition {
+                code,
+                name: format!("{prefix} / {zone_prefix}{zone}{hemisphere}"),
+                kind: Kind::Projected {
+                    base,
+                    projection: Projection::TransverseMercator {
+                        latitude_of_origin: 0.,
+                        central_meridian: f64::from(zone) * 6. - 183.,
+                        scale_factor: 0.9996,
+                        false_easting: 500000.,
+                        false_northing: if south { 10000000. } else { 0. },
+                    },
+                    unit: METRE,
+                },
+            })
+        },
+    )
+}
+
+/// Produces WKT for a horizontal system and an optional vertical system.
+///
+/// With a vertical system, the WKT is a compound system. Returns an error if
+/// either code is unknown, or if they're of the wrong kinds.
+///
+/// # Examples
+///
+/// ```
+/// use las::crs::{epsg, WktVersion};
+/// let wkt = epsg::to_wkt(4326, None, WktVersion::Wkt2).unwrap();
+/// assert!(wkt.starts_with("GEOGCRS[\"WGS 84\""));
+/// assert!(wkt.ends_with("ID[\"EPSG\",4326]]"));
+/// ```
+pub fn to_wkt(horizontal: u16, vertical: Option<u16>, version: WktVersion) -> Result<String> {
+    let horizontal = horizontal_definition(horizontal)?;
+    let vertical = vertical.map(vertical_definition).transpose()?;
+    let wkt = horizontal.to_wkt(version)?;
+    if let Some(vertical) = vertical {
+        let keyword = match version {
+            WktVersion::Wkt1 => "COMPD_CS",
+            WktVersion::Wkt2 => "COMPOUNDCRS",
+        };
+        Ok(format!(
+            "{keyword}[\"{} + {}\",{},{}]",
+            horizontal.name,
+            vertical.name,
+            wkt,
+            vertical.to_wkt(version)?
+        ))
+    } else {
+        Ok(wkt)
+    }
+}
+
+/// Produces GeoTiff keys for a horizontal system and an optional vertical system.
+///
+/// Sets the CRS keys and their units. Returns an error if either code is
+/// unknown, or if they're of the wrong kinds.
+///
+/// # Examples
+///
+/// ```
+/// use las::crs::epsg;
+/// let crs = epsg::to_geotiff(25832, Some(5941)).unwrap();
+/// assert_eq!(Some(25832), crs.get_projected_crs_geo_key_value());
+/// assert_eq!(Some(5941), crs.get_vertical_crs_geo_key_value());
+/// ```
+pub fn to_geotiff(horizontal: u16, vertical: Option<u16>) -> Result<GeoTiffCrs> {
+    let horizontal = horizontal_definition(horizontal)?;
+    let vertical = vertical.map(vertical_definition).transpose()?;
+    let mut crs = match horizontal.kind {
+        Kind::Projected { unit, .. } => GeoTiffCrs::default()
+            .with_projected_crs(horizontal.code)
+            .with_linear_units(unit.code),
+        _ => GeoTiffCrs::default()
+            .with_geodetic_crs(horizontal.code)
+            .with_entry(ANGULAR_UNITS_GEO_KEY, GeoTiffData::U16(DEGREE_GEOTIFF_CODE)),
+    };
+    if let Some(Definition {
+        code,
+        kind: Kind::Vertical { unit, .. },
+        ..
+    }) = vertical
+    {
+        crs = crs.with_vertical_crs(code).with_vertical_units(unit.code);
+    }
+    Ok(crs)
+}
+
+fn horizontal_definition(code: u16) -> Result<Definition> {
+    match get(code) {
+        Some(Definition {
+            kind: Kind::Vertical { .. },
+            ..
+        }) => Err(Error::UnresolvedCrs),
+        Some(definition) => Ok(definition),
+        None => Err(Error::UnknownEpsgCode(code)),
+    }
+}
+
+fn vertical_definition(code: u16) -> Result<Definition> {
+    match get(code) {
+        Some(
+            definition @ Definition {
+                kind: Kind::Vertical { .. },
+                ..
+            },
+        ) => Ok(definition),
+        Some(_) => Err(Error::UnresolvedCrs),
+        None => Err(Error::UnknownEpsgCode(code)),
+    }
+}
+
+impl Definition {
+    /// Produces WKT for this system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{epsg, WktVersion};
+    /// let wkt = epsg::get(5703).unwrap().to_wkt(WktVersion::Wkt1).unwrap();
+    /// assert!(wkt.starts_with("VERT_CS[\"NAVD88 height\""));
+    /// ```
+    pub fn to_wkt(&self, version: WktVersion) -> Result<String> {
+        let code = self.code;
+        let name = &self.name;
+        let wkt = match (version, self.kind) {
+            (WktVersion::Wkt1, Kind::Geographic(datum)) => geogcs(code, name, datum),
+            (WktVersion::Wkt2, Kind::Geographic(datum)) => format!(
+                "GEOGCRS[\"{name}\",{},CS[ellipsoidal,2],\
+                 AXIS[\"geodetic latitude (Lat)\",north,ORDER[1],{DEGREE_WKT2}],\
+                 AXIS[\"geodetic longitude (Lon)\",east,ORDER[2],{DEGREE_WKT2}],\
+                 ID[\"EPSG\",{code}]]",
+                datum_wkt2(datum)
+            ),
+            (
+                WktVersion::Wkt1,
+                Kind::Projected {
+                    base,
+                    projection,
+                    unit,
+                },
+            ) => {
+                let (base_name, datum) = base_definition(base)?;
+                let mut parameters = String::new();
+                for parameter in projection.parameters() {
+                    parameters.push_str(&format!(
+                        "PARAMETER[\"{}\",{}],",
+                        parameter.wkt1_name, parameter.value
+                    ));
+                }
+                format!(
+                    "PROJCS[\"{name}\",{},PROJECTION[\"{}\"],{parameters}{},\
+                     AXIS[\"Easting\",EAST],AXIS[\"Northing\",NORTH],\
+                     AUTHORITY[\"EPSG\",\"{code}\"]]",
+                    geogcs(base, &base_name, datum),
+                    projection.wkt1_name(),
+                    unit_wkt1(unit),
+                )
+            }
+            (
+                WktVersion::Wkt2,
+                Kind::Projected {
+                    base,
+                    projection,
+                    unit,
+                },
+            ) => {
+                let (base_name, datum) = base_definition(base)?;
+                let length_unit = unit_wkt2(unit);
+                let mut parameters = String::new();
+                for parameter in projection.parameters() {
+                    let parameter_unit = match parameter.unit {
+                        ParameterUnit::Angle => DEGREE_WKT2.to_string(),
+                        ParameterUnit::Scale => "SCALEUNIT[\"unity\",1]".to_string(),
+                        ParameterUnit::Length => length_unit.clone(),
+                    };
+                    parameters.push_str(&format!(
+                        ",PARAMETER[\"{}\",{},{parameter_unit},ID[\"EPSG\",{}]]",
+                        parameter.wkt2_name, parameter.value, parameter.code
+                    ));
+                }
+                let (method_name, method_code) = projection.method();
+                format!(
+                    "PROJCRS[\"{name}\",BASEGEOGCRS[\"{base_name}\",{},ID[\"EPSG\",{base}]],\
+                     CONVERSION[\"{}\",METHOD[\"{method_name}\",ID[\"EPSG\",{method_code}]]{parameters}],\
+                     CS[Cartesian,2],AXIS[\"(E)\",east,ORDER[1],{length_unit}],\
+                     AXIS[\"(N)\",north,ORDER[2],{length_unit}],ID[\"EPSG\",{code}]]",
+                    datum_wkt2(datum),
+                    name.split(" / ").last().unwrap_or(name),
+                )
+            }
+            (WktVersion::Wkt1, Kind::Vertical { datum, unit }) => format!(
+                "VERT_CS[\"{name}\",VERT_DATUM[\"{}\",2005,AUTHORITY[\"EPSG\",\"{}\"]],{},\
+                 AXIS[\"Gravity-related height\",UP],AUTHORITY[\"EPSG\",\"{code}\"]]",
+                datum.name,
+                datum.code,
+                unit_wkt1(unit)
+            ),
+            (WktVersion::Wkt2, Kind::Vertical { datum, unit }) => format!(
+                "VERTCRS[\"{name}\",VDATUM[\"{}\"],CS[vertical,1],\
+                 AXIS[\"gravity-related height (H)\",up,{}],ID[\"EPSG\",{code}]]",
+                datum.name,
+                unit_wkt2(unit)
+            ),
+        };
+        Ok(wkt)
+    }
+}
+
+const DEGREE_WKT2: &str = "ANGLEUNIT[\"degree\",0.0174532925199433]";
+
+fn base_definition(base: u16) -> Result<(String, Datum)> {
+    match get(base) {
+        Some(Definition {
+            name,
+            kind: Kind::Geographic(datum),
+            ..
+        }) => Ok((name, datum)),
+        _ => Err(Error::UnknownEpsgCode(base)),
+    }
+}
+
+fn geogcs(code: u16, name: &str, datum: Datum) -> String {
+    let ellipsoid = datum.ellipsoid;
+    format!(
+        "GEOGCS[\"{name}\",DATUM[\"{}\",SPHEROID[\"{}\",{},{},AUTHORITY[\"EPSG\",\"{}\"]],\
+         AUTHORITY[\"EPSG\",\"{}\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],\
+         UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],\
+         AXIS[\"Latitude\",NORTH],AXIS[\"Longitude\",EAST],AUTHORITY[\"EPSG\",\"{code}\"]]",
+        datum.wkt1_name,
+        ellipsoid.name,
+        ellipsoid.semi_major_axis,
+        ellipsoid.inverse_flattening,
+        ellipsoid.code,
+        datum.code,
+    )
+}
+
+fn datum_wkt2(datum: Datum) -> String {
+    let ellipsoid = datum.ellipsoid;
+    format!(
+        "DATUM[\"{}\",ELLIPSOID[\"{}\",{},{},LENGTHUNIT[\"metre\",1]]],\
+         PRIMEM[\"Greenwich\",0,{DEGREE_WKT2}]",
+        datum.name, ellipsoid.name, ellipsoid.semi_major_axis, ellipsoid.inverse_flattening,
+    )
+}
+
+fn unit_wkt1(unit: Unit) -> String {
+    format!(
+        "UNIT[\"{}\",{},AUTHORITY[\"EPSG\",\"{}\"]]",
+        unit.name, unit.metres, unit.code
+    )
+}
+
+fn unit_wkt2(unit: Unit) -> String {
+    format!("LENGTHUNIT[\"{}\",{}]", unit.name, unit.metres)
+}
+
+struct Parameter {
+    wkt1_name: &'static str,
+    wkt2_name: &'static str,
+    code: u16,
+    value: f64,
+    unit: ParameterUnit,
+}
+
+enum ParameterUnit {
+    Angle,
+    Scale,
+    Length,
+}
+
+impl Projection {
+    fn wkt1_name(&self) -> &'static str {
+        match self {
+            Projection::TransverseMercator { .. } => "Transverse_Mercator",
+            Projection::LambertConformalConic2Sp { .. } => "Lambert_Conformal_Conic_2SP",
+        }
+    }
+
+    fn method(&self) -> (&'static str, u16) {
+        match self {
+            Projection::TransverseMercator { .. } => ("Transverse Mercator", 9807),
+            Projection::LambertConformalConic2Sp { .. } => ("Lambert Conic Conformal (2SP)", 9802),
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let parameter = |wkt1_name, wkt2_name, code, value, unit| Parameter {
+            wkt1_name,
+            wkt2_name,
+            code,
+            value,
+            unit,
+        };
+        match *self {
+            Projection::TransverseMercator {
+                latitude_of_origin,
+                central_meridian,
+                scale_factor,
+                false_easting,
+                false_northing,
+            } => vec![
+                parameter(
+                    "latitude_of_origin",
+                    "Latitude of natural origin",
+                    8801,
+                    latitude_of_origin,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "central_meridian",
+                    "Longitude of natural origin",
+                    8802,
+                    central_meridian,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "scale_factor",
+                    "Scale factor at natural origin",
+                    8805,
+                    scale_factor,
+                    ParameterUnit::Scale,
+                ),
+                parameter(
+                    "false_easting",
+                    "False easting",
+                    8806,
+                    false_easting,
+                    ParameterUnit::Length,
+                ),
+                parameter(
+                    "false_northing",
+                    "False northing",
+                    8807,
+                    false_northing,
+                    ParameterUnit::Length,
+                ),
+            ],
+            Projection::LambertConformalConic2Sp {
+                standard_parallel_1,
+                standard_parallel_2,
+                latitude_of_origin,
+                central_meridian,
+                false_easting,
+                false_northing,
+            } => vec![
+                parameter(
+                    "standard_parallel_1",
+                    "Latitude of 1st standard parallel",
+                    8823,
+                    standard_parallel_1,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "standard_parallel_2",
+                    "Latitude of 2nd standard parallel",
+                    8824,
+                    standard_parallel_2,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "latitude_of_origin",
+                    "Latitude of false origin",
+                    8821,
+                    latitude_of_origin,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "central_meridian",
+                    "Longitude of false origin",
+                    8822,
+                    central_meridian,
+                    ParameterUnit::Angle,
+                ),
+                parameter(
+                    "false_easting",
+                    "Easting at false origin",
+                    8826,
+                    false_easting,
+                    ParameterUnit::Length,
+                ),
+                parameter(
+                    "false_northing",
+                    "Northing at false origin",
+                    8827,
+                    false_northing,
+                    ParameterUnit::Length,
+                ),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crs::wkt::Wkt;
+
+    #[test]
+    fn utm_zones() {
+        let definition = get(32610).unwrap();
+        assert_eq!("WGS 84 / UTM zone 10N", definition.name);
+        assert_eq!(
+            Kind::Projected {
+                base: 4326,
+                projection: Projection::TransverseMercator {
+                    latitude_of_origin: 0.,
+                    central_meridian: -123.,
+                    scale_factor: 0.9996,
+                    false_easting: 500000.,
+                    false_northing: 0.,
+                },
+                unit: METRE,
+            },
+            definition.kind
+        );
+        assert_eq!("WGS 84 / UTM zone 60S", get(32760).unwrap().name);
+        assert!(get(32661).is_none());
+        assert_eq!("ETRS89 / UTM zone 32N", get(25832).unwrap().name);
+        assert_eq!("NAD83(2011) / UTM zone 10N", get(6339).unwrap().name);
+        assert_eq!("NAD83(2011) / UTM zone 59N", get(6328).unwrap().name);
+        assert_eq!("GDA2020 / MGA zone 55", get(7855).unwrap().name);
+    }
+
+    #[test]
+    fn wkt1() {
+        assert_eq!(
+            "PROJCS[\"WGS 84 / UTM zone 10N\",GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",\
+             SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",\"7030\"]],\
+             AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],\
+             UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],\
+             AXIS[\"Latitude\",NORTH],AXIS[\"Longitude\",EAST],AUTHORITY[\"EPSG\",\"4326\"]],\
+             PROJECTION[\"Transverse_Mercator\"],PARAMETER[\"latitude_of_origin\",0],\
+             PARAMETER[\"central_meridian\",-123],PARAMETER[\"scale_factor\",0.9996],\
+             PARAMETER[\"false_easting\",500000],PARAMETER[\"false_northing\",0],\
+             UNIT[\"metre\",1,AUTHORITY[\"EPSG\",\"9001\"]],AXIS[\"Easting\",EAST],\
+             AXIS[\"Northing\",NORTH],AUTHORITY[\"EPSG\",\"32610\"]]",
+            to_wkt(32610, None, WktVersion::Wkt1).unwrap()
+        );
+    }
+
+    #[test]
+    fn wkt2() {
+        let wkt = to_wkt(2994, Some(6360), WktVersion::Wkt2).unwrap();
+        assert!(wkt.starts_with(
+            "COMPOUNDCRS[\"NAD83(HARN) / Oregon Lambert (ft) + NAVD88 height (ftUS)\",PROJCRS["
+        ));
+        assert!(wkt.contains(
+            "PARAMETER[\"Easting at false origin\",1312335.958,LENGTHUNIT[\"foot\",0.3048],ID[\"EPSG\",8826]]"
+        ));
+        assert!(wkt.ends_with(
+            "VERTCRS[\"NAVD88 height (ftUS)\",VDATUM[\"North American Vertical Datum 1988\"],\
+             CS[vertical,1],AXIS[\"gravity-related height (H)\",up,\
+             LENGTHUNIT[\"US survey foot\",0.304800609601219]],ID[\"EPSG\",6360]]]"
+        ));
+    }
+
+    #[test]
+    fn parses() {
+        for version in [WktVersion::Wkt1, WktVersion::Wkt2] {
+            let wkt: Wkt = to_wkt(2994, Some(6360), version).unwrap().parse().unwrap();
+            assert_eq!(version, wkt.version);
+            assert_eq!(Some(2994), wkt.horizontal_epsg());
+            assert_eq!(Some(6360), wkt.vertical_epsg());
+            assert_eq!("foot", wkt.linear_unit().unwrap().name);
+            assert_eq!("US survey foot", wkt.vertical_unit().unwrap().name);
+        }
+    }
+
+    #[test]
+    fn wrong_kinds() {
+        assert!(matches!(
+            to_wkt(1, None, WktVersion::Wkt1),
+            Err(Error::UnknownEpsgCode(1))
+        ));
+        assert!(to_wkt(5703, None, WktVersion::Wkt1).is_err());
+        assert!(to_geotiff(4326, Some(4326)).is_err());
+    }
+
+    #[test]
+    fn geotiff() {
+        let crs = to_geotiff(4326, Some(3855)).unwrap();
+        assert_eq!(Some(2), crs.get_gt_model_type_geo_key_value());
+        assert_eq!(Some(4326), crs.get_geodetic_crs_geo_key_value());
+        assert_eq!(Some(3855), crs.get_vertical_crs_geo_key_value());
+    }
+}
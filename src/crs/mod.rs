@@ -0,0 +1,1042 @@
+//! Module for handling Coordinate Reference System (CRS) data in a headers variable length records
+//!
+//! CRSes are stored either as [WKT](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry) or as [GeoTiff tags](https://docs.ogc.org/is/19-008r4/19-008r4.html).
+//! Use [Header::get_wkt_crs_bytes] or [Header::get_geotiff_crs] respectively to read the crs-data from the header's (E)VLRs.
+//! The returned objects are not CRS-aware, they have only parsed the data available in the CRS-(E)VLRs.
+//! [Header::get_crs] returns either representation as a [Crs], which can report the EPSG codes it refers to.
+//! [Header::get_wkt_crs] parses WKT1 or WKT2 into a typed [wkt::Wkt] tree.
+//!
+//! Write WKT with [Header::set_wkt_crs] (las 1.4 only) or GeoTiff with [Header::set_geotiff_crs].
+//!
+//! With the `epsg` feature, the [epsg] module embeds a subset of the EPSG registry.
+//! It turns EPSG codes into WKT or GeoTiff keys, and [Header::set_crs] uses it to
+//! convert a [Crs] to whichever representation the header's version and point format need.
+//! For codes outside of the subset, use the [las-crs](https://docs.rs/las-crs/latest/las_crs) crate.
+
+#[cfg(feature = "epsg")]
+pub mod epsg;
+pub mod wkt;
+
+use self::wkt::Wkt;
+use crate::{
+    vlr::{GeoAsciiParams, GeoDoubleParams, GeoKeyDirectory, GeoKeyEntry, KnownVlr},
+    Error, Header, Result, Vlr,
+};
+
+const MAIN_VLR_ID: u16 = 34735;
+const DOUBLE_VLR_ID: u16 = 34736;
+const ASCII_VLR_ID: u16 = 34737;
+
+const GT_MODEL_TYPE_GEO_KEY: u16 = 1024;
+const GEODETIC_CRS_GEO_KEY: u16 = 2048;
+const PROJECTED_CRS_GEO_KEY: u16 = 3072;
+const PROJ_LINEAR_UNITS_GEO_KEY: u16 = 3076;
+const VERTICAL_CRS_GEO_KEY: u16 = 4096;
+const VERTICAL_UNITS_GEO_KEY: u16 = 4099;
+
+const MODEL_TYPE_PROJECTED: u16 = 1;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+
+const USER_DEFINED_GEO_KEY_VALUE: u16 = 32767;
+
+impl Header {
+    /// Removes all CRS (E)VLRs from the header
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut header = reader.header().clone();
+    /// header.remove_crs_vlrs();
+    /// assert!(!header.has_crs_vlrs());
+    /// ```
+    pub fn remove_crs_vlrs(&mut self) {
+        self.vlrs.retain(|v| !v.is_crs());
+        self.evlrs.retain(|v| !v.is_crs());
+        self.has_wkt_crs = false;
+    }
+
+    /// Adds a WKT CRS VLR to the header
+    ///
+    /// Returns Err if the header already contains CRS (E)VLRs or the Las version is below 1.4.
+    ///
+    /// The WKT bytes can be obtained from a horizontal EPSG code by using the [crs-definitions](https://docs.rs/crs-definitions/latest/crs_definitions/) crate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Version};
+    /// let mut builder = Builder::from(Version::new(1, 4));
+    /// let mut header = builder.into_header().unwrap();
+    /// let wkt_bytes = b"PROJCS[\"WGS 84\"]".to_vec();
+    /// header.set_wkt_crs(wkt_bytes).unwrap();
+    /// assert!(header.has_crs_vlrs());
+    /// ```
+    pub fn set_wkt_crs(&mut self, wkt_crs_bytes: Vec<u8>) -> Result<()> {
+        if self.version() < crate::Version::new(1, 4) {
+            return Err(Error::UnsupportedFeature {
+                version: self.version(),
+                feature: "WKT CRS VLR",
+            });
+        }
+
+        if self.all_vlrs().any(|v| v.is_crs()) {
+            log::warn!("Header already contains CRL VLR, removing");
+            self.remove_crs_vlrs();
+        }
+
+        let num_bytes = wkt_crs_bytes.len();
+        let vlr = Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 2112,
+            description: String::new(),
+            data: wkt_crs_bytes,
+        };
+        if num_bytes > u16::MAX as usize {
+            self.evlrs.push(vlr);
+        } else {
+            self.vlrs.push(vlr);
+        };
+        self.has_wkt_crs = true;
+
+        Ok(())
+    }
+
+    /// Gets the WKT-CRS-data if the WKT-CRS (E)VLR exists
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header();
+    /// if let Some(wkt_bytes) = header.get_wkt_crs_bytes() {
+    ///     println!("WKT CRS data: {} bytes", wkt_bytes.len());
+    /// }
+    /// ```
+    pub fn get_wkt_crs_bytes(&self) -> Option<&[u8]> {
+        self.all_vlrs()
+            .find(|&v| v.is_wkt_crs())
+            .map(|cv| cv.data.as_slice())
+    }
+
+    /// Gets all the GeoTiff CRS data if the GeoTiff-CRS (E)VLR(s) exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header();
+    /// match header.get_geotiff_crs() {
+    ///     Ok(Some(geotiff_crs)) => {
+    ///         println!("Found {} GeoTIFF key entries", geotiff_crs.entries.len());
+    ///     }
+    ///     Ok(None) => println!("No GeoTIFF CRS data"),
+    ///     Err(e) => eprintln!("Error reading GeoTIFF CRS: {}", e),
+    /// }
+    /// ```
+    pub fn get_geotiff_crs(&self) -> Result<Option<GeoTiffCrs>> {
+        let mut directory = None;
+        let mut doubles = None;
+        let mut ascii = None;
+        for vlr in self.all_vlrs().filter(|&v| v.is_geotiff_crs()) {
+            match vlr.record_id {
+                MAIN_VLR_ID => directory = Some(vlr.decode_as::<GeoKeyDirectory>()?),
+                DOUBLE_VLR_ID => doubles = Some(vlr.decode_as::<GeoDoubleParams>()?),
+                ASCII_VLR_ID => ascii = Some(vlr.decode_as::<GeoAsciiParams>()?),
+                _ => continue,
+            };
+        }
+        directory
+            .map(|directory| GeoTiffCrs::from_vlrs(&directory, doubles.as_ref(), ascii.as_ref()))
+            .transpose()
+    }
+
+    /// Gets the header's CRS, if it has one.
+    ///
+    /// Prefers the WKT (E)VLR if the header's WKT bit is set or if there are no
+    /// GeoTiff VLRs, and the GeoTiff VLRs otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let crs = reader.header().get_crs().unwrap().unwrap();
+    /// assert_eq!(Some(2994), crs.horizontal_epsg());
+    /// ```
+    pub fn get_crs(&self) -> Result<Option<Crs>> {
+        let wkt = self.get_wkt_crs_bytes().map(|bytes| {
+            let bytes = bytes.split(|&b| b == 0).next().unwrap_or_default();
+            String::from_utf8_lossy(bytes).into_owned()
+        });
+        if (self.has_wkt_crs() || self.point_format().is_extended)
+            && let Some(wkt) = wkt
+        {
+            return Ok(Some(Crs::Wkt(wkt)));
+        }
+        match self.get_geotiff_crs()? {
+            Some(geotiff) => Ok(Some(Crs::GeoTiff(geotiff))),
+            None => Ok(wkt.map(Crs::Wkt)),
+        }
+    }
+
+    /// Replaces the header's CRS, converting it to the representation the header needs.
+    ///
+    /// Extended point formats get WKT, and las versions before 1.4 get GeoTiff.
+    /// Las 1.4 headers with legacy point formats keep GeoTiff input as GeoTiff
+    /// and get WKT otherwise. WKT is written as WKT1, as the las specification
+    /// requires, so WKT2 input is converted through its EPSG codes. Converting
+    /// needs the `epsg` feature, and returns an error if it needs an EPSG code
+    /// that isn't in the embedded subset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "epsg")]
+    /// # {
+    /// use las::{crs::Crs, Builder, Version};
+    /// let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+    /// header.set_crs(&Crs::Epsg { horizontal: 32610, vertical: Some(5703) }).unwrap();
+    /// assert!(header.get_geotiff_crs().unwrap().is_some());
+    /// # }
+    /// ```
+    pub fn set_crs(&mut self, crs: &Crs) -> Result<()> {
+        let wkt = self.point_format().is_extended
+            || (self.version() >= crate::Version::new(1, 4) && !matches!(crs, Crs::GeoTiff(_)));
+        match (wkt, crs) {
+            (true, Crs::Wkt(wkt)) if wkt.parse::<Wkt>()?.version == WktVersion::Wkt1 => {
+                self.set_wkt_crs(wkt.clone().into_bytes())
+            }
+            (false, Crs::GeoTiff(geotiff)) => self.set_geotiff_crs(geotiff.clone()),
+            #[cfg(feature = "epsg")]
+            (true, _) => self.set_wkt_crs(crs.to_wkt(WktVersion::Wkt1)?.into_bytes()),
+            #[cfg(feature = "epsg")]
+            (false, _) => self.set_geotiff_crs(crs.to_geotiff()?),
+            #[cfg(not(feature = "epsg"))]
+            _ => Err(Error::UnresolvedCrs),
+        }
+    }
+
+    /// Replaces the header's CRS with GeoTiff CRS VLRs.
+    ///
+    /// Writes the GeoKeyDirectory (34735) VLR, plus the double params (34736)
+    /// and ASCII params (34737) VLRs if any key needs them. Works for every las
+    /// version, but returns Err for point formats that require a WKT CRS.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::GeoTiffCrs, Builder, Version};
+    /// let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+    /// let crs = GeoTiffCrs::default()
+    ///     .with_projected_crs(25832)
+    ///     .with_vertical_crs(5941);
+    /// header.set_geotiff_crs(crs).unwrap();
+    /// let crs = header.get_geotiff_crs().unwrap().unwrap();
+    /// assert_eq!(Some(25832), crs.get_projected_crs_geo_key_value());
+    /// ```
+    pub fn set_geotiff_crs(&mut self, crs: GeoTiffCrs) -> Result<()> {
+        if self.point_format().is_extended {
+            return Err(Error::WktRequired(*self.point_format()));
+        }
+        if self.all_vlrs().any(|v| v.is_crs()) {
+            log::warn!("Header already contains CRS VLR, removing");
+            self.remove_crs_vlrs();
+        }
+        self.vlrs.extend(crs.to_vlrs()?);
+        Ok(())
+    }
+}
+
+/// A coordinate reference system, as stored in a header or as EPSG codes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Crs {
+    /// EPSG codes for a horizontal system and an optional vertical system.
+    Epsg {
+        /// The EPSG code of the geographic or projected system.
+        horizontal: u16,
+
+        /// The EPSG code of the vertical system.
+        vertical: Option<u16>,
+    },
+
+    /// Well-known text, from a WKT (E)VLR.
+    Wkt(String),
+
+    /// GeoTiff keys, from the GeoTiff VLRs.
+    GeoTiff(GeoTiffCrs),
+}
+
+/// A version of the well-known text format for CRSes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WktVersion {
+    /// WKT1, as specified by OGC 01-009 and used by las 1.4.
+    Wkt1,
+
+    /// WKT2, as specified by OGC 18-010.
+    Wkt2,
+}
+
+impl Crs {
+    /// Returns the EPSG code of the horizontal system, if there is one.
+    ///
+    /// For WKT, this is the `AUTHORITY` or `ID` of the root system, or of the
+    /// horizontal part of a compound system. For GeoTiff, it is the projected
+    /// CRS key, or the geodetic CRS key if there's no projected one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::Crs;
+    /// let crs = Crs::Wkt(r#"GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]]"#.to_string());
+    /// assert_eq!(Some(4326), crs.horizontal_epsg());
+    /// ```
+    pub fn horizontal_epsg(&self) -> Option<u16> {
+        match self {
+            Crs::Epsg { horizontal, .. } => Some(*horizontal),
+            Crs::Wkt(wkt) => wkt.parse::<Wkt>().ok()?.horizontal_epsg(),
+            Crs::GeoTiff(geotiff) => geotiff
+                .get_projected_crs_geo_key_value()
+                .filter(|&code| is_epsg_code(code))
+                .or_else(|| {
+                    geotiff
+                        .get_geodetic_crs_geo_key_value()
+                        .filter(|&code| is_epsg_code(code))
+                }),
+        }
+    }
+
+    /// Returns the EPSG code of the vertical system, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{Crs, GeoTiffCrs};
+    /// let crs = Crs::GeoTiff(GeoTiffCrs::default().with_vertical_crs(5703));
+    /// assert_eq!(Some(5703), crs.vertical_epsg());
+    /// ```
+    pub fn vertical_epsg(&self) -> Option<u16> {
+        match self {
+            Crs::Epsg { vertical, .. } => *vertical,
+            Crs::Wkt(wkt) => wkt.parse::<Wkt>().ok()?.vertical_epsg(),
+            Crs::GeoTiff(geotiff) => geotiff
+                .get_vertical_crs_geo_key_value()
+                .filter(|&code| is_epsg_code(code)),
+        }
+    }
+
+    /// Converts this CRS to WKT.
+    ///
+    /// WKT of the requested version is returned unchanged. WKT of the other
+    /// version, EPSG codes and GeoTiff keys are looked up in the embedded
+    /// [epsg] subset by their EPSG codes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{Crs, WktVersion};
+    /// let crs = Crs::Epsg { horizontal: 25832, vertical: None };
+    /// let wkt = crs.to_wkt(WktVersion::Wkt2).unwrap();
+    /// assert!(wkt.starts_with(r#"PROJCRS["ETRS89 / UTM zone 32N""#));
+    /// ```
+    #[cfg(feature = "epsg")]
+    pub fn to_wkt(&self, version: WktVersion) -> Result<String> {
+        if let Crs::Wkt(wkt) = self
+            && wkt.parse::<Wkt>()?.version == version
+        {
+            return Ok(wkt.clone());
+        }
+        let horizontal = self.horizontal_epsg().ok_or(Error::UnresolvedCrs)?;
+        epsg::to_wkt(horizontal, self.vertical_epsg(), version)
+    }
+
+    /// Converts this CRS to GeoTiff keys.
+    ///
+    /// GeoTiff keys are returned unchanged. EPSG codes, and the codes WKT refers
+    /// to, are looked up in the embedded [epsg] subset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::Crs;
+    /// let crs = Crs::Wkt(r#"GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]]"#.to_string());
+    /// let geotiff = crs.to_geotiff().unwrap();
+    /// assert_eq!(Some(4326), geotiff.get_geodetic_crs_geo_key_value());
+    /// ```
+    #[cfg(feature = "epsg")]
+    pub fn to_geotiff(&self) -> Result<GeoTiffCrs> {
+        if let Crs::GeoTiff(geotiff) = self {
+            return Ok(geotiff.clone());
+        }
+        let horizontal = self.horizontal_epsg().ok_or(Error::UnresolvedCrs)?;
+        epsg::to_geotiff(horizontal, self.vertical_epsg())
+    }
+}
+
+fn is_epsg_code(code: u16) -> bool {
+    code != 0 && code != USER_DEFINED_GEO_KEY_VALUE
+}
+
+/// Struct for the GeoTiff CRS data
+///
+/// Build one for writing with the `with_*` methods:
+///
+/// ```
+/// use las::crs::GeoTiffCrs;
+/// let crs = GeoTiffCrs::default()
+///     .with_projected_crs(25832)
+///     .with_linear_units(9001);
+/// assert_eq!(Some(1), crs.get_gt_model_type_geo_key_value());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeoTiffCrs {
+    /// The GeoTiff key entries
+    pub entries: Vec<GeoTiffKeyEntry>,
+}
+
+impl GeoTiffCrs {
+    /// Get the projected CRS geo key value if it exists.
+    ///
+    /// From the [GeoTiff spec](https://www.ogc.org/standards/geotiff/):
+    ///
+    /// > The ProjectedCRSGeoKey SHALL have type u16. \
+    /// > ProjectedCRSGeoKey values in the range `1024..=32766` SHALL be EPSG projected CRS codes.
+    /// >
+    /// > A value of `0` SHALL indicate intentionally omitted parameters. \
+    /// > Values `1..=1023` are reserved. \
+    /// > A value of `32767` indicates a user-defined projected crs component
+    /// > (please read chapters 7.4.2 and 7.5 of the GeoTiff spec for more info). \
+    /// > Values `32768..` SHALL be considered private.
+    pub fn get_projected_crs_geo_key_value(&self) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|gk| gk.is_projected_crs_geo_key())
+            .and_then(|gk| {
+                if let GeoTiffData::U16(k) = gk.data {
+                    Some(k)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Get the geodetic (or geocentric) CRS geo key value if it exists.
+    ///
+    /// From the [GeoTiff spec](https://www.ogc.org/standards/geotiff/):
+    ///
+    /// > The GeodeticCRSGeoKey SHALL have type u16. \
+    /// > GeodeticCRSGeoKey values in the range `1024..=32766` SHALL be EPSG geographic 2D or geocentric CRS codes.
+    /// >
+    /// > A value of `0` SHALL indicate intentionally omitted parameters. \
+    /// > Values `1..=1023` are reserved. \
+    /// > A value of `32767` indicates a user-defined geographic crs component
+    /// > (please read chapters 7.4.3 and 7.5 of the GeoTiff spec for more info). \
+    /// > Values `32768..` SHALL be considered private.
+    pub fn get_geodetic_crs_geo_key_value(&self) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|gk| gk.is_geodetic_crs_geo_key())
+            .and_then(|gk| {
+                if let GeoTiffData::U16(k) = gk.data {
+                    Some(k)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Get the vertical CRS geo key value if it exists.
+    ///
+    /// From the [GeoTiff spec](https://www.ogc.org/standards/geotiff/):
+    ///
+    /// > The VerticalCRSGeoKey SHALL have type u16. \
+    /// > VerticalCRSGeoKey values in the range `1024..=32766` SHALL be EPSG Vertical CRS Codes or EPSG geographic 3D CRS codes.
+    /// >
+    /// > A value of `0` SHALL indicate intentionally omitted parameters. \
+    /// > Values `1..=1023` are reserved. \
+    /// > A value of `32767` indicates a user-defined vertical crs component
+    /// > (please read chapters 7.4.4 and 7.5 of the GeoTiff spec for more info). \
+    /// > Values `32768..` SHALL be considered private.
+    pub fn get_vertical_crs_geo_key_value(&self) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|gk| gk.is_vertical_crs_geo_key())
+            .and_then(|gk| {
+                if let GeoTiffData::U16(k) = gk.data {
+                    Some(k)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Get the GTModelTypeGeoKey value if it exists.
+    ///
+    /// From the [GeoTiff spec](https://www.ogc.org/standards/geotiff/):
+    ///
+    /// > The GTModelTypeGeoKey value SHALL be:
+    /// > * `0` to indicate that the Model CRS in undefined or unknown
+    /// > * `1` to indicate that the Model CRS is a 2D projected coordinate reference system, indicated by the value of the ProjectedCRSGeoKey
+    /// > * `2` to indicate that the Model CRS is a geographic 2D coordinate reference system, indicated by the value of the GeodeticCRSGeoKey
+    /// > * `3` to indicate that the Model CRS is a geocentric Cartesian 3D coordinate reference system, indicated by the value of the GeodeticCRSGeoKey
+    /// > * `32767` to indicate that the Model CRS type is user-defined.
+    pub fn get_gt_model_type_geo_key_value(&self) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|gk| gk.is_gt_model_type_geo_key())
+            .and_then(|gk| {
+                if let GeoTiffData::U16(k) = gk.data {
+                    Some(k)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Sets the projected CRS geo key to an EPSG code and the model type to projected.
+    pub fn with_projected_crs(self, epsg: u16) -> Self {
+        self.with_u16(GT_MODEL_TYPE_GEO_KEY, MODEL_TYPE_PROJECTED)
+            .with_u16(PROJECTED_CRS_GEO_KEY, epsg)
+    }
+
+    /// Sets the geodetic CRS geo key to an EPSG code.
+    ///
+    /// Sets the model type to geographic, unless it is already projected.
+    pub fn with_geodetic_crs(self, epsg: u16) -> Self {
+        let crs = if self.get_gt_model_type_geo_key_value() == Some(MODEL_TYPE_PROJECTED) {
+            self
+        } else {
+            self.with_u16(GT_MODEL_TYPE_GEO_KEY, MODEL_TYPE_GEOGRAPHIC)
+        };
+        crs.with_u16(GEODETIC_CRS_GEO_KEY, epsg)
+    }
+
+    /// Sets the vertical CRS geo key to an EPSG code.
+    pub fn with_vertical_crs(self, epsg: u16) -> Self {
+        self.with_u16(VERTICAL_CRS_GEO_KEY, epsg)
+    }
+
+    /// Sets the linear units of the projected CRS to an EPSG unit code, e.g. `9001` for metres.
+    pub fn with_linear_units(self, epsg: u16) -> Self {
+        self.with_u16(PROJ_LINEAR_UNITS_GEO_KEY, epsg)
+    }
+
+    /// Sets the units of the vertical CRS to an EPSG unit code, e.g. `9001` for metres.
+    pub fn with_vertical_units(self, epsg: u16) -> Self {
+        self.with_u16(VERTICAL_UNITS_GEO_KEY, epsg)
+    }
+
+    /// Sets a key, replacing any existing entry with the same id.
+    ///
+    /// Entries are kept sorted by id, as the GeoTiff spec requires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoTiffCrs, GeoTiffData};
+    /// // GTCitationGeoKey
+    /// let crs = GeoTiffCrs::default().with_entry(1026, GeoTiffData::String("my crs|".to_string()));
+    /// ```
+    pub fn with_entry(mut self, id: u16, data: GeoTiffData) -> Self {
+        match self.entries.binary_search_by_key(&id, |entry| entry.id) {
+            Ok(i) => self.entries[i].data = data,
+            Err(i) => self.entries.insert(i, GeoTiffKeyEntry { id, data }),
+        }
+        self
+    }
+
+    fn with_u16(self, id: u16, value: u16) -> Self {
+        self.with_entry(id, GeoTiffData::U16(value))
+    }
+
+    /// Encodes these entries as GeoKeyDirectory, double params and ASCII params VLRs.
+    ///
+    /// Entries are written in order. Strings are written as Latin-1 and get a
+    /// `|` terminator if they don't already end with one.
+    fn to_vlrs(&self) -> Result<Vec<Vlr>> {
+        let too_long = |n: usize| u16::try_from(n).map_err(|_| Error::VlrTooLong(n));
+        let mut keys = Vec::with_capacity(self.entries.len());
+        let mut doubles = GeoDoubleParams::default();
+        let mut ascii = GeoAsciiParams::default();
+        let mut ascii_len = 0;
+        for entry in &self.entries {
+            let (location, count, value_offset) = match &entry.data {
+                GeoTiffData::U16(value) => (0, 1, *value),
+                GeoTiffData::Doubles(values) => {
+                    let offset = too_long(doubles.values.len())?;
+                    doubles.values.extend_from_slice(values);
+                    (DOUBLE_VLR_ID, too_long(values.len())?, offset)
+                }
+                GeoTiffData::String(string) => {
+                    if string.chars().any(|c| u8::try_from(c).is_err()) {
+                        return Err(Error::NotAscii(string.clone()));
+                    }
+                    // Offsets count Latin-1 bytes, i.e. chars
+                    let offset = too_long(ascii_len)?;
+                    let mut count = string.chars().count();
+                    ascii.text.push_str(string);
+                    if !string.ends_with('|') {
+                        ascii.text.push('|');
+                        count += 1;
+                    }
+                    ascii_len += count;
+                    (ASCII_VLR_ID, too_long(count)?, offset)
+                }
+            };
+            keys.push(GeoKeyEntry {
+                id: entry.id,
+                location,
+                count,
+                value_offset,
+            });
+        }
+
+        let directory = GeoKeyDirectory {
+            key_directory_version: 1,
+            key_revision: 1,
+            minor_revision: 0,
+            keys,
+        };
+        let mut vlrs = vec![directory.encode()?];
+        if !doubles.values.is_empty() {
+            vlrs.push(doubles.encode()?);
+        }
+        if !ascii.text.is_empty() {
+            vlrs.push(ascii.encode()?);
+        }
+        Ok(vlrs)
+    }
+
+    fn from_vlrs(
+        directory: &GeoKeyDirectory,
+        doubles: Option<&GeoDoubleParams>,
+        ascii: Option<&GeoAsciiParams>,
+    ) -> Result<Self> {
+        // Validate GeoTIFF header values according to spec
+        if directory.key_directory_version != 1
+            || directory.key_revision != 1
+            || directory.minor_revision > 1
+        {
+            return Err(Error::InvalidGeoTiffHeader {
+                expected_version: 1,
+                actual_version: directory.key_directory_version,
+                expected_revision: 1,
+                actual_revision: directory.key_revision,
+                expected_minor: 1,
+                actual_minor: directory.minor_revision,
+            });
+        }
+        let entries = directory
+            .keys
+            .iter()
+            .map(|key| GeoTiffKeyEntry::from_key(key, doubles, ascii))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(GeoTiffCrs { entries })
+    }
+}
+
+/// GeoTiff data enum
+/// GeoTiff data can either be a u16, an ascii string or sequence of f64
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GeoTiffData {
+    /// a single u16
+    U16(u16),
+    /// an ascii string
+    String(String),
+    /// a sequence of f64
+    Doubles(Vec<f64>),
+}
+
+/// A single GeoTiff key entry
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeoTiffKeyEntry {
+    /// The Id of the entry
+    pub id: u16,
+    /// The data in this entry
+    pub data: GeoTiffData,
+}
+
+impl GeoTiffKeyEntry {
+    /// Is this key the geotiff model type geo key?
+    pub fn is_gt_model_type_geo_key(&self) -> bool {
+        self.id == 1024
+    }
+
+    /// Is this key entry a geodetic crs geo key?
+    pub fn is_geodetic_crs_geo_key(&self) -> bool {
+        self.id == 2048
+    }
+
+    /// Is this key entry a projected crs geo key?
+    pub fn is_projected_crs_geo_key(&self) -> bool {
+        self.id == 3072
+    }
+
+    /// Is this key entry a vertical crs geo key?
+    pub fn is_vertical_crs_geo_key(&self) -> bool {
+        self.id == 4096
+    }
+
+    fn from_key(
+        key: &GeoKeyEntry,
+        doubles: Option<&GeoDoubleParams>,
+        ascii: Option<&GeoAsciiParams>,
+    ) -> Result<Self> {
+        // The offset is an index into the params, not a byte offset
+        let range =
+            usize::from(key.value_offset)..usize::from(key.value_offset) + usize::from(key.count);
+        let data = match key.location {
+            0 => GeoTiffData::U16(key.value_offset),
+            DOUBLE_VLR_ID => GeoTiffData::Doubles(
+                doubles
+                    .and_then(|doubles| doubles.values.get(range))
+                    .ok_or(Error::UnreadableGeoTiffCrs)?
+                    .to_vec(),
+            ),
+            ASCII_VLR_ID => {
+                // The text is Latin-1, so each byte is one char
+                let text = ascii.ok_or(Error::UnreadableGeoTiffCrs)?.text.chars();
+                let string: String = text.skip(range.start).take(range.len()).collect();
+                if string.chars().count() != range.len() {
+                    return Err(Error::UnreadableGeoTiffCrs);
+                }
+                GeoTiffData::String(string)
+            }
+            _ => return Err(Error::UndefinedDataForGeoTiffKey(key.id)),
+        };
+        Ok(GeoTiffKeyEntry { id: key.id, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Reader, Version, Writer};
+    use std::io::Cursor;
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_get_epsg_crs_wkt_vlr_autzen() {
+        let reader = Reader::from_path("tests/data/autzen.copc.laz").expect("Cannot open reader");
+        let crs = reader
+            .header()
+            .get_wkt_crs_bytes()
+            .expect("Could not get WKT bytes");
+
+        let crs_str = String::from_utf8_lossy(crs);
+        let (horizontal_component, vertical_component) = crs_str.split_once("VERT_CS").unwrap();
+
+        // NAD83 / Oregon GIC Lambert (ft)
+        let horizontal_crs = "AUTHORITY[\"EPSG\",\"2992\"]";
+        assert!(horizontal_component.contains(horizontal_crs));
+
+        // NAVD88 height (ftUS)
+        let vertical_crs = "AUTHORITY[\"EPSG\",\"6360\"]";
+        assert!(vertical_component.contains(vertical_crs));
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_get_epsg_crs_geotiff_vlr_norway() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        let crs = reader.header().get_geotiff_crs().unwrap().unwrap();
+
+        let horizontal = crs
+            .get_projected_crs_geo_key_value()
+            .expect("No projected crs geo key found");
+        let vertical = crs
+            .get_vertical_crs_geo_key_value()
+            .expect("No vertical crs geo key found");
+
+        assert!(horizontal == 25832);
+        assert!(vertical == 5941);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_remove_crs_vlrs() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        let mut header = reader.header().to_owned();
+        header.remove_crs_vlrs();
+
+        if header.all_vlrs().any(|vlr| vlr.is_crs()) {
+            panic!("CRS VLRs are still in the header")
+        }
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_write_crs_vlr_las_v1_4() {
+        let reader = Reader::from_path("tests/data/autzen.copc.laz").expect("Cannot open reader");
+        let mut header = reader.header().to_owned();
+        // remove the current crs vlr(s)
+        header.remove_crs_vlrs();
+
+        let random_bytes =
+            "Test bytes. Just seeing if writing and reading is consistent:)".as_bytes();
+
+        // add a new crs vlr (not the correct one, but does not matter)
+        header
+            .set_wkt_crs(random_bytes.to_vec())
+            .expect("Could not add wkt crs vlr");
+
+        let read_bytes = header.get_wkt_crs_bytes().unwrap();
+
+        assert!(read_bytes == random_bytes);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_write_geotiff_crs_norway() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        let crs = reader.header().get_geotiff_crs().unwrap().unwrap();
+
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        header.set_geotiff_crs(crs.clone()).unwrap();
+        assert_eq!(crs, header.get_geotiff_crs().unwrap().unwrap());
+
+        // The key directory and ascii params are byte-for-byte identical. The
+        // original double params hold values no key points to, so they aren't.
+        for record_id in [MAIN_VLR_ID, ASCII_VLR_ID] {
+            let data = |header: &Header| {
+                header
+                    .all_vlrs()
+                    .find(|vlr| vlr.is_geotiff_crs() && vlr.record_id == record_id)
+                    .unwrap()
+                    .data
+                    .clone()
+            };
+            assert_eq!(data(reader.header()), data(&header));
+        }
+    }
+
+    #[test]
+    fn test_write_geotiff_crs_las_v1_2() {
+        let crs = GeoTiffCrs::default()
+            .with_vertical_crs(5941)
+            .with_projected_crs(25832)
+            .with_linear_units(9001)
+            .with_vertical_units(9001)
+            .with_entry(3073, GeoTiffData::String("UTM sone 32".to_string()))
+            .with_entry(4097, GeoTiffData::String("NN2000|".to_string()))
+            .with_entry(2057, GeoTiffData::Doubles(vec![6378137.0]))
+            .with_entry(2059, GeoTiffData::Doubles(vec![298.257222101]));
+        let ids: Vec<u16> = crs.entries.iter().map(|entry| entry.id).collect();
+        assert_eq!(
+            vec![1024, 2057, 2059, 3072, 3073, 3076, 4096, 4097, 4099],
+            ids
+        );
+
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        assert!(header.set_wkt_crs(b"ignored".to_vec()).is_err());
+        header.set_geotiff_crs(crs.clone()).unwrap();
+        assert_eq!(3, header.vlrs().len());
+        assert!(!header.has_wkt_crs());
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.write_point(Default::default()).unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        cursor.set_position(0);
+        let reader = Reader::new(cursor).unwrap();
+        let read = reader.header().get_geotiff_crs().unwrap().unwrap();
+        assert_eq!(Some(25832), read.get_projected_crs_geo_key_value());
+        assert_eq!(Some(5941), read.get_vertical_crs_geo_key_value());
+        assert_eq!(
+            GeoTiffData::String("UTM sone 32|".to_string()),
+            read.entries[4].data
+        );
+        assert_eq!(
+            GeoTiffData::Doubles(vec![298.257222101]),
+            read.entries[2].data
+        );
+    }
+
+    #[test]
+    fn test_geotiff_latin1_strings() {
+        let crs = GeoTiffCrs::default()
+            .with_entry(1026, GeoTiffData::String("Zürich|".to_string()))
+            .with_entry(3073, GeoTiffData::String("UTM|".to_string()));
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        header.set_geotiff_crs(crs.clone()).unwrap();
+        assert_eq!(crs, header.get_geotiff_crs().unwrap().unwrap());
+    }
+
+    #[test]
+    fn test_geotiff_model_type() {
+        let crs = GeoTiffCrs::default().with_geodetic_crs(4326);
+        assert_eq!(Some(2), crs.get_gt_model_type_geo_key_value());
+        let crs = GeoTiffCrs::default()
+            .with_projected_crs(32610)
+            .with_geodetic_crs(4326);
+        assert_eq!(Some(1), crs.get_gt_model_type_geo_key_value());
+        assert_eq!(Some(4326), crs.get_geodetic_crs_geo_key_value());
+    }
+
+    #[test]
+    fn test_write_geotiff_crs_replaces_and_requires_legacy_format() {
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        header
+            .set_geotiff_crs(GeoTiffCrs::default().with_projected_crs(32610))
+            .unwrap();
+        header
+            .set_geotiff_crs(GeoTiffCrs::default().with_projected_crs(32611))
+            .unwrap();
+        assert_eq!(1, header.vlrs().len());
+
+        let mut builder = Builder::from(Version::new(1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut header = builder.into_header().unwrap();
+        assert!(header.set_geotiff_crs(GeoTiffCrs::default()).is_err());
+    }
+
+    #[test]
+    fn test_write_crs_vlr_las_v1_2() {
+        let reader = Reader::from_path("tests/data/autzen.las").expect("Cannot open reader");
+        let mut header = reader.header().to_owned();
+        // remove the current crs vlr(s)
+        header.remove_crs_vlrs();
+
+        // try to add a new crs vlr (not supported below las 1.4)
+        let res = header.set_wkt_crs("just some bytes".as_bytes().to_vec());
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_get_crs_autzen() {
+        let reader = Reader::from_path("tests/data/autzen.las").expect("Cannot open reader");
+        let crs = reader.header().get_crs().unwrap().unwrap();
+        assert!(matches!(crs, Crs::GeoTiff(_)));
+        assert_eq!(Some(2994), crs.horizontal_epsg());
+        assert_eq!(None, crs.vertical_epsg());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn test_get_crs_norway() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        let crs = reader.header().get_crs().unwrap().unwrap();
+        assert_eq!(Some(25832), crs.horizontal_epsg());
+        assert_eq!(Some(5941), crs.vertical_epsg());
+    }
+
+    #[test]
+    fn test_wkt_epsg_codes() {
+        let wkt_epsg_codes = |wkt: &str| {
+            let crs = Crs::Wkt(wkt.to_string());
+            (crs.horizontal_epsg(), crs.vertical_epsg())
+        };
+        let wkt = r#"COMPOUNDCRS["x + y",
+            PROJCRS["x",BASEGEOGCRS["b",ID["EPSG",4326]],CONVERSION["c",ID["EPSG",16010]],ID["EPSG",32610]],
+            VERTCRS["y",VDATUM["v"],ID["EPSG",5703]]]"#;
+        assert_eq!((Some(32610), Some(5703)), wkt_epsg_codes(wkt));
+        let wkt = r#"VERT_CS["y",VERT_DATUM["v",2005,AUTHORITY["EPSG","5103"]],AUTHORITY["EPSG","5703"]]"#;
+        assert_eq!((None, Some(5703)), wkt_epsg_codes(wkt));
+        let wkt = r#"PROJCS["x",GEOGCS["b",AUTHORITY["EPSG","4326"]],AUTHORITY["ESRI","102100"]]"#;
+        assert_eq!((None, None), wkt_epsg_codes(wkt));
+    }
+
+    #[test]
+    fn test_geotiff_user_defined() {
+        let crs = Crs::GeoTiff(
+            GeoTiffCrs::default()
+                .with_projected_crs(USER_DEFINED_GEO_KEY_VALUE)
+                .with_geodetic_crs(4269),
+        );
+        assert_eq!(Some(4269), crs.horizontal_epsg());
+    }
+
+    #[cfg(feature = "epsg")]
+    #[test]
+    fn test_set_crs_converts() {
+        let epsg = Crs::Epsg {
+            horizontal: 25832,
+            vertical: Some(5941),
+        };
+
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        header.set_crs(&epsg).unwrap();
+        let crs = header.get_crs().unwrap().unwrap();
+        assert!(matches!(crs, Crs::GeoTiff(_)));
+
+        let mut builder = Builder::from(Version::new(1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut header = builder.into_header().unwrap();
+        header.set_crs(&crs).unwrap();
+        assert!(header.has_wkt_crs());
+        let crs = header.get_crs().unwrap().unwrap();
+        assert!(matches!(&crs, Crs::Wkt(wkt) if wkt.starts_with("COMPD_CS[")));
+        assert_eq!(Some(25832), crs.horizontal_epsg());
+        assert_eq!(Some(5941), crs.vertical_epsg());
+
+        let mut header = Builder::from(Version::new(1, 4)).into_header().unwrap();
+        header
+            .set_crs(&Crs::GeoTiff(
+                GeoTiffCrs::default().with_projected_crs(32610),
+            ))
+            .unwrap();
+        assert!(!header.has_wkt_crs());
+        header.set_crs(&epsg).unwrap();
+        assert!(header.has_wkt_crs());
+
+        let mut header = Builder::from(Version::new(1, 2)).into_header().unwrap();
+        assert!(matches!(
+            header.set_crs(&Crs::Wkt("LOCAL_CS[\"x\"]".to_string())),
+            Err(Error::UnresolvedCrs)
+        ));
+    }
+
+    #[cfg(feature = "epsg")]
+    #[test]
+    fn test_set_crs_converts_wkt2() {
+        let wkt2 = Crs::Epsg {
+            horizontal: 25832,
+            vertical: None,
+        }
+        .to_wkt(WktVersion::Wkt2)
+        .unwrap();
+        let crs = Crs::Wkt(wkt2.clone());
+        assert_eq!(wkt2, crs.to_wkt(WktVersion::Wkt2).unwrap());
+        let wkt1 = crs.to_wkt(WktVersion::Wkt1).unwrap();
+        assert!(wkt1.starts_with("PROJCS["));
+
+        let mut builder = Builder::from(Version::new(1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut header = builder.into_header().unwrap();
+        header.set_crs(&crs).unwrap();
+        let crs = header.get_crs().unwrap().unwrap();
+        assert_eq!(Crs::Wkt(wkt1), crs);
+        assert_eq!(Some(25832), crs.horizontal_epsg());
+
+        assert!(matches!(
+            header.set_crs(&Crs::Wkt("PROJCRS[\"x\"]".to_string())),
+            Err(Error::UnresolvedCrs)
+        ));
+    }
+
+    #[cfg(not(feature = "epsg"))]
+    #[test]
+    fn test_set_crs_rejects_wkt2_without_epsg() {
+        let mut builder = Builder::from(Version::new(1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut header = builder.into_header().unwrap();
+        let wkt1 = r#"GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]]"#;
+        header.set_crs(&Crs::Wkt(wkt1.to_string())).unwrap();
+        assert!(matches!(
+            header.set_crs(&Crs::Wkt(
+                r#"GEOGCRS["WGS 84",ID["EPSG",4326]]"#.to_string()
+            )),
+            Err(Error::UnresolvedCrs)
+        ));
+    }
+}
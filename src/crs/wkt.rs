@@ -0,0 +1,858 @@
+//! Parse WKT1 and WKT2 coordinate reference systems into a typed tree.
+//!
+//! [Wkt] holds the root [System], which is geographic, projected, vertical,
+//! compound, or some other kind that is kept as a raw [Node]:
+//!
+//! ```
+//! use las::crs::{wkt::Wkt, WktVersion};
+//! let wkt: Wkt = r#"COMPD_CS["NAD83 / UTM zone 10N + NAVD88 height",
+//!     PROJCS["NAD83 / UTM zone 10N",GEOGCS["NAD83",AUTHORITY["EPSG","4269"]],
+//!         UNIT["metre",1,AUTHORITY["EPSG","9001"]],AUTHORITY["EPSG","26910"]],
+//!     VERT_CS["NAVD88 height",UNIT["metre",1],AUTHORITY["EPSG","5703"]]]"#
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(WktVersion::Wkt1, wkt.version);
+//! assert!(wkt.is_compound());
+//! assert_eq!(Some(26910), wkt.horizontal_epsg());
+//! assert_eq!(Some(5703), wkt.vertical_epsg());
+//! assert_eq!("metre", wkt.linear_unit().unwrap().name);
+//! ```
+
+use super::WktVersion;
+use crate::{Error, Header, Result};
+use std::{iter::Peekable, str::CharIndices, str::FromStr};
+
+/// The deepest nesting of brackets the parser accepts, so untrusted WKT can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+const WKT1_KEYWORDS: [&str; 7] = [
+    "GEOGCS",
+    "GEOCCS",
+    "PROJCS",
+    "VERT_CS",
+    "COMPD_CS",
+    "LOCAL_CS",
+    "FITTED_CS",
+];
+
+/// A parsed WKT coordinate reference system.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wkt {
+    /// The WKT version, as determined by the root keyword.
+    pub version: WktVersion,
+
+    /// The root coordinate reference system.
+    ///
+    /// A `BOUNDCRS` is read as its source system.
+    pub system: System,
+}
+
+/// A coordinate reference system.
+#[derive(Clone, Debug, PartialEq)]
+pub enum System {
+    /// A geographic or geodetic system, e.g. `GEOGCS` or `GEOGCRS`.
+    Geographic(Geographic),
+
+    /// A projected system, e.g. `PROJCS` or `PROJCRS`.
+    Projected(Projected),
+
+    /// A vertical system, e.g. `VERT_CS` or `VERTCRS`.
+    Vertical(Vertical),
+
+    /// A compound system, e.g. `COMPD_CS` or `COMPOUNDCRS`.
+    Compound(Compound),
+
+    /// Any other kind of system, e.g. `LOCAL_CS` or a geocentric `GEOCCS`.
+    Other(Node),
+}
+
+/// A geographic or geodetic coordinate reference system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Geographic {
+    /// The name.
+    pub name: String,
+
+    /// The name of the datum or datum ensemble.
+    pub datum: Option<String>,
+
+    /// The unit, usually angular.
+    pub unit: Option<Unit>,
+
+    /// The axes.
+    pub axes: Vec<Axis>,
+
+    /// The authority and code.
+    pub authority: Option<Authority>,
+}
+
+/// A projected coordinate reference system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Projected {
+    /// The name.
+    pub name: String,
+
+    /// The geographic system that is projected.
+    pub base: Option<Box<Geographic>>,
+
+    /// The name of the projection method.
+    pub projection: Option<String>,
+
+    /// The projection parameters.
+    pub parameters: Vec<Parameter>,
+
+    /// The linear unit.
+    pub unit: Option<Unit>,
+
+    /// The axes.
+    pub axes: Vec<Axis>,
+
+    /// The authority and code.
+    pub authority: Option<Authority>,
+}
+
+/// A vertical coordinate reference system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vertical {
+    /// The name.
+    pub name: String,
+
+    /// The name of the vertical datum.
+    pub datum: Option<String>,
+
+    /// The linear unit.
+    pub unit: Option<Unit>,
+
+    /// The axes.
+    pub axes: Vec<Axis>,
+
+    /// The authority and code.
+    pub authority: Option<Authority>,
+}
+
+/// A compound coordinate reference system, usually horizontal plus vertical.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Compound {
+    /// The name.
+    pub name: String,
+
+    /// The component systems, in order.
+    pub components: Vec<System>,
+
+    /// The authority and code.
+    pub authority: Option<Authority>,
+}
+
+/// An `AUTHORITY` or `ID`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Authority {
+    /// The authority's name, e.g. `"EPSG"`.
+    pub name: String,
+
+    /// The code, which is text in WKT1 and usually a number in WKT2.
+    pub code: String,
+}
+
+/// A `UNIT`, `LENGTHUNIT` or `ANGLEUNIT`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    /// The name, e.g. `"metre"`.
+    pub name: String,
+
+    /// The conversion factor to metres or radians.
+    pub factor: f64,
+
+    /// The authority and code.
+    pub authority: Option<Authority>,
+}
+
+/// An `AXIS`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Axis {
+    /// The name, e.g. `"Easting"` or `"(E)"`.
+    pub name: String,
+
+    /// The direction, e.g. `"EAST"` or `"east"`.
+    pub direction: String,
+
+    /// The axis unit, which WKT2 can put on each axis.
+    pub unit: Option<Unit>,
+}
+
+/// A projection `PARAMETER`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// The name, e.g. `"central_meridian"`.
+    pub name: String,
+
+    /// The value.
+    pub value: f64,
+}
+
+/// A raw WKT node, i.e. a keyword and its bracketed values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The keyword, as written.
+    pub keyword: String,
+
+    /// The values, in order.
+    pub values: Vec<Value>,
+}
+
+/// A value inside a WKT node.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A quoted string.
+    Text(String),
+
+    /// A number.
+    Number(f64),
+
+    /// An unquoted enumeration value, e.g. `EAST` or `Cartesian`.
+    Identifier(String),
+
+    /// A nested node.
+    Node(Node),
+}
+
+impl Header {
+    /// Parses the header's WKT CRS (E)VLR, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Version};
+    /// let mut header = Builder::from(Version::new(1, 4)).into_header().unwrap();
+    /// header
+    ///     .set_wkt_crs(br#"GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]]"#.to_vec())
+    ///     .unwrap();
+    /// let wkt = header.get_wkt_crs().unwrap().unwrap();
+    /// assert_eq!(Some(4326), wkt.horizontal_epsg());
+    /// ```
+    pub fn get_wkt_crs(&self) -> Result<Option<Wkt>> {
+        self.get_wkt_crs_bytes().map(Wkt::from_bytes).transpose()
+    }
+}
+
+impl Wkt {
+    /// Parses WKT bytes, as stored in a WKT CRS (E)VLR.
+    ///
+    /// Tolerates trailing nulls and surrounding whitespace. Invalid UTF-8 is
+    /// replaced, since some writers use Latin-1 names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::wkt::Wkt;
+    /// let wkt = Wkt::from_bytes(b"\n VERT_CS [ \"NAVD88 height\" ] \0\0").unwrap();
+    /// assert!(!wkt.is_compound());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Wkt> {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).parse()
+    }
+
+    /// Returns true if the root system is compound.
+    pub fn is_compound(&self) -> bool {
+        matches!(self.system, System::Compound(_))
+    }
+
+    /// Returns the horizontal system, i.e. the root or the first geographic or projected component.
+    pub fn horizontal(&self) -> Option<&System> {
+        self.system
+            .find(&|system| matches!(system, System::Geographic(_) | System::Projected(_)))
+    }
+
+    /// Returns the vertical system, i.e. the root or the first vertical component.
+    pub fn vertical(&self) -> Option<&System> {
+        self.system
+            .find(&|system| matches!(system, System::Vertical(_)))
+    }
+
+    /// Returns the EPSG code of the horizontal system.
+    ///
+    /// Returns `None` if the horizontal system has no authority or the authority isn't EPSG.
+    pub fn horizontal_epsg(&self) -> Option<u16> {
+        self.horizontal().and_then(System::epsg)
+    }
+
+    /// Returns the EPSG code of the vertical system.
+    pub fn vertical_epsg(&self) -> Option<u16> {
+        self.vertical().and_then(System::epsg)
+    }
+
+    /// Returns the linear unit of the horizontal system, if it is projected.
+    pub fn linear_unit(&self) -> Option<&Unit> {
+        match self.horizontal()? {
+            System::Projected(projected) => projected.unit.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the linear unit of the vertical system.
+    pub fn vertical_unit(&self) -> Option<&Unit> {
+        match self.vertical()? {
+            System::Vertical(vertical) => vertical.unit.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Wkt {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Wkt> {
+        let node = Node::from_str(s)?;
+        let version = if WKT1_KEYWORDS
+            .iter()
+            .any(|keyword| node.keyword.eq_ignore_ascii_case(keyword))
+        {
+            WktVersion::Wkt1
+        } else {
+            WktVersion::Wkt2
+        };
+        Ok(Wkt {
+            version,
+            system: System::from(node),
+        })
+    }
+}
+
+impl System {
+    /// Returns this system's authority.
+    pub fn authority(&self) -> Option<Authority> {
+        match self {
+            System::Geographic(geographic) => geographic.authority.clone(),
+            System::Projected(projected) => projected.authority.clone(),
+            System::Vertical(vertical) => vertical.authority.clone(),
+            System::Compound(compound) => compound.authority.clone(),
+            System::Other(node) => node.authority(),
+        }
+    }
+
+    /// Returns this system's EPSG code, if its authority is EPSG.
+    pub fn epsg(&self) -> Option<u16> {
+        self.authority()?.epsg()
+    }
+
+    fn find(&self, predicate: &impl Fn(&System) -> bool) -> Option<&System> {
+        if predicate(self) {
+            Some(self)
+        } else if let System::Compound(compound) = self {
+            compound
+                .components
+                .iter()
+                .find_map(|component| component.find(predicate))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Node> for System {
+    fn from(node: Node) -> System {
+        match node.keyword.to_ascii_uppercase().as_str() {
+            "GEODCRS" | "GEODETICCRS" | "BASEGEODCRS" if node.is_geocentric() => {
+                System::Other(node)
+            }
+            "GEOGCS" | "GEOGCRS" | "GEODCRS" | "GEOGRAPHICCRS" | "GEODETICCRS" | "BASEGEOGCRS"
+            | "BASEGEODCRS" => System::Geographic(Geographic::from(&node)),
+            "PROJCS" | "PROJCRS" | "PROJECTEDCRS" => System::Projected(Projected::from(&node)),
+            "VERT_CS" | "VERTCRS" | "VERTICALCRS" => System::Vertical(Vertical::from(&node)),
+            "COMPD_CS" | "COMPOUNDCRS" => System::Compound(Compound {
+                name: node.name(),
+                components: node
+                    .nodes()
+                    .filter(|child| !child.is(&["AUTHORITY", "ID"]))
+                    .cloned()
+                    .map(System::from)
+                    .collect(),
+                authority: node.authority(),
+            }),
+            "BOUNDCRS" => match node
+                .child(&["SOURCECRS"])
+                .and_then(|source| source.nodes().next())
+            {
+                Some(source) => System::from(source.clone()),
+                None => System::Other(node),
+            },
+            _ => System::Other(node),
+        }
+    }
+}
+
+impl From<&Node> for Geographic {
+    fn from(node: &Node) -> Geographic {
+        Geographic {
+            name: node.name(),
+            datum: node
+                .child(&["DATUM", "TRF", "GEODETICDATUM", "ENSEMBLE"])
+                .map(Node::name),
+            unit: node.unit(),
+            axes: node.axes(),
+            authority: node.authority(),
+        }
+    }
+}
+
+impl From<&Node> for Projected {
+    fn from(node: &Node) -> Projected {
+        let conversion = node.child(&["CONVERSION", "DERIVINGCONVERSION"]);
+        let projection = conversion
+            .and_then(|conversion| conversion.child(&["METHOD", "PROJECTION"]))
+            .or_else(|| node.child(&["PROJECTION"]))
+            .map(Node::name);
+        let parameters = conversion
+            .unwrap_or(node)
+            .nodes()
+            .filter(|child| child.is(&["PARAMETER"]))
+            .filter_map(|parameter| {
+                Some(Parameter {
+                    name: parameter.name(),
+                    value: parameter.number(1)?,
+                })
+            })
+            .collect();
+        Projected {
+            name: node.name(),
+            base: node
+                .child(&["GEOGCS", "BASEGEOGCRS", "BASEGEODCRS"])
+                .map(|base| Box::new(Geographic::from(base))),
+            projection,
+            parameters,
+            unit: node.unit(),
+            axes: node.axes(),
+            authority: node.authority(),
+        }
+    }
+}
+
+impl From<&Node> for Vertical {
+    fn from(node: &Node) -> Vertical {
+        Vertical {
+            name: node.name(),
+            datum: node
+                .child(&["VERT_DATUM", "VDATUM", "VRF", "VERTICALDATUM", "ENSEMBLE"])
+                .map(Node::name),
+            unit: node.unit(),
+            axes: node.axes(),
+            authority: node.authority(),
+        }
+    }
+}
+
+impl Authority {
+    /// Returns the code as an EPSG code, if this is the EPSG authority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::wkt::Authority;
+    /// let authority = Authority { name: "EPSG".to_string(), code: "4326".to_string() };
+    /// assert_eq!(Some(4326), authority.epsg());
+    /// ```
+    pub fn epsg(&self) -> Option<u16> {
+        if self.name.eq_ignore_ascii_case("EPSG") {
+            self.code.trim().parse().ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl From<&Node> for Authority {
+    fn from(node: &Node) -> Authority {
+        let code = match node.values.get(1) {
+            Some(Value::Text(code)) | Some(Value::Identifier(code)) => code.clone(),
+            Some(Value::Number(code)) => code.to_string(),
+            _ => String::new(),
+        };
+        Authority {
+            name: node.name(),
+            code,
+        }
+    }
+}
+
+impl From<&Node> for Unit {
+    fn from(node: &Node) -> Unit {
+        Unit {
+            name: node.name(),
+            factor: node.number(1).unwrap_or(1.),
+            authority: node.authority(),
+        }
+    }
+}
+
+impl Node {
+    /// Returns the first child node with one of these keywords, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::wkt::Node;
+    /// let node: Node = r#"GEOGCS["WGS 84",DATUM["WGS_1984"]]"#.parse().unwrap();
+    /// assert!(node.child(&["DATUM"]).is_some());
+    /// ```
+    pub fn child(&self, keywords: &[&str]) -> Option<&Node> {
+        self.nodes().find(|child| child.is(keywords))
+    }
+
+    /// Returns the child nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.values.iter().filter_map(|value| match value {
+            Value::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    fn is(&self, keywords: &[&str]) -> bool {
+        keywords
+            .iter()
+            .any(|keyword| self.keyword.eq_ignore_ascii_case(keyword))
+    }
+
+    fn name(&self) -> String {
+        match self.values.first() {
+            Some(Value::Text(name)) | Some(Value::Identifier(name)) => name.clone(),
+            _ => String::new(),
+        }
+    }
+
+    fn number(&self, index: usize) -> Option<f64> {
+        match self.values.get(index)? {
+            Value::Number(number) => Some(*number),
+            Value::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn authority(&self) -> Option<Authority> {
+        self.child(&["AUTHORITY", "ID"]).map(Authority::from)
+    }
+
+    fn is_geocentric(&self) -> bool {
+        self.child(&["CS"])
+            .is_some_and(|cs| cs.name().eq_ignore_ascii_case("cartesian"))
+    }
+
+    fn unit(&self) -> Option<Unit> {
+        self.child(&["UNIT", "LENGTHUNIT", "ANGLEUNIT"])
+            .map(Unit::from)
+            .or_else(|| self.axes().into_iter().find_map(|axis| axis.unit))
+    }
+
+    fn axes(&self) -> Vec<Axis> {
+        self.nodes()
+            .filter(|child| child.is(&["AXIS"]))
+            .map(|axis| Axis {
+                name: axis.name(),
+                direction: match axis.values.get(1) {
+                    Some(Value::Identifier(direction)) | Some(Value::Text(direction)) => {
+                        direction.clone()
+                    }
+                    _ => String::new(),
+                },
+                unit: axis
+                    .child(&["UNIT", "LENGTHUNIT", "ANGLEUNIT"])
+                    .map(Unit::from),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Node {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Node> {
+        let mut parser = Parser {
+            s,
+            chars: s.char_indices().peekable(),
+            depth: 0,
+        };
+        parser.skip_whitespace();
+        let node = match parser.value()? {
+            Value::Node(node) => node,
+            _ => return Err(parser.error("expected a keyword and brackets")),
+        };
+        parser.skip_whitespace();
+        if parser.chars.next().is_some() {
+            return Err(parser.error("unexpected characters after the closing bracket"));
+        }
+        Ok(node)
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Value> {
+        match self.chars.peek() {
+            Some((_, '"')) => self.text().map(Value::Text),
+            Some(_) => {
+                let word = self.word();
+                if word.is_empty() {
+                    return Err(self.error("expected a value"));
+                }
+                self.skip_whitespace();
+                if let Some((_, '[' | '(')) = self.chars.peek() {
+                    if self.depth == MAX_DEPTH {
+                        return Err(self.error("too deeply nested"));
+                    }
+                    let _ = self.chars.next();
+                    self.depth += 1;
+                    let values = self.values()?;
+                    self.depth -= 1;
+                    Ok(Value::Node(Node {
+                        keyword: word,
+                        values,
+                    }))
+                } else if let Ok(number) = word.parse() {
+                    Ok(Value::Number(number))
+                } else {
+                    Ok(Value::Identifier(word))
+                }
+            }
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn values(&mut self) -> Result<Vec<Value>> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if let Some((_, ']' | ')')) = self.chars.peek() {
+                let _ = self.chars.next();
+                return Ok(values);
+            }
+            if !values.is_empty() {
+                match self.chars.next() {
+                    Some((_, ',')) => self.skip_whitespace(),
+                    _ => return Err(self.error("expected a comma or a closing bracket")),
+                }
+            }
+            values.push(self.value()?);
+        }
+    }
+
+    fn text(&mut self) -> Result<String> {
+        let _ = self.chars.next();
+        let mut text = String::new();
+        while let Some((_, c)) = self.chars.next() {
+            if c == '"' {
+                if let Some((_, '"')) = self.chars.peek() {
+                    let _ = self.chars.next();
+                } else {
+                    return Ok(text);
+                }
+            }
+            text.push(c);
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.position();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c.is_alphanumeric() || matches!(c, '_' | '.' | '+' | '-') {
+                let _ = self.chars.next();
+            } else {
+                break;
+            }
+        }
+        let end = self.position();
+        self.s[start..end].to_string()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((_, c)) = self.chars.peek() {
+            if c.is_whitespace() {
+                let _ = self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map(|&(i, _)| i).unwrap_or(self.s.len())
+    }
+
+    fn error(&mut self, message: &str) -> Error {
+        Error::InvalidWkt(format!("{message} at byte {}", self.position()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    const AUTZEN: &str = r#"PROJCS["NAD83(HARN) / Oregon Lambert (ft)",GEOGCS["NAD83(HARN)",DATUM["NAD83_High_Accuracy_Regional_Network",SPHEROID["GRS 1980",6378137,298.257222101,AUTHORITY["EPSG","7019"]],TOWGS84[0,0,0,0,0,0,0],AUTHORITY["EPSG","6152"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4152"]],UNIT["foot",0.3048,AUTHORITY["EPSG","9002"]],PROJECTION["Lambert_Conformal_Conic_2SP"],PARAMETER["standard_parallel_1",43],PARAMETER["standard_parallel_2",45.5],PARAMETER["latitude_of_origin",41.75],PARAMETER["central_meridian",-120.5],PARAMETER["false_easting",1312335.958],PARAMETER["false_northing",0],AUTHORITY["EPSG","2994"],AXIS["X",EAST],AXIS["Y",NORTH]]"#;
+
+    #[test]
+    fn wkt1_projected() {
+        let wkt: Wkt = AUTZEN.parse().unwrap();
+        assert_eq!(WktVersion::Wkt1, wkt.version);
+        assert!(!wkt.is_compound());
+        assert_eq!(Some(2994), wkt.horizontal_epsg());
+        assert_eq!(None, wkt.vertical_epsg());
+        let unit = wkt.linear_unit().unwrap();
+        assert_eq!("foot", unit.name);
+        assert_eq!(0.3048, unit.factor);
+        let System::Projected(projected) = wkt.system else {
+            panic!("not projected");
+        };
+        assert_eq!(
+            Some("Lambert_Conformal_Conic_2SP"),
+            projected.projection.as_deref()
+        );
+        assert_eq!(6, projected.parameters.len());
+        assert_eq!(
+            Parameter {
+                name: "central_meridian".to_string(),
+                value: -120.5
+            },
+            projected.parameters[3]
+        );
+        let base = projected.base.unwrap();
+        assert_eq!(Some(4152), base.authority.unwrap().epsg());
+        assert_eq!(
+            Some("NAD83_High_Accuracy_Regional_Network"),
+            base.datum.as_deref()
+        );
+        assert_eq!("EAST", projected.axes[0].direction);
+    }
+
+    #[test]
+    fn wkt2_compound() {
+        let wkt: Wkt = r#"COMPOUNDCRS["ETRS89 / UTM zone 32N + NN2000 height",
+            PROJCRS["ETRS89 / UTM zone 32N",
+                BASEGEOGCRS["ETRS89",ENSEMBLE["European Terrestrial Reference System 1989 ensemble"],ID["EPSG",4258]],
+                CONVERSION["UTM zone 32N",METHOD["Transverse Mercator",ID["EPSG",9807]],
+                    PARAMETER["Longitude of natural origin",9,ANGLEUNIT["degree",0.0174532925199433]]],
+                CS[Cartesian,2],
+                AXIS["(E)",east,ORDER[1],LENGTHUNIT["metre",1]],
+                AXIS["(N)",north,ORDER[2],LENGTHUNIT["metre",1]],
+                ID["EPSG",25832]],
+            VERTCRS["NN2000 height",VDATUM["Norway Normal Null 2000"],CS[vertical,1],
+                AXIS["gravity-related height (H)",up,LENGTHUNIT["metre",1]],
+                ID["EPSG",5941]]]"#
+            .parse()
+            .unwrap();
+        assert_eq!(WktVersion::Wkt2, wkt.version);
+        assert!(wkt.is_compound());
+        assert_eq!(Some(25832), wkt.horizontal_epsg());
+        assert_eq!(Some(5941), wkt.vertical_epsg());
+        assert_eq!("metre", wkt.linear_unit().unwrap().name);
+        assert_eq!("metre", wkt.vertical_unit().unwrap().name);
+        let Some(System::Projected(projected)) = wkt.horizontal() else {
+            panic!("not projected");
+        };
+        assert_eq!(Some("Transverse Mercator"), projected.projection.as_deref());
+        assert_eq!(9., projected.parameters[0].value);
+        assert_eq!("north", projected.axes[1].direction);
+    }
+
+    #[test]
+    fn geocentric() {
+        for wkt in [
+            r#"GEOCCS["WGS 84",DATUM["WGS_1984"],UNIT["metre",1],AUTHORITY["EPSG","4978"]]"#,
+            r#"GEODCRS["WGS 84",DATUM["World Geodetic System 1984"],CS[Cartesian,3],
+                AXIS["(X)",geocentricX],ID["EPSG",4978]]"#,
+        ] {
+            let wkt: Wkt = wkt.parse().unwrap();
+            assert!(matches!(wkt.system, System::Other(_)));
+            assert_eq!(None, wkt.horizontal_epsg());
+            assert_eq!(Some(4978), wkt.system.epsg());
+        }
+    }
+
+    #[test]
+    fn bound() {
+        let wkt: Wkt = r#"BOUNDCRS[
+            SOURCECRS[COMPOUNDCRS["NAD83(2011) / UTM zone 10N + NAVD88 height",
+                PROJCRS["NAD83(2011) / UTM zone 10N",
+                    BASEGEOGCRS["NAD83(2011)",DATUM["NAD83 (National Spatial Reference System 2011)"]],
+                    CONVERSION["UTM zone 10N",METHOD["Transverse Mercator"]],
+                    CS[Cartesian,2],
+                    AXIS["easting",east,LENGTHUNIT["metre",1]],
+                    ID["EPSG",6339]],
+                VERTCRS["NAVD88 height",VDATUM["North American Vertical Datum 1988"],CS[vertical,1],
+                    AXIS["gravity-related height (H)",up,LENGTHUNIT["metre",1]],
+                    ID["EPSG",5703]]]],
+            TARGETCRS[GEOGCRS["WGS 84",DATUM["World Geodetic System 1984"],ID["EPSG",4326]]],
+            ABRIDGEDTRANSFORMATION["Transformation from NAD83(2011) to WGS84",
+                METHOD["Position Vector transformation (geog2D domain)"],
+                PARAMETER["X-axis translation",0]]]"#
+            .parse()
+            .unwrap();
+        assert_eq!(WktVersion::Wkt2, wkt.version);
+        assert!(wkt.is_compound());
+        assert_eq!(Some(6339), wkt.horizontal_epsg());
+        assert_eq!(Some(5703), wkt.vertical_epsg());
+        assert_eq!("metre", wkt.linear_unit().unwrap().name);
+    }
+
+    #[test]
+    fn null_terminated_and_whitespace() {
+        let bytes = b"  \r\n\tGEOGCS ( \"WGS 84\" ,\n AUTHORITY [ \"EPSG\" , \"4326\" ] )\n\0\0\0";
+        let wkt = Wkt::from_bytes(bytes).unwrap();
+        assert_eq!(Some(4326), wkt.horizontal_epsg());
+        assert!(matches!(wkt.system, System::Geographic(_)));
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        let node: Node = r#"LOCAL_CS["a ""quoted"" name"]"#.parse().unwrap();
+        assert_eq!(
+            vec![Value::Text("a \"quoted\" name".to_string())],
+            node.values
+        );
+    }
+
+    #[test]
+    fn other_authority() {
+        let wkt: Wkt = r#"PROJCS["x",AUTHORITY["ESRI","102100"]]"#.parse().unwrap();
+        assert_eq!(None, wkt.horizontal_epsg());
+        assert_eq!("ESRI", wkt.system.authority().unwrap().name);
+    }
+
+    #[test]
+    fn nesting() {
+        let nested = |depth: usize| format!("{}{}", "A[".repeat(depth), "]".repeat(depth));
+        assert!(nested(MAX_DEPTH).parse::<Node>().is_ok());
+        assert!(matches!(
+            nested(MAX_DEPTH + 1).parse::<Node>(),
+            Err(Error::InvalidWkt(_))
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        for wkt in [
+            "",
+            "GEOGCS",
+            "GEOGCS[\"WGS 84\"",
+            "GEOGCS[\"WGS 84]",
+            "GEOGCS[\"WGS 84\" 1]",
+            "GEOGCS[\"WGS 84\"] trailing",
+            "\"WGS 84\"",
+            &"A[".repeat(100_000),
+        ] {
+            assert!(
+                matches!(wkt.parse::<Wkt>(), Err(Error::InvalidWkt(_))),
+                "{wkt}"
+            );
+        }
+    }
+
+    #[test]
+    fn header_without_wkt() {
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert!(reader.header().get_wkt_crs().unwrap().is_none());
+    }
+}
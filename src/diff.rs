@@ -0,0 +1,970 @@
+//! Compare two files.
+//!
+//! Byte comparison is too strict for regression tests of processing
+//! pipelines: GUIDs, creation dates and generating software change from run
+//! to run, and re-scaled coordinates can move by a rounding step. [diff]
+//! compares headers field by field, VLRs by key and content, and points
+//! dimension by dimension, within [Tolerances].
+//!
+//! ```
+//! use las::diff::{HeaderField, Tolerances};
+//!
+//! let tolerances = Tolerances::new()
+//!     .with_ignored(HeaderField::Guid)
+//!     .with_ignored(HeaderField::Date);
+//! let report = las::diff("tests/data/autzen.las", "tests/data/autzen.las", &tolerances).unwrap();
+//! assert!(report.is_empty());
+//! ```
+
+use crate::{point::Dimension, Header, PointData, PointDataBuilder, PointRef, Reader, Result, Vlr};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    path::Path,
+};
+
+/// The number of points read at a time when comparing in order.
+const CHUNK_SIZE: u64 = 100_000;
+
+/// How points in the two files are paired up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Matching {
+    /// The nth point of one file is compared with the nth point of the other.
+    #[default]
+    InOrder,
+
+    /// Points are paired by GPS time, then x, y and z, within the tolerances
+    /// of those dimensions.
+    ///
+    /// Each point is paired with the unpaired point closest to it in GPS
+    /// time whose x, y and z are within the tolerances.
+    ///
+    /// Use this when a pipeline may reorder, drop or add points. Points
+    /// without a match are reported as only in one file. Both files are read
+    /// into memory.
+    GpsTimeXyz,
+}
+
+/// A header field that can be compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HeaderField {
+    /// The las version.
+    Version,
+
+    /// The point format.
+    PointFormat,
+
+    /// The number of points.
+    NumberOfPoints,
+
+    /// The number of points by return.
+    NumberOfPointsByReturn,
+
+    /// The bounds, compared with the x, y and z tolerances.
+    Bounds,
+
+    /// The scales and offsets.
+    Transforms,
+
+    /// The file source id.
+    FileSourceId,
+
+    /// The GPS time type.
+    GpsTimeType,
+
+    /// The project GUID.
+    Guid,
+
+    /// The file creation date.
+    Date,
+
+    /// The system identifier.
+    SystemIdentifier,
+
+    /// The generating software.
+    GeneratingSoftware,
+
+    /// The synthetic return numbers bit of the global encoding.
+    HasSyntheticReturnNumbers,
+
+    /// The WKT CRS bit of the global encoding.
+    HasWktCrs,
+}
+
+/// Tolerances and options for [diff].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tolerances {
+    tolerances: HashMap<Dimension, f64>,
+    matching: Matching,
+    max_examples: usize,
+    ignored: HashSet<HeaderField>,
+}
+
+/// A header field that differs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderDifference {
+    /// The field.
+    pub field: HeaderField,
+
+    /// The value in the first file.
+    pub a: String,
+
+    /// The value in the second file.
+    pub b: String,
+}
+
+/// How a VLR differs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VlrChange {
+    /// The VLR is only in the first file.
+    OnlyInA,
+
+    /// The VLR is only in the second file.
+    OnlyInB,
+
+    /// The descriptions differ.
+    Description,
+
+    /// The data differ.
+    Data,
+}
+
+/// A VLR or EVLR that differs.
+///
+/// VLRs are matched by user id and record id. If a file has more than one
+/// VLR with the same key they're matched in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VlrDifference {
+    /// The user id.
+    pub user_id: String,
+
+    /// The record id.
+    pub record_id: u16,
+
+    /// How it differs.
+    pub change: VlrChange,
+}
+
+/// A point value that differs by more than its tolerance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointDifference {
+    /// The index of the point in the first file.
+    pub index_a: u64,
+
+    /// The index of the point in the second file.
+    pub index_b: u64,
+
+    /// The dimension.
+    pub dimension: Dimension,
+
+    /// The value in the first file.
+    pub a: f64,
+
+    /// The value in the second file.
+    pub b: f64,
+}
+
+/// The differences between two files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiffReport {
+    header: Vec<HeaderDifference>,
+    vlrs: Vec<VlrDifference>,
+    number_of_points: (u64, u64),
+    compared_points: u64,
+    differing_points: u64,
+    by_dimension: HashMap<Dimension, u64>,
+    examples: Vec<PointDifference>,
+    only_in_a: u64,
+    only_in_b: u64,
+    examples_only_in_a: Vec<u64>,
+    examples_only_in_b: Vec<u64>,
+}
+
+/// Compares two files.
+///
+/// Headers are compared field by field, skipping the fields ignored by
+/// `tolerances`. VLRs and EVLRs are compared by user id, record id and
+/// content. Points are paired according to [Tolerances::matching] and every
+/// dimension present in both files is compared. Points that don't pair up are
+/// counted as only in one file.
+///
+/// # Examples
+///
+/// ```
+/// use las::diff::Tolerances;
+/// let report = las::diff("tests/data/autzen.las", "tests/data/autzen.las", &Tolerances::new()).unwrap();
+/// assert_eq!(106, report.compared_points());
+/// assert_eq!(0, report.differing_points());
+/// ```
+pub fn diff<P: AsRef<Path>, Q: AsRef<Path>>(
+    a: P,
+    b: Q,
+    tolerances: &Tolerances,
+) -> Result<DiffReport> {
+    let mut a = Reader::from_path(a)?;
+    let mut b = Reader::from_path(b)?;
+    let mut report = DiffReport {
+        number_of_points: (a.header().number_of_points(), b.header().number_of_points()),
+        ..Default::default()
+    };
+    report.header = diff_headers(a.header(), b.header(), tolerances);
+    report.vlrs = diff_vlrs(a.header(), b.header());
+    match tolerances.matching {
+        Matching::InOrder => {
+            let mut points_a = PointDataBuilder::new().for_header(a.header()).build();
+            let mut points_b = PointDataBuilder::new().for_header(b.header()).build();
+            let mut index = 0;
+            loop {
+                let n_a = a.fill_points(CHUNK_SIZE, &mut points_a)?;
+                let n_b = b.fill_points(CHUNK_SIZE, &mut points_b)?;
+                for (point_a, point_b) in points_a.views().zip(points_b.views()) {
+                    report.compare(point_a, point_b, index, index, tolerances);
+                    index += 1;
+                }
+                if n_a < CHUNK_SIZE || n_b < CHUNK_SIZE {
+                    break;
+                }
+            }
+            let (n_a, n_b) = report.number_of_points;
+            for index in index..n_a {
+                report.push_only_in_a(index, tolerances);
+            }
+            for index in index..n_b {
+                report.push_only_in_b(index, tolerances);
+            }
+        }
+        Matching::GpsTimeXyz => {
+            let points_a = a.read_all()?;
+            let points_b = b.read_all()?;
+            let (keys_a, keys_b) = (sorted_keys(&points_a), sorted_keys(&points_b));
+            let mut matched = vec![false; keys_b.len()];
+            for &(index_a, key_a) in &keys_a {
+                let Some(position) = find_match(&key_a, &keys_b, &matched, tolerances) else {
+                    report.push_only_in_a(index_a as u64, tolerances);
+                    continue;
+                };
+                matched[position] = true;
+                let index_b = keys_b[position].0;
+                if let (Some(point_a), Some(point_b)) =
+                    (points_a.get(index_a), points_b.get(index_b))
+                {
+                    report.compare(point_a, point_b, index_a as u64, index_b as u64, tolerances);
+                }
+            }
+            for (&(index, _), &is_matched) in keys_b.iter().zip(&matched) {
+                if !is_matched {
+                    report.push_only_in_b(index as u64, tolerances);
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+impl Tolerances {
+    /// Creates tolerances that require exact equality, compare points in
+    /// order, keep ten examples, and compare every header field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{diff::Tolerances, point::Dimension};
+    /// assert_eq!(0., Tolerances::new().tolerance(Dimension::X));
+    /// ```
+    pub fn new() -> Tolerances {
+        Tolerances::default()
+    }
+
+    /// Sets the largest allowed absolute difference for a dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{diff::Tolerances, point::Dimension};
+    /// let tolerances = Tolerances::new().with_tolerance(Dimension::Z, 0.01);
+    /// assert_eq!(0.01, tolerances.tolerance(Dimension::Z));
+    /// ```
+    pub fn with_tolerance(mut self, dimension: Dimension, tolerance: f64) -> Tolerances {
+        let _ = self.tolerances.insert(dimension, tolerance);
+        self
+    }
+
+    /// Sets how points are paired up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::diff::{Matching, Tolerances};
+    /// let tolerances = Tolerances::new().with_matching(Matching::GpsTimeXyz);
+    /// assert_eq!(Matching::GpsTimeXyz, tolerances.matching());
+    /// ```
+    pub fn with_matching(mut self, matching: Matching) -> Tolerances {
+        self.matching = matching;
+        self
+    }
+
+    /// Sets how many example point differences the report keeps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::diff::Tolerances;
+    /// assert_eq!(3, Tolerances::new().with_max_examples(3).max_examples());
+    /// ```
+    pub fn with_max_examples(mut self, max_examples: usize) -> Tolerances {
+        self.max_examples = max_examples;
+        self
+    }
+
+    /// Skips a header field, e.g. [HeaderField::Guid] or [HeaderField::Date].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::diff::{HeaderField, Tolerances};
+    /// let tolerances = Tolerances::new().with_ignored(HeaderField::Guid);
+    /// assert!(tolerances.is_ignored(HeaderField::Guid));
+    /// ```
+    pub fn with_ignored(mut self, field: HeaderField) -> Tolerances {
+        let _ = self.ignored.insert(field);
+        self
+    }
+
+    /// Returns the tolerance for a dimension.
+    pub fn tolerance(&self, dimension: Dimension) -> f64 {
+        self.tolerances.get(&dimension).copied().unwrap_or(0.)
+    }
+
+    /// Returns how points are paired up.
+    pub fn matching(&self) -> Matching {
+        self.matching
+    }
+
+    /// Returns how many example point differences the report keeps.
+    pub fn max_examples(&self) -> usize {
+        self.max_examples
+    }
+
+    /// Returns true if a header field is skipped.
+    pub fn is_ignored(&self, field: HeaderField) -> bool {
+        self.ignored.contains(&field)
+    }
+
+    fn within(&self, dimension: Dimension, a: f64, b: f64) -> bool {
+        a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= self.tolerance(dimension)
+    }
+}
+
+impl Default for Tolerances {
+    fn default() -> Tolerances {
+        Tolerances {
+            tolerances: HashMap::new(),
+            matching: Matching::InOrder,
+            max_examples: 10,
+            ignored: HashSet::new(),
+        }
+    }
+}
+
+impl DiffReport {
+    /// Returns true if nothing differs and every point was paired up.
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty()
+            && self.vlrs.is_empty()
+            && self.differing_points == 0
+            && self.only_in_a == 0
+            && self.only_in_b == 0
+    }
+
+    /// Returns the header fields that differ.
+    pub fn header_differences(&self) -> &[HeaderDifference] {
+        &self.header
+    }
+
+    /// Returns the VLRs and EVLRs that differ.
+    pub fn vlr_differences(&self) -> &[VlrDifference] {
+        &self.vlrs
+    }
+
+    /// Returns the number of points in each file, according to their headers.
+    pub fn number_of_points(&self) -> (u64, u64) {
+        self.number_of_points
+    }
+
+    /// Returns the number of point pairs that were compared.
+    pub fn compared_points(&self) -> u64 {
+        self.compared_points
+    }
+
+    /// Returns the number of point pairs with at least one differing dimension.
+    pub fn differing_points(&self) -> u64 {
+        self.differing_points
+    }
+
+    /// Returns the number of point pairs that differ in a dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{diff::Tolerances, point::Dimension};
+    /// let report = las::diff("tests/data/autzen.las", "tests/data/autzen.las", &Tolerances::new()).unwrap();
+    /// assert_eq!(0, report.differing_points_in(Dimension::Z));
+    /// ```
+    pub fn differing_points_in(&self, dimension: Dimension) -> u64 {
+        self.by_dimension.get(&dimension).copied().unwrap_or(0)
+    }
+
+    /// Returns the dimensions with differing points and their counts, in
+    /// [Dimension::ALL] order.
+    pub fn differing_dimensions(&self) -> impl Iterator<Item = (Dimension, u64)> + '_ {
+        Dimension::ALL.into_iter().filter_map(|dimension| {
+            self.by_dimension
+                .get(&dimension)
+                .map(|&count| (dimension, count))
+        })
+    }
+
+    /// Returns the first point differences, up to [Tolerances::max_examples].
+    pub fn examples(&self) -> &[PointDifference] {
+        &self.examples
+    }
+
+    /// Returns the number of points in the first file that weren't paired with a point in the second.
+    pub fn points_only_in_a(&self) -> u64 {
+        self.only_in_a
+    }
+
+    /// Returns the number of points in the second file that weren't paired with a point in the first.
+    pub fn points_only_in_b(&self) -> u64 {
+        self.only_in_b
+    }
+
+    /// Returns the indices of the first points only in the first file, up to [Tolerances::max_examples].
+    pub fn examples_only_in_a(&self) -> &[u64] {
+        &self.examples_only_in_a
+    }
+
+    /// Returns the indices of the first points only in the second file, up to [Tolerances::max_examples].
+    pub fn examples_only_in_b(&self) -> &[u64] {
+        &self.examples_only_in_b
+    }
+
+    fn push_only_in_a(&mut self, index: u64, tolerances: &Tolerances) {
+        self.only_in_a += 1;
+        if self.examples_only_in_a.len() < tolerances.max_examples {
+            self.examples_only_in_a.push(index);
+        }
+    }
+
+    fn push_only_in_b(&mut self, index: u64, tolerances: &Tolerances) {
+        self.only_in_b += 1;
+        if self.examples_only_in_b.len() < tolerances.max_examples {
+            self.examples_only_in_b.push(index);
+        }
+    }
+
+    fn compare(
+        &mut self,
+        a: PointRef<'_>,
+        b: PointRef<'_>,
+        index_a: u64,
+        index_b: u64,
+        tolerances: &Tolerances,
+    ) {
+        self.compared_points += 1;
+        let mut differs = false;
+        for dimension in Dimension::ALL {
+            let (Some(value_a), Some(value_b)) = (a.value(dimension), b.value(dimension)) else {
+                continue;
+            };
+            if tolerances.within(dimension, value_a, value_b) {
+                continue;
+            }
+            differs = true;
+            *self.by_dimension.entry(dimension).or_default() += 1;
+            if self.examples.len() < tolerances.max_examples {
+                self.examples.push(PointDifference {
+                    index_a,
+                    index_b,
+                    dimension,
+                    a: value_a,
+                    b: value_b,
+                });
+            }
+        }
+        if differs {
+            self.differing_points += 1;
+        }
+    }
+}
+
+impl fmt::Display for HeaderField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HeaderField::Version => "version",
+            HeaderField::PointFormat => "point format",
+            HeaderField::NumberOfPoints => "number of points",
+            HeaderField::NumberOfPointsByReturn => "number of points by return",
+            HeaderField::Bounds => "bounds",
+            HeaderField::Transforms => "transforms",
+            HeaderField::FileSourceId => "file source id",
+            HeaderField::GpsTimeType => "gps time type",
+            HeaderField::Guid => "guid",
+            HeaderField::Date => "date",
+            HeaderField::SystemIdentifier => "system identifier",
+            HeaderField::GeneratingSoftware => "generating software",
+            HeaderField::HasSyntheticReturnNumbers => "synthetic return numbers",
+            HeaderField::HasWktCrs => "wkt crs",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl fmt::Display for HeaderDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.field, self.a, self.b)
+    }
+}
+
+impl fmt::Display for VlrDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let change = match self.change {
+            VlrChange::OnlyInA => "only in a",
+            VlrChange::OnlyInB => "only in b",
+            VlrChange::Description => "descriptions differ",
+            VlrChange::Data => "data differ",
+        };
+        write!(f, "vlr {} {}: {change}", self.user_id, self.record_id)
+    }
+}
+
+impl fmt::Display for PointDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "point {}/{}: {:?} {} != {}",
+            self.index_a, self.index_b, self.dimension, self.a, self.b
+        )
+    }
+}
+
+fn diff_headers(a: &Header, b: &Header, tolerances: &Tolerances) -> Vec<HeaderDifference> {
+    let mut differences = Vec::new();
+    let mut push = |field: HeaderField, equal: bool, value_a: String, value_b: String| {
+        if !equal && !tolerances.is_ignored(field) {
+            differences.push(HeaderDifference {
+                field,
+                a: value_a,
+                b: value_b,
+            });
+        }
+    };
+    macro_rules! compare {
+        ($field:expr, $a:expr, $b:expr) => {{
+            let (value_a, value_b) = ($a, $b);
+            push(
+                $field,
+                value_a == value_b,
+                format!("{value_a:?}"),
+                format!("{value_b:?}"),
+            );
+        }};
+    }
+
+    compare!(HeaderField::Version, a.version(), b.version());
+    compare!(HeaderField::PointFormat, a.point_format(), b.point_format());
+    compare!(
+        HeaderField::NumberOfPoints,
+        a.number_of_points(),
+        b.number_of_points()
+    );
+    compare!(
+        HeaderField::NumberOfPointsByReturn,
+        points_by_return(a),
+        points_by_return(b)
+    );
+    let (bounds_a, bounds_b) = (a.bounds(), b.bounds());
+    push(
+        HeaderField::Bounds,
+        [
+            (Dimension::X, bounds_a.min.x, bounds_b.min.x),
+            (Dimension::Y, bounds_a.min.y, bounds_b.min.y),
+            (Dimension::Z, bounds_a.min.z, bounds_b.min.z),
+            (Dimension::X, bounds_a.max.x, bounds_b.max.x),
+            (Dimension::Y, bounds_a.max.y, bounds_b.max.y),
+            (Dimension::Z, bounds_a.max.z, bounds_b.max.z),
+        ]
+        .into_iter()
+        .all(|(dimension, value_a, value_b)| tolerances.within(dimension, value_a, value_b)),
+        format!("{bounds_a:?}"),
+        format!("{bounds_b:?}"),
+    );
+    compare!(HeaderField::Transforms, a.transforms(), b.transforms());
+    compare!(
+        HeaderField::FileSourceId,
+        a.file_source_id(),
+        b.file_source_id()
+    );
+    compare!(
+        HeaderField::GpsTimeType,
+        a.gps_time_type(),
+        b.gps_time_type()
+    );
+    compare!(HeaderField::Guid, a.guid(), b.guid());
+    compare!(HeaderField::Date, a.date(), b.date());
+    compare!(
+        HeaderField::SystemIdentifier,
+        a.system_identifier(),
+        b.system_identifier()
+    );
+    compare!(
+        HeaderField::GeneratingSoftware,
+        a.generating_software(),
+        b.generating_software()
+    );
+    compare!(
+        HeaderField::HasSyntheticReturnNumbers,
+        a.has_synthetic_return_numbers(),
+        b.has_synthetic_return_numbers()
+    );
+    compare!(HeaderField::HasWktCrs, a.has_wkt_crs(), b.has_wkt_crs());
+    differences
+}
+
+fn points_by_return(header: &Header) -> Vec<u64> {
+    (1..=15)
+        .map(|n| header.number_of_points_by_return(n).unwrap_or(0))
+        .collect()
+}
+
+fn diff_vlrs(a: &Header, b: &Header) -> Vec<VlrDifference> {
+    let (groups_a, mut groups_b) = (group_vlrs(a), group_vlrs(b));
+    let mut differences = Vec::new();
+    let mut push = |(user_id, record_id): &(String, u16), change: VlrChange| {
+        differences.push(VlrDifference {
+            user_id: user_id.clone(),
+            record_id: *record_id,
+            change,
+        })
+    };
+    for (key, vlrs_a) in &groups_a {
+        let vlrs_b = groups_b.remove(key).unwrap_or_default();
+        for i in 0..vlrs_a.len().max(vlrs_b.len()) {
+            match (vlrs_a.get(i), vlrs_b.get(i)) {
+                (Some(vlr_a), Some(vlr_b)) => {
+                    if vlr_a.description != vlr_b.description {
+                        push(key, VlrChange::Description);
+                    }
+                    if vlr_a.data != vlr_b.data {
+                        push(key, VlrChange::Data);
+                    }
+                }
+                (Some(_), None) => push(key, VlrChange::OnlyInA),
+                (None, Some(_)) => push(key, VlrChange::OnlyInB),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+    for (key, vlrs_b) in &groups_b {
+        for _ in vlrs_b {
+            push(key, VlrChange::OnlyInB);
+        }
+    }
+    differences
+}
+
+fn group_vlrs(header: &Header) -> BTreeMap<(String, u16), Vec<&Vlr>> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for vlr in header.all_vlrs() {
+        groups
+            .entry((vlr.user_id.clone(), vlr.record_id))
+            .or_default()
+            .push(vlr);
+    }
+    groups
+}
+
+/// Returns point indices and their keys, sorted by GPS time (if any), x, y and z.
+fn sorted_keys(points: &PointData) -> Vec<(usize, [f64; 4])> {
+    let mut keys: Vec<(usize, [f64; 4])> = points
+        .views()
+        .enumerate()
+        .map(|(index, point)| {
+            let key = [
+                point.gps_time().unwrap_or(0.),
+                point.x(),
+                point.y(),
+                point.z(),
+            ];
+            (index, key)
+        })
+        .collect();
+    keys.sort_by(|(_, a), (_, b)| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+    keys
+}
+
+/// Returns the position in `keys` of the unmatched point that matches `key`
+/// within the tolerances, preferring the closest GPS time.
+///
+/// Candidates are the points within the GPS time tolerance. Each run of
+/// points with the same GPS time is sorted by x, so only the points within
+/// the x tolerance of each run are checked.
+fn find_match(
+    key: &[f64; 4],
+    keys: &[(usize, [f64; 4])],
+    matched: &[bool],
+    tolerances: &Tolerances,
+) -> Option<usize> {
+    let tolerance = |dimension| tolerances.tolerance(dimension);
+    let gps_time = tolerance(Dimension::GpsTime);
+    let start = keys.partition_point(|(_, k)| k[0].total_cmp(&(key[0] - gps_time)).is_lt());
+    let end = keys.partition_point(|(_, k)| k[0].total_cmp(&(key[0] + gps_time)).is_le());
+    let mut best: Option<(usize, f64)> = None;
+    let mut run = start;
+    while run < end {
+        let time = keys[run].1[0];
+        let run_end = run + keys[run..end].partition_point(|(_, k)| k[0] == time);
+        let run_end = run_end.max(run + 1);
+        let x = key[1] - tolerance(Dimension::X);
+        let mut position = run + keys[run..run_end].partition_point(|(_, k)| k[1] < x);
+        while position < run_end && tolerances.within(Dimension::X, key[1], keys[position].1[1]) {
+            let candidate = &keys[position].1;
+            if !matched[position]
+                && tolerances.within(Dimension::GpsTime, key[0], candidate[0])
+                && tolerances.within(Dimension::Y, key[2], candidate[2])
+                && tolerances.within(Dimension::Z, key[3], candidate[3])
+            {
+                let distance = (key[0] - candidate[0]).abs();
+                if best.is_none_or(|(_, best)| distance < best) {
+                    best = Some((position, distance));
+                }
+                break;
+            }
+            position += 1;
+        }
+        run = run_end;
+    }
+    best.map(|(position, _)| position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::copy_to_temp_dir, Point, Writer};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    const AUTZEN: &str = "tests/data/autzen.las";
+
+    fn write(points: impl IntoIterator<Item = Point>) -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("points.las");
+        let reader = Reader::from_path(AUTZEN).unwrap();
+        let mut writer = Writer::from_path(&path, reader.header().clone()).unwrap();
+        for point in points {
+            writer.write_point(point).unwrap();
+        }
+        writer.close().unwrap();
+        (dir, path)
+    }
+
+    fn autzen_points() -> Vec<Point> {
+        Reader::from_path(AUTZEN)
+            .unwrap()
+            .read_all()
+            .unwrap()
+            .points()
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn identical() {
+        let report = diff(AUTZEN, AUTZEN, &Tolerances::new()).unwrap();
+        assert!(report.is_empty());
+        assert_eq!((106, 106), report.number_of_points());
+        assert_eq!(106, report.compared_points());
+    }
+
+    #[test]
+    fn ignored_header_fields() {
+        let (_dir, path) = copy_to_temp_dir(AUTZEN);
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.set_guid(Uuid::from_u128(42));
+        crate::rewrite_metadata(&path, &header).unwrap();
+
+        let report = diff(AUTZEN, &path, &Tolerances::new()).unwrap();
+        assert!(!report.is_empty());
+        assert_eq!(1, report.header_differences().len());
+        assert_eq!(HeaderField::Guid, report.header_differences()[0].field);
+        assert!(report.vlr_differences().is_empty());
+        assert_eq!(0, report.differing_points());
+
+        let tolerances = Tolerances::new().with_ignored(HeaderField::Guid);
+        assert!(diff(AUTZEN, &path, &tolerances).unwrap().is_empty());
+    }
+
+    #[test]
+    fn vlrs() {
+        let (_dir, path) = copy_to_temp_dir(AUTZEN);
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        let removed = header.vlrs_mut().remove(0);
+        header.vlrs_mut()[0].description = "changed".to_string();
+        crate::rewrite_metadata(&path, &header).unwrap();
+
+        let report = diff(AUTZEN, &path, &Tolerances::new()).unwrap();
+        let differences = report.vlr_differences();
+        assert_eq!(2, differences.len());
+        assert!(differences.contains(&VlrDifference {
+            user_id: removed.user_id,
+            record_id: removed.record_id,
+            change: VlrChange::OnlyInA,
+        }));
+        assert!(differences
+            .iter()
+            .any(|difference| difference.change == VlrChange::Description));
+    }
+
+    #[test]
+    fn points_with_tolerance() {
+        let (_dir, path) = write(
+            autzen_points()
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut point)| {
+                    if i % 2 == 0 {
+                        point.z += 0.01;
+                    }
+                    point
+                }),
+        );
+        let tolerances = Tolerances::new()
+            .with_ignored(HeaderField::Bounds)
+            .with_max_examples(3);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!(53, report.differing_points());
+        assert_eq!(53, report.differing_points_in(Dimension::Z));
+        assert_eq!(0, report.differing_points_in(Dimension::X));
+        assert_eq!(
+            vec![(Dimension::Z, 53)],
+            report.differing_dimensions().collect::<Vec<_>>()
+        );
+        assert_eq!(3, report.examples().len());
+        assert_eq!(0, report.examples()[0].index_a);
+        assert_eq!(2, report.examples()[1].index_a);
+
+        let tolerances = tolerances.with_tolerance(Dimension::Z, 0.011);
+        assert_eq!(
+            0,
+            diff(AUTZEN, &path, &tolerances).unwrap().differing_points()
+        );
+    }
+
+    #[test]
+    fn gps_time_xyz_matching() {
+        let mut points = autzen_points();
+        points.reverse();
+        let (_dir, path) = write(points);
+        let in_order = diff(AUTZEN, &path, &Tolerances::new()).unwrap();
+        assert!(in_order.differing_points() > 0);
+
+        let tolerances = Tolerances::new().with_matching(Matching::GpsTimeXyz);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!(106, report.compared_points());
+        assert_eq!(0, report.differing_points());
+    }
+
+    #[test]
+    fn different_number_of_points() {
+        let (_dir, path) = write(autzen_points().into_iter().take(100));
+        let tolerances = Tolerances::new()
+            .with_ignored(HeaderField::NumberOfPoints)
+            .with_ignored(HeaderField::NumberOfPointsByReturn)
+            .with_ignored(HeaderField::Bounds);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!((106, 100), report.number_of_points());
+        assert_eq!(100, report.compared_points());
+        assert_eq!(0, report.differing_points());
+        assert_eq!(6, report.points_only_in_a());
+        assert_eq!(&[100, 101, 102, 103, 104, 105], report.examples_only_in_a());
+        assert!(!report.is_empty());
+    }
+
+    #[test]
+    fn gps_time_xyz_matching_with_missing_and_extra_points() {
+        let tolerances = Tolerances::new()
+            .with_matching(Matching::GpsTimeXyz)
+            .with_ignored(HeaderField::NumberOfPoints)
+            .with_ignored(HeaderField::NumberOfPointsByReturn)
+            .with_ignored(HeaderField::Bounds);
+
+        let mut points = autzen_points();
+        let removed = points.remove(50);
+        let (_dir, path) = write(points);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!(105, report.compared_points());
+        assert_eq!(0, report.differing_points());
+        assert_eq!(1, report.points_only_in_a());
+        assert_eq!(&[50], report.examples_only_in_a());
+        assert_eq!(0, report.points_only_in_b());
+
+        let mut points = autzen_points();
+        points.insert(
+            0,
+            Point {
+                gps_time: removed.gps_time.map(|t| t + 0.5),
+                ..removed
+            },
+        );
+        let (_dir, path) = write(points);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!(106, report.compared_points());
+        assert_eq!(0, report.differing_points());
+        assert_eq!(&[0], report.examples_only_in_b());
+    }
+
+    #[test]
+    fn gps_time_xyz_matching_with_interleaved_times() {
+        let point = |gps_time: f64, x: f64| Point {
+            gps_time: Some(gps_time),
+            x,
+            ..autzen_points()[0].clone()
+        };
+        let (_dir_a, a) = write([point(1.00, 0.)]);
+        let (_dir_b, b) = write([point(1.04, 5.), point(1.06, 0.)]);
+        let tolerances = Tolerances::new()
+            .with_matching(Matching::GpsTimeXyz)
+            .with_tolerance(Dimension::GpsTime, 0.1)
+            .with_ignored(HeaderField::NumberOfPoints)
+            .with_ignored(HeaderField::NumberOfPointsByReturn)
+            .with_ignored(HeaderField::Bounds);
+        let report = diff(&a, &b, &tolerances).unwrap();
+        assert_eq!(1, report.compared_points());
+        assert_eq!(0, report.differing_points());
+        assert_eq!(0, report.points_only_in_a());
+        assert_eq!(&[0], report.examples_only_in_b());
+    }
+
+    #[test]
+    fn gps_time_xyz_matching_within_tolerance() {
+        let (_dir, path) = write(autzen_points().into_iter().map(|mut point| {
+            point.x += 0.01;
+            point
+        }));
+        let tolerances = Tolerances::new()
+            .with_matching(Matching::GpsTimeXyz)
+            .with_ignored(HeaderField::Bounds)
+            .with_tolerance(Dimension::X, 0.02);
+        let report = diff(AUTZEN, &path, &tolerances).unwrap();
+        assert_eq!(106, report.compared_points());
+        assert!(report.is_empty());
+    }
+}
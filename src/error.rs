@@ -204,7 +204,7 @@ pub enum Error {
     #[error("the vlr is too long: {0}")]
     VlrTooLong(usize),
 
-    /// The GeoTiff-CRS (E)VLR(s) could not be read. Either the ascii- or double-vlrs are missing
+    /// The GeoTiff-CRS (E)VLR(s) could not be read. Either the ascii- or double-vlrs are missing or too short
     #[error("Unable to read the ascii or double GeoTiff (E)VLR(s)")]
     UnreadableGeoTiffCrs,
 
@@ -228,4 +228,142 @@ pub enum Error {
         /// Actual minor revision
         actual_minor: u16,
     },
+
+    /// The tile size must be finite and positive.
+    #[error("invalid tile size: {0}")]
+    InvalidTileSize(f64),
+
+    /// The gps time window must be finite and positive.
+    #[error("invalid gps time window: {0}")]
+    InvalidGpsTimeWindow(f64),
+
+    /// The point format doesn't have gps time.
+    #[error("point format {0} does not have gps time")]
+    NoGpsTime(Format),
+
+    /// The writer doesn't write variable-size laz chunks.
+    #[error("the writer does not write variable-size laz chunks")]
+    NoVariableLazChunks,
+
+    /// Laz chunks can't be ended while the writer is sorting points.
+    #[error("laz chunks can't be ended while sorting points")]
+    EndChunkWhileSorting,
+
+    /// A column doesn't have one value per point.
+    #[error("column has {len} values, but there are {expected} points")]
+    ColumnLength {
+        /// The number of values in the column.
+        len: usize,
+
+        /// The number of points.
+        expected: usize,
+    },
+
+    /// The point format has no color.
+    #[error("point format {0} does not have color")]
+    NoColor(Format),
+
+    /// The point data have different formats or transforms.
+    #[error("the point data have different formats or transforms")]
+    IncompatiblePointData,
+
+    /// The point format doesn't have this dimension.
+    #[error("point format {format} does not have dimension {dimension:?}")]
+    NoDimension {
+        /// The dimension.
+        dimension: crate::point::Dimension,
+
+        /// The point format.
+        format: Format,
+    },
+
+    /// This is not a valid extra bytes data type.
+    #[error("invalid extra bytes data type: {0}")]
+    InvalidExtraBytesDataType(u8),
+
+    /// The extra bytes vlr is not a whole number of descriptors.
+    #[error("the extra bytes vlr length {0} is not a multiple of 192")]
+    InvalidExtraBytesVlrLength(usize),
+
+    /// This UTM zone isn't between 1 and 60.
+    #[error("invalid utm zone: {0}")]
+    InvalidUtmZone(u8),
+
+    /// Coordinates that should be geocentric aren't anywhere near the Earth's surface.
+    #[error("coordinates are not geocentric: ({0}, {1}, {2})")]
+    NotGeocentric(f64, f64, f64),
+
+    /// The WKT can't be parsed.
+    #[error("invalid wkt: {0}")]
+    InvalidWkt(String),
+
+    /// [arrow_schema::ArrowError]
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    /// The record batch can't be converted to point data.
+    #[cfg(feature = "arrow")]
+    #[error("invalid record batch: {0}")]
+    InvalidRecordBatch(String),
+
+    /// [parquet::errors::ParquetError]
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    /// The Parquet file metadata can't be converted to a LAS header.
+    #[cfg(feature = "parquet")]
+    #[error("invalid parquet metadata: {0}")]
+    InvalidParquetMetadata(String),
+
+    /// This EPSG code isn't in the embedded EPSG subset.
+    #[cfg(feature = "epsg")]
+    #[error("unknown epsg code: {0}")]
+    UnknownEpsgCode(u16),
+
+    /// The CRS doesn't resolve to EPSG codes of the right kinds, or resolving it needs the `epsg` feature.
+    #[error("the crs does not resolve to epsg codes of the right kinds")]
+    UnresolvedCrs,
+
+    /// A GPS week is needed to convert GPS week time to standard time.
+    #[error("a gps week is required to convert gps week time to standard time")]
+    MissingGpsWeek,
+
+    /// The vlr's data has the wrong length for its type.
+    #[error("invalid data length for vlr with user id {user_id} and record id {record_id}: {len}")]
+    InvalidVlrLength {
+        /// The user id of the vlr.
+        user_id: String,
+        /// The record id of the vlr.
+        record_id: u16,
+        /// The length of the vlr's data.
+        len: usize,
+    },
+
+    /// Waveform packet descriptor indices start at one.
+    #[error("invalid waveform packet descriptor index: {0}")]
+    InvalidWaveformPacketDescriptorIndex(u8),
+
+    /// The vlr isn't of the expected type.
+    #[error("unexpected vlr with user id {user_id} and record id {record_id}")]
+    UnexpectedVlr {
+        /// The user id of the vlr.
+        user_id: String,
+        /// The record id of the vlr.
+        record_id: u16,
+    },
+
+    /// COPC metadata can't be rewritten, because the COPC offsets would be wrong.
+    #[error("cannot rewrite the metadata of a copc file")]
+    CopcMetadataRewrite,
+
+    /// The header's version has a different header layout than the file's.
+    #[error("the header's version {header} is not compatible with the file's version {file}")]
+    VersionMismatch {
+        /// The version of the file.
+        file: Version,
+        /// The version of the header.
+        header: Version,
+    },
 }
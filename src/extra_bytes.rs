@@ -0,0 +1,419 @@
+//! Descriptions of the extra bytes at the end of each point record.
+//!
+//! Extra bytes are described by the "LASF_Spec" VLR with record id 4, which
+//! holds one 192-byte [Descriptor] per extra dimension, in record order.
+//!
+//! ```
+//! use las::extra_bytes::{DataType, Descriptor};
+//! use las::{Builder, point::Format};
+//!
+//! let mut descriptor = Descriptor::new("height", DataType::I16);
+//! descriptor.scale = Some(0.01);
+//!
+//! let mut builder = Builder::from((1, 4));
+//! builder.point_format = Format::new(1).unwrap();
+//! builder.point_format.extra_bytes = 2;
+//! builder.vlrs.push(Descriptor::to_vlr(&[descriptor.clone()]).unwrap());
+//! let header = builder.into_header().unwrap();
+//! assert_eq!(vec![descriptor], header.extra_bytes_descriptors().unwrap());
+//! ```
+
+use crate::{utils::AsLasStr, utils::FromLasStr, Error, Header, Result, Vlr};
+
+const USER_ID: &str = "LASF_Spec";
+const RECORD_ID: u16 = 4;
+const DESCRIPTOR_LEN: usize = 192;
+
+/// The type of an extra dimension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataType {
+    /// Bytes without a documented type, with the number of bytes.
+    Undocumented(u8),
+
+    /// An unsigned char.
+    U8,
+
+    /// A char.
+    I8,
+
+    /// An unsigned short.
+    U16,
+
+    /// A short.
+    I16,
+
+    /// An unsigned long.
+    U32,
+
+    /// A long.
+    I32,
+
+    /// An unsigned long long.
+    U64,
+
+    /// A long long.
+    I64,
+
+    /// A float.
+    F32,
+
+    /// A double.
+    F64,
+}
+
+/// A description of one extra dimension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Descriptor {
+    /// The type of the values.
+    pub data_type: DataType,
+
+    /// The name of the dimension.
+    pub name: String,
+
+    /// A description of the dimension.
+    pub description: String,
+
+    /// The raw no data value, if there is one.
+    pub no_data: Option<[u8; 8]>,
+
+    /// The raw minimum value, if there is one.
+    pub min: Option<[u8; 8]>,
+
+    /// The raw maximum value, if there is one.
+    pub max: Option<[u8; 8]>,
+
+    /// The scale applied to the values, if there is one.
+    pub scale: Option<f64>,
+
+    /// The offset applied to the values, if there is one.
+    pub offset: Option<f64>,
+}
+
+impl DataType {
+    /// Creates a data type from its code and the descriptor's options byte.
+    ///
+    /// Undocumented bytes store their length in the options byte. The
+    /// deprecated array types (11 through 30) are treated as undocumented
+    /// bytes of the same length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    /// assert_eq!(DataType::F64, DataType::new(10, 0).unwrap());
+    /// assert_eq!(DataType::Undocumented(3), DataType::new(0, 3).unwrap());
+    /// assert!(DataType::new(31, 0).is_err());
+    /// ```
+    pub fn new(code: u8, options: u8) -> Result<DataType> {
+        Ok(match code {
+            0 => DataType::Undocumented(options),
+            1 => DataType::U8,
+            2 => DataType::I8,
+            3 => DataType::U16,
+            4 => DataType::I16,
+            5 => DataType::U32,
+            6 => DataType::I32,
+            7 => DataType::U64,
+            8 => DataType::I64,
+            9 => DataType::F32,
+            10 => DataType::F64,
+            11..=30 => {
+                let count = if code <= 20 { 2 } else { 3 };
+                let base = (code - 1) % 10 + 1;
+                DataType::Undocumented(DataType::new(base, 0)?.len() * count)
+            }
+            _ => return Err(Error::InvalidExtraBytesDataType(code)),
+        })
+    }
+
+    /// Returns this type's code.
+    pub fn code(self) -> u8 {
+        match self {
+            DataType::Undocumented(_) => 0,
+            DataType::U8 => 1,
+            DataType::I8 => 2,
+            DataType::U16 => 3,
+            DataType::I16 => 4,
+            DataType::U32 => 5,
+            DataType::I32 => 6,
+            DataType::U64 => 7,
+            DataType::I64 => 8,
+            DataType::F32 => 9,
+            DataType::F64 => 10,
+        }
+    }
+
+    /// Returns the number of bytes of one value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    /// assert_eq!(4, DataType::F32.len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(self) -> u8 {
+        match self {
+            DataType::Undocumented(n) => n,
+            DataType::U8 | DataType::I8 => 1,
+            DataType::U16 | DataType::I16 => 2,
+            DataType::U32 | DataType::I32 | DataType::F32 => 4,
+            DataType::U64 | DataType::I64 | DataType::F64 => 8,
+        }
+    }
+
+    /// Decodes a value of this type as a float.
+    ///
+    /// Returns `None` for undocumented bytes, or if `bytes` is shorter than
+    /// this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    /// assert_eq!(Some(-2.), DataType::I16.decode(&(-2i16).to_le_bytes()));
+    /// ```
+    pub fn decode(self, bytes: &[u8]) -> Option<f64> {
+        let bytes = bytes.get(..usize::from(self.len()))?;
+        let array = |n: usize| {
+            let mut array = [0; 8];
+            array[..n].copy_from_slice(&bytes[..n]);
+            array
+        };
+        Some(match self {
+            DataType::Undocumented(_) => return None,
+            DataType::U8 => f64::from(bytes[0]),
+            DataType::I8 => f64::from(bytes[0] as i8),
+            DataType::U16 => f64::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+            DataType::I16 => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+            DataType::U32 => {
+                f64::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            DataType::I32 => {
+                f64::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            DataType::U64 => u64::from_le_bytes(array(8)) as f64,
+            DataType::I64 => i64::from_le_bytes(array(8)) as f64,
+            DataType::F32 => {
+                f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            DataType::F64 => f64::from_le_bytes(array(8)),
+        })
+    }
+}
+
+impl Descriptor {
+    /// Creates a descriptor with just a name and a type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("amplitude", DataType::U16);
+    /// assert_eq!(None, descriptor.scale);
+    /// ```
+    pub fn new(name: impl Into<String>, data_type: DataType) -> Descriptor {
+        Descriptor {
+            data_type,
+            name: name.into(),
+            description: String::new(),
+            no_data: None,
+            min: None,
+            max: None,
+            scale: None,
+            offset: None,
+        }
+    }
+
+    /// Reads one descriptor from its 192 bytes.
+    pub fn read_from(bytes: &[u8]) -> Result<Descriptor> {
+        if bytes.len() != DESCRIPTOR_LEN {
+            return Err(Error::InvalidExtraBytesVlrLength(bytes.len()));
+        }
+        let options = bytes[3];
+        let eight = |offset: usize| {
+            let mut array = [0; 8];
+            array.copy_from_slice(&bytes[offset..offset + 8]);
+            array
+        };
+        // Undocumented bytes use the options byte for their length instead.
+        let is_undocumented = bytes[2] == 0;
+        let option = |bit: u8, offset: usize| {
+            (!is_undocumented && options >> bit & 1 == 1).then(|| eight(offset))
+        };
+        Ok(Descriptor {
+            data_type: DataType::new(bytes[2], options)?,
+            name: (&bytes[4..36]).as_las_string_lossy(),
+            no_data: option(0, 40),
+            min: option(1, 64),
+            max: option(2, 88),
+            scale: option(3, 112).map(f64::from_le_bytes),
+            offset: option(4, 136).map(f64::from_le_bytes),
+            description: (&bytes[160..192]).as_las_string_lossy(),
+        })
+    }
+
+    /// Writes this descriptor as 192 bytes.
+    pub fn to_bytes(&self) -> Result<[u8; DESCRIPTOR_LEN]> {
+        let mut bytes = [0; DESCRIPTOR_LEN];
+        bytes[2] = self.data_type.code();
+        let mut options = match self.data_type {
+            DataType::Undocumented(n) => n,
+            _ => 0,
+        };
+        (&mut bytes[4..36]).from_las_str(&self.name)?;
+        let fields = [
+            (self.no_data, 40),
+            (self.min, 64),
+            (self.max, 88),
+            (self.scale.map(f64::to_le_bytes), 112),
+            (self.offset.map(f64::to_le_bytes), 136),
+        ];
+        for (bit, (value, offset)) in fields.into_iter().enumerate() {
+            if let Some(value) = value {
+                if !matches!(self.data_type, DataType::Undocumented(_)) {
+                    options |= 1 << bit;
+                }
+                bytes[offset..offset + 8].copy_from_slice(&value);
+            }
+        }
+        bytes[3] = options;
+        (&mut bytes[160..192]).from_las_str(&self.description)?;
+        Ok(bytes)
+    }
+
+    /// Reads all descriptors from an extra bytes VLR.
+    pub fn from_vlr(vlr: &Vlr) -> Result<Vec<Descriptor>> {
+        if !vlr.data.len().is_multiple_of(DESCRIPTOR_LEN) {
+            return Err(Error::InvalidExtraBytesVlrLength(vlr.data.len()));
+        }
+        vlr.data
+            .chunks_exact(DESCRIPTOR_LEN)
+            .map(Descriptor::read_from)
+            .collect()
+    }
+
+    /// Creates an extra bytes VLR holding these descriptors.
+    pub fn to_vlr(descriptors: &[Descriptor]) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(descriptors.len() * DESCRIPTOR_LEN);
+        for descriptor in descriptors {
+            data.extend_from_slice(&descriptor.to_bytes()?);
+        }
+        Ok(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: "Extra bytes".to_string(),
+            data,
+        })
+    }
+
+    /// Returns true if the value is stored with a scale or an offset.
+    pub fn is_scaled(&self) -> bool {
+        self.scale.is_some() || self.offset.is_some()
+    }
+
+    /// Decodes a value and applies the scale and offset.
+    ///
+    /// Returns `None` for undocumented bytes, or if `bytes` is shorter than
+    /// the data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let mut descriptor = Descriptor::new("height", DataType::U8);
+    /// descriptor.scale = Some(0.5);
+    /// assert_eq!(Some(21.), descriptor.decode(&[42]));
+    /// ```
+    pub fn decode(&self, bytes: &[u8]) -> Option<f64> {
+        self.data_type
+            .decode(bytes)
+            .map(|n| n * self.scale.unwrap_or(1.) + self.offset.unwrap_or(0.))
+    }
+}
+
+impl Vlr {
+    /// Returns true if this is an extra bytes VLR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let vlr = Vlr { user_id: "LASF_Spec".to_string(), record_id: 4, ..Default::default() };
+    /// assert!(vlr.is_extra_bytes());
+    /// ```
+    pub fn is_extra_bytes(&self) -> bool {
+        self.user_id == USER_ID && self.record_id == RECORD_ID
+    }
+}
+
+impl Header {
+    /// Returns the descriptors of the extra bytes, or an empty vector if there is no extra bytes VLR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.header().extra_bytes_descriptors().unwrap().is_empty());
+    /// ```
+    pub fn extra_bytes_descriptors(&self) -> Result<Vec<Descriptor>> {
+        self.all_vlrs()
+            .find(|vlr| vlr.is_extra_bytes())
+            .map(Descriptor::from_vlr)
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let descriptor = Descriptor {
+            data_type: DataType::I32,
+            name: "range".to_string(),
+            description: "distance to the sensor".to_string(),
+            no_data: Some((-1i64).to_le_bytes()),
+            min: None,
+            max: Some(1000i64.to_le_bytes()),
+            scale: Some(0.001),
+            offset: Some(10.),
+        };
+        let bytes = descriptor.to_bytes().unwrap();
+        assert_eq!(0b11101, bytes[3]);
+        assert_eq!(descriptor, Descriptor::read_from(&bytes).unwrap());
+    }
+
+    #[test]
+    fn undocumented() {
+        let descriptor = Descriptor::new("blob", DataType::Undocumented(3));
+        let bytes = descriptor.to_bytes().unwrap();
+        assert_eq!(3, bytes[3]);
+        assert_eq!(descriptor, Descriptor::read_from(&bytes).unwrap());
+        assert_eq!(None, descriptor.decode(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn decode_short() {
+        assert_eq!(None, DataType::F64.decode(&[0; 7]));
+        assert_eq!(None, Descriptor::new("x", DataType::U16).decode(&[1]));
+        assert_eq!(Some(1.), DataType::U8.decode(&[1, 2]));
+    }
+
+    #[test]
+    fn deprecated_arrays() {
+        assert_eq!(DataType::Undocumented(4), DataType::new(13, 0).unwrap());
+        assert_eq!(DataType::Undocumented(24), DataType::new(30, 0).unwrap());
+    }
+
+    #[test]
+    fn bad_vlr_length() {
+        let vlr = Vlr {
+            data: vec![0; 100],
+            ..Descriptor::to_vlr(&[]).unwrap()
+        };
+        assert!(Descriptor::from_vlr(&vlr).is_err());
+    }
+}
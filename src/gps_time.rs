@@ -0,0 +1,462 @@
+//! Convert GPS times between week time, adjusted standard time and UTC.
+//!
+//! Las files store GPS time either as seconds since the start of a GPS week
+//! ([GpsTimeType::Week]) or as adjusted standard GPS time, i.e. seconds since
+//! the GPS epoch minus one billion ([GpsTimeType::Standard]). Week time doesn't
+//! say which week it is, so converting it needs a week number, which can be
+//! inferred from the file creation date with [Header::infer_gps_week].
+//!
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use las::gps_time;
+//!
+//! let utc = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+//! let adjusted_standard = gps_time::utc_to_adjusted_standard(utc);
+//! assert_eq!(167264018., adjusted_standard);
+//! assert_eq!((1930, 18.), gps_time::adjusted_standard_to_week(adjusted_standard));
+//! assert_eq!(Some(utc), gps_time::adjusted_standard_to_utc(adjusted_standard));
+//! ```
+//!
+//! UTC conversions use a built-in table of leap seconds, which is current up
+//! to the leap second of 2017-01-01.
+
+use crate::{GpsTimeType, Header, PointData, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// The number of seconds in a GPS week.
+pub const SECONDS_PER_WEEK: f64 = 604800.;
+
+/// Adjusted standard GPS time is standard GPS time minus this offset.
+pub const ADJUSTED_STANDARD_OFFSET: f64 = 1e9;
+
+const SECONDS_PER_DAY: i64 = 86400;
+const GPS_EPOCH_UNIX_TIMESTAMP: i64 = 315964800;
+
+/// The UTC dates on which GPS time got one more second ahead of UTC.
+const LEAP_SECONDS: [(i32, u32); 18] = [
+    (1981, 7),
+    (1982, 7),
+    (1983, 7),
+    (1985, 7),
+    (1988, 1),
+    (1990, 1),
+    (1991, 1),
+    (1992, 7),
+    (1993, 7),
+    (1994, 7),
+    (1996, 1),
+    (1997, 7),
+    (1999, 1),
+    (2006, 1),
+    (2009, 1),
+    (2012, 7),
+    (2015, 7),
+    (2017, 1),
+];
+
+/// Converts GPS week time to adjusted standard GPS time.
+///
+/// # Examples
+///
+/// ```
+/// use las::gps_time;
+/// assert_eq!(167264018., gps_time::week_to_adjusted_standard(1930, 18.));
+/// ```
+pub fn week_to_adjusted_standard(week: u32, seconds_of_week: f64) -> f64 {
+    f64::from(week) * SECONDS_PER_WEEK + seconds_of_week - ADJUSTED_STANDARD_OFFSET
+}
+
+/// Returns the GPS week of `seconds_of_week`, given the week of a `reference` time from the same survey.
+///
+/// Times more than half a week before the reference are taken to be after a
+/// week rollover, and times more than half a week after it to be before one.
+///
+/// # Examples
+///
+/// ```
+/// use las::gps_time;
+/// // Saturday night, then Sunday morning
+/// let saturday = 6. * 86400. + 82800.;
+/// assert_eq!(1930, gps_time::week_near(1930, saturday, saturday + 60.));
+/// assert_eq!(1931, gps_time::week_near(1930, saturday, 3600.));
+/// ```
+pub fn week_near(week: u32, reference: f64, seconds_of_week: f64) -> u32 {
+    let delta = seconds_of_week - reference;
+    if delta < -SECONDS_PER_WEEK / 2. {
+        week + 1
+    } else if delta > SECONDS_PER_WEEK / 2. {
+        week.saturating_sub(1)
+    } else {
+        week
+    }
+}
+
+/// Converts adjusted standard GPS time to a GPS week and the seconds of that week.
+///
+/// Times before the GPS epoch are in week zero, with negative seconds.
+///
+/// # Examples
+///
+/// ```
+/// use las::gps_time;
+/// assert_eq!((1930, 18.), gps_time::adjusted_standard_to_week(167264018.));
+/// ```
+pub fn adjusted_standard_to_week(adjusted_standard: f64) -> (u32, f64) {
+    let gps = adjusted_standard + ADJUSTED_STANDARD_OFFSET;
+    let week = (gps / SECONDS_PER_WEEK).floor().max(0.);
+    (week as u32, gps - week * SECONDS_PER_WEEK)
+}
+
+/// Returns how many seconds GPS time was ahead of UTC at this time.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// assert_eq!(0, gps_time::leap_seconds(Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap()));
+/// assert_eq!(18, gps_time::leap_seconds(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+/// ```
+pub fn leap_seconds(utc: DateTime<Utc>) -> u32 {
+    let date = utc.date_naive();
+    LEAP_SECONDS
+        .iter()
+        .take_while(|&&(year, month)| leap_second_date(year, month) <= date)
+        .count() as u32
+}
+
+/// Converts standard GPS time, i.e. seconds since the GPS epoch, to UTC.
+///
+/// Returns `None` if the time is out of range.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = gps_time::gps_to_utc(0.).unwrap();
+/// assert_eq!(Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap(), utc);
+/// ```
+pub fn gps_to_utc(gps: f64) -> Option<DateTime<Utc>> {
+    if !gps.is_finite() {
+        return None;
+    }
+    let leap_seconds = LEAP_SECONDS
+        .iter()
+        .enumerate()
+        .take_while(|&(i, &(year, month))| {
+            let utc = leap_second_date(year, month)
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is valid")
+                .and_utc();
+            (utc.timestamp() - GPS_EPOCH_UNIX_TIMESTAMP + i as i64 + 1) as f64 <= gps
+        })
+        .count();
+    let seconds = gps - leap_seconds as f64;
+    let whole = seconds.floor();
+    let nanoseconds = ((seconds - whole) * 1e9).round().min(999_999_999.) as u32;
+    DateTime::from_timestamp(GPS_EPOCH_UNIX_TIMESTAMP + whole as i64, nanoseconds)
+}
+
+/// Converts UTC to standard GPS time, i.e. seconds since the GPS epoch.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap();
+/// assert_eq!(0., gps_time::utc_to_gps(utc));
+/// ```
+pub fn utc_to_gps(utc: DateTime<Utc>) -> f64 {
+    (utc.timestamp() - GPS_EPOCH_UNIX_TIMESTAMP) as f64
+        + f64::from(utc.timestamp_subsec_nanos()) / 1e9
+        + f64::from(leap_seconds(utc))
+}
+
+/// Converts adjusted standard GPS time to UTC.
+pub fn adjusted_standard_to_utc(adjusted_standard: f64) -> Option<DateTime<Utc>> {
+    gps_to_utc(adjusted_standard + ADJUSTED_STANDARD_OFFSET)
+}
+
+/// Converts UTC to adjusted standard GPS time.
+pub fn utc_to_adjusted_standard(utc: DateTime<Utc>) -> f64 {
+    utc_to_gps(utc) - ADJUSTED_STANDARD_OFFSET
+}
+
+/// Converts GPS week time to UTC.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = gps_time::week_to_utc(1930, 18.).unwrap();
+/// assert_eq!(Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap(), utc);
+/// ```
+pub fn week_to_utc(week: u32, seconds_of_week: f64) -> Option<DateTime<Utc>> {
+    gps_to_utc(f64::from(week) * SECONDS_PER_WEEK + seconds_of_week)
+}
+
+/// Returns the GPS week that contains the start of this date.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use las::gps_time;
+/// assert_eq!(1930, gps_time::week_of(NaiveDate::from_ymd_opt(2017, 1, 1).unwrap()));
+/// assert_eq!(1930, gps_time::week_of(NaiveDate::from_ymd_opt(2017, 1, 7).unwrap()));
+/// ```
+pub fn week_of(date: NaiveDate) -> u32 {
+    (days_since_epoch(date) / 7).max(0) as u32
+}
+
+/// Infers the GPS week of a week time from a date on or shortly after it, e.g. a file creation date.
+///
+/// Returns the week of `date`, unless `seconds_of_week` would be after the end
+/// of `date`, in which case the time must be from the week before.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use las::gps_time;
+/// let monday = NaiveDate::from_ymd_opt(2017, 1, 2).unwrap();
+/// assert_eq!(1930, gps_time::infer_week(monday, 3600.));
+/// // Saturday's data, in a file created on Monday
+/// assert_eq!(1929, gps_time::infer_week(monday, 6. * 86400. + 3600.));
+/// ```
+pub fn infer_week(date: NaiveDate, seconds_of_week: f64) -> u32 {
+    let week = week_of(date);
+    let end_of_date = (days_since_epoch(date).rem_euclid(7) + 1) * SECONDS_PER_DAY;
+    if seconds_of_week > end_of_date as f64 {
+        week.saturating_sub(1)
+    } else {
+        week
+    }
+}
+
+fn days_since_epoch(date: NaiveDate) -> i64 {
+    let epoch = NaiveDate::from_ymd_opt(1980, 1, 6).expect("the gps epoch is a valid date");
+    (date - epoch).num_days()
+}
+
+fn leap_second_date(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, 1).expect("leap second dates are valid")
+}
+
+impl Header {
+    /// Infers the GPS week of a week time from the file creation date.
+    ///
+    /// Returns `None` if the header has no date. See [infer_week].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use las::Builder;
+    /// let mut builder = Builder::default();
+    /// builder.date = NaiveDate::from_ymd_opt(2017, 1, 2);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(Some(1930), header.infer_gps_week(3600.));
+    /// ```
+    pub fn infer_gps_week(&self, seconds_of_week: f64) -> Option<u32> {
+        self.date().map(|date| infer_week(date, seconds_of_week))
+    }
+
+    /// Re-encodes the GPS times of `points` from this header's [GpsTimeType] to `to`, and sets the header's to match.
+    ///
+    /// `week` is the GPS week of the first point, see
+    /// [PointData::convert_gps_time]. If it's `None`, it's inferred from the
+    /// first point and the file creation date.
+    ///
+    /// When converting a file a slab at a time, convert every slab with
+    /// [PointData::convert_gps_time], passing the week it returns for one slab
+    /// to the next, then set the flag once with [Header::set_gps_time_type].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use las::{point::Format, Builder, GpsTimeType, Point, PointDataBuilder};
+    ///
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.point_format = Format::new(1).unwrap();
+    /// builder.date = NaiveDate::from_ymd_opt(2017, 1, 2);
+    /// let mut header = builder.into_header().unwrap();
+    /// let mut points = PointDataBuilder::new()
+    ///     .for_header(&header)
+    ///     .build_from_points([Point { gps_time: Some(18.), ..Default::default() }])
+    ///     .unwrap();
+    /// header.convert_gps_time(&mut points, GpsTimeType::Standard, None).unwrap();
+    /// assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+    /// assert_eq!(Some(167264018.), points.gps_time().unwrap().next());
+    /// ```
+    pub fn convert_gps_time(
+        &mut self,
+        points: &mut PointData,
+        to: GpsTimeType,
+        week: Option<u32>,
+    ) -> Result<()> {
+        let first = points.gps_time().and_then(|mut gps_time| gps_time.next());
+        let week = week
+            .or_else(|| first.and_then(|seconds_of_week| self.infer_gps_week(seconds_of_week)))
+            .map(|week| (week, first.unwrap_or(0.)));
+        let _ = points.convert_gps_time(self.gps_time_type(), to, week)?;
+        self.set_gps_time_type(to);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Error, GpsTimeType, Point, PointDataBuilder};
+    use chrono::TimeZone;
+
+    #[test]
+    fn leap_second_boundaries() {
+        let before = Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59).unwrap();
+        let after = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(17, leap_seconds(before));
+        assert_eq!(18, leap_seconds(after));
+        assert_eq!(utc_to_gps(before) + 2., utc_to_gps(after));
+        for utc in [before, after] {
+            assert_eq!(Some(utc), gps_to_utc(utc_to_gps(utc)));
+        }
+        let utc = Utc.with_ymd_and_hms(2010, 6, 15, 12, 30, 0).unwrap()
+            + chrono::TimeDelta::milliseconds(250);
+        assert_eq!(Some(utc), gps_to_utc(utc_to_gps(utc)));
+        assert_eq!(None, gps_to_utc(f64::NAN));
+    }
+
+    #[test]
+    fn week_roundtrip() {
+        let (week, seconds) = adjusted_standard_to_week(123456789.5);
+        assert_eq!(123456789.5, week_to_adjusted_standard(week, seconds));
+        assert_eq!(
+            (0, -1.),
+            adjusted_standard_to_week(-ADJUSTED_STANDARD_OFFSET - 1.)
+        );
+    }
+
+    #[test]
+    fn infer_week_at_week_start() {
+        let sunday = NaiveDate::from_ymd_opt(2017, 1, 1).unwrap();
+        assert_eq!(1930, infer_week(sunday, 86400.));
+        assert_eq!(1929, infer_week(sunday, 86401.));
+    }
+
+    #[test]
+    fn convert_point_data() {
+        let mut points = PointDataBuilder::new()
+            .with_format(Format::new(1).unwrap())
+            .build_from_points([
+                Point {
+                    gps_time: Some(18.),
+                    ..Default::default()
+                },
+                Point {
+                    gps_time: Some(SECONDS_PER_WEEK - 1.),
+                    ..Default::default()
+                },
+            ])
+            .unwrap();
+        assert!(matches!(
+            points.convert_gps_time(GpsTimeType::Week, GpsTimeType::Standard, None),
+            Err(Error::MissingGpsWeek)
+        ));
+        let week = points
+            .convert_gps_time(GpsTimeType::Week, GpsTimeType::Standard, Some((1930, 18.)))
+            .unwrap();
+        assert_eq!(Some((1929, SECONDS_PER_WEEK - 1.)), week);
+        let gps_time: Vec<f64> = points.gps_time().unwrap().collect();
+        // The second point is one second before the first, in the week before
+        assert_eq!(vec![167264018., 167263999.], gps_time);
+        assert_eq!(
+            week,
+            points
+                .convert_gps_time(GpsTimeType::Standard, GpsTimeType::Week, None)
+                .unwrap()
+        );
+        let gps_time: Vec<f64> = points.gps_time().unwrap().collect();
+        assert_eq!(vec![18., SECONDS_PER_WEEK - 1.], gps_time);
+
+        let mut points = PointDataBuilder::new()
+            .build_from_points([Point::default()])
+            .unwrap();
+        assert!(points
+            .convert_gps_time(GpsTimeType::Standard, GpsTimeType::Week, None)
+            .is_err());
+    }
+
+    #[test]
+    fn convert_across_week_rollover() {
+        let saturday_night = SECONDS_PER_WEEK - 60.;
+        let mut points = PointDataBuilder::new()
+            .with_format(Format::new(1).unwrap())
+            .build_from_points(
+                [saturday_night, SECONDS_PER_WEEK - 1., 0., 60.].map(|t| Point {
+                    gps_time: Some(t),
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        let mut header = Builder::from((1, 2)).into_header().unwrap();
+        header
+            .convert_gps_time(&mut points, GpsTimeType::Standard, Some(1930))
+            .unwrap();
+        assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+        let gps_time: Vec<f64> = points.gps_time().unwrap().collect();
+        let start = week_to_adjusted_standard(1930, saturday_night);
+        assert_eq!(
+            vec![start, start + 59., start + 60., start + 120.],
+            gps_time
+        );
+    }
+
+    #[test]
+    fn convert_slabs_across_week_rollover() {
+        let slab = |gps_time: [f64; 2]| {
+            PointDataBuilder::new()
+                .with_format(Format::new(1).unwrap())
+                .build_from_points(gps_time.map(|t| Point {
+                    gps_time: Some(t),
+                    ..Default::default()
+                }))
+                .unwrap()
+        };
+        let saturday_night = SECONDS_PER_WEEK - 60.;
+        let mut week = Some((1930, saturday_night));
+        let mut gps_time = Vec::new();
+        for times in [
+            [saturday_night, 30.],
+            [60., 90.],
+            [SECONDS_PER_WEEK / 2., SECONDS_PER_WEEK - 1.],
+            [10., 20.],
+        ] {
+            let mut points = slab(times);
+            week = points
+                .convert_gps_time(GpsTimeType::Week, GpsTimeType::Standard, week)
+                .unwrap();
+            gps_time.extend(points.gps_time().unwrap());
+        }
+        assert_eq!(Some((1932, 20.)), week);
+        let start = week_to_adjusted_standard(1930, saturday_night);
+        let week = SECONDS_PER_WEEK;
+        assert_eq!(
+            vec![
+                start,
+                start + 90.,
+                start + 120.,
+                start + 150.,
+                start + 60. + week / 2.,
+                start + 59. + week,
+                start + 70. + week,
+                start + 80. + week,
+            ],
+            gps_time
+        );
+    }
+}
@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 /// Use this structure to build a [Header].
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Builder {
     /// The date of file creation.
     pub date: Option<NaiveDate>,
@@ -28,6 +29,10 @@ pub struct Builder {
     pub has_synthetic_return_numbers: bool,
 
     /// Does this file has a WKT CRS?
+    ///
+    /// Not serialized: a deserialized header has a WKT CRS if it has a WKT
+    /// CRS (E)VLR.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub has_wkt_crs: bool,
 
     /// Bytes after the header but before the vlrs.
@@ -264,6 +269,17 @@ impl Builder {
         }
         None
     }
+
+    /// Adds the point counts of returns above `max_return_number` to the count of `max_return_number`.
+    pub(crate) fn cap_return_numbers(&mut self, max_return_number: u8) {
+        let mut number_of_points_by_return = HashMap::new();
+        for (&return_number, &n) in &self.number_of_points_by_return {
+            *number_of_points_by_return
+                .entry(return_number.min(max_return_number))
+                .or_insert(0) += n;
+        }
+        self.number_of_points_by_return = number_of_points_by_return;
+    }
 }
 
 impl<V: Into<Version>> From<V> for Builder {
@@ -301,6 +317,33 @@ impl From<Header> for Builder {
     }
 }
 
+impl TryFrom<Builder> for Header {
+    type Error = Error;
+
+    /// Builds a header that can be written.
+    ///
+    /// Unlike [Builder::into_header], this also checks the point counts
+    /// against the version, as a writer would, and sets
+    /// [has_wkt_crs](Builder::has_wkt_crs) from the (E)VLRs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Header};
+    /// let header = Header::try_from(Builder::from((1, 4))).unwrap();
+    /// ```
+    fn try_from(mut builder: Builder) -> Result<Header> {
+        builder.has_wkt_crs = builder
+            .vlrs
+            .iter()
+            .chain(&builder.evlrs)
+            .any(Vlr::is_wkt_crs);
+        let header = builder.into_header()?;
+        let _ = header.clone().into_raw()?;
+        Ok(header)
+    }
+}
+
 fn number_of_points_hash_map<T: Copy + Into<u64>>(slice: &[T]) -> HashMap<u8, u64> {
     assert!(slice.len() < u8::MAX as usize);
     slice
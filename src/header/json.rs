@@ -0,0 +1,179 @@
+use super::Header;
+use crate::{Result, Vlr};
+use serde_json::{json, Map, Value};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl Header {
+    /// Summarizes this header as JSON, in the shape of PDAL's `info --metadata`.
+    ///
+    /// Vlrs and evlrs are numbered together as `vlr_0`, `vlr_1`, and so on,
+    /// with their data base64 encoded. Use serde directly to serialize a
+    /// header that can be deserialized again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let json = reader.header().to_summary_json().unwrap();
+    /// assert_eq!(106, json["count"]);
+    /// assert_eq!(1, json["dataformat_id"]);
+    /// assert_eq!("liblas", json["vlr_0"]["user_id"]);
+    /// ```
+    pub fn to_summary_json(&self) -> Result<Value> {
+        let raw = self.clone().into_raw()?;
+        let global_encoding = raw.global_encoding.to_le_bytes();
+        let mut summary = json!({
+            "compressed": self.point_format.is_compressed,
+            "copc": self.all_vlrs().any(|vlr| vlr.user_id == "copc" && vlr.record_id == 1),
+            "count": self.number_of_points,
+            "creation_doy": raw.file_creation_day_of_year,
+            "creation_year": raw.file_creation_year,
+            "dataformat_id": self.point_format.to_u8()?,
+            "dataoffset": raw.offset_to_point_data,
+            "filesource_id": raw.file_source_id,
+            "global_encoding": raw.global_encoding,
+            "global_encoding_base64": base64(&global_encoding),
+            "header_size": raw.header_size,
+            "major_version": self.version.major,
+            "minor_version": self.version.minor,
+            "maxx": raw.max_x,
+            "maxy": raw.max_y,
+            "maxz": raw.max_z,
+            "minx": raw.min_x,
+            "miny": raw.min_y,
+            "minz": raw.min_z,
+            "offset_x": raw.x_offset,
+            "offset_y": raw.y_offset,
+            "offset_z": raw.z_offset,
+            "point_length": raw.point_data_record_length,
+            "project_id": self.guid.to_string(),
+            "scale_x": raw.x_scale_factor,
+            "scale_y": raw.y_scale_factor,
+            "scale_z": raw.z_scale_factor,
+            "software_id": self.generating_software,
+            "spatialreference": self
+                .get_wkt_crs_bytes()
+                .map(|bytes| {
+                    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                    String::from_utf8_lossy(&bytes[..len]).into_owned()
+                })
+                .unwrap_or_default(),
+            "system_id": self.system_identifier,
+        });
+        let map = summary
+            .as_object_mut()
+            .expect("the summary is a json object");
+        for (i, vlr) in self.all_vlrs().enumerate() {
+            let _ = map.insert(format!("vlr_{i}"), vlr_json(vlr));
+        }
+        Ok(summary)
+    }
+}
+
+fn vlr_json(vlr: &Vlr) -> Value {
+    let mut map = Map::new();
+    let _ = map.insert("data".to_string(), base64(&vlr.data).into());
+    let _ = map.insert("description".to_string(), vlr.description.clone().into());
+    let _ = map.insert("record_id".to_string(), vlr.record_id.into());
+    let _ = map.insert("user_id".to_string(), vlr.user_id.clone().into());
+    Value::Object(map)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crs::GeoTiffCrs, Builder, Point, Reader};
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYg==", base64(b"foob"));
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let header = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .header()
+            .clone();
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(header, serde_json::from_str::<Header>(&json).unwrap());
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr::default());
+        let header = builder.into_header().unwrap();
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(header, serde_json::from_value::<Header>(json).unwrap());
+    }
+
+    #[test]
+    fn deserialize_checks_invariants() {
+        let json = serde_json::to_value(Header::from((1, 2))).unwrap();
+        assert!(json.get("has_wkt_crs").is_none());
+        assert!(json.get("start_of_first_evlr").is_none());
+
+        let mut extended = json.clone();
+        extended["point_format"]["is_extended"] = true.into();
+        assert!(serde_json::from_value::<Header>(extended).is_err());
+
+        let mut return_number = serde_json::to_value(Header::from((1, 4))).unwrap();
+        return_number["number_of_points_by_return"] = json!({ "16": 1 });
+        assert!(serde_json::from_value::<Header>(return_number).is_err());
+    }
+
+    #[test]
+    fn point_and_crs_roundtrip() {
+        let points: Vec<Point> = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .read_points(3)
+            .unwrap()
+            .points()
+            .collect::<Result<_>>()
+            .unwrap();
+        let json = serde_json::to_string(&points).unwrap();
+        assert_eq!(points, serde_json::from_str::<Vec<Point>>(&json).unwrap());
+
+        let crs = GeoTiffCrs::default().with_projected_crs(2994);
+        let json = serde_json::to_string(&crs).unwrap();
+        assert_eq!(crs, serde_json::from_str::<GeoTiffCrs>(&json).unwrap());
+    }
+
+    #[test]
+    fn summary() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: b"foo".to_vec(),
+            ..Default::default()
+        });
+        let header = builder.into_header().unwrap();
+        let json = header.to_summary_json().unwrap();
+        assert_eq!(4, json["minor_version"]);
+        assert_eq!(375, json["header_size"]);
+        assert_eq!("Zm9v", json["vlr_0"]["data"]);
+        assert_eq!(false, json["compressed"]);
+    }
+}
@@ -76,12 +76,23 @@ use thiserror::Error;
 use uuid::Uuid;
 
 mod builder;
+#[cfg(feature = "serde")]
+mod json;
 
 /// Metadata describing the layout, source, and interpretation of the points.
 ///
 /// Headers include *all* las metadata, including regular and extended variable length records and
 /// any file padding (e.g. extra bytes after the header).
+///
+/// With the `serde` feature, headers are (de)serialized as a [Builder], and
+/// deserializing fails for headers that [Builder::into_header] or a writer
+/// would reject.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Builder", into = "Builder")
+)]
 pub struct Header {
     bounds: Bounds,
     date: Option<NaiveDate>,
@@ -173,9 +184,11 @@ impl Header {
                 }
             }
             let _ = read.seek(SeekFrom::Start(evlr.start_of_first_evlr))?;
-            builder
-                .evlrs
-                .push(raw::Vlr::read_from(read.by_ref(), true).map(Vlr::new)?);
+            for _ in 0..evlr.number_of_evlrs {
+                builder
+                    .evlrs
+                    .push(raw::Vlr::read_from(read.by_ref(), true).map(Vlr::new)?);
+            }
         }
 
         let _ = read.seek(SeekFrom::Start(offset_to_point_data))?;
@@ -304,6 +317,20 @@ impl Header {
         self.file_source_id
     }
 
+    /// Sets this header's file source id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let mut header = Header::default();
+    /// header.set_file_source_id(42);
+    /// assert_eq!(42, header.file_source_id());
+    /// ```
+    pub fn set_file_source_id(&mut self, file_source_id: u16) {
+        self.file_source_id = file_source_id;
+    }
+
     /// Returns the gps time type.
     ///
     /// This affects what the gps time values on points means.
@@ -321,6 +348,22 @@ impl Header {
         self.gps_time_type
     }
 
+    /// Sets the gps time type.
+    ///
+    /// This only changes the flag. Use [Header::convert_gps_time] to convert the values too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTimeType, Header};
+    /// let mut header = Header::default();
+    /// header.set_gps_time_type(GpsTimeType::Standard);
+    /// assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+    /// ```
+    pub fn set_gps_time_type(&mut self, gps_time_type: GpsTimeType) {
+        self.gps_time_type = gps_time_type;
+    }
+
     /// Returns true if the return numbers on the point data records have been synthetically
     /// generated.
     ///
@@ -360,6 +403,21 @@ impl Header {
         self.guid
     }
 
+    /// Sets this header's guid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// use uuid::Uuid;
+    /// let mut header = Header::default();
+    /// header.set_guid(Uuid::from_u128(42));
+    /// assert_eq!(Uuid::from_u128(42), header.guid());
+    /// ```
+    pub fn set_guid(&mut self, guid: Uuid) {
+        self.guid = guid;
+    }
+
     /// Returns this header's version.
     ///
     /// # Examples
@@ -386,6 +444,26 @@ impl Header {
         &self.system_identifier
     }
 
+    /// Sets this header's system identifier.
+    ///
+    /// Returns an error if it's longer than 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let mut header = Header::default();
+    /// header.set_system_identifier("MODIFICATION").unwrap();
+    /// assert_eq!("MODIFICATION", header.system_identifier());
+    /// assert!(header.set_system_identifier("x".repeat(33)).is_err());
+    /// ```
+    pub fn set_system_identifier(&mut self, system_identifier: impl Into<String>) -> Result<()> {
+        let system_identifier = system_identifier.into();
+        [0; 32].as_mut().from_las_str(&system_identifier)?;
+        self.system_identifier = system_identifier;
+        Ok(())
+    }
+
     /// Returns this header's generating software.
     ///
     /// # Examples
@@ -398,6 +476,28 @@ impl Header {
         &self.generating_software
     }
 
+    /// Sets this header's generating software.
+    ///
+    /// Returns an error if it's longer than 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let mut header = Header::default();
+    /// header.set_generating_software("my-tool 1.0").unwrap();
+    /// assert_eq!("my-tool 1.0", header.generating_software());
+    /// ```
+    pub fn set_generating_software(
+        &mut self,
+        generating_software: impl Into<String>,
+    ) -> Result<()> {
+        let generating_software = generating_software.into();
+        [0; 32].as_mut().from_las_str(&generating_software)?;
+        self.generating_software = generating_software;
+        Ok(())
+    }
+
     /// Returns this header's file creation date.
     ///
     /// Can be `None`, which is against spec but happens with files in the wild.
@@ -412,6 +512,22 @@ impl Header {
         self.date
     }
 
+    /// Sets this header's file creation date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use las::Header;
+    /// let mut header = Header::default();
+    /// let date = NaiveDate::from_ymd_opt(2024, 3, 1);
+    /// header.set_date(date);
+    /// assert_eq!(date, header.date());
+    /// ```
+    pub fn set_date(&mut self, date: Option<NaiveDate>) {
+        self.date = date;
+    }
+
     /// Returns this header's padding.
     ///
     /// These are bytes that are after the header but before the vlr. Not recommended to use.
@@ -547,6 +663,20 @@ impl Header {
         &self.vlrs
     }
 
+    /// Returns a mutable reference to header's variable length records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Vlr};
+    /// let mut header = Header::default();
+    /// header.vlrs_mut().push(Vlr::default());
+    /// assert_eq!(1, header.vlrs().len());
+    /// ```
+    pub fn vlrs_mut(&mut self) -> &mut Vec<Vlr> {
+        &mut self.vlrs
+    }
+
     /// Returns a reference to header's extended variable length records.
     ///
     /// # Examples
@@ -562,6 +692,22 @@ impl Header {
         &self.evlrs
     }
 
+    /// Returns a mutable reference to header's extended variable length records.
+    ///
+    /// Evlrs are only written for las 1.4 and later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Vlr};
+    /// let mut header = Header::from((1, 4));
+    /// header.evlrs_mut().push(Vlr::default());
+    /// assert_eq!(1, header.evlrs().len());
+    /// ```
+    pub fn evlrs_mut(&mut self) -> &mut Vec<Vlr> {
+        &mut self.evlrs
+    }
+
     /// Returns an iterator over all this header's vlrs, both extended and regular.
     ///
     /// # Examples
@@ -810,6 +956,11 @@ impl Header {
         }))
     }
 
+    /// Returns the position, relative to the start of the las data, just past the last point record.
+    pub(crate) fn end_of_point_data(&self) -> Result<u64> {
+        Ok(u64::from(self.offset_to_point_data()?) + self.point_data_len())
+    }
+
     fn point_data_len(&self) -> u64 {
         self.number_of_points * u64::from(self.point_format.len())
     }
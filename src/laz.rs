@@ -1,7 +1,7 @@
 //! Utility functions for working with laszip compressed data.
 
-use crate::{Error, Header, Result, Vlr};
-use laz::{LazItemRecordBuilder, LazItemType, LazVlr};
+use crate::{vlr::KnownVlr, writer::LazChunkSize, Error, Header, Result, Vlr};
+use laz::{LazItemRecordBuilder, LazItemType, LazVlr, LazVlrBuilder};
 use std::io::Cursor;
 
 /// Returns true if this [Vlr] is the laszip Vlr.
@@ -39,6 +39,11 @@ impl Header {
     /// header.add_laz_vlr().unwrap();
     /// ```
     pub fn add_laz_vlr(&mut self) -> Result<()> {
+        self.add_laz_vlr_with_chunk_size(LazChunkSize::default())
+    }
+
+    /// Adds a new laszip vlr with the given chunk size.
+    pub(crate) fn add_laz_vlr_with_chunk_size(&mut self, chunk_size: LazChunkSize) -> Result<()> {
         let point_format = self.point_format();
         let mut laz_items = LazItemRecordBuilder::new();
         if !point_format.is_extended {
@@ -79,7 +84,14 @@ impl Header {
                 let _ = laz_items.add_item(LazItemType::Byte14(point_format.extra_bytes));
             }
         }
-        let laz_vlr = LazVlr::from_laz_items(laz_items.build());
+        let laz_vlr = match chunk_size {
+            LazChunkSize::Fixed(n) => LazVlrBuilder::new(laz_items.build())
+                .with_fixed_chunk_size(n.clamp(1, u32::MAX - 1)),
+            LazChunkSize::Variable => {
+                LazVlrBuilder::new(laz_items.build()).with_variable_chunk_size()
+            }
+        }
+        .build();
         let mut cursor = Cursor::new(Vec::<u8>::new());
         laz_vlr.write_to(&mut cursor)?;
         let vlr = Vlr {
@@ -123,3 +135,24 @@ impl TryFrom<&Vlr> for LazVlr {
         LazVlr::from_buffer(&vlr.data).map_err(Error::from)
     }
 }
+
+impl KnownVlr for LazVlr {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LazVlr::USER_ID && record_id == LazVlr::RECORD_ID
+    }
+
+    fn decode(vlr: &Vlr) -> Result<LazVlr> {
+        vlr.try_into()
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let mut data = Vec::new();
+        self.write_to(&mut data)?;
+        Ok(Vlr {
+            user_id: LazVlr::USER_ID.to_owned(),
+            record_id: LazVlr::RECORD_ID,
+            description: LazVlr::DESCRIPTION.to_owned(),
+            data,
+        })
+    }
+}
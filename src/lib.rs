@@ -168,17 +168,30 @@
     warnings
 )]
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
 #[cfg(feature = "laz")]
 pub mod copc;
 #[cfg(feature = "laz")]
 pub mod laz;
+#[cfg(feature = "parquet")]
+pub mod parquet;
 
+pub mod convert;
 pub mod crs;
+pub mod diff;
+pub mod extra_bytes;
 pub mod feature;
+pub mod gps_time;
 pub mod header;
 pub mod point;
 pub mod raw;
 pub mod reader;
+pub mod repair;
+pub mod reproject;
+pub mod rewrite;
+pub mod stats;
+pub mod validate;
 pub mod vlr;
 pub mod writer;
 
@@ -187,6 +200,7 @@ mod color;
 mod error;
 mod gps_time_type;
 mod point_data;
+mod space_filling_curve;
 mod transform;
 mod utils;
 mod vector;
@@ -197,21 +211,26 @@ pub use crate::copc::CopcEntryReader;
 pub use crate::{
     bounds::Bounds,
     color::Color,
+    diff::diff,
     error::Error,
     feature::Feature,
     gps_time_type::GpsTimeType,
     header::{Builder, Header},
     point::Point,
-    point_data::{PointData, PointDataBuilder, PointDataIter},
+    point_data::{PointData, PointDataBuilder, PointDataIter, PointMut, PointRef},
     reader::{Reader, ReaderOptions},
+    repair::repair,
+    reproject::CoordinateTransform,
+    rewrite::rewrite_metadata,
+    space_filling_curve::SpaceFillingCurve,
     transform::Transform,
     vector::Vector,
     version::Version,
     vlr::Vlr,
-    writer::{Writer, WriterOptions},
+    writer::{SplitWriter, TileWriter, Writer, WriterOptions},
 };
 #[cfg(feature = "laz")]
-pub use reader::LazParallelism;
+pub use {reader::LazParallelism, writer::LazChunkSize};
 
 /// Crate-specific result type.
 pub type Result<T> = std::result::Result<T, Error>;
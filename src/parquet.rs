@@ -0,0 +1,485 @@
+//! Write point clouds to [Apache Parquet](https://parquet.apache.org/) files.
+//!
+//! Points are converted to Arrow record batches (see [crate::arrow]) and
+//! written with one row per point, so the files can be queried by tools like
+//! DuckDB or pandas. Coordinates are either written as separate `x`, `y` and
+//! `z` columns or as a [GeoParquet](https://geoparquet.org/) `geometry` column
+//! of WKB points.
+//!
+//! The LAS header, including its VLRs and EVLRs, is stored in the file's
+//! key-value metadata along with the WKT CRS, so the header can be rebuilt
+//! with [read_header].
+//!
+//! ```
+//! use las::{parquet::ParquetOptions, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let dir = tempfile::tempdir().unwrap();
+//! let path = dir.path().join("autzen.parquet");
+//! let count = las::parquet::write(&mut reader, &path, ParquetOptions::default()).unwrap();
+//! assert_eq!(count, reader.header().number_of_points());
+//!
+//! let header = las::parquet::read_header(&path).unwrap();
+//! assert_eq!(header.number_of_points(), reader.header().number_of_points());
+//! ```
+
+use crate::{
+    arrow::ArrowOptions, header::Builder, raw, Error, Header, PointDataBuilder, Reader, Result, Vlr,
+};
+use arrow_array::{ArrayRef, BinaryArray, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::Compression,
+    file::{
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+    },
+    format::KeyValue,
+};
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+    sync::Arc,
+};
+
+/// File metadata key for the hex-encoded LAS header and VLRs.
+pub const HEADER_KEY: &str = "las:header";
+
+/// File metadata key for the hex-encoded EVLRs.
+pub const EVLRS_KEY: &str = "las:evlrs";
+
+/// File metadata key for the WKT CRS.
+pub const CRS_KEY: &str = "las:crs";
+
+/// File metadata key for the GeoParquet metadata.
+pub const GEO_KEY: &str = "geo";
+
+/// The default number of points per row group.
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 1_000_000;
+
+const GEOMETRY_COLUMN: &str = "geometry";
+
+/// How point coordinates are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Geometry {
+    /// Separate `x`, `y` and `z` columns.
+    #[default]
+    Xyz,
+
+    /// A single GeoParquet `geometry` column of WKB `Point Z`s.
+    Point,
+}
+
+/// Options for writing a Parquet file.
+#[derive(Clone, Copy, Debug)]
+pub struct ParquetOptions {
+    row_group_size: usize,
+    geometry: Geometry,
+    compression: Compression,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        ParquetOptions {
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            geometry: Geometry::default(),
+            compression: Compression::SNAPPY,
+        }
+    }
+}
+
+impl ParquetOptions {
+    /// Sets the maximum number of points in each row group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::parquet::ParquetOptions;
+    /// let options = ParquetOptions::default().with_row_group_size(100_000);
+    /// ```
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    /// Sets how point coordinates are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::parquet::{Geometry, ParquetOptions};
+    /// let options = ParquetOptions::default().with_geometry(Geometry::Point);
+    /// ```
+    pub fn with_geometry(mut self, geometry: Geometry) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
+    /// Sets the column compression.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::parquet::ParquetOptions;
+    /// use parquet::basic::Compression;
+    /// let options = ParquetOptions::default().with_compression(Compression::UNCOMPRESSED);
+    /// ```
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+/// Writes the remaining points of a reader to a Parquet file.
+///
+/// The stored header's point counts and bounds are those of the points
+/// written. Returns the number of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{parquet::{Geometry, ParquetOptions}, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("autzen.parquet");
+/// let options = ParquetOptions::default().with_geometry(Geometry::Point);
+/// las::parquet::write(&mut reader, path, options).unwrap();
+/// ```
+pub fn write<P: AsRef<Path>>(reader: &mut Reader, path: P, options: ParquetOptions) -> Result<u64> {
+    let mut header = reader.header().clone();
+    let arrow_options = ArrowOptions::for_header(&header)?;
+    let mut points = PointDataBuilder::new().for_header(&header).build();
+    let schema = convert(points.to_record_batch(&arrow_options)?, options.geometry)?.schema();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(options.row_group_size)
+        .set_compression(options.compression)
+        .build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema, Some(properties))?;
+
+    // The reader may have been partly read, so the counts and bounds come
+    // from the points that are written.
+    header.clear();
+    let mut count = 0;
+    loop {
+        let n = reader.fill_points(options.row_group_size as u64, &mut points)?;
+        if n == 0 {
+            break;
+        }
+        count += n;
+        header.add_point_data(&points);
+        let batch = convert(points.to_record_batch(&arrow_options)?, options.geometry)?;
+        writer.write(&batch)?;
+    }
+
+    writer.append_key_value_metadata(KeyValue::new(
+        HEADER_KEY.to_string(),
+        encode_header(&header)?,
+    ));
+    writer.append_key_value_metadata(KeyValue::new(EVLRS_KEY.to_string(), encode_evlrs(&header)?));
+    if let Some(crs) = arrow_options.crs() {
+        writer.append_key_value_metadata(KeyValue::new(CRS_KEY.to_string(), crs.to_string()));
+    }
+    if options.geometry == Geometry::Point {
+        writer.append_key_value_metadata(KeyValue::new(GEO_KEY.to_string(), geo_metadata(&header)));
+    }
+    let _ = writer.close()?;
+    Ok(count)
+}
+
+/// Rebuilds the LAS header stored in a Parquet file's metadata.
+///
+/// # Examples
+///
+/// ```
+/// use las::{parquet::ParquetOptions, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("autzen.parquet");
+/// las::parquet::write(&mut reader, &path, ParquetOptions::default()).unwrap();
+/// let header = las::parquet::read_header(&path).unwrap();
+/// assert_eq!(header.bounds(), reader.header().bounds());
+/// ```
+pub fn read_header<P: AsRef<Path>>(path: P) -> Result<Header> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let metadata = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .cloned()
+        .unwrap_or_default();
+    let value = |key: &str| {
+        metadata
+            .iter()
+            .find(|kv| kv.key == key)
+            .and_then(|kv| kv.value.as_deref())
+    };
+    let header = value(HEADER_KEY)
+        .ok_or_else(|| Error::InvalidParquetMetadata(format!("missing {HEADER_KEY}")))?;
+    let mut read = Cursor::new(decode_hex(header)?);
+    let raw_header = raw::Header::read_from(&mut read)?;
+    let number_of_variable_length_records = raw_header.number_of_variable_length_records;
+    let mut builder = Builder::new(raw_header)?;
+    for _ in 0..number_of_variable_length_records {
+        builder
+            .vlrs
+            .push(raw::Vlr::read_from(&mut read, false).map(Vlr::new)?);
+    }
+    let _ = read.read_to_end(&mut builder.vlr_padding)?;
+    if let Some(evlrs) = value(EVLRS_KEY) {
+        let bytes = decode_hex(evlrs)?;
+        let mut read = Cursor::new(bytes.as_slice());
+        while (read.position() as usize) < bytes.len() {
+            builder
+                .evlrs
+                .push(raw::Vlr::read_from(&mut read, true).map(Vlr::new)?);
+        }
+    }
+    builder.into_header()
+}
+
+fn convert(batch: RecordBatch, geometry: Geometry) -> Result<RecordBatch> {
+    match geometry {
+        Geometry::Xyz => Ok(batch),
+        Geometry::Point => {
+            let column = |name: &str| {
+                batch
+                    .column_by_name(name)
+                    .and_then(|array| {
+                        array
+                            .as_any()
+                            .downcast_ref::<arrow_array::Float64Array>()
+                            .cloned()
+                    })
+                    .ok_or_else(|| Error::InvalidRecordBatch(format!("no Float64 {name} column")))
+            };
+            let (x, y, z) = (column("x")?, column("y")?, column("z")?);
+            let wkb = BinaryArray::from_iter_values(
+                x.values()
+                    .iter()
+                    .zip(y.values())
+                    .zip(z.values())
+                    .map(|((&x, &y), &z)| wkb_point(x, y, z)),
+            );
+            let schema = batch.schema();
+            let mut fields = vec![Arc::new(Field::new(
+                GEOMETRY_COLUMN,
+                DataType::Binary,
+                false,
+            ))];
+            let mut columns: Vec<ArrayRef> = vec![Arc::new(wkb)];
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                if !["x", "y", "z"].contains(&field.name().as_str()) {
+                    fields.push(field.clone());
+                    columns.push(column.clone());
+                }
+            }
+            let schema = Schema::new(fields).with_metadata(schema.metadata().clone());
+            RecordBatch::try_new(Arc::new(schema), columns).map_err(Error::from)
+        }
+    }
+}
+
+fn wkb_point(x: f64, y: f64, z: f64) -> [u8; 29] {
+    let mut wkb = [0; 29];
+    wkb[0] = 1; // little endian
+    wkb[1..5].copy_from_slice(&1001u32.to_le_bytes()); // ISO Point Z
+    wkb[5..13].copy_from_slice(&x.to_le_bytes());
+    wkb[13..21].copy_from_slice(&y.to_le_bytes());
+    wkb[21..29].copy_from_slice(&z.to_le_bytes());
+    wkb
+}
+
+fn geo_metadata(header: &Header) -> String {
+    let bounds = header.bounds();
+    let bbox = [
+        bounds.min.x,
+        bounds.min.y,
+        bounds.min.z,
+        bounds.max.x,
+        bounds.max.y,
+        bounds.max.z,
+    ];
+    let bbox = if bbox.iter().all(|n| n.is_finite()) {
+        format!(r#","bbox":[{}]"#, bbox.map(|n| n.to_string()).join(","))
+    } else {
+        String::new()
+    };
+    // GeoParquet only allows PROJJSON CRSes, so the WKT lives under CRS_KEY
+    // and the geometry CRS is explicitly unknown rather than defaulting to
+    // OGC:CRS84.
+    format!(
+        r#"{{"version":"1.1.0","primary_column":"{GEOMETRY_COLUMN}","columns":{{"{GEOMETRY_COLUMN}":{{"encoding":"WKB","geometry_types":["Point Z"],"crs":null{bbox}}}}}}}"#
+    )
+}
+
+fn encode_header(header: &Header) -> Result<String> {
+    let mut bytes = Vec::new();
+    header.write_to(&mut bytes)?;
+    Ok(encode_hex(&bytes))
+}
+
+fn encode_evlrs(header: &Header) -> Result<String> {
+    let mut bytes = Vec::new();
+    for evlr in header.evlrs() {
+        evlr.clone().into_raw(true)?.write_to(&mut bytes)?;
+    }
+    Ok(encode_hex(&bytes))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return Err(Error::InvalidParquetMetadata(
+            "odd-length hex string".to_string(),
+        ));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| Error::InvalidParquetMetadata(format!("invalid hex at {i}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PointData;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn write_autzen(options: ParquetOptions) -> (tempfile::TempDir, std::path::PathBuf, Header) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("autzen.parquet");
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let count = write(&mut reader, &path, options).unwrap();
+        assert_eq!(106, count);
+        (dir, path, reader.header().clone())
+    }
+
+    fn batches(path: &Path) -> Vec<RecordBatch> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+        // Batches don't carry the schema metadata, so put it back.
+        let schema = builder.schema().clone();
+        builder
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().with_schema(schema.clone()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn xyz_roundtrip() {
+        let (_dir, path, _) = write_autzen(ParquetOptions::default());
+        let batches = batches(&path);
+        let mut points = PointData::from_record_batch(&batches[0]).unwrap();
+        for batch in &batches[1..] {
+            points
+                .extend_from(&PointData::from_record_batch(batch).unwrap())
+                .unwrap();
+        }
+        let expected = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(expected.raw_bytes(), points.raw_bytes());
+    }
+
+    #[test]
+    fn row_groups() {
+        let (_dir, path, _) = write_autzen(ParquetOptions::default().with_row_group_size(25));
+        let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+        assert_eq!(5, reader.metadata().num_row_groups());
+        assert_eq!(25, reader.metadata().row_group(0).num_rows());
+    }
+
+    #[test]
+    fn point_geometry() {
+        let (_dir, path, _) =
+            write_autzen(ParquetOptions::default().with_geometry(Geometry::Point));
+        let batch = &batches(&path)[0];
+        assert!(batch.column_by_name("x").is_none());
+        let geometry = batch
+            .column_by_name(GEOMETRY_COLUMN)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<BinaryArray>()
+            .unwrap();
+        let point = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .read_points(1)
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .to_point()
+            .unwrap();
+        assert_eq!(wkb_point(point.x, point.y, point.z), geometry.value(0));
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let geo = reader
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == GEO_KEY)
+            .and_then(|kv| kv.value.clone())
+            .unwrap();
+        assert!(geo.contains(r#""primary_column":"geometry""#));
+        assert!(geo.contains(r#""bbox":["#));
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let (_dir, path, expected) = write_autzen(ParquetOptions::default());
+        let header = read_header(path).unwrap();
+        assert_eq!(expected.number_of_points(), header.number_of_points());
+        assert_eq!(expected.bounds(), header.bounds());
+        assert_eq!(expected.point_format(), header.point_format());
+        assert_eq!(expected.transforms(), header.transforms());
+        assert_eq!(expected.vlrs(), header.vlrs());
+        assert_eq!(expected.evlrs(), header.evlrs());
+        assert_eq!(expected.guid(), header.guid());
+    }
+
+    #[test]
+    fn partly_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("autzen.parquet");
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        reader.seek(6).unwrap();
+        let remaining = reader.read_all().unwrap();
+        reader.seek(6).unwrap();
+        assert_eq!(
+            100,
+            write(&mut reader, &path, ParquetOptions::default()).unwrap()
+        );
+        let header = read_header(&path).unwrap();
+        assert_eq!(100, header.number_of_points());
+        let mut expected = reader.header().clone();
+        expected.clear();
+        expected.add_point_data(&remaining);
+        assert_eq!(expected.bounds(), header.bounds());
+        assert_eq!(
+            expected.number_of_points_by_return(1),
+            header.number_of_points_by_return(1)
+        );
+    }
+
+    #[test]
+    fn hex() {
+        let bytes = [0, 1, 0xab, 0xff];
+        assert_eq!("0001abff", encode_hex(&bytes));
+        assert_eq!(bytes.to_vec(), decode_hex("0001abff").unwrap());
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("zz").is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{point::Error, Result};
+use crate::{
+    point::Error,
+    vlr::{ClassificationLookup, ClassificationLookupEntry, KnownVlr},
+    Header, Result,
+};
+use std::collections::BTreeMap;
 
 /// The ASPRS classification table.
 ///
@@ -29,6 +34,7 @@ use crate::{point::Error, Result};
 /// assert!(Classification::new(12).is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum Classification {
     #[default]
@@ -50,6 +56,9 @@ pub enum Classification {
     WireStructureConnector,
     BridgeDeck,
     HighNoise,
+    OverheadStructure,
+    IgnoredSnow,
+    TemporaryExclusion,
     Reserved(u8),
     UserDefinable(u8),
 }
@@ -87,10 +96,28 @@ impl Classification {
             16 => Classification::WireStructureConnector,
             17 => Classification::BridgeDeck,
             18 => Classification::HighNoise,
-            19..=63 => Classification::Reserved(n),
+            19 => Classification::OverheadStructure,
+            20 => Classification::IgnoredSnow,
+            21 => Classification::TemporaryExclusion,
+            22..=63 => Classification::Reserved(n),
             64..=255 => Classification::UserDefinable(n),
         })
     }
+
+    /// Returns the ASPRS name of this classification.
+    ///
+    /// Returns `None` for reserved and user definable classifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert_eq!(Some("Ground"), Classification::Ground.name());
+    /// assert_eq!(None, Classification::UserDefinable(64).name());
+    /// ```
+    pub fn name(&self) -> Option<&'static str> {
+        standard_name(u8::from(*self))
+    }
 }
 
 impl From<Classification> for u8 {
@@ -114,7 +141,208 @@ impl From<Classification> for u8 {
             Classification::WireStructureConnector => 16,
             Classification::BridgeDeck => 17,
             Classification::HighNoise => 18,
+            Classification::OverheadStructure => 19,
+            Classification::IgnoredSnow => 20,
+            Classification::TemporaryExclusion => 21,
             Classification::Reserved(n) | Classification::UserDefinable(n) => n,
         }
     }
 }
+
+/// Names for classification codes, as used by one file.
+///
+/// The default scheme has the ASPRS names. Custom names, e.g. for user
+/// definable classes, take precedence and are stored in the classification
+/// lookup vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::point::ClassificationScheme;
+/// let scheme = ClassificationScheme::default().with_name(64, "Pole");
+/// assert_eq!(Some("Ground"), scheme.name(2));
+/// assert_eq!(Some("Pole"), scheme.name(64));
+/// assert_eq!("Class 65", scheme.label(65));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClassificationScheme {
+    names: BTreeMap<u8, String>,
+}
+
+impl ClassificationScheme {
+    /// Sets the name of a classification code.
+    pub fn with_name(mut self, code: u8, name: impl Into<String>) -> ClassificationScheme {
+        let _ = self.names.insert(code, name.into());
+        self
+    }
+
+    /// Returns the name of a classification code, if it has one.
+    pub fn name(&self, code: u8) -> Option<&str> {
+        self.names
+            .get(&code)
+            .map(String::as_str)
+            .or_else(|| standard_name(code))
+    }
+
+    /// Returns the name of a classification code, or `Class <code>` if it has none.
+    pub fn label(&self, code: u8) -> String {
+        self.name(code)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Class {code}"))
+    }
+
+    /// Returns the custom names, by code.
+    pub fn custom_names(&self) -> &BTreeMap<u8, String> {
+        &self.names
+    }
+
+    /// Creates a scheme from a classification lookup vlr.
+    ///
+    /// Entries with empty descriptions are skipped.
+    pub fn from_lookup(lookup: &ClassificationLookup) -> ClassificationScheme {
+        lookup
+            .entries
+            .iter()
+            .filter(|entry| !entry.description.is_empty())
+            .fold(ClassificationScheme::default(), |scheme, entry| {
+                scheme.with_name(entry.class, entry.description.clone())
+            })
+    }
+
+    /// Creates a classification lookup vlr holding the custom names.
+    pub fn to_lookup(&self) -> ClassificationLookup {
+        ClassificationLookup {
+            entries: self
+                .names
+                .iter()
+                .map(|(&class, description)| ClassificationLookupEntry {
+                    class,
+                    description: description.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Header {
+    /// Returns this header's classification scheme.
+    ///
+    /// The custom names come from the classification lookup vlr, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let scheme = reader.header().classification_scheme().unwrap();
+    /// assert_eq!(Some("Ground"), scheme.name(2));
+    /// ```
+    pub fn classification_scheme(&self) -> Result<ClassificationScheme> {
+        self.all_vlrs()
+            .find(|vlr| ClassificationLookup::matches(&vlr.user_id, vlr.record_id))
+            .map_or(Ok(ClassificationScheme::default()), |vlr| {
+                Ok(ClassificationScheme::from_lookup(&vlr.decode_as()?))
+            })
+    }
+
+    /// Stores the custom names of a classification scheme in a classification lookup vlr.
+    ///
+    /// Replaces any existing classification lookup vlr, or removes it if the
+    /// scheme has no custom names. Names can be at most 15 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::ClassificationScheme, Header};
+    /// let mut header = Header::default();
+    /// let scheme = ClassificationScheme::default().with_name(64, "Pole");
+    /// header.set_classification_scheme(&scheme).unwrap();
+    /// assert_eq!(scheme, header.classification_scheme().unwrap());
+    /// ```
+    pub fn set_classification_scheme(&mut self, scheme: &ClassificationScheme) -> Result<()> {
+        let vlr = if scheme.names.is_empty() {
+            None
+        } else {
+            Some(scheme.to_lookup().encode()?)
+        };
+        let is_lookup =
+            |vlr: &crate::Vlr| ClassificationLookup::matches(&vlr.user_id, vlr.record_id);
+        self.vlrs.retain(|vlr| !is_lookup(vlr));
+        self.evlrs.retain(|vlr| !is_lookup(vlr));
+        self.vlrs.extend(vlr);
+        Ok(())
+    }
+}
+
+fn standard_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0 => "Created, never classified",
+        1 => "Unclassified",
+        2 => "Ground",
+        3 => "Low vegetation",
+        4 => "Medium vegetation",
+        5 => "High vegetation",
+        6 => "Building",
+        7 => "Low point (noise)",
+        8 => "Model key-point",
+        9 => "Water",
+        10 => "Rail",
+        11 => "Road surface",
+        12 => "Overlap points",
+        13 => "Wire - guard",
+        14 => "Wire - conductor",
+        15 => "Transmission tower",
+        16 => "Wire-structure connector",
+        17 => "Bridge deck",
+        18 => "High noise",
+        19 => "Overhead structure",
+        20 => "Ignored snow",
+        21 => "Temporary exclusion",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Reader, Writer};
+    use std::io::Cursor;
+
+    #[test]
+    fn roundtrip_new_classes() {
+        for n in 19..=22 {
+            assert_eq!(n, u8::from(Classification::new(n).unwrap()));
+        }
+        assert_eq!(
+            Classification::Reserved(22),
+            Classification::new(22).unwrap()
+        );
+    }
+
+    #[test]
+    fn scheme_roundtrip_through_file() {
+        let scheme = (64..=80).fold(ClassificationScheme::default(), |scheme, code| {
+            scheme.with_name(code, format!("Utility {code}"))
+        });
+        let mut header = Builder::from((1, 4)).into_header().unwrap();
+        header.set_classification_scheme(&scheme).unwrap();
+        header.set_classification_scheme(&scheme).unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.close().unwrap();
+        let cursor = writer.into_inner().unwrap();
+        let reader = Reader::new(Cursor::new(cursor.into_inner())).unwrap();
+        assert_eq!(1, reader.header().vlrs().len());
+        let read = reader.header().classification_scheme().unwrap();
+        assert_eq!(scheme, read);
+        assert_eq!("Utility 70", read.label(70));
+        assert_eq!("Class 81", read.label(81));
+    }
+
+    #[test]
+    fn name_too_long() {
+        let scheme = ClassificationScheme::default().with_name(64, "Sixteen chars!!!");
+        assert!(Header::default()
+            .set_classification_scheme(&scheme)
+            .is_err());
+    }
+}
@@ -0,0 +1,100 @@
+/// A field of a point record.
+///
+/// Used to pick a field at runtime, e.g. with
+/// [PointData::sort_by_field](crate::PointData::sort_by_field).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    /// The x coordinate.
+    X,
+
+    /// The y coordinate.
+    Y,
+
+    /// The z coordinate.
+    Z,
+
+    /// The pulse return magnitude.
+    Intensity,
+
+    /// The pulse return number for a given output pulse.
+    ReturnNumber,
+
+    /// The total number of returns for a given pulse.
+    NumberOfReturns,
+
+    /// The scan direction, zero for right to left and one for left to right.
+    ScanDirection,
+
+    /// One if this point is at the end of a scan, zero otherwise.
+    EdgeOfFlightLine,
+
+    /// The classification.
+    Classification,
+
+    /// The synthetic flag.
+    Synthetic,
+
+    /// The key point flag.
+    KeyPoint,
+
+    /// The withheld flag.
+    Withheld,
+
+    /// The overlap flag.
+    Overlap,
+
+    /// The scanner channel.
+    ScannerChannel,
+
+    /// The scan angle, in degrees.
+    ScanAngle,
+
+    /// The user data.
+    UserData,
+
+    /// The point source ID.
+    PointSourceId,
+
+    /// The GPS time.
+    GpsTime,
+
+    /// The red channel.
+    Red,
+
+    /// The green channel.
+    Green,
+
+    /// The blue channel.
+    Blue,
+
+    /// The near infrared channel.
+    Nir,
+}
+
+impl Dimension {
+    /// Every dimension, in point record order.
+    pub const ALL: [Dimension; 22] = [
+        Dimension::X,
+        Dimension::Y,
+        Dimension::Z,
+        Dimension::Intensity,
+        Dimension::ReturnNumber,
+        Dimension::NumberOfReturns,
+        Dimension::ScanDirection,
+        Dimension::EdgeOfFlightLine,
+        Dimension::Classification,
+        Dimension::Synthetic,
+        Dimension::KeyPoint,
+        Dimension::Withheld,
+        Dimension::Overlap,
+        Dimension::ScannerChannel,
+        Dimension::ScanAngle,
+        Dimension::UserData,
+        Dimension::PointSourceId,
+        Dimension::GpsTime,
+        Dimension::Red,
+        Dimension::Green,
+        Dimension::Blue,
+        Dimension::Nir,
+    ];
+}
@@ -1,4 +1,7 @@
-use crate::{point::Error, Result};
+use crate::{
+    point::{Dimension, Error},
+    Result,
+};
 use std::fmt;
 
 const TIME_FORMATS: &[u8] = &[1, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -54,6 +57,7 @@ fn point_format_id_uncompressed_to_compressed(point_format_id: u8) -> u8 {
 /// assert!(format.to_u8().is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Format {
     /// Does this point format include gps time?
     pub has_gps_time: bool,
@@ -144,6 +148,25 @@ impl Format {
             .sum::<usize>() as u16
     }
 
+    /// Returns true if points in this format have a value for this dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{Dimension, Format};
+    /// let format = Format::new(1).unwrap();
+    /// assert!(format.has_dimension(Dimension::GpsTime));
+    /// assert!(!format.has_dimension(Dimension::Red));
+    /// ```
+    pub fn has_dimension(&self, dimension: Dimension) -> bool {
+        match dimension {
+            Dimension::GpsTime => self.has_gps_time,
+            Dimension::Red | Dimension::Green | Dimension::Blue => self.has_color,
+            Dimension::Nir => self.has_nir,
+            _ => true,
+        }
+    }
+
     /// Converts this point format to a u8.
     ///
     /// Can return an error if there is an invalid combination of attributes.
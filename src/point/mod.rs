@@ -13,15 +13,22 @@
 //! scale and offset from the header are applied.
 
 mod classification;
+mod dimension;
 mod format;
 mod scan_direction;
 
-pub use self::{classification::Classification, format::Format, scan_direction::ScanDirection};
+pub use self::{
+    classification::{Classification, ClassificationScheme},
+    dimension::Dimension,
+    format::Format,
+    scan_direction::ScanDirection,
+};
 use crate::{raw, raw::point::Waveform, Color, Error, Result, Transform, Vector};
 use thiserror::Error;
 
 /// A three dimensional point.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// The x coordinate, as a float.
     pub x: f64,
@@ -0,0 +1,2179 @@
+//! A byte-slab of point records, for high-throughput bulk reads and
+//! column-oriented analysis.
+//!
+//! [PointData] holds a single contiguous `Vec<u8>` of decompressed LAS
+//! records in their on-disk layout, paired with the format and coordinate
+//! transforms needed to decode individual fields. It is the "bytes-out"
+//! counterpart to the [Point](crate::Point) iteration API on
+//! [Reader](crate::Reader): the `Reader` is the factory, `PointData` is
+//! the product.
+//!
+//! Construct one via [`Reader::read_points`](crate::Reader::read_points)
+//! (next `n` points), [`Reader::read_all`](crate::Reader::read_all) (the
+//! whole file), or [`Reader::fill_points`](crate::Reader::fill_points)
+//! (reuse an existing `PointData`). For callers that drive a
+//! decompressor manually (e.g. against COPC chunks), build an empty
+//! [`PointData`] via [`PointDataBuilder`] and fill the bytes via
+//! [`PointData::resize_for`].
+//!
+//! # Example
+//!
+//! ```
+//! use las::Reader;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let points = reader.read_all().unwrap();
+//!
+//! // Row-oriented: iterate points as owned `Point` values.
+//! for p in points.points().take(3) {
+//!     let p = p.unwrap();
+//!     println!("{:.3} {:.3} {:.3} intensity={}", p.x, p.y, p.z, p.intensity);
+//! }
+//!
+//! // Column-oriented: one pass over the x column, no `Point` materialization.
+//! let min_x = points.x().fold(f64::INFINITY, f64::min);
+//! let max_x = points.x().fold(f64::NEG_INFINITY, f64::max);
+//! assert!(min_x <= max_x);
+//! ```
+
+use crate::{
+    gps_time,
+    point::{Dimension, Format, ScanDirection},
+    raw,
+    raw::point::{Flags, Layout, Waveform},
+    Bounds, Color, CoordinateTransform, Error, GpsTimeType, Point, Result, SpaceFillingCurve,
+    Transform, Vector,
+};
+use std::{
+    cmp::Ordering,
+    io::Cursor,
+    ops::{Bound, Range, RangeBounds},
+};
+
+mod view;
+
+pub use view::{PointMut, PointRef};
+
+/// A set of decompressed LAS point records held as one contiguous byte slab.
+///
+/// `PointData` mirrors the on-disk layout for a specific point format.
+/// It's produced by [`Reader`](crate::Reader) — call
+/// [`Reader::read_points`](crate::Reader::read_points) or
+/// [`Reader::read_all`](crate::Reader::read_all) — or constructed directly
+/// via [`PointDataBuilder`] (paired with [`PointData::resize_for`] when
+/// driving an external decompressor, e.g. COPC).
+///
+/// The row view is [`PointData::points`], yielding owned [`Point`] values;
+/// column views are [`PointData::x`], [`PointData::intensity`], etc., each
+/// a cheap iterator over one field at a time.
+///
+/// # Example
+///
+/// ```
+/// use las::Reader;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let points = reader.read_points(10).unwrap();
+/// assert_eq!(points.len(), 10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PointData {
+    bytes: Vec<u8>,
+    format: Format,
+    transforms: Vector<Transform>,
+    layout: Layout,
+}
+
+/// Builder for [`PointData`].
+///
+/// Construct via [`PointDataBuilder::new`], configure the point format and
+/// coordinate transforms, then finalize with one of:
+///
+/// - [`build`](Self::build) — an empty `PointData`, sized to zero points.
+/// - [`build_from_bytes`](Self::build_from_bytes) — wrap an existing
+///   `Vec<u8>` of tightly-packed records for the configured format.
+/// - [`build_from_points`](Self::build_from_points) — encode owned
+///   [`Point`]s against the configured format and transforms.
+///
+/// Configure by either passing a [`Header`](crate::Header) wholesale with
+/// [`for_header`](Self::for_header) — the typical case when you already
+/// have a [`Reader`](crate::Reader) or [`Writer`](crate::Writer) in hand —
+/// or by setting the pieces individually with
+/// [`with_format`](Self::with_format) and
+/// [`with_transforms`](Self::with_transforms).
+///
+/// The format and transforms are cached for per-point accessor dispatch
+/// and must match the file these records will be filled from or were
+/// produced from.
+///
+/// # Example
+///
+/// ```
+/// use las::{PointDataBuilder, Reader};
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let points = PointDataBuilder::new().for_header(reader.header()).build();
+/// assert!(points.is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PointDataBuilder {
+    format: Format,
+    transforms: Vector<Transform>,
+}
+
+impl PointDataBuilder {
+    /// Creates a new builder with default format and transforms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the point format the resulting `PointData` will be decoded against.
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the coordinate transforms applied when decoding x/y/z.
+    pub fn with_transforms(mut self, transforms: Vector<Transform>) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
+    /// Configures format and transforms from a [`Header`](crate::Header).
+    ///
+    /// Shorthand for chaining
+    /// [`with_format`](Self::with_format)`(*header.point_format())` and
+    /// [`with_transforms`](Self::with_transforms)`(*header.transforms())`.
+    /// Works equally well with `reader.header()`, `writer.header()`, or a
+    /// `Header` built manually via [`Builder`](crate::Builder).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use las::{PointDataBuilder, Reader};
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = PointDataBuilder::new().for_header(reader.header()).build();
+    /// assert!(points.is_empty());
+    /// ```
+    pub fn for_header(self, header: &crate::Header) -> Self {
+        self.with_format(*header.point_format())
+            .with_transforms(*header.transforms())
+    }
+
+    /// Builds an empty `PointData`.
+    pub fn build(self) -> PointData {
+        let layout = Layout::for_format(&self.format);
+        PointData {
+            bytes: Vec::new(),
+            format: self.format,
+            transforms: self.transforms,
+            layout,
+        }
+    }
+
+    /// Builds a `PointData` by taking ownership of an existing byte buffer.
+    ///
+    /// The byte buffer must be a sequence of tightly-packed point records for
+    /// the configured format — i.e. its length must be a multiple of the
+    /// format's record length. Useful for callers that already have a
+    /// decompressed `Vec<u8>` in hand (custom decompressor, memory-mapped
+    /// region, received over the wire).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidCloudByteLength`](crate::Error::InvalidByteBufferLength)
+    /// if `bytes.len()` is not a multiple of the format's record length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use las::{point::Format, PointDataBuilder, Transform, Vector};
+    ///
+    /// let format = Format::new(0).unwrap();
+    /// let transforms: Vector<Transform> = Default::default();
+    /// let record_len = format.len() as usize;
+    /// let bytes = vec![0u8; record_len * 5];
+    /// let points = PointDataBuilder::new()
+    ///     .with_format(format)
+    ///     .with_transforms(transforms)
+    ///     .build_from_bytes(bytes)
+    ///     .unwrap();
+    /// assert_eq!(points.len(), 5);
+    /// ```
+    pub fn build_from_bytes(self, bytes: Vec<u8>) -> Result<PointData> {
+        let layout = Layout::for_format(&self.format);
+        if layout.record_len == 0 || !bytes.len().is_multiple_of(layout.record_len) {
+            return Err(Error::InvalidByteBufferLength {
+                len: bytes.len(),
+                record_len: layout.record_len,
+            });
+        }
+        Ok(PointData {
+            bytes,
+            format: self.format,
+            transforms: self.transforms,
+            layout,
+        })
+    }
+
+    /// Builds a `PointData` by encoding owned [`Point`]s against the
+    /// configured format and transforms.
+    ///
+    /// This is the bulk-write counterpart to
+    /// [`Reader::read_points`](crate::Reader::read_points): if you're
+    /// constructing points programmatically (not reading a file), hand them
+    /// here and pass the result to
+    /// [`Writer::write_points`](crate::Writer::write_points).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any point's optional field set doesn't match the
+    /// target format, or if a coordinate transform overflows.
+    pub fn build_from_points<I>(self, points: I) -> Result<PointData>
+    where
+        I: IntoIterator<Item = Point>,
+    {
+        let layout = Layout::for_format(&self.format);
+        let iter = points.into_iter();
+        let (lower, _) = iter.size_hint();
+        let mut bytes = Vec::with_capacity(lower * layout.record_len);
+        for point in iter {
+            let raw = point.into_raw(&self.transforms)?;
+            raw.write_to(&mut bytes, &self.format)?;
+        }
+        Ok(PointData {
+            bytes,
+            format: self.format,
+            transforms: self.transforms,
+            layout,
+        })
+    }
+}
+
+impl PointData {
+    /// Returns the number of points currently held.
+    pub fn len(&self) -> usize {
+        self.bytes
+            .len()
+            .checked_div(self.layout.record_len)
+            .unwrap_or(0)
+    }
+
+    /// Returns true if this `PointData` contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the point format these records were built for.
+    pub fn format(&self) -> &Format {
+        &self.format
+    }
+
+    /// Returns the coordinate transforms these records were built with.
+    pub fn transforms(&self) -> &Vector<Transform> {
+        &self.transforms
+    }
+
+    /// Returns the underlying byte buffer.
+    ///
+    /// Its length is `self.len() * self.format().len() as usize`. Callers that
+    /// want to parse fields themselves or hand the slab to another system can
+    /// use this as an escape hatch.
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the record length in bytes for this format.
+    pub fn record_len(&self) -> usize {
+        self.layout.record_len
+    }
+
+    /// Decodes rows into owned [`Point`] values, row by row.
+    ///
+    /// Each call to `.next()` runs `raw::Point::read_from` + [`Point::new`]
+    /// on one record. Not named `.iter()` because it's not a view over
+    /// existing `Point`s — there is no `Point` in memory until you ask
+    /// for one. Prefer the column accessors ([`PointData::x`],
+    /// [`PointData::intensity`], …) when you only need a subset of fields;
+    /// they skip the full-record decode, or [`PointData::views`] for
+    /// per-point loops that shouldn't allocate.
+    pub fn points(&self) -> PointDataIter<'_> {
+        PointDataIter {
+            cursor: Cursor::new(&self.bytes),
+            format: &self.format,
+            transforms: &self.transforms,
+            remaining: self.len(),
+        }
+    }
+
+    /// Returns borrowed views of the points, which decode fields lazily.
+    ///
+    /// Unlike [PointData::points], this doesn't allocate or decode fields
+    /// that aren't asked for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let max_intensity = points.views().map(|p| p.intensity()).max();
+    /// ```
+    pub fn views(&self) -> impl ExactSizeIterator<Item = PointRef<'_>> + '_ {
+        self.bytes
+            .chunks_exact(self.layout.record_len)
+            .map(|record| PointRef::new(record, &self.format, &self.transforms, &self.layout))
+    }
+
+    /// Returns mutable views of the points, for editing them in place.
+    pub fn views_mut(&mut self) -> impl ExactSizeIterator<Item = PointMut<'_>> + '_ {
+        let (format, transforms, layout) = (&self.format, &self.transforms, &self.layout);
+        self.bytes
+            .chunks_exact_mut(layout.record_len)
+            .map(move |record| PointMut::new(record, format, transforms, layout))
+    }
+
+    /// Returns a view of the point at `index`, or `None` if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<PointRef<'_>> {
+        let range = self.record_range(index)?;
+        self.bytes
+            .get(range)
+            .map(|record| PointRef::new(record, &self.format, &self.transforms, &self.layout))
+    }
+
+    /// Returns a mutable view of the point at `index`, or `None` if it's out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<PointMut<'_>> {
+        let range = self.record_range(index)?;
+        self.bytes
+            .get_mut(range)
+            .map(|record| PointMut::new(record, &self.format, &self.transforms, &self.layout))
+    }
+
+    /// Resizes the underlying byte buffer to hold exactly `n` points and
+    /// returns a mutable view of it.
+    ///
+    /// This is the primary entry point for callers that drive a decompressor
+    /// directly (e.g. against COPC chunks that bypass [Reader](crate::Reader)).
+    /// Call `resize_for`, decompress into the returned slice, and then use the
+    /// column accessors or [PointData::points] as usual.
+    pub fn resize_for(&mut self, n: usize) -> &mut [u8] {
+        let new_len = n.checked_mul(self.layout.record_len).expect("overflow");
+        self.bytes.resize(new_len, 0u8);
+        &mut self.bytes
+    }
+
+    /// Exposes the underlying byte vector for a backend to fill directly.
+    ///
+    /// Used by [`Reader::fill_points`] so the reader's [`ReadPoints`]
+    /// backend can write straight into this `PointData`'s own storage
+    /// instead of through an intermediate buffer.
+    pub(crate) fn take_bytes_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.bytes
+    }
+
+    /// Raw scaled x values (little-endian i32 loads from the x column).
+    pub fn x_raw(&self) -> impl Iterator<Item = i32> + '_ {
+        self.i32_column(0)
+    }
+
+    /// Raw scaled y values.
+    pub fn y_raw(&self) -> impl Iterator<Item = i32> + '_ {
+        self.i32_column(4)
+    }
+
+    /// Raw scaled z values.
+    pub fn z_raw(&self) -> impl Iterator<Item = i32> + '_ {
+        self.i32_column(8)
+    }
+
+    /// World x values, with scale and offset applied.
+    pub fn x(&self) -> impl Iterator<Item = f64> + '_ {
+        let t = self.transforms.x;
+        self.x_raw().map(move |n| t.direct(n))
+    }
+
+    /// World y values.
+    pub fn y(&self) -> impl Iterator<Item = f64> + '_ {
+        let t = self.transforms.y;
+        self.y_raw().map(move |n| t.direct(n))
+    }
+
+    /// World z values.
+    pub fn z(&self) -> impl Iterator<Item = f64> + '_ {
+        let t = self.transforms.z;
+        self.z_raw().map(move |n| t.direct(n))
+    }
+
+    /// Intensity column.
+    pub fn intensity(&self) -> impl Iterator<Item = u16> + '_ {
+        self.u16_column(12)
+    }
+
+    /// Classification byte column. For legacy formats this is the low 5 bits
+    /// of the second flags byte; for extended formats it is the third flags
+    /// byte directly.
+    pub fn classification(&self) -> impl Iterator<Item = u8> + '_ {
+        let is_extended = self.format.is_extended;
+        self.records().map(move |rec| {
+            if is_extended {
+                rec[16]
+            } else {
+                rec[15] & 0b0001_1111
+            }
+        })
+    }
+
+    /// Return number column.
+    pub fn return_number(&self) -> impl Iterator<Item = u8> + '_ {
+        let is_extended = self.format.is_extended;
+        self.records().map(move |rec| {
+            if is_extended {
+                rec[14] & 15
+            } else {
+                rec[14] & 7
+            }
+        })
+    }
+
+    /// Number-of-returns column.
+    pub fn number_of_returns(&self) -> impl Iterator<Item = u8> + '_ {
+        let is_extended = self.format.is_extended;
+        self.records().map(move |rec| {
+            if is_extended {
+                (rec[14] >> 4) & 15
+            } else {
+                (rec[14] >> 3) & 7
+            }
+        })
+    }
+
+    /// Scan angle column, in degrees.
+    ///
+    /// Legacy formats store scan angle as an `i8` rank in `[-90, 90]` degrees.
+    /// Extended formats store it as an `i16` in units of `0.006` degrees. Both
+    /// are normalized to `f32` degrees here.
+    pub fn scan_angle_degrees(&self) -> impl Iterator<Item = f32> + '_ {
+        let is_extended = self.format.is_extended;
+        let sa_off = self.layout.scan_angle;
+        self.records().map(move |rec| {
+            if is_extended {
+                let raw = i16::from_le_bytes([rec[sa_off], rec[sa_off + 1]]);
+                f32::from(raw) * 0.006
+            } else {
+                f32::from(rec[sa_off] as i8)
+            }
+        })
+    }
+
+    /// User data byte column.
+    pub fn user_data(&self) -> impl Iterator<Item = u8> + '_ {
+        let ud = self.layout.user_data;
+        self.records().map(move |rec| rec[ud])
+    }
+
+    /// Point source ID column.
+    pub fn point_source_id(&self) -> impl Iterator<Item = u16> + '_ {
+        let ps = self.layout.point_source_id;
+        self.records()
+            .map(move |rec| u16::from_le_bytes([rec[ps], rec[ps + 1]]))
+    }
+
+    /// GPS time column, or `None` if the format has no gps_time field.
+    pub fn gps_time(&self) -> Option<impl Iterator<Item = f64> + '_> {
+        let g = self.layout.gps_time?;
+        Some(self.records().map(move |rec| {
+            f64::from_le_bytes([
+                rec[g],
+                rec[g + 1],
+                rec[g + 2],
+                rec[g + 3],
+                rec[g + 4],
+                rec[g + 5],
+                rec[g + 6],
+                rec[g + 7],
+            ])
+        }))
+    }
+
+    /// RGB column, or `None` if the format has no color.
+    pub fn rgb(&self) -> Option<impl Iterator<Item = (u16, u16, u16)> + '_> {
+        let c = self.layout.rgb?;
+        Some(self.records().map(move |rec| {
+            (
+                u16::from_le_bytes([rec[c], rec[c + 1]]),
+                u16::from_le_bytes([rec[c + 2], rec[c + 3]]),
+                u16::from_le_bytes([rec[c + 4], rec[c + 5]]),
+            )
+        }))
+    }
+
+    /// NIR column, or `None` if the format has no NIR field.
+    pub fn nir(&self) -> Option<impl Iterator<Item = u16> + '_> {
+        let n = self.layout.nir?;
+        Some(
+            self.records()
+                .map(move |rec| u16::from_le_bytes([rec[n], rec[n + 1]])),
+        )
+    }
+
+    /// Scan direction column.
+    pub fn scan_direction(&self) -> impl Iterator<Item = ScanDirection> + '_ {
+        self.flags().map(|flags| flags.scan_direction())
+    }
+
+    /// Edge of flight line column.
+    pub fn is_edge_of_flight_line(&self) -> impl Iterator<Item = bool> + '_ {
+        self.flags().map(|flags| flags.is_edge_of_flight_line())
+    }
+
+    /// Synthetic flag column.
+    pub fn is_synthetic(&self) -> impl Iterator<Item = bool> + '_ {
+        self.flags().map(|flags| flags.is_synthetic())
+    }
+
+    /// Key point flag column.
+    pub fn is_key_point(&self) -> impl Iterator<Item = bool> + '_ {
+        self.flags().map(|flags| flags.is_key_point())
+    }
+
+    /// Withheld flag column.
+    pub fn is_withheld(&self) -> impl Iterator<Item = bool> + '_ {
+        self.flags().map(|flags| flags.is_withheld())
+    }
+
+    /// Overlap column. For legacy formats, overlap points are the ones
+    /// classified as overlap; for extended formats it is the overlap flag.
+    pub fn is_overlap(&self) -> impl Iterator<Item = bool> + '_ {
+        self.flags().map(|flags| flags.is_overlap())
+    }
+
+    /// Scanner channel column. Always zero for legacy formats.
+    pub fn scanner_channel(&self) -> impl Iterator<Item = u8> + '_ {
+        self.flags().map(|flags| flags.scanner_channel())
+    }
+
+    /// Waveform packet column, or `None` if the format has no waveforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// assert!(points.waveform().is_none());
+    /// ```
+    pub fn waveform(&self) -> Option<impl Iterator<Item = Waveform> + '_> {
+        let w = self.layout.waveform?;
+        Some(self.records().map(move |rec| view::waveform(rec, w)))
+    }
+
+    /// Raw extra bytes column, one slice per point.
+    ///
+    /// The slices are empty if the format has no extra bytes.
+    pub fn extra_bytes(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        let e = self.layout.extra_bytes;
+        self.records().map(move |rec| &rec[e..])
+    }
+
+    /// Sets the world x, y and z values, quantizing them through the transforms.
+    ///
+    /// Nothing is written if any value can't be represented with the
+    /// transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_points(2).unwrap();
+    /// let z: Vec<f64> = points.z().map(|z| z + 1.).collect();
+    /// let (x, y): (Vec<f64>, Vec<f64>) = points.x().zip(points.y()).unzip();
+    /// points.set_xyz(&x, &y, &z).unwrap();
+    /// ```
+    pub fn set_xyz(&mut self, x: &[f64], y: &[f64], z: &[f64]) -> Result<()> {
+        self.check_len(x.len())?;
+        self.check_len(y.len())?;
+        self.check_len(z.len())?;
+        let mut raw = Vec::with_capacity(self.len());
+        for ((&x, &y), &z) in x.iter().zip(y).zip(z) {
+            raw.push((
+                self.transforms.x.inverse(x)?,
+                self.transforms.y.inverse(y)?,
+                self.transforms.z.inverse(z)?,
+            ));
+        }
+        for (i, (x, y, z)) in raw.into_iter().enumerate() {
+            self.set_xyz_raw_at(i, x, y, z);
+        }
+        Ok(())
+    }
+
+    /// Sets the world x, y and z values of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_xyz_at(&mut self, index: usize, x: f64, y: f64, z: f64) -> Result<()> {
+        self.view_mut(index).set_xyz(x, y, z)
+    }
+
+    /// Sets the raw x, y and z values of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_xyz_raw_at(&mut self, index: usize, x: i32, y: i32, z: i32) {
+        self.view_mut(index).set_xyz_raw(x, y, z)
+    }
+
+    /// Transforms the x, y and z values and re-quantizes them with new transforms.
+    ///
+    /// Returns the bounds of the transformed points. If a transformed
+    /// coordinate doesn't fit the new transforms, returns an error and leaves
+    /// the points unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reproject::Affine, Reader};
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_points(2).unwrap();
+    /// let transforms = *points.transforms();
+    /// let z: Vec<f64> = points.z().collect();
+    /// let bounds = points
+    ///     .transform_coordinates(&Affine::translation(0., 0., 10.), transforms)
+    ///     .unwrap();
+    /// assert_eq!(z[0] + 10., points.z().next().unwrap());
+    /// assert!(bounds.min.z <= z[0] + 10.);
+    /// ```
+    pub fn transform_coordinates(
+        &mut self,
+        transform: &dyn CoordinateTransform,
+        transforms: Vector<Transform>,
+    ) -> Result<Bounds> {
+        let mut x: Vec<f64> = self.x().collect();
+        let mut y: Vec<f64> = self.y().collect();
+        let mut z: Vec<f64> = self.z().collect();
+        transform.transform(&mut x, &mut y, &mut z)?;
+        let old = std::mem::replace(&mut self.transforms, transforms);
+        if let Err(err) = self.set_xyz(&x, &y, &z) {
+            self.transforms = old;
+            return Err(err);
+        }
+        let mut bounds = Bounds::default();
+        for ((x, y), z) in self.x().zip(self.y()).zip(self.z()) {
+            bounds.grow(&Point {
+                x,
+                y,
+                z,
+                ..Default::default()
+            });
+        }
+        Ok(bounds)
+    }
+
+    /// Sets the intensity column.
+    pub fn set_intensity(&mut self, intensity: &[u16]) -> Result<()> {
+        self.check_len(intensity.len())?;
+        for (i, &intensity) in intensity.iter().enumerate() {
+            self.set_intensity_at(i, intensity);
+        }
+        Ok(())
+    }
+
+    /// Sets the intensity of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_intensity_at(&mut self, index: usize, intensity: u16) {
+        self.view_mut(index).set_intensity(intensity)
+    }
+
+    /// Sets the classification column.
+    ///
+    /// Legacy formats only have room for classifications up to 31. Nothing
+    /// is written if any classification doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_points(3).unwrap();
+    /// points.set_classification(&[2, 2, 6]).unwrap();
+    /// assert_eq!(vec![2, 2, 6], points.classification().collect::<Vec<_>>());
+    /// ```
+    pub fn set_classification(&mut self, classification: &[u8]) -> Result<()> {
+        self.check_len(classification.len())?;
+        if let Some(&c) = classification
+            .iter()
+            .find(|&&c| !self.format.is_extended && c > 31)
+        {
+            return Err(Error::InvalidClassification(c));
+        }
+        for (i, &c) in classification.iter().enumerate() {
+            self.set_classification_at(i, c)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the classification of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_classification_at(&mut self, index: usize, classification: u8) -> Result<()> {
+        self.view_mut(index).set_classification(classification)
+    }
+
+    /// Sets the user data column.
+    pub fn set_user_data(&mut self, user_data: &[u8]) -> Result<()> {
+        self.check_len(user_data.len())?;
+        for (i, &user_data) in user_data.iter().enumerate() {
+            self.set_user_data_at(i, user_data);
+        }
+        Ok(())
+    }
+
+    /// Sets the user data of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_user_data_at(&mut self, index: usize, user_data: u8) {
+        self.view_mut(index).set_user_data(user_data)
+    }
+
+    /// Sets the point source ID column.
+    pub fn set_point_source_id(&mut self, point_source_id: &[u16]) -> Result<()> {
+        self.check_len(point_source_id.len())?;
+        for (i, &point_source_id) in point_source_id.iter().enumerate() {
+            self.set_point_source_id_at(i, point_source_id);
+        }
+        Ok(())
+    }
+
+    /// Sets the point source ID of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_point_source_id_at(&mut self, index: usize, point_source_id: u16) {
+        self.view_mut(index).set_point_source_id(point_source_id)
+    }
+
+    /// Sets the GPS time column.
+    ///
+    /// Returns an error if the format has no gps_time field.
+    pub fn set_gps_time(&mut self, gps_time: &[f64]) -> Result<()> {
+        self.check_len(gps_time.len())?;
+        for (i, &gps_time) in gps_time.iter().enumerate() {
+            self.set_gps_time_at(i, gps_time)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the GPS time of one point.
+    ///
+    /// Returns an error if the format has no gps_time field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_gps_time_at(&mut self, index: usize, gps_time: f64) -> Result<()> {
+        self.view_mut(index).set_gps_time(gps_time)
+    }
+
+    /// Re-encodes the GPS time column from one [GpsTimeType] to another.
+    ///
+    /// Converting week time to standard time needs a GPS week and a time in
+    /// it near the first point, e.g. the first point's seconds of week and
+    /// its week from [Header::infer_gps_week](crate::Header::infer_gps_week).
+    /// Each point is placed in the week of the point before it or a
+    /// neighbouring one, see [gps_time::week_near], so a survey across the
+    /// Saturday/Sunday rollover converts correctly.
+    ///
+    /// Returns the week and seconds of week of the last point, or `week` if
+    /// there are no points. When converting a file a slab at a time, pass
+    /// that as `week` to the next slab so a rollover in one slab carries over
+    /// to the later ones. Use
+    /// [Header::convert_gps_time](crate::Header::convert_gps_time) to set the
+    /// header's gps time type at the same time.
+    ///
+    /// Returns an error if the format has no gps_time field, or if `week` is
+    /// needed but missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTimeType, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut header = reader.header().clone();
+    /// let mut points = reader.read_points(10).unwrap();
+    /// let first = points.gps_time().unwrap().next().unwrap();
+    /// let mut week = Some((header.infer_gps_week(first).unwrap_or(1930), first));
+    /// while points.len() > 0 {
+    ///     week = points.convert_gps_time(GpsTimeType::Week, GpsTimeType::Standard, week).unwrap();
+    ///     points = reader.read_points(10).unwrap();
+    /// }
+    /// header.set_gps_time_type(GpsTimeType::Standard);
+    /// ```
+    pub fn convert_gps_time(
+        &mut self,
+        from: GpsTimeType,
+        to: GpsTimeType,
+        week: Option<(u32, f64)>,
+    ) -> Result<Option<(u32, f64)>> {
+        let gps_time: Vec<f64> = self
+            .gps_time()
+            .ok_or(Error::NoGpsTime(self.format))?
+            .collect();
+        let mut last = week;
+        let gps_time: Vec<f64> = match (from, to) {
+            (GpsTimeType::Week, GpsTimeType::Standard) => {
+                let (mut week, mut reference) = week.ok_or(Error::MissingGpsWeek)?;
+                gps_time
+                    .into_iter()
+                    .map(|t| {
+                        week = gps_time::week_near(week, reference, t);
+                        reference = t;
+                        last = Some((week, t));
+                        gps_time::week_to_adjusted_standard(week, t)
+                    })
+                    .collect()
+            }
+            (GpsTimeType::Standard, GpsTimeType::Week) => gps_time
+                .into_iter()
+                .map(|t| {
+                    let (week, t) = gps_time::adjusted_standard_to_week(t);
+                    last = Some((week, t));
+                    t
+                })
+                .collect(),
+            _ => return Ok(week),
+        };
+        self.set_gps_time(&gps_time)?;
+        Ok(last)
+    }
+
+    /// Sets the RGB column.
+    ///
+    /// Returns an error if the format has no color.
+    pub fn set_rgb(&mut self, rgb: &[(u16, u16, u16)]) -> Result<()> {
+        self.check_len(rgb.len())?;
+        for (i, &(red, green, blue)) in rgb.iter().enumerate() {
+            self.set_rgb_at(i, red, green, blue)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the color of one point.
+    ///
+    /// Returns an error if the format has no color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_rgb_at(&mut self, index: usize, red: u16, green: u16, blue: u16) -> Result<()> {
+        self.view_mut(index).set_color(Color { red, green, blue })
+    }
+
+    /// Sets the return number column.
+    ///
+    /// Legacy formats have room for return numbers up to 7, extended formats
+    /// up to 15. Nothing is written if any return number doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_points(2).unwrap();
+    /// points.set_return_number(&[1, 2]).unwrap();
+    /// assert!(points.set_return_number(&[1, 8]).is_err());
+    /// ```
+    pub fn set_return_number(&mut self, return_number: &[u8]) -> Result<()> {
+        self.check_len(return_number.len())?;
+        for &n in return_number {
+            view::check_return_number(n, &self.format)?;
+        }
+        for (i, &n) in return_number.iter().enumerate() {
+            self.set_return_number_at(i, n)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the return number of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_return_number_at(&mut self, index: usize, return_number: u8) -> Result<()> {
+        self.view_mut(index).set_return_number(return_number)
+    }
+
+    /// Sets the number-of-returns column.
+    ///
+    /// Legacy formats have room for up to 7 returns, extended formats up to
+    /// 15. Nothing is written if any number doesn't fit.
+    pub fn set_number_of_returns(&mut self, number_of_returns: &[u8]) -> Result<()> {
+        self.check_len(number_of_returns.len())?;
+        for &n in number_of_returns {
+            view::check_return_number(n, &self.format)?;
+        }
+        for (i, &n) in number_of_returns.iter().enumerate() {
+            self.set_number_of_returns_at(i, n)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the number of returns of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_number_of_returns_at(&mut self, index: usize, number_of_returns: u8) -> Result<()> {
+        self.view_mut(index)
+            .set_number_of_returns(number_of_returns)
+    }
+
+    /// Sets the scan angle column, in degrees.
+    ///
+    /// Legacy formats round the angles to whole degrees.
+    pub fn set_scan_angle(&mut self, degrees: &[f32]) -> Result<()> {
+        self.check_len(degrees.len())?;
+        for (i, &degrees) in degrees.iter().enumerate() {
+            self.set_scan_angle_at(i, degrees);
+        }
+        Ok(())
+    }
+
+    /// Sets the scan angle of one point, in degrees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_scan_angle_at(&mut self, index: usize, degrees: f32) {
+        self.view_mut(index).set_scan_angle(degrees)
+    }
+
+    /// Sets the scan direction column.
+    pub fn set_scan_direction(&mut self, scan_direction: &[ScanDirection]) -> Result<()> {
+        self.check_len(scan_direction.len())?;
+        for (i, &scan_direction) in scan_direction.iter().enumerate() {
+            self.set_scan_direction_at(i, scan_direction);
+        }
+        Ok(())
+    }
+
+    /// Sets the scan direction of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_scan_direction_at(&mut self, index: usize, scan_direction: ScanDirection) {
+        self.view_mut(index).set_scan_direction(scan_direction)
+    }
+
+    /// Sets the edge of flight line column.
+    pub fn set_edge_of_flight_line(&mut self, is_edge_of_flight_line: &[bool]) -> Result<()> {
+        self.check_len(is_edge_of_flight_line.len())?;
+        for (i, &is_edge_of_flight_line) in is_edge_of_flight_line.iter().enumerate() {
+            self.set_edge_of_flight_line_at(i, is_edge_of_flight_line);
+        }
+        Ok(())
+    }
+
+    /// Sets the edge of flight line flag of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_edge_of_flight_line_at(&mut self, index: usize, is_edge_of_flight_line: bool) {
+        self.view_mut(index)
+            .set_edge_of_flight_line(is_edge_of_flight_line)
+    }
+
+    /// Sets the synthetic column.
+    pub fn set_synthetic(&mut self, is_synthetic: &[bool]) -> Result<()> {
+        self.check_len(is_synthetic.len())?;
+        for (i, &is_synthetic) in is_synthetic.iter().enumerate() {
+            self.set_synthetic_at(i, is_synthetic);
+        }
+        Ok(())
+    }
+
+    /// Sets the synthetic flag of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_synthetic_at(&mut self, index: usize, is_synthetic: bool) {
+        self.view_mut(index).set_synthetic(is_synthetic)
+    }
+
+    /// Sets the key point column.
+    pub fn set_key_point(&mut self, is_key_point: &[bool]) -> Result<()> {
+        self.check_len(is_key_point.len())?;
+        for (i, &is_key_point) in is_key_point.iter().enumerate() {
+            self.set_key_point_at(i, is_key_point);
+        }
+        Ok(())
+    }
+
+    /// Sets the key point flag of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_key_point_at(&mut self, index: usize, is_key_point: bool) {
+        self.view_mut(index).set_key_point(is_key_point)
+    }
+
+    /// Sets the withheld column.
+    pub fn set_withheld(&mut self, is_withheld: &[bool]) -> Result<()> {
+        self.check_len(is_withheld.len())?;
+        for (i, &is_withheld) in is_withheld.iter().enumerate() {
+            self.set_withheld_at(i, is_withheld);
+        }
+        Ok(())
+    }
+
+    /// Sets the withheld flag of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_withheld_at(&mut self, index: usize, is_withheld: bool) {
+        self.view_mut(index).set_withheld(is_withheld)
+    }
+
+    /// Sets the overlap column.
+    ///
+    /// Legacy formats mark overlap points with the overlap classification, see
+    /// [PointMut::set_overlap].
+    pub fn set_overlap(&mut self, is_overlap: &[bool]) -> Result<()> {
+        self.check_len(is_overlap.len())?;
+        for (i, &is_overlap) in is_overlap.iter().enumerate() {
+            self.set_overlap_at(i, is_overlap);
+        }
+        Ok(())
+    }
+
+    /// Sets the overlap flag of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_overlap_at(&mut self, index: usize, is_overlap: bool) {
+        self.view_mut(index).set_overlap(is_overlap)
+    }
+
+    /// Sets the scanner channel column.
+    ///
+    /// Channels go up to 3, and legacy formats only have channel zero.
+    /// Nothing is written if any channel doesn't fit.
+    pub fn set_scanner_channel(&mut self, scanner_channel: &[u8]) -> Result<()> {
+        self.check_len(scanner_channel.len())?;
+        let max = if self.format.is_extended { 3 } else { 0 };
+        if let Some(&channel) = scanner_channel.iter().find(|&&channel| channel > max) {
+            return Err(Error::InvalidScannerChannel(channel));
+        }
+        for (i, &channel) in scanner_channel.iter().enumerate() {
+            self.set_scanner_channel_at(i, channel)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the scanner channel of one point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_scanner_channel_at(&mut self, index: usize, scanner_channel: u8) -> Result<()> {
+        self.view_mut(index).set_scanner_channel(scanner_channel)
+    }
+
+    /// Sets the NIR column.
+    ///
+    /// Returns an error if the format has no NIR field.
+    pub fn set_nir(&mut self, nir: &[u16]) -> Result<()> {
+        self.check_len(nir.len())?;
+        if !self.format.has_nir {
+            return Err(Error::NoDimension {
+                dimension: Dimension::Nir,
+                format: self.format,
+            });
+        }
+        for (i, &nir) in nir.iter().enumerate() {
+            self.set_nir_at(i, nir)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the NIR value of one point.
+    ///
+    /// Returns an error if the format has no NIR field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_nir_at(&mut self, index: usize, nir: u16) -> Result<()> {
+        self.view_mut(index).set_nir(nir)
+    }
+
+    /// Returns the points for which `predicate` returns true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let first_returns = points.filter(|p| p.return_number() == 1);
+    /// assert!(first_returns.len() <= points.len());
+    /// ```
+    pub fn filter<F: FnMut(PointRef<'_>) -> bool>(&self, mut predicate: F) -> PointData {
+        let bytes = self
+            .views()
+            .filter(|&p| predicate(p))
+            .flat_map(|p| p.bytes().iter().copied())
+            .collect();
+        self.with_bytes(bytes)
+    }
+
+    /// Returns the points whose entry in `mask` is true.
+    ///
+    /// Returns an error if `mask` doesn't have one entry per point.
+    pub fn filter_mask(&self, mask: &[bool]) -> Result<PointData> {
+        self.check_len(mask.len())?;
+        let indices = mask
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &keep)| keep.then_some(i));
+        Ok(self.with_bytes(self.gather(indices)))
+    }
+
+    /// Returns the points at `indices`, in that order.
+    ///
+    /// Indices may repeat.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let picked = points.take(&[5, 0, 5]);
+    /// assert_eq!(3, picked.len());
+    /// ```
+    pub fn take(&self, indices: &[usize]) -> PointData {
+        self.with_bytes(self.gather(indices.iter().copied()))
+    }
+
+    /// Returns a copy of the points in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// assert_eq!(10, points.slice(10..20).len());
+    /// ```
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> PointData {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len(),
+        };
+        let record_len = self.layout.record_len;
+        self.with_bytes(self.bytes[start * record_len..end * record_len].to_vec())
+    }
+
+    /// Splits the points in two at `mid`.
+    ///
+    /// The first half holds the points before `mid`, the second the rest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > self.len()`.
+    pub fn split_at(&self, mid: usize) -> (PointData, PointData) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Appends the points from `other`.
+    ///
+    /// Returns an error if `other` has a different format or different
+    /// transforms, since its records couldn't be decoded the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut points = reader.read_points(10).unwrap();
+    /// points.extend_from(&reader.read_points(10).unwrap()).unwrap();
+    /// assert_eq!(20, points.len());
+    /// ```
+    pub fn extend_from(&mut self, other: &PointData) -> Result<()> {
+        if self.format != other.format || self.transforms != other.transforms {
+            return Err(Error::IncompatiblePointData);
+        }
+        self.bytes.extend_from_slice(&other.bytes);
+        Ok(())
+    }
+
+    /// Sorts the points along a space-filling curve, so that points close
+    /// together in space end up close together in the data.
+    ///
+    /// The sort is stable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, SpaceFillingCurve};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut points = reader.read_all().unwrap();
+    /// points.sort_by_space(SpaceFillingCurve::Hilbert);
+    /// ```
+    pub fn sort_by_space(&mut self, curve: SpaceFillingCurve) {
+        let keys: Vec<u64> = self
+            .records()
+            .map(|record| curve.record_index(record))
+            .collect();
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by_key(|&i| keys[i]);
+        self.permute(&order);
+    }
+
+    /// Sorts the points by one field, in ascending order.
+    ///
+    /// The sort is stable. Returns an error if the format doesn't have the
+    /// field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Reader};
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// points.sort_by_field(Dimension::GpsTime).unwrap();
+    /// ```
+    pub fn sort_by_field(&mut self, dimension: Dimension) -> Result<()> {
+        if !self.format.has_dimension(dimension) {
+            return Err(Error::NoDimension {
+                dimension,
+                format: self.format,
+            });
+        }
+        let keys: Vec<f64> = self
+            .views()
+            .map(|p| p.value(dimension).expect("the format has the dimension"))
+            .collect();
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+        self.permute(&order);
+        Ok(())
+    }
+
+    /// Sorts the points by a key extracted from each point.
+    ///
+    /// The sort is stable, and the key is extracted once per point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// points.sort_by_key(|p| std::cmp::Reverse(p.intensity()));
+    /// ```
+    pub fn sort_by_key<K: Ord, F: FnMut(PointRef<'_>) -> K>(&mut self, mut f: F) {
+        let keys: Vec<K> = self.views().map(&mut f).collect();
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+        self.permute(&order);
+    }
+
+    /// Sorts the points with a comparison function.
+    ///
+    /// The sort is stable.
+    ///
+    /// # Examples
+    ///
+    /// Sort by point source ID, then by GPS time, e.g. to process swaths:
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// points.sort_by(|a, b| {
+    ///     a.point_source_id()
+    ///         .cmp(&b.point_source_id())
+    ///         .then(a.gps_time().unwrap().total_cmp(&b.gps_time().unwrap()))
+    /// });
+    /// ```
+    pub fn sort_by<F: FnMut(PointRef<'_>, PointRef<'_>) -> Ordering>(&mut self, mut compare: F) {
+        let views: Vec<PointRef<'_>> = self.views().collect();
+        let mut order: Vec<usize> = (0..views.len()).collect();
+        order.sort_by(|&a, &b| compare(views[a], views[b]));
+        self.permute(&order);
+    }
+
+    /// Reorders the records so that record `i` is the old record `order[i]`.
+    fn permute(&mut self, order: &[usize]) {
+        self.bytes = self.gather(order.iter().copied());
+    }
+
+    fn gather(&self, indices: impl Iterator<Item = usize>) -> Vec<u8> {
+        let record_len = self.layout.record_len;
+        let mut bytes = Vec::with_capacity(indices.size_hint().0 * record_len);
+        for i in indices {
+            bytes.extend_from_slice(&self.bytes[i * record_len..(i + 1) * record_len]);
+        }
+        bytes
+    }
+
+    fn with_bytes(&self, bytes: Vec<u8>) -> PointData {
+        PointData {
+            bytes,
+            format: self.format,
+            transforms: self.transforms,
+            layout: self.layout,
+        }
+    }
+
+    fn flags(&self) -> impl Iterator<Item = Flags> + '_ {
+        let is_extended = self.format.is_extended;
+        self.records().map(move |rec| view::flags(rec, is_extended))
+    }
+
+    fn record_range(&self, index: usize) -> Option<Range<usize>> {
+        let record_len = self.layout.record_len;
+        let start = index.checked_mul(record_len)?;
+        Some(start..start.checked_add(record_len)?)
+    }
+
+    fn view_mut(&mut self, index: usize) -> PointMut<'_> {
+        let record_len = self.layout.record_len;
+        PointMut::new(
+            &mut self.bytes[index * record_len..(index + 1) * record_len],
+            &self.format,
+            &self.transforms,
+            &self.layout,
+        )
+    }
+
+    fn check_len(&self, len: usize) -> Result<()> {
+        if len == self.len() {
+            Ok(())
+        } else {
+            Err(Error::ColumnLength {
+                len,
+                expected: self.len(),
+            })
+        }
+    }
+
+    fn records(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.bytes.chunks_exact(self.layout.record_len)
+    }
+
+    fn i32_column(&self, field_offset: usize) -> I32Column<'_> {
+        I32Column {
+            bytes: &self.bytes,
+            stride: self.layout.record_len,
+            field: field_offset,
+            pos: 0,
+        }
+    }
+
+    fn u16_column(&self, field_offset: usize) -> U16Column<'_> {
+        U16Column {
+            bytes: &self.bytes,
+            stride: self.layout.record_len,
+            field: field_offset,
+            pos: 0,
+        }
+    }
+}
+
+/// Iterator over points in a [PointData], yielding owned [Point] values.
+///
+/// Returned by [`PointData::points`]. Each call to `next` decodes one record
+/// through [`raw::Point::read_from`] + [`Point::new`], matching the cost and
+/// semantics of [`Reader::fill_points`](crate::Reader::fill_points).
+#[allow(missing_debug_implementations)]
+pub struct PointDataIter<'a> {
+    cursor: Cursor<&'a [u8]>,
+    format: &'a Format,
+    transforms: &'a Vector<Transform>,
+    remaining: usize,
+}
+
+impl Iterator for PointDataIter<'_> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(
+            raw::Point::read_from(&mut self.cursor, self.format)
+                .map(|raw_point| Point::new(raw_point, self.transforms)),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for PointDataIter<'_> {}
+
+#[derive(Debug)]
+struct I32Column<'a> {
+    bytes: &'a [u8],
+    stride: usize,
+    field: usize,
+    pos: usize,
+}
+
+impl Iterator for I32Column<'_> {
+    type Item = i32;
+    fn next(&mut self) -> Option<i32> {
+        let start = self.pos + self.field;
+        if start + 4 > self.bytes.len() {
+            return None;
+        }
+        let v = i32::from_le_bytes([
+            self.bytes[start],
+            self.bytes[start + 1],
+            self.bytes[start + 2],
+            self.bytes[start + 3],
+        ]);
+        self.pos += self.stride;
+        Some(v)
+    }
+}
+
+#[derive(Debug)]
+struct U16Column<'a> {
+    bytes: &'a [u8],
+    stride: usize,
+    field: usize,
+    pos: usize,
+}
+
+impl Iterator for U16Column<'_> {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        let start = self.pos + self.field;
+        if start + 2 > self.bytes.len() {
+            return None;
+        }
+        let v = u16::from_le_bytes([self.bytes[start], self.bytes[start + 1]]);
+        self.pos += self.stride;
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::Format,
+        raw::{
+            self,
+            point::{Flags, ScanAngle, Waveform},
+        },
+        Transform, Vector,
+    };
+
+    fn default_transforms() -> Vector<Transform> {
+        Vector {
+            x: Transform::default(),
+            y: Transform::default(),
+            z: Transform::default(),
+        }
+    }
+
+    fn empty_point_data(format: Format) -> PointData {
+        PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(default_transforms())
+            .build()
+    }
+
+    fn build_raw_point(format: &Format, i: i32) -> raw::Point {
+        let flags = if format.is_extended {
+            // return_number=1, number_of_returns=2, scanner_channel=1,
+            // synthetic=1, classification=5.
+            Flags::ThreeByte((2 << 4) | 1, (1 << 4) | 1, 5)
+        } else {
+            // two-byte: return_number=1, number_of_returns=2; b byte:
+            // classification=3, synthetic=1 (bit 5).
+            Flags::TwoByte((2 << 3) | 1, 3 | 0b0010_0000)
+        };
+        let scan_angle = if format.is_extended {
+            ScanAngle::Scaled(1500) // 9.0 degrees
+        } else {
+            ScanAngle::Rank(-12)
+        };
+        raw::Point {
+            x: i,
+            y: i + 1,
+            z: i + 2,
+            intensity: 1000 + i as u16,
+            flags,
+            scan_angle,
+            user_data: 7,
+            point_source_id: 42,
+            gps_time: if format.has_gps_time {
+                Some(1234.5 + f64::from(i))
+            } else {
+                None
+            },
+            color: if format.has_color {
+                Some(Color {
+                    red: 100 + i as u16,
+                    green: 200 + i as u16,
+                    blue: 300 + i as u16,
+                })
+            } else {
+                None
+            },
+            waveform: if format.has_waveform {
+                Some(Waveform::default())
+            } else {
+                None
+            },
+            nir: if format.has_nir { Some(4242) } else { None },
+            extra_bytes: vec![0u8; format.extra_bytes as usize],
+        }
+    }
+
+    fn build_points_for_format(format: Format) -> PointData {
+        let n = 5i32;
+        let mut buf: Vec<u8> = Vec::new();
+        for i in 0..n {
+            let rp = build_raw_point(&format, i);
+            rp.write_to(&mut buf, &format).unwrap();
+        }
+        let mut points = empty_point_data(format);
+        let slab = points.resize_for(n as usize);
+        slab.copy_from_slice(&buf);
+        points
+    }
+
+    fn check_format(format: Format) {
+        let points = build_points_for_format(format);
+        assert_eq!(points.len(), 5);
+
+        // Columns match per-point decode.
+        let xs_col: Vec<i32> = points.x_raw().collect();
+        let ys_col: Vec<i32> = points.y_raw().collect();
+        let zs_col: Vec<i32> = points.z_raw().collect();
+        let int_col: Vec<u16> = points.intensity().collect();
+        let cls_col: Vec<u8> = points.classification().collect();
+        let rn_col: Vec<u8> = points.return_number().collect();
+        let nr_col: Vec<u8> = points.number_of_returns().collect();
+        let sa_col: Vec<f32> = points.scan_angle_degrees().collect();
+        let ud_col: Vec<u8> = points.user_data().collect();
+        let ps_col: Vec<u16> = points.point_source_id().collect();
+        let sd_col: Vec<ScanDirection> = points.scan_direction().collect();
+        let edge_col: Vec<bool> = points.is_edge_of_flight_line().collect();
+        let syn_col: Vec<bool> = points.is_synthetic().collect();
+        let key_col: Vec<bool> = points.is_key_point().collect();
+        let wh_col: Vec<bool> = points.is_withheld().collect();
+        let ov_col: Vec<bool> = points.is_overlap().collect();
+        let ch_col: Vec<u8> = points.scanner_channel().collect();
+
+        for ((i, p), view) in points.points().enumerate().zip(points.views()) {
+            let p = p.unwrap();
+            assert_eq!(p, view.to_point().unwrap());
+            assert_eq!(p.x, view.x());
+            assert_eq!(p.intensity, view.intensity());
+            assert_eq!(u8::from(p.classification), view.classification());
+            assert_eq!(p.return_number, view.return_number());
+            assert_eq!(p.scan_angle, view.scan_angle_degrees());
+            assert_eq!(p.gps_time, view.gps_time());
+            assert_eq!(p.color, view.color());
+            assert_eq!(p.nir, view.nir());
+            assert_eq!(p.waveform, view.waveform());
+            assert_eq!(p.extra_bytes, view.extra_bytes());
+            assert_eq!(p.is_synthetic, view.is_synthetic());
+            assert_eq!(xs_col[i], i as i32);
+            assert_eq!(ys_col[i], i as i32 + 1);
+            assert_eq!(zs_col[i], i as i32 + 2);
+            assert_eq!(int_col[i], 1000 + i as u16);
+            assert_eq!(rn_col[i], 1);
+            assert_eq!(nr_col[i], 2);
+            assert_eq!(ud_col[i], 7);
+            assert_eq!(ps_col[i], 42);
+            assert_eq!(rn_col[i], p.return_number);
+            assert_eq!(nr_col[i], p.number_of_returns);
+            assert_eq!(ud_col[i], p.user_data);
+            assert_eq!(ps_col[i], p.point_source_id);
+            assert_eq!(sd_col[i], p.scan_direction);
+            assert_eq!(edge_col[i], p.is_edge_of_flight_line);
+            assert_eq!(syn_col[i], p.is_synthetic);
+            assert_eq!(key_col[i], p.is_key_point);
+            assert_eq!(wh_col[i], p.is_withheld);
+            assert_eq!(ov_col[i], p.is_overlap);
+            assert_eq!(ch_col[i], p.scanner_channel);
+            if format.is_extended {
+                assert_eq!(cls_col[i], 5);
+                assert!((sa_col[i] - 9.0).abs() < 1e-3);
+            } else {
+                assert_eq!(cls_col[i], 3);
+                assert!((sa_col[i] - (-12.0)).abs() < 1e-3);
+            }
+            if format.has_gps_time {
+                assert_eq!(p.gps_time, Some(1234.5 + f64::from(i as i32)));
+            } else {
+                assert_eq!(p.gps_time, None);
+            }
+            if format.has_color {
+                let c = p.color.unwrap();
+                assert_eq!(
+                    (c.red, c.green, c.blue),
+                    (100 + i as u16, 200 + i as u16, 300 + i as u16)
+                );
+            } else {
+                assert!(p.color.is_none());
+            }
+            if format.has_nir {
+                assert_eq!(p.nir, Some(4242));
+            } else {
+                assert_eq!(p.nir, None);
+            }
+        }
+
+        if format.has_gps_time {
+            let gps_col: Vec<f64> = points.gps_time().unwrap().collect();
+            let gps_row: Vec<f64> = points
+                .points()
+                .map(|p| p.unwrap().gps_time.unwrap())
+                .collect();
+            assert_eq!(gps_col, gps_row);
+        } else {
+            assert!(points.gps_time().is_none());
+        }
+        if format.has_color {
+            let rgb_col: Vec<(u16, u16, u16)> = points.rgb().unwrap().collect();
+            let rgb_row: Vec<(u16, u16, u16)> = points
+                .points()
+                .map(|p| {
+                    let c = p.unwrap().color.unwrap();
+                    (c.red, c.green, c.blue)
+                })
+                .collect();
+            assert_eq!(rgb_col, rgb_row);
+        }
+        if format.has_nir {
+            let nir_col: Vec<u16> = points.nir().unwrap().collect();
+            let nir_row: Vec<u16> = points.points().map(|p| p.unwrap().nir.unwrap()).collect();
+            assert_eq!(nir_col, nir_row);
+        }
+    }
+
+    #[test]
+    fn format_0() {
+        check_format(Format::new(0).unwrap());
+    }
+
+    #[test]
+    fn format_1() {
+        check_format(Format::new(1).unwrap());
+    }
+
+    #[test]
+    fn format_2() {
+        check_format(Format::new(2).unwrap());
+    }
+
+    #[test]
+    fn format_3() {
+        check_format(Format::new(3).unwrap());
+    }
+
+    #[test]
+    fn format_6() {
+        check_format(Format::new(6).unwrap());
+    }
+
+    #[test]
+    fn format_7() {
+        check_format(Format::new(7).unwrap());
+    }
+
+    #[test]
+    fn format_8() {
+        check_format(Format::new(8).unwrap());
+    }
+
+    #[test]
+    fn record_len_matches_format_len() {
+        for n in [0u8, 1, 2, 3, 6, 7, 8] {
+            let f = Format::new(n).unwrap();
+            let points = empty_point_data(f);
+            assert_eq!(points.record_len(), f.len() as usize, "format {n}");
+        }
+    }
+
+    #[test]
+    fn build_from_bytes_valid() {
+        let format = Format::new(1).unwrap();
+        let record_len = format.len() as usize;
+        let mut buf = Vec::new();
+        for i in 0..3 {
+            let rp = build_raw_point(&format, i);
+            rp.write_to(&mut buf, &format).unwrap();
+        }
+        assert_eq!(buf.len(), record_len * 3);
+        let points = PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(default_transforms())
+            .build_from_bytes(buf)
+            .unwrap();
+        assert_eq!(points.len(), 3);
+        let xs: Vec<i32> = points.x_raw().collect();
+        assert_eq!(xs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn build_from_bytes_rejects_bad_length() {
+        let format = Format::new(0).unwrap();
+        let record_len = format.len() as usize;
+        let buf = vec![0u8; record_len * 2 + 1];
+        let result = PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(default_transforms())
+            .build_from_bytes(buf);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn build_from_bytes_accepts_empty() {
+        let format = Format::new(0).unwrap();
+        let points = PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(default_transforms())
+            .build_from_bytes(Vec::new())
+            .unwrap();
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn resize_for_and_fill() {
+        let format = Format::new(1).unwrap();
+        let mut points = empty_point_data(format);
+        let mut buf = Vec::new();
+        for i in 0..2 {
+            build_raw_point(&format, i)
+                .write_to(&mut buf, &format)
+                .unwrap();
+        }
+        let slab = points.resize_for(2);
+        slab.copy_from_slice(&buf);
+        assert_eq!(points.len(), 2);
+        let xs: Vec<i32> = points.x_raw().collect();
+        assert_eq!(xs, vec![0, 1]);
+    }
+
+    #[test]
+    fn points_matches_columns() {
+        let format = Format::new(7).unwrap();
+        let points = build_points_for_format(format);
+        let owned: Vec<Point> = points.points().map(Result::unwrap).collect();
+        let xs_col: Vec<f64> = points.x().collect();
+        let ints: Vec<u16> = points.intensity().collect();
+        assert_eq!(owned.len(), points.len());
+        for (i, p) in owned.iter().enumerate() {
+            assert!((p.x - xs_col[i]).abs() < 1e-12);
+            assert_eq!(p.intensity, ints[i]);
+        }
+    }
+
+    #[test]
+    fn sort_by_space() {
+        let format = Format::new(1).unwrap();
+        let mut points = empty_point_data(format);
+        let mut buf = Vec::new();
+        for (x, y) in [(1, 1), (0, 0), (1, 0), (0, 1), (0, 0)] {
+            let mut raw_point = build_raw_point(&format, 0);
+            raw_point.x = x;
+            raw_point.y = y;
+            raw_point.z = x + y;
+            raw_point.write_to(&mut buf, &format).unwrap();
+        }
+        points.resize_for(5).copy_from_slice(&buf);
+        let mut morton = points.clone();
+        morton.sort_by_space(SpaceFillingCurve::Morton);
+        let xy: Vec<(i32, i32)> = morton.x_raw().zip(morton.y_raw()).collect();
+        assert_eq!(xy, vec![(0, 0), (0, 0), (1, 0), (0, 1), (1, 1)]);
+        points.sort_by_space(SpaceFillingCurve::Hilbert);
+        let xy: Vec<(i32, i32)> = points.x_raw().zip(points.y_raw()).collect();
+        assert_eq!(xy, vec![(0, 0), (0, 0), (0, 1), (1, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn set_columns() {
+        let format = Format::new(3).unwrap();
+        let mut points = build_points_for_format(format);
+        points
+            .set_xyz(&[1.; 5], &[2.; 5], &[-3.5, 0., 0., 0., 4.25])
+            .unwrap();
+        points.set_classification(&[2, 3, 4, 5, 6]).unwrap();
+        points.set_classification_at(1, 9).unwrap();
+        points.set_rgb(&[(1, 2, 3); 5]).unwrap();
+        points.set_gps_time_at(4, 42.).unwrap();
+        points.set_intensity_at(0, 7);
+        let owned: Vec<Point> = points.points().map(Result::unwrap).collect();
+        assert_eq!(-3.5, owned[0].z);
+        assert_eq!(4.25, owned[4].z);
+        assert!(owned.iter().all(|p| p.x == 1. && p.y == 2.));
+        assert_eq!(
+            vec![2, 9, 4, 5, 6],
+            points.classification().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(42.), owned[4].gps_time);
+        assert_eq!(7, owned[0].intensity);
+        assert_eq!(1, owned[0].color.unwrap().red);
+        // The synthetic flag shares a byte with the classification.
+        assert!(owned.iter().all(|p| p.is_synthetic));
+    }
+
+    #[test]
+    fn set_columns_errors() {
+        let mut points = build_points_for_format(Format::new(0).unwrap());
+        assert!(points.set_classification(&[0; 4]).is_err());
+        assert!(points.set_classification(&[0, 0, 0, 0, 32]).is_err());
+        assert_eq!(vec![3; 5], points.classification().collect::<Vec<_>>());
+        assert!(points.set_gps_time(&[0.; 5]).is_err());
+        assert!(points.set_rgb_at(0, 0, 0, 0).is_err());
+        assert!(points
+            .set_xyz(&[0.; 5], &[0.; 5], &[0., 0., 0., 0., 1e300])
+            .is_err());
+        assert_eq!(vec![0, 1, 2, 3, 4], points.x_raw().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn set_flag_columns() {
+        for format in [Format::new(1).unwrap(), Format::new(8).unwrap()] {
+            let mut points = build_points_for_format(format);
+            points.set_return_number(&[1, 2, 3, 4, 5]).unwrap();
+            points.set_number_of_returns(&[5; 5]).unwrap();
+            points.set_scan_angle(&[-3., -1., 0., 1., 3.]).unwrap();
+            points
+                .set_scan_direction(&[ScanDirection::LeftToRight; 5])
+                .unwrap();
+            points
+                .set_edge_of_flight_line(&[true, false, false, false, true])
+                .unwrap();
+            points.set_withheld_at(1, true);
+            points.set_key_point_at(2, true);
+            points
+                .set_overlap(&[false, false, false, true, false])
+                .unwrap();
+            assert_eq!(
+                points.set_scanner_channel(&[0, 1, 2, 3, 0]).is_ok(),
+                format.is_extended
+            );
+            assert_eq!(points.set_nir(&[7; 5]).is_ok(), format.has_nir);
+            let owned: Vec<Point> = points.points().map(Result::unwrap).collect();
+            assert_eq!(
+                vec![1, 2, 3, 4, 5],
+                owned.iter().map(|p| p.return_number).collect::<Vec<_>>()
+            );
+            assert!(owned.iter().all(|p| p.number_of_returns == 5));
+            assert_eq!(
+                vec![-3., -1., 0., 1., 3.],
+                points
+                    .scan_angle_degrees()
+                    .map(f32::round)
+                    .collect::<Vec<_>>()
+            );
+            assert!(owned
+                .iter()
+                .all(|p| p.scan_direction == ScanDirection::LeftToRight));
+            assert_eq!(
+                vec![true, false, false, false, true],
+                points.is_edge_of_flight_line().collect::<Vec<_>>()
+            );
+            assert!(owned[1].is_withheld);
+            assert!(owned[2].is_key_point);
+            assert_eq!(
+                vec![false, false, false, true, false],
+                points.is_overlap().collect::<Vec<_>>()
+            );
+            if format.is_extended {
+                assert_eq!(
+                    vec![0, 1, 2, 3, 0],
+                    points.scanner_channel().collect::<Vec<_>>()
+                );
+                assert_eq!(Some(7), owned[0].nir);
+            }
+        }
+    }
+
+    #[test]
+    fn set_flag_columns_errors() {
+        let mut points = build_points_for_format(Format::new(1).unwrap());
+        assert!(points.set_return_number(&[1, 2, 3, 4, 8]).is_err());
+        assert!(points.set_number_of_returns(&[1; 4]).is_err());
+        assert!(points.set_scanner_channel_at(0, 1).is_err());
+        assert!(points.set_nir(&[0; 5]).is_err());
+        let mut points = build_points_for_format(Format::new(6).unwrap());
+        let scanner_channel: Vec<u8> = points.scanner_channel().collect();
+        assert!(points.set_return_number(&[1, 2, 3, 4, 16]).is_err());
+        assert!(points.set_scanner_channel(&[0, 0, 0, 0, 4]).is_err());
+        assert_eq!(
+            scanner_channel,
+            points.scanner_channel().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn flag_waveform_and_extra_bytes_columns() {
+        let mut format = Format::new(9).unwrap();
+        format.extra_bytes = 2;
+        let mut buf = Vec::new();
+        let mut raw_point = build_raw_point(&format, 0);
+        // Scan direction, edge, key point, overlap and channel 2.
+        raw_point.flags = Flags::ThreeByte(0x21, 0b1110_1010, 1);
+        raw_point.waveform = Some(Waveform {
+            wave_packet_descriptor_index: 1,
+            byte_offset_to_waveform_data: 2,
+            waveform_packet_size_in_bytes: 3,
+            return_point_waveform_location: 4.,
+            x_t: 5.,
+            y_t: 6.,
+            z_t: 7.,
+        });
+        raw_point.extra_bytes = vec![8, 9];
+        raw_point.write_to(&mut buf, &format).unwrap();
+        let mut points = empty_point_data(format);
+        points.resize_for(1).copy_from_slice(&buf);
+        let expected = Point::new(raw_point, &default_transforms());
+        assert_eq!(
+            vec![ScanDirection::LeftToRight],
+            points.scan_direction().collect::<Vec<_>>()
+        );
+        assert!(points.is_edge_of_flight_line().all(|b| b));
+        assert!(points.is_key_point().all(|b| b));
+        assert!(points.is_overlap().all(|b| b));
+        assert!(!points.is_synthetic().any(|b| b));
+        assert!(!points.is_withheld().any(|b| b));
+        assert_eq!(vec![2], points.scanner_channel().collect::<Vec<_>>());
+        assert_eq!(expected.waveform, points.waveform().unwrap().next());
+        assert_eq!(
+            vec![&[8u8, 9][..]],
+            points.extra_bytes().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn views_mut() {
+        for format in [Format::new(1).unwrap(), Format::new(7).unwrap()] {
+            let mut points = build_points_for_format(format);
+            for (i, mut point) in points.views_mut().enumerate() {
+                point.set_xyz(1., 2., i as f64).unwrap();
+                assert!(point.set_classification(32).is_err() != format.is_extended);
+                point.set_classification(6).unwrap();
+                point.set_withheld(true);
+                point.set_synthetic(false);
+                point.set_point_source_id(3);
+            }
+            points.get_mut(4).unwrap().set_gps_time(8.).unwrap();
+            assert!(points.get_mut(5).is_none());
+            let point = points.get(4).unwrap().to_point().unwrap();
+            assert_eq!((1., 2., 4.), (point.x, point.y, point.z));
+            assert_eq!(u8::from(point.classification), 6);
+            assert!(point.is_withheld);
+            assert!(!point.is_synthetic);
+            assert!(!point.is_key_point);
+            assert_eq!(point.point_source_id, 3);
+            assert_eq!(point.gps_time, Some(8.));
+        }
+    }
+
+    #[test]
+    fn views_mut_flags() {
+        for format in [Format::new(3).unwrap(), Format::new(8).unwrap()] {
+            let mut points = build_points_for_format(format);
+            let mut point = points.get_mut(0).unwrap();
+            point.set_overlap(true);
+            assert_eq!(point.set_scanner_channel(2).is_ok(), format.is_extended);
+            assert!(point.set_scanner_channel(4).is_err());
+            assert_eq!(point.set_return_number(9).is_ok(), format.is_extended);
+            assert!(point.set_return_number(16).is_err());
+            point.set_return_number(3).unwrap();
+            point.set_number_of_returns(5).unwrap();
+            point.set_scan_angle(-12.);
+            point.set_scan_direction(ScanDirection::LeftToRight);
+            point.set_edge_of_flight_line(true);
+            assert_eq!(point.set_nir(42).is_ok(), format.has_nir);
+            let point = point.as_ref();
+            assert!(point.is_overlap());
+            assert_eq!(
+                point.scanner_channel(),
+                if format.is_extended { 2 } else { 0 }
+            );
+            assert_eq!(point.return_number(), 3);
+            assert_eq!(point.number_of_returns(), 5);
+            assert_eq!(point.scan_angle_degrees().round(), -12.);
+            assert_eq!(point.scan_direction(), ScanDirection::LeftToRight);
+            assert!(point.is_edge_of_flight_line());
+            assert_eq!(point.nir(), format.has_nir.then_some(42));
+            let mut point = points.get_mut(0).unwrap();
+            point.set_overlap(false);
+            point.set_edge_of_flight_line(false);
+            let point = point.as_ref();
+            assert!(!point.is_overlap());
+            assert!(!point.is_edge_of_flight_line());
+            assert_eq!(point.return_number(), 3);
+            assert_eq!(point.scan_direction(), ScanDirection::LeftToRight);
+            assert!(points.get(usize::MAX).is_none());
+            assert!(points.get_mut(usize::MAX / 2).is_none());
+        }
+    }
+
+    #[test]
+    fn filter_take_slice() {
+        let points = build_points_for_format(Format::new(3).unwrap());
+        let x = |points: &PointData| points.x_raw().collect::<Vec<_>>();
+        assert_eq!(vec![1, 3], x(&points.filter(|p| p.x_raw() % 2 == 1)));
+        assert_eq!(
+            vec![0, 4],
+            x(&points
+                .filter_mask(&[true, false, false, false, true])
+                .unwrap())
+        );
+        assert!(points.filter_mask(&[true]).is_err());
+        assert_eq!(vec![4, 4, 1], x(&points.take(&[4, 4, 1])));
+        assert_eq!(vec![1, 2], x(&points.slice(1..3)));
+        assert_eq!(vec![2, 3, 4], x(&points.slice(2..)));
+        assert_eq!(vec![0, 1, 2], x(&points.slice(..=2)));
+        let (head, tail) = points.split_at(2);
+        assert_eq!(vec![0, 1], x(&head));
+        assert_eq!(vec![2, 3, 4], x(&tail));
+        assert_eq!(points.format(), tail.format());
+    }
+
+    #[test]
+    fn extend_from() {
+        let format = Format::new(3).unwrap();
+        let points = build_points_for_format(format);
+        let (mut head, tail) = points.split_at(1);
+        head.extend_from(&tail).unwrap();
+        assert_eq!(points.raw_bytes(), head.raw_bytes());
+
+        assert!(head
+            .extend_from(&build_points_for_format(Format::new(2).unwrap()))
+            .is_err());
+        let mut transforms = default_transforms();
+        transforms.x.offset = 1.;
+        let other = PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(transforms)
+            .build();
+        assert!(head.extend_from(&other).is_err());
+        assert_eq!(5, head.len());
+    }
+
+    #[test]
+    fn sort_by_field() {
+        let format = Format::new(3).unwrap();
+        let mut points = build_points_for_format(format);
+        points.set_gps_time(&[3., 1., 4., 1., 5.]).unwrap();
+        points.sort_by_field(Dimension::GpsTime).unwrap();
+        assert_eq!(
+            vec![1., 1., 3., 4., 5.],
+            points.gps_time().unwrap().collect::<Vec<_>>()
+        );
+        // Stable, so the two points at 1s keep their order.
+        assert_eq!(vec![1, 3, 0, 2, 4], points.x_raw().collect::<Vec<_>>());
+
+        let mut points = build_points_for_format(Format::new(0).unwrap());
+        assert!(points.sort_by_field(Dimension::GpsTime).is_err());
+        points.sort_by_field(Dimension::Intensity).unwrap();
+
+        let mut points = PointDataBuilder::new().build();
+        assert!(matches!(
+            points.sort_by_field(Dimension::Red),
+            Err(Error::NoDimension { .. })
+        ));
+    }
+
+    #[test]
+    fn sort_by_key_and_sort_by() {
+        let mut points = build_points_for_format(Format::new(1).unwrap());
+        points.set_point_source_id(&[2, 1, 2, 1, 1]).unwrap();
+        points.sort_by_key(|p| (p.point_source_id(), std::cmp::Reverse(p.x_raw())));
+        assert_eq!(vec![4, 3, 1, 2, 0], points.x_raw().collect::<Vec<_>>());
+        points.sort_by(|a, b| a.x_raw().cmp(&b.x_raw()));
+        assert_eq!(vec![0, 1, 2, 3, 4], points.x_raw().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn transform_coordinates() {
+        use crate::reproject::Affine;
+
+        let format = Format::new(0).unwrap();
+        let raw_points: Vec<raw::Point> = (0..3).map(|i| build_raw_point(&format, i)).collect();
+        let mut bytes = Vec::new();
+        for raw_point in &raw_points {
+            raw_point.write_to(&mut bytes, &format).unwrap();
+        }
+        let mut points = PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(default_transforms())
+            .build_from_bytes(bytes)
+            .unwrap();
+        let transforms = Vector {
+            x: Transform {
+                scale: 0.0005,
+                offset: 100.,
+            },
+            ..default_transforms()
+        };
+        let bounds = points
+            .transform_coordinates(&Affine::translation(100., 0., -1.), transforms)
+            .unwrap();
+        assert_eq!(&transforms, points.transforms());
+        assert_eq!(vec![0, 2, 4], points.x_raw().collect::<Vec<_>>());
+        assert_eq!(vec![-0.998, -0.997, -0.996], points.z().collect::<Vec<_>>());
+        assert_eq!(100., bounds.min.x);
+        assert_eq!(0.003, bounds.max.y);
+        assert_eq!(-0.996, bounds.max.z);
+
+        let too_far = Affine::translation(1e12, 0., 0.);
+        assert!(points
+            .transform_coordinates(&too_far, default_transforms())
+            .is_err());
+        assert_eq!(&transforms, points.transforms());
+        assert_eq!(100., points.x().next().unwrap());
+    }
+}
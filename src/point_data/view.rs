@@ -0,0 +1,563 @@
+//! Borrowed views of single records in a [PointData](super::PointData).
+
+use crate::{
+    point::{Classification, Dimension, Format, ScanDirection},
+    raw::{
+        self,
+        point::{Flags, Layout, ScanAngle, Waveform, OVERLAP_CLASSIFICATION_CODE},
+    },
+    Color, Error, Point, Result, Transform, Vector,
+};
+
+/// A borrowed view of one point record.
+///
+/// Fields are decoded from the record bytes only when asked for, so
+/// iterating over [`PointData::views`](super::PointData::views) doesn't
+/// allocate.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+///
+/// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+/// let ground = points.views().filter(|p| p.classification() == 2).count();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PointRef<'a> {
+    record: &'a [u8],
+    format: &'a Format,
+    transforms: &'a Vector<Transform>,
+    layout: &'a Layout,
+}
+
+/// A mutable view of one point record, for editing it in place.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+///
+/// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+/// for mut point in points.views_mut() {
+///     if point.as_ref().z() < 400. {
+///         point.set_classification(2).unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PointMut<'a> {
+    record: &'a mut [u8],
+    format: &'a Format,
+    transforms: &'a Vector<Transform>,
+    layout: &'a Layout,
+}
+
+impl<'a> PointRef<'a> {
+    pub(super) fn new(
+        record: &'a [u8],
+        format: &'a Format,
+        transforms: &'a Vector<Transform>,
+        layout: &'a Layout,
+    ) -> PointRef<'a> {
+        PointRef {
+            record,
+            format,
+            transforms,
+            layout,
+        }
+    }
+
+    /// Returns the record bytes.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.record
+    }
+
+    /// Returns the raw x value.
+    pub fn x_raw(&self) -> i32 {
+        i32_at(self.record, 0)
+    }
+
+    /// Returns the raw y value.
+    pub fn y_raw(&self) -> i32 {
+        i32_at(self.record, 4)
+    }
+
+    /// Returns the raw z value.
+    pub fn z_raw(&self) -> i32 {
+        i32_at(self.record, 8)
+    }
+
+    /// Returns the world x value, with scale and offset applied.
+    pub fn x(&self) -> f64 {
+        self.transforms.x.direct(self.x_raw())
+    }
+
+    /// Returns the world y value.
+    pub fn y(&self) -> f64 {
+        self.transforms.y.direct(self.y_raw())
+    }
+
+    /// Returns the world z value.
+    pub fn z(&self) -> f64 {
+        self.transforms.z.direct(self.z_raw())
+    }
+
+    /// Returns the intensity.
+    pub fn intensity(&self) -> u16 {
+        u16_at(self.record, 12)
+    }
+
+    /// Returns the return number.
+    pub fn return_number(&self) -> u8 {
+        self.flags().return_number()
+    }
+
+    /// Returns the number of returns.
+    pub fn number_of_returns(&self) -> u8 {
+        self.flags().number_of_returns()
+    }
+
+    /// Returns the scan direction.
+    pub fn scan_direction(&self) -> ScanDirection {
+        self.flags().scan_direction()
+    }
+
+    /// Returns whether this point is at the edge of a flight line.
+    pub fn is_edge_of_flight_line(&self) -> bool {
+        self.flags().is_edge_of_flight_line()
+    }
+
+    /// Returns the classification byte.
+    pub fn classification(&self) -> u8 {
+        if self.format.is_extended {
+            self.record[16]
+        } else {
+            self.record[15] & 0b0001_1111
+        }
+    }
+
+    /// Returns whether this point is synthetic.
+    pub fn is_synthetic(&self) -> bool {
+        self.flags().is_synthetic()
+    }
+
+    /// Returns whether this point is a key point.
+    pub fn is_key_point(&self) -> bool {
+        self.flags().is_key_point()
+    }
+
+    /// Returns whether this point is withheld.
+    pub fn is_withheld(&self) -> bool {
+        self.flags().is_withheld()
+    }
+
+    /// Returns whether this point is an overlap point.
+    pub fn is_overlap(&self) -> bool {
+        self.flags().is_overlap()
+    }
+
+    /// Returns the scanner channel, which is always zero for legacy formats.
+    pub fn scanner_channel(&self) -> u8 {
+        self.flags().scanner_channel()
+    }
+
+    /// Returns the scan angle in degrees.
+    pub fn scan_angle_degrees(&self) -> f32 {
+        let offset = self.layout.scan_angle;
+        if self.format.is_extended {
+            f32::from(i16::from_le_bytes([
+                self.record[offset],
+                self.record[offset + 1],
+            ])) * 0.006
+        } else {
+            f32::from(self.record[offset] as i8)
+        }
+    }
+
+    /// Returns the user data.
+    pub fn user_data(&self) -> u8 {
+        self.record[self.layout.user_data]
+    }
+
+    /// Returns the point source ID.
+    pub fn point_source_id(&self) -> u16 {
+        u16_at(self.record, self.layout.point_source_id)
+    }
+
+    /// Returns the GPS time, if the format has one.
+    pub fn gps_time(&self) -> Option<f64> {
+        self.layout
+            .gps_time
+            .map(|offset| f64_at(self.record, offset))
+    }
+
+    /// Returns the color, if the format has one.
+    pub fn color(&self) -> Option<Color> {
+        self.layout.rgb.map(|offset| Color {
+            red: u16_at(self.record, offset),
+            green: u16_at(self.record, offset + 2),
+            blue: u16_at(self.record, offset + 4),
+        })
+    }
+
+    /// Returns the NIR value, if the format has one.
+    pub fn nir(&self) -> Option<u16> {
+        self.layout.nir.map(|offset| u16_at(self.record, offset))
+    }
+
+    /// Returns the waveform packet, if the format has one.
+    pub fn waveform(&self) -> Option<Waveform> {
+        self.layout
+            .waveform
+            .map(|offset| waveform(self.record, offset))
+    }
+
+    /// Returns the extra bytes.
+    pub fn extra_bytes(&self) -> &'a [u8] {
+        &self.record[self.layout.extra_bytes..]
+    }
+
+    /// Returns the value of a field as a float, or `None` if the format doesn't have it.
+    ///
+    /// Flags are returned as zero or one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Reader};
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_points(1).unwrap();
+    /// let point = points.get(0).unwrap();
+    /// assert_eq!(Some(point.z()), point.value(Dimension::Z));
+    /// assert_eq!(None, point.value(Dimension::Red));
+    /// ```
+    pub fn value(&self, dimension: Dimension) -> Option<f64> {
+        let flag = |b: bool| Some(f64::from(u8::from(b)));
+        match dimension {
+            Dimension::X => Some(self.x()),
+            Dimension::Y => Some(self.y()),
+            Dimension::Z => Some(self.z()),
+            Dimension::Intensity => Some(self.intensity().into()),
+            Dimension::ReturnNumber => Some(self.return_number().into()),
+            Dimension::NumberOfReturns => Some(self.number_of_returns().into()),
+            Dimension::ScanDirection => flag(self.scan_direction() == ScanDirection::LeftToRight),
+            Dimension::EdgeOfFlightLine => flag(self.is_edge_of_flight_line()),
+            Dimension::Classification => Some(self.classification().into()),
+            Dimension::Synthetic => flag(self.is_synthetic()),
+            Dimension::KeyPoint => flag(self.is_key_point()),
+            Dimension::Withheld => flag(self.is_withheld()),
+            Dimension::Overlap => flag(self.is_overlap()),
+            Dimension::ScannerChannel => Some(self.scanner_channel().into()),
+            Dimension::ScanAngle => Some(self.scan_angle_degrees().into()),
+            Dimension::UserData => Some(self.user_data().into()),
+            Dimension::PointSourceId => Some(self.point_source_id().into()),
+            Dimension::GpsTime => self.gps_time(),
+            Dimension::Red => self.color().map(|color| color.red.into()),
+            Dimension::Green => self.color().map(|color| color.green.into()),
+            Dimension::Blue => self.color().map(|color| color.blue.into()),
+            Dimension::Nir => self.nir().map(f64::from),
+        }
+    }
+
+    /// Decodes this record into an owned [Point].
+    pub fn to_point(&self) -> Result<Point> {
+        raw::Point::read_from(self.record, self.format)
+            .map(|raw_point| Point::new(raw_point, self.transforms))
+    }
+
+    fn flags(&self) -> Flags {
+        flags(self.record, self.format.is_extended)
+    }
+}
+
+impl<'a> PointMut<'a> {
+    pub(super) fn new(
+        record: &'a mut [u8],
+        format: &'a Format,
+        transforms: &'a Vector<Transform>,
+        layout: &'a Layout,
+    ) -> PointMut<'a> {
+        PointMut {
+            record,
+            format,
+            transforms,
+            layout,
+        }
+    }
+
+    /// Returns a read-only view of this record.
+    pub fn as_ref(&self) -> PointRef<'_> {
+        PointRef::new(self.record, self.format, self.transforms, self.layout)
+    }
+
+    /// Sets the raw x, y and z values.
+    pub fn set_xyz_raw(&mut self, x: i32, y: i32, z: i32) {
+        self.record[0..4].copy_from_slice(&x.to_le_bytes());
+        self.record[4..8].copy_from_slice(&y.to_le_bytes());
+        self.record[8..12].copy_from_slice(&z.to_le_bytes());
+    }
+
+    /// Sets the world x, y and z values, quantizing them through the transforms.
+    ///
+    /// Nothing is written if any value can't be represented with the transforms.
+    pub fn set_xyz(&mut self, x: f64, y: f64, z: f64) -> Result<()> {
+        let x = self.transforms.x.inverse(x)?;
+        let y = self.transforms.y.inverse(y)?;
+        let z = self.transforms.z.inverse(z)?;
+        self.set_xyz_raw(x, y, z);
+        Ok(())
+    }
+
+    /// Sets the intensity.
+    pub fn set_intensity(&mut self, intensity: u16) {
+        self.record[12..14].copy_from_slice(&intensity.to_le_bytes());
+    }
+
+    /// Sets the classification.
+    ///
+    /// Legacy formats only have room for classifications up to 31.
+    pub fn set_classification(&mut self, classification: u8) -> Result<()> {
+        if self.format.is_extended {
+            self.record[16] = classification;
+        } else if classification > 31 {
+            return Err(Error::InvalidClassification(classification));
+        } else {
+            self.record[15] = (self.record[15] & 0b1110_0000) | classification;
+        }
+        Ok(())
+    }
+
+    /// Sets whether this point is synthetic.
+    pub fn set_synthetic(&mut self, is_synthetic: bool) {
+        self.set_class_flag(5, 0, is_synthetic);
+    }
+
+    /// Sets whether this point is a key point.
+    pub fn set_key_point(&mut self, is_key_point: bool) {
+        self.set_class_flag(6, 1, is_key_point);
+    }
+
+    /// Sets whether this point is withheld.
+    pub fn set_withheld(&mut self, is_withheld: bool) {
+        self.set_class_flag(7, 2, is_withheld);
+    }
+
+    /// Sets whether this point is an overlap point.
+    ///
+    /// Legacy formats mark overlap points with the overlap classification, so
+    /// setting this replaces the classification, and clearing it on an
+    /// overlap point leaves the point unclassified.
+    pub fn set_overlap(&mut self, is_overlap: bool) {
+        if self.format.is_extended {
+            self.set_bits(15, 0b0000_1000, u8::from(is_overlap) << 3);
+        } else if is_overlap {
+            self.set_bits(15, 0b0001_1111, OVERLAP_CLASSIFICATION_CODE);
+        } else if self.record[15] & 0b0001_1111 == OVERLAP_CLASSIFICATION_CODE {
+            self.set_bits(15, 0b0001_1111, Classification::Unclassified.into());
+        }
+    }
+
+    /// Sets the scanner channel.
+    ///
+    /// Channels go up to 3, and legacy formats only have channel zero.
+    pub fn set_scanner_channel(&mut self, scanner_channel: u8) -> Result<()> {
+        if scanner_channel > 3 || (!self.format.is_extended && scanner_channel > 0) {
+            return Err(Error::InvalidScannerChannel(scanner_channel));
+        }
+        if self.format.is_extended {
+            self.set_bits(15, 0b0011_0000, scanner_channel << 4);
+        }
+        Ok(())
+    }
+
+    /// Sets the return number.
+    ///
+    /// Legacy formats have room for return numbers up to 7, extended formats
+    /// up to 15.
+    pub fn set_return_number(&mut self, return_number: u8) -> Result<()> {
+        check_return_number(return_number, self.format)?;
+        if self.format.is_extended {
+            self.set_bits(14, 0b0000_1111, return_number);
+        } else {
+            self.set_bits(14, 0b0000_0111, return_number);
+        }
+        Ok(())
+    }
+
+    /// Sets the number of returns.
+    ///
+    /// Legacy formats have room for up to 7 returns, extended formats up to 15.
+    pub fn set_number_of_returns(&mut self, number_of_returns: u8) -> Result<()> {
+        check_return_number(number_of_returns, self.format)?;
+        if self.format.is_extended {
+            self.set_bits(14, 0b1111_0000, number_of_returns << 4);
+        } else {
+            self.set_bits(14, 0b0011_1000, number_of_returns << 3);
+        }
+        Ok(())
+    }
+
+    /// Sets the scan angle in degrees.
+    ///
+    /// Legacy formats round the angle to whole degrees.
+    pub fn set_scan_angle(&mut self, degrees: f32) {
+        let offset = self.layout.scan_angle;
+        let scan_angle = ScanAngle::from(degrees);
+        if self.format.is_extended {
+            self.record[offset..offset + 2].copy_from_slice(&i16::from(scan_angle).to_le_bytes());
+        } else {
+            self.record[offset] = i8::from(scan_angle) as u8;
+        }
+    }
+
+    /// Sets the scan direction.
+    pub fn set_scan_direction(&mut self, scan_direction: ScanDirection) {
+        let value = u8::from(scan_direction == ScanDirection::LeftToRight) << 6;
+        self.set_bits(self.direction_byte(), 0b0100_0000, value);
+    }
+
+    /// Sets whether this point is at the edge of a flight line.
+    pub fn set_edge_of_flight_line(&mut self, is_edge_of_flight_line: bool) {
+        let value = u8::from(is_edge_of_flight_line) << 7;
+        self.set_bits(self.direction_byte(), 0b1000_0000, value);
+    }
+
+    /// Sets the user data.
+    pub fn set_user_data(&mut self, user_data: u8) {
+        self.record[self.layout.user_data] = user_data;
+    }
+
+    /// Sets the point source ID.
+    pub fn set_point_source_id(&mut self, point_source_id: u16) {
+        let offset = self.layout.point_source_id;
+        self.record[offset..offset + 2].copy_from_slice(&point_source_id.to_le_bytes());
+    }
+
+    /// Sets the GPS time.
+    ///
+    /// Returns an error if the format has no gps_time field.
+    pub fn set_gps_time(&mut self, gps_time: f64) -> Result<()> {
+        let offset = self.layout.gps_time.ok_or(Error::NoGpsTime(*self.format))?;
+        self.record[offset..offset + 8].copy_from_slice(&gps_time.to_le_bytes());
+        Ok(())
+    }
+
+    /// Sets the color.
+    ///
+    /// Returns an error if the format has no color.
+    pub fn set_color(&mut self, color: Color) -> Result<()> {
+        let offset = self.layout.rgb.ok_or(Error::NoColor(*self.format))?;
+        self.record[offset..offset + 2].copy_from_slice(&color.red.to_le_bytes());
+        self.record[offset + 2..offset + 4].copy_from_slice(&color.green.to_le_bytes());
+        self.record[offset + 4..offset + 6].copy_from_slice(&color.blue.to_le_bytes());
+        Ok(())
+    }
+
+    /// Sets the NIR value.
+    ///
+    /// Returns an error if the format has no NIR field.
+    pub fn set_nir(&mut self, nir: u16) -> Result<()> {
+        let offset = self.layout.nir.ok_or(Error::NoDimension {
+            dimension: Dimension::Nir,
+            format: *self.format,
+        })?;
+        self.record[offset..offset + 2].copy_from_slice(&nir.to_le_bytes());
+        Ok(())
+    }
+
+    fn direction_byte(&self) -> usize {
+        if self.format.is_extended {
+            15
+        } else {
+            14
+        }
+    }
+
+    fn set_bits(&mut self, byte: usize, mask: u8, value: u8) {
+        self.record[byte] = (self.record[byte] & !mask) | (value & mask);
+    }
+
+    fn set_class_flag(&mut self, legacy_bit: u8, extended_bit: u8, value: bool) {
+        let bit = if self.format.is_extended {
+            extended_bit
+        } else {
+            legacy_bit
+        };
+        if value {
+            self.record[15] |= 1 << bit;
+        } else {
+            self.record[15] &= !(1 << bit);
+        }
+    }
+}
+
+pub(super) fn check_return_number(return_number: u8, format: &Format) -> Result<()> {
+    let max = if format.is_extended { 15 } else { 7 };
+    if return_number > max {
+        Err(Error::ReturnNumber {
+            return_number,
+            version: None,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+pub(super) fn flags(record: &[u8], is_extended: bool) -> Flags {
+    if is_extended {
+        Flags::ThreeByte(record[14], record[15], record[16])
+    } else {
+        Flags::TwoByte(record[14], record[15])
+    }
+}
+
+pub(super) fn waveform(record: &[u8], offset: usize) -> Waveform {
+    Waveform {
+        wave_packet_descriptor_index: record[offset],
+        byte_offset_to_waveform_data: u64::from_le_bytes(
+            record[offset + 1..offset + 9]
+                .try_into()
+                .expect("slice is eight bytes"),
+        ),
+        waveform_packet_size_in_bytes: u32::from_le_bytes(
+            record[offset + 9..offset + 13]
+                .try_into()
+                .expect("slice is four bytes"),
+        ),
+        return_point_waveform_location: f32_at(record, offset + 13),
+        x_t: f32_at(record, offset + 17),
+        y_t: f32_at(record, offset + 21),
+        z_t: f32_at(record, offset + 25),
+    }
+}
+
+fn u16_at(record: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([record[offset], record[offset + 1]])
+}
+
+fn i32_at(record: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes([
+        record[offset],
+        record[offset + 1],
+        record[offset + 2],
+        record[offset + 3],
+    ])
+}
+
+fn f32_at(record: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes([
+        record[offset],
+        record[offset + 1],
+        record[offset + 2],
+        record[offset + 3],
+    ])
+}
+
+fn f64_at(record: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(
+        record[offset..offset + 8]
+            .try_into()
+            .expect("slice is eight bytes"),
+    )
+}
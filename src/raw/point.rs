@@ -7,7 +7,7 @@ use crate::{
 use std::io::{Read, Write};
 
 const SCAN_ANGLE_SCALE_FACTOR: f32 = 0.006;
-const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
+pub(crate) const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
 
 /// A raw point.
 ///
@@ -177,8 +177,11 @@ pub struct Point {
     /// | 15 | Transmission tower |
     /// | 16 | Wire-structure connector (e.g. insulator) |
     /// | 17 | Bridge deck |
-    /// | 17 | High noise |
-    /// | 19-63 | Reserved |
+    /// | 18 | High noise |
+    /// | 19 | Overhead structure |
+    /// | 20 | Ignored snow |
+    /// | 21 | Temporary exclusion |
+    /// | 22-63 | Reserved |
     /// | 64-255 | Userdefinable |
     pub flags: Flags,
 
@@ -244,6 +247,7 @@ pub struct Point {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Waveform {
     /// This value plus 99 is the Record ID of the Waveform Packet Descriptor and indicates the
@@ -425,6 +429,10 @@ pub(crate) struct Layout {
     pub rgb: Option<usize>,
     /// Offset of the NIR u16, if the format has NIR.
     pub nir: Option<usize>,
+    /// Offset of the waveform packet, if the format has waveforms.
+    pub waveform: Option<usize>,
+    /// Offset of the extra bytes, which run to the end of the record.
+    pub extra_bytes: usize,
     /// Total record length in bytes.
     pub record_len: usize,
 }
@@ -440,6 +448,8 @@ impl Layout {
             gps_time: None,
             rgb: None,
             nir: None,
+            waveform: None,
+            extra_bytes: 0,
             record_len: 0,
         };
         let mut offset = 0usize;
@@ -451,14 +461,10 @@ impl Layout {
                 Field::GpsTime => layout.gps_time = Some(offset),
                 Field::Color => layout.rgb = Some(offset),
                 Field::Nir => layout.nir = Some(offset),
+                Field::Waveform => layout.waveform = Some(offset),
+                Field::ExtraBytes => layout.extra_bytes = offset,
                 // Other fields' offsets are fixed or not needed by PointData.
-                Field::X
-                | Field::Y
-                | Field::Z
-                | Field::Intensity
-                | Field::Flags
-                | Field::Waveform
-                | Field::ExtraBytes => {}
+                Field::X | Field::Y | Field::Z | Field::Intensity | Field::Flags => {}
             }
             offset += field.size(format);
         }
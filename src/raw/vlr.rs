@@ -4,7 +4,7 @@ use crate::Result;
 use std::io::{Read, Write};
 
 /// A raw variable length record.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Vlr {
     /// This value must be set to zero
     pub reserved: u16,
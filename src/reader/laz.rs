@@ -1,7 +1,13 @@
 use super::ReadPoints;
 use crate::{Header, Result};
-use laz::LazDecompressor;
-use std::io::{Read, Seek};
+use laz::{
+    laszip::{ChunkTable, LazItem},
+    record::{
+        LayeredPointRecordDecompressor, RecordDecompressor, SequentialPointRecordDecompressor,
+    },
+    DecompressionSelection, LazDecompressor, LazVlr,
+};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
 
 pub(crate) struct PointReader<D: LazDecompressor> {
     decompressor: D,
@@ -38,6 +44,20 @@ impl<R: Read + Seek + Send + Sync> PointReader<laz::LasZipDecompressor<'_, R>> {
     }
 }
 
+impl<R: Read + Seek + Send + Sync + 'static> PointReader<VariableChunkDecompressor<R>> {
+    pub(crate) fn new_variable(
+        read: R,
+        header: Header,
+    ) -> Result<PointReader<VariableChunkDecompressor<R>>> {
+        let decompressor = VariableChunkDecompressor::new(read, &header.laz_vlr()?)?;
+        Ok(PointReader {
+            decompressor,
+            header,
+            index: 0,
+        })
+    }
+}
+
 impl<D> ReadPoints for PointReader<D>
 where
     D: LazDecompressor + Send,
@@ -62,3 +82,110 @@ where
         &self.header
     }
 }
+
+/// Decompresses variable-size chunks by walking the chunk table.
+///
+/// The laz decompressors find a point's position in its chunk with `index %
+/// point_count`, which is only right for fixed-size chunks, so this one keeps
+/// track of the chunks itself.
+pub(crate) struct VariableChunkDecompressor<R> {
+    record_decompressor: Box<dyn RecordDecompressor<R> + Send + Sync>,
+    items: Vec<LazItem>,
+    // The byte offset and number of points of each chunk
+    chunks: Vec<(u64, u64)>,
+    chunk: usize,
+    points_left_in_chunk: u64,
+}
+
+impl<R: Read + Seek + Send + Sync + 'static> VariableChunkDecompressor<R> {
+    fn new(mut read: R, vlr: &LazVlr) -> Result<VariableChunkDecompressor<R>> {
+        let chunk_table = ChunkTable::read_from(&mut read, vlr)?;
+        // Reading the chunk table leaves the stream at the first chunk
+        let mut offset = read.stream_position()?;
+        let chunks = chunk_table
+            .as_ref()
+            .iter()
+            .map(|entry| {
+                let chunk = (offset, entry.point_count);
+                offset += entry.byte_count;
+                chunk
+            })
+            .collect();
+        let record_decompressor: Box<dyn RecordDecompressor<R> + Send + Sync> =
+            if vlr.items().first().is_some_and(|item| item.version() >= 3) {
+                Box::new(LayeredPointRecordDecompressor::new(read))
+            } else {
+                Box::new(SequentialPointRecordDecompressor::new(read))
+            };
+        let mut decompressor = VariableChunkDecompressor {
+            record_decompressor,
+            items: vlr.items().clone(),
+            chunks,
+            chunk: 0,
+            points_left_in_chunk: 0,
+        };
+        if !decompressor.chunks.is_empty() {
+            decompressor.start_chunk(0)?;
+        }
+        Ok(decompressor)
+    }
+}
+
+impl<R: Read + Seek> VariableChunkDecompressor<R> {
+    fn start_chunk(&mut self, chunk: usize) -> laz::Result<()> {
+        let (offset, point_count) = self.chunks[chunk];
+        let _ = self
+            .record_decompressor
+            .get_mut()
+            .seek(SeekFrom::Start(offset))?;
+        self.record_decompressor.reset();
+        self.record_decompressor.set_fields_from(&self.items)?;
+        self.record_decompressor
+            .set_selection(DecompressionSelection::all());
+        self.chunk = chunk;
+        self.points_left_in_chunk = point_count;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> LazDecompressor for VariableChunkDecompressor<R> {
+    fn decompress_one(&mut self, point: &mut [u8]) -> laz::Result<()> {
+        while self.points_left_in_chunk == 0 {
+            if self.chunk + 1 >= self.chunks.len() {
+                return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            self.start_chunk(self.chunk + 1)?;
+        }
+        self.record_decompressor.decompress_next(point)?;
+        self.points_left_in_chunk -= 1;
+        Ok(())
+    }
+
+    fn decompress_many(&mut self, points: &mut [u8]) -> laz::Result<()> {
+        let record_size = self.record_decompressor.record_size();
+        for point in points.chunks_exact_mut(record_size) {
+            self.decompress_one(point)?;
+        }
+        Ok(())
+    }
+
+    fn seek(&mut self, index: u64) -> laz::Result<()> {
+        let mut start = 0;
+        for chunk in 0..self.chunks.len() {
+            let point_count = self.chunks[chunk].1;
+            if index < start + point_count {
+                self.start_chunk(chunk)?;
+                let mut skipped = vec![0; self.record_decompressor.record_size()];
+                for _ in start..index {
+                    self.decompress_one(&mut skipped)?;
+                }
+                return Ok(());
+            }
+            start += point_count;
+        }
+        // Past the last point, so there's nothing left to read
+        self.chunk = self.chunks.len().saturating_sub(1);
+        self.points_left_in_chunk = 0;
+        Ok(())
+    }
+}
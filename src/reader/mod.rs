@@ -189,6 +189,10 @@ impl Reader {
             #[cfg(feature = "laz")]
             {
                 let point_reader: Box<dyn ReadPoints> = match options.laz_parallelism {
+                    // The laz decompressors can't seek within variable-size chunks
+                    _ if header.laz_vlr()?.uses_variable_size_chunks() => {
+                        laz::PointReader::new_variable(read, header).map(Box::new)?
+                    }
                     #[cfg(feature = "laz-parallel")]
                     LazParallelism::Yes => {
                         laz::PointReader::new_parallel(read, header).map(Box::new)?
@@ -0,0 +1,669 @@
+//! Rewrite a file's header to match its point data.
+//!
+//! Vendor files sometimes ship with bounds, point counts or offsets that don't
+//! match the points they actually hold. [repair] scans the points, reports
+//! every field that disagrees with them, and patches the header in place.
+
+use crate::{point::Format, raw, Bounds, Error, PointDataBuilder, Reader, Result, Vector};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+const VLR_HEADER_LEN: u64 = 54;
+const EVLR_HEADER_LEN: u64 = 60;
+const SCAN_BLOCK_LEN: u64 = 1 << 20;
+
+/// A header field that didn't match the file's contents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Discrepancy {
+    /// The offset to the point data pointed inside the VLRs.
+    OffsetToPointData {
+        /// The value in the header.
+        header: u32,
+
+        /// The end of the VLRs.
+        actual: u32,
+    },
+
+    /// The start of the first EVLR didn't point to the EVLRs.
+    StartOfFirstEvlr {
+        /// The value in the header.
+        header: u64,
+
+        /// The end of the point data.
+        actual: u64,
+    },
+
+    /// The legacy (32-bit) number of point records.
+    LegacyNumberOfPoints {
+        /// The value in the header.
+        header: u32,
+
+        /// The value computed from the points.
+        actual: u32,
+    },
+
+    /// A legacy (32-bit) number of points by return.
+    LegacyNumberOfPointsByReturn {
+        /// The return number, starting at one.
+        return_number: u8,
+
+        /// The value in the header.
+        header: u32,
+
+        /// The value computed from the points.
+        actual: u32,
+    },
+
+    /// The las 1.4 (64-bit) number of point records.
+    NumberOfPoints {
+        /// The value in the header.
+        header: u64,
+
+        /// The value computed from the points.
+        actual: u64,
+    },
+
+    /// A las 1.4 (64-bit) number of points by return.
+    NumberOfPointsByReturn {
+        /// The return number, starting at one.
+        return_number: u8,
+
+        /// The value in the header.
+        header: u64,
+
+        /// The value computed from the points.
+        actual: u64,
+    },
+
+    /// The bounds.
+    Bounds {
+        /// The value in the header.
+        header: Bounds,
+
+        /// The value computed from the points.
+        actual: Bounds,
+    },
+}
+
+/// Rewrites a file's header in place so it matches the file's point data.
+///
+/// Fixes, in order:
+///
+/// 1. `offset_to_point_data`, if it points inside the VLRs.
+/// 2. The start of the first EVLR, if there aren't EVLRs where it points but
+///    there are right after the point data.
+/// 3. The bounds and the legacy and las 1.4 point counts, by reading every
+///    point.
+///
+/// For uncompressed data, the points are assumed to fill the space between
+/// the point data offset and the EVLRs (or the end of the file), so the
+/// header's number of points isn't trusted. Compressed files need the `laz`
+/// feature, and their number of points is trusted.
+///
+/// Returns every discrepancy that was fixed, so an empty list means the file
+/// was already consistent.
+///
+/// # Examples
+///
+/// ```
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("autzen.las");
+/// std::fs::copy("tests/data/autzen.las", &path).unwrap();
+/// let discrepancies = las::repair(&path).unwrap();
+/// assert!(discrepancies.is_empty());
+/// ```
+pub fn repair<P: AsRef<Path>>(path: P) -> Result<Vec<Discrepancy>> {
+    let path = path.as_ref();
+    let mut discrepancies = Vec::new();
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut raw_header = raw::Header::read_from(&mut file)?;
+    let format = Format::new(raw_header.point_data_record_format)?;
+    let record_len = u64::from(raw_header.point_data_record_length);
+
+    let end_of_vlrs = records_end(
+        &mut file,
+        u64::from(raw_header.header_size),
+        u64::from(raw_header.number_of_variable_length_records),
+        false,
+        file_len,
+    )?
+    .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof))?;
+    if u64::from(raw_header.offset_to_point_data) < end_of_vlrs {
+        let actual = u32::try_from(end_of_vlrs)
+            .map_err(|_| Error::OffsetToPointDataTooLarge(end_of_vlrs as usize))?;
+        discrepancies.push(Discrepancy::OffsetToPointData {
+            header: raw_header.offset_to_point_data,
+            actual,
+        });
+        raw_header.offset_to_point_data = actual;
+    }
+    let offset_to_point_data = u64::from(raw_header.offset_to_point_data);
+
+    let number_of_evlrs = raw_header
+        .evlr
+        .map(|evlr| u64::from(evlr.number_of_evlrs))
+        .unwrap_or(0);
+    let evlrs_at = |file: &mut File, start: u64| -> Result<bool> {
+        Ok(start >= offset_to_point_data
+            && records_end(file, start, number_of_evlrs, true, file_len)? == Some(file_len))
+    };
+    let evlrs_are_valid = match raw_header.evlr {
+        Some(evlr) if number_of_evlrs > 0 => evlrs_at(&mut file, evlr.start_of_first_evlr)?,
+        _ => true,
+    };
+
+    let mut number_of_points = raw_header
+        .large_file
+        .map(|large_file| large_file.number_of_point_records)
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| u64::from(raw_header.number_of_point_records));
+    if !format.is_compressed && record_len > 0 {
+        // The points run up to the EVLRs or the end of the file. If the EVLR
+        // pointer is broken, find the count that puts valid EVLRs right after
+        // the points.
+        let end_of_point_data = match raw_header.evlr {
+            Some(evlr) if number_of_evlrs > 0 && evlrs_are_valid => evlr.start_of_first_evlr,
+            _ => file_len,
+        };
+        let fits = end_of_point_data.saturating_sub(offset_to_point_data) / record_len;
+        number_of_points = if number_of_evlrs > 0 && !evlrs_are_valid {
+            find_evlrs(
+                &mut file,
+                offset_to_point_data,
+                record_len,
+                fits,
+                file_len,
+                evlrs_at,
+            )?
+            .unwrap_or(number_of_points)
+        } else {
+            fits
+        };
+    }
+    if let Some(evlr) = raw_header.evlr.as_mut()
+        && number_of_evlrs > 0
+        && !evlrs_are_valid
+    {
+        let actual = if format.is_compressed {
+            None
+        } else {
+            Some(offset_to_point_data + number_of_points * record_len)
+        };
+        if let Some(actual) = actual
+            && evlrs_at(&mut file, actual)?
+        {
+            discrepancies.push(Discrepancy::StartOfFirstEvlr {
+                header: evlr.start_of_first_evlr,
+                actual,
+            });
+            evlr.start_of_first_evlr = actual;
+        } else {
+            return Err(Error::OffsetToEvlrsTooSmall(evlr.start_of_first_evlr));
+        }
+    }
+
+    // Read the points through the structural fixes, but keep the original
+    // counts and bounds around for the comparison. Nothing is written until
+    // every fix is known, so a failed read leaves the file untouched.
+    let original = raw_header.clone();
+    raw_header.number_of_point_records = u32::try_from(number_of_points).unwrap_or(0);
+    if let Some(large_file) = raw_header.large_file.as_mut() {
+        large_file.number_of_point_records = number_of_points;
+    }
+    let mut reader = Reader::new(PatchedHeader::new(&raw_header, File::open(path)?)?)?;
+    let mut header = reader.header().clone();
+    header.clear();
+    let mut points = PointDataBuilder::new().for_header(&header).build();
+    while reader.fill_points(1_000_000, &mut points)? > 0 {
+        header.add_point_data(&points);
+    }
+    let actual = header.into_raw()?;
+
+    let mut repaired = original.clone();
+    if original.number_of_point_records != actual.number_of_point_records {
+        discrepancies.push(Discrepancy::LegacyNumberOfPoints {
+            header: original.number_of_point_records,
+            actual: actual.number_of_point_records,
+        });
+    }
+    repaired.number_of_point_records = actual.number_of_point_records;
+    for (i, (&header, &actual)) in original
+        .number_of_points_by_return
+        .iter()
+        .zip(&actual.number_of_points_by_return)
+        .enumerate()
+    {
+        if header != actual {
+            discrepancies.push(Discrepancy::LegacyNumberOfPointsByReturn {
+                return_number: i as u8 + 1,
+                header,
+                actual,
+            });
+        }
+    }
+    repaired.number_of_points_by_return = actual.number_of_points_by_return;
+    if let (Some(original), Some(repaired), Some(actual)) = (
+        original.large_file,
+        repaired.large_file.as_mut(),
+        actual.large_file,
+    ) {
+        if original.number_of_point_records != actual.number_of_point_records {
+            discrepancies.push(Discrepancy::NumberOfPoints {
+                header: original.number_of_point_records,
+                actual: actual.number_of_point_records,
+            });
+        }
+        for (i, (&header, &actual)) in original
+            .number_of_points_by_return
+            .iter()
+            .zip(&actual.number_of_points_by_return)
+            .enumerate()
+        {
+            if header != actual {
+                discrepancies.push(Discrepancy::NumberOfPointsByReturn {
+                    return_number: i as u8 + 1,
+                    header,
+                    actual,
+                });
+            }
+        }
+        *repaired = actual;
+    }
+    let (header_bounds, actual_bounds) = (bounds(&original), bounds(&actual));
+    if header_bounds != actual_bounds {
+        discrepancies.push(Discrepancy::Bounds {
+            header: header_bounds,
+            actual: actual_bounds,
+        });
+    }
+    repaired.min_x = actual.min_x;
+    repaired.min_y = actual.min_y;
+    repaired.min_z = actual.min_z;
+    repaired.max_x = actual.max_x;
+    repaired.max_y = actual.max_y;
+    repaired.max_z = actual.max_z;
+    if !discrepancies.is_empty() {
+        write_header(&mut file, &repaired)?;
+    }
+    Ok(discrepancies)
+}
+
+/// Scans backward from the end of the point data for the largest number of
+/// points (at most `fits`) that puts valid EVLRs right after the points.
+///
+/// The candidates are read a block at a time, and only the ones whose first
+/// record length stays inside the file are followed, so EVLRs near the end of
+/// the file are found without a seek per point.
+fn find_evlrs(
+    file: &mut File,
+    offset_to_point_data: u64,
+    record_len: u64,
+    fits: u64,
+    file_len: u64,
+    evlrs_at: impl Fn(&mut File, u64) -> Result<bool>,
+) -> Result<Option<u64>> {
+    let per_block = (SCAN_BLOCK_LEN / record_len).max(1);
+    let mut buf = Vec::new();
+    let mut high = fits;
+    loop {
+        let low = high.saturating_sub(per_block - 1);
+        let start = offset_to_point_data + low * record_len;
+        let end = (offset_to_point_data + high * record_len + EVLR_HEADER_LEN).min(file_len);
+        if start < end {
+            buf.resize((end - start) as usize, 0);
+            let _ = file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut buf)?;
+            for n in (low..=high).rev() {
+                let i = ((n - low) * record_len) as usize;
+                let Some(bytes) = buf.get(i + 20..i + 28) else {
+                    continue;
+                };
+                let record_length = u64::from_le_bytes(bytes.try_into().expect("eight bytes"));
+                let position = start + i as u64;
+                let fits_in_file = position
+                    .checked_add(EVLR_HEADER_LEN)
+                    .and_then(|p| p.checked_add(record_length))
+                    .is_some_and(|p| p <= file_len);
+                if fits_in_file && evlrs_at(file, position)? {
+                    return Ok(Some(n));
+                }
+            }
+        }
+        if low == 0 {
+            return Ok(None);
+        }
+        high = low - 1;
+    }
+}
+
+/// Returns the end of `n` (e)vlrs starting at `start`, or `None` if they run past the end of the file.
+fn records_end(
+    file: &mut File,
+    start: u64,
+    n: u64,
+    extended: bool,
+    file_len: u64,
+) -> Result<Option<u64>> {
+    let header_len = if extended {
+        EVLR_HEADER_LEN
+    } else {
+        VLR_HEADER_LEN
+    };
+    let mut position = start;
+    for _ in 0..n {
+        if position + header_len > file_len {
+            return Ok(None);
+        }
+        let _ = file.seek(SeekFrom::Start(position + 20))?;
+        let record_length = if extended {
+            let mut buf = [0; 8];
+            file.read_exact(&mut buf)?;
+            u64::from_le_bytes(buf)
+        } else {
+            let mut buf = [0; 2];
+            file.read_exact(&mut buf)?;
+            u64::from(u16::from_le_bytes(buf))
+        };
+        position = match position
+            .checked_add(header_len)
+            .and_then(|p| p.checked_add(record_length))
+        {
+            Some(position) if position <= file_len => position,
+            _ => return Ok(None),
+        };
+    }
+    Ok(Some(position))
+}
+
+fn write_header(file: &mut File, raw_header: &raw::Header) -> Result<()> {
+    let _ = file.seek(SeekFrom::Start(0))?;
+    let mut write = BufWriter::new(file);
+    raw_header.write_to(&mut write)?;
+    write
+        .into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_data()?;
+    Ok(())
+}
+
+/// A file whose header bytes are replaced by an in-memory header.
+struct PatchedHeader {
+    header: Vec<u8>,
+    file: BufReader<File>,
+    position: u64,
+    file_position: Option<u64>,
+}
+
+impl PatchedHeader {
+    fn new(raw_header: &raw::Header, file: File) -> Result<PatchedHeader> {
+        let mut header = Vec::new();
+        raw_header.write_to(&mut header)?;
+        Ok(PatchedHeader {
+            header,
+            file: BufReader::new(file),
+            position: 0,
+            file_position: None,
+        })
+    }
+}
+
+impl Read for PatchedHeader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = if let Some(header) = self.header.get(self.position as usize..)
+            && !header.is_empty()
+        {
+            let n = header.len().min(buf.len());
+            buf[..n].copy_from_slice(&header[..n]);
+            n
+        } else {
+            if self.file_position != Some(self.position) {
+                let _ = self.file.seek(SeekFrom::Start(self.position))?;
+            }
+            let n = self.file.read(buf)?;
+            self.file_position = Some(self.position + n as u64);
+            n
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for PatchedHeader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(position) => position,
+            SeekFrom::Current(offset) => self
+                .position
+                .checked_add_signed(offset)
+                .ok_or_else(|| std::io::Error::from(ErrorKind::InvalidInput))?,
+            SeekFrom::End(_) => {
+                let position = self.file.seek(pos)?;
+                self.file_position = Some(position);
+                position
+            }
+        };
+        Ok(self.position)
+    }
+}
+
+fn bounds(raw_header: &raw::Header) -> Bounds {
+    Bounds {
+        min: Vector {
+            x: raw_header.min_x,
+            y: raw_header.min_y,
+            z: raw_header.min_z,
+        },
+        max: Vector {
+            x: raw_header.max_x,
+            y: raw_header.max_y,
+            z: raw_header.max_z,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::copy_to_temp_dir, Builder, Point, Vlr, Writer};
+
+    fn patch(path: &Path, f: impl FnOnce(&mut raw::Header)) {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .unwrap();
+        let mut raw_header = raw::Header::read_from(&mut file).unwrap();
+        f(&mut raw_header);
+        write_header(&mut file, &raw_header).unwrap();
+    }
+
+    fn read_raw_header(path: &Path) -> raw::Header {
+        raw::Header::read_from(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn consistent() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        assert!(repair(&path).unwrap().is_empty());
+        assert_eq!(
+            read_raw_header(Path::new("tests/data/autzen.las")),
+            read_raw_header(&path)
+        );
+    }
+
+    #[test]
+    fn counts_and_bounds() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        let expected = read_raw_header(&path);
+        patch(&path, |raw_header| {
+            raw_header.number_of_point_records = 100;
+            raw_header.number_of_points_by_return[0] = 1;
+            raw_header.max_x = 0.;
+        });
+        let discrepancies = repair(&path).unwrap();
+        assert!(discrepancies.contains(&Discrepancy::LegacyNumberOfPoints {
+            header: 100,
+            actual: 106,
+        }));
+        assert!(discrepancies.iter().any(|d| matches!(
+            d,
+            Discrepancy::LegacyNumberOfPointsByReturn {
+                return_number: 1,
+                header: 1,
+                ..
+            }
+        )));
+        assert!(discrepancies
+            .iter()
+            .any(|d| matches!(d, Discrepancy::Bounds { .. })));
+        assert_eq!(expected, read_raw_header(&path));
+        assert!(repair(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn too_many_points() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        patch(&path, |raw_header| {
+            raw_header.number_of_point_records = 1000
+        });
+        let discrepancies = repair(&path).unwrap();
+        assert_eq!(
+            vec![Discrepancy::LegacyNumberOfPoints {
+                header: 1000,
+                actual: 106,
+            }],
+            discrepancies
+        );
+    }
+
+    #[test]
+    fn offset_to_point_data() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        let expected = read_raw_header(&path);
+        patch(&path, |raw_header| {
+            raw_header.offset_to_point_data = u32::from(raw_header.header_size)
+        });
+        let discrepancies = repair(&path).unwrap();
+        assert_eq!(
+            vec![Discrepancy::OffsetToPointData {
+                header: u32::from(expected.header_size),
+                actual: expected.offset_to_point_data,
+            }],
+            discrepancies
+        );
+        assert_eq!(expected, read_raw_header(&path));
+    }
+
+    fn write_with_evlr(path: &Path, number_of_points: u32) {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "test".to_string(),
+            data: vec![42; 10],
+            ..Default::default()
+        });
+        let mut writer = Writer::from_path(path, builder.into_header().unwrap()).unwrap();
+        for i in 0..number_of_points {
+            writer
+                .write_point(Point {
+                    x: f64::from(i),
+                    return_number: 1,
+                    number_of_returns: 1,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn consistent_file_is_not_written() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        let modified = std::time::SystemTime::UNIX_EPOCH;
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(repair(&path).unwrap().is_empty());
+        assert_eq!(modified, path.metadata().unwrap().modified().unwrap());
+    }
+
+    #[test]
+    fn start_of_first_evlr() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evlr.las");
+        write_with_evlr(&path, 3);
+        let expected = read_raw_header(&path);
+
+        patch(&path, |raw_header| {
+            raw_header.evlr.as_mut().unwrap().start_of_first_evlr = 12;
+            raw_header
+                .large_file
+                .as_mut()
+                .unwrap()
+                .number_of_point_records = 100;
+        });
+        let discrepancies = repair(&path).unwrap();
+        assert!(discrepancies.contains(&Discrepancy::StartOfFirstEvlr {
+            header: 12,
+            actual: expected.evlr.unwrap().start_of_first_evlr,
+        }));
+        assert!(discrepancies.contains(&Discrepancy::NumberOfPoints {
+            header: 100,
+            actual: 3,
+        }));
+        assert_eq!(expected, read_raw_header(&path));
+        let reader = Reader::from_path(&path).unwrap();
+        assert_eq!(1, reader.header().evlrs().len());
+    }
+
+    #[test]
+    fn start_of_first_evlr_after_many_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evlr.las");
+        write_with_evlr(&path, 200_000);
+        let expected = read_raw_header(&path);
+        patch(&path, |raw_header| {
+            raw_header.evlr.as_mut().unwrap().start_of_first_evlr = 12;
+        });
+        let discrepancies = repair(&path).unwrap();
+        assert_eq!(
+            vec![Discrepancy::StartOfFirstEvlr {
+                header: 12,
+                actual: expected.evlr.unwrap().start_of_first_evlr,
+            }],
+            discrepancies
+        );
+        assert_eq!(expected, read_raw_header(&path));
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.laz");
+        let expected = read_raw_header(&path);
+        patch(&path, |raw_header| raw_header.min_z = -1.);
+        assert_eq!(1, repair(&path).unwrap().len());
+        assert_eq!(expected, read_raw_header(&path));
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn failed_read_leaves_file_untouched() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.laz");
+        patch(&path, |raw_header| {
+            raw_header.offset_to_point_data = u32::from(raw_header.header_size)
+        });
+        let patched = read_raw_header(&path);
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len / 2).unwrap();
+        assert!(repair(&path).is_err());
+        assert_eq!(patched, read_raw_header(&path));
+    }
+}
@@ -0,0 +1,711 @@
+//! Transform point coordinates between coordinate reference systems.
+//!
+//! A [CoordinateTransform] changes x, y and z values in place. Apply one to
+//! decoded points with [PointData::transform_coordinates](crate::PointData::transform_coordinates),
+//! or while writing with [Writer::with_coordinate_transform](crate::Writer::with_coordinate_transform).
+//!
+//! These pure-Rust transforms are built in, so no PROJ install is needed:
+//!
+//! - [Affine], a general linear transform plus a translation
+//! - [Geocentric], between geographic and geocentric cartesian coordinates
+//! - [Helmert], a seven-parameter similarity transform between geocentric frames
+//! - [Utm], between geographic coordinates and Universal Transverse Mercator
+//!
+//! Transforms can be chained with a [Pipeline], e.g. to apply a [Helmert]
+//! datum shift to geographic coordinates by going through [Geocentric]
+//! coordinates.
+//!
+//! ```
+//! use las::reproject::{CoordinateTransform, Utm};
+//! let utm = Utm::from_geographic(31, false).unwrap();
+//! let (mut x, mut y, mut z) = ([3.], [0.], [10.]);
+//! utm.transform(&mut x, &mut y, &mut z).unwrap();
+//! assert_eq!([500000.], x);
+//! assert_eq!([0.], y);
+//! ```
+
+use crate::{Error, Result};
+use std::fmt::Debug;
+
+const WGS84_SEMI_MAJOR_AXIS: f64 = 6378137.;
+const WGS84_INVERSE_FLATTENING: f64 = 298.257223563;
+const UTM_SCALE_FACTOR: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500000.;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10000000.;
+const ARC_SECONDS_TO_RADIANS: f64 = std::f64::consts::PI / (180. * 3600.);
+// Points on or near the Earth's surface are between these distances from its centre
+const MIN_GEOCENTRIC_RADIUS: f64 = 6_000_000.;
+const MAX_GEOCENTRIC_RADIUS: f64 = 7_000_000.;
+
+/// Transforms coordinates in place.
+///
+/// Geographic coordinates are x = longitude and y = latitude, in degrees, as
+/// in las files.
+pub trait CoordinateTransform: Debug + Send + Sync {
+    /// Transforms these coordinates in place.
+    ///
+    /// The slices have the same length.
+    fn transform(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) -> Result<()>;
+}
+
+/// An affine transform, i.e. a 3x3 linear transform followed by a translation.
+///
+/// # Examples
+///
+/// ```
+/// use las::reproject::{Affine, CoordinateTransform};
+/// let affine = Affine::translation(1., 2., 3.);
+/// let (mut x, mut y, mut z) = ([1.], [1.], [1.]);
+/// affine.transform(&mut x, &mut y, &mut z).unwrap();
+/// assert_eq!(([2.], [3.], [4.]), (x, y, z));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    /// The rows of the matrix, where the last column is the translation.
+    pub matrix: [[f64; 4]; 3],
+}
+
+/// The rotation convention of a [Helmert] transform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationConvention {
+    /// Position vector rotation (EPSG method 1033).
+    #[default]
+    PositionVector,
+
+    /// Coordinate frame rotation (EPSG method 1032), which has rotations of the opposite sign.
+    CoordinateFrame,
+}
+
+/// A seven-parameter Helmert transform between geocentric cartesian coordinates.
+///
+/// Uses the small-angle approximation, as published transform parameters do.
+/// The input must be geocentric, so geographic or projected coordinates have
+/// to go through [Geocentric] first (see [Pipeline]). Coordinates that aren't
+/// anywhere near the Earth's surface are rejected with
+/// [Error::NotGeocentric].
+///
+/// # Examples
+///
+/// ```
+/// use las::reproject::{CoordinateTransform, Helmert};
+/// let helmert = Helmert {
+///     tx: 1.,
+///     scale: 1.,
+///     ..Default::default()
+/// };
+/// let (mut x, mut y, mut z) = ([6_378_137.], [0.], [0.]);
+/// helmert.transform(&mut x, &mut y, &mut z).unwrap();
+/// assert!((x[0] - 6_378_144.378137).abs() < 1e-6);
+///
+/// let (mut x, mut y, mut z) = ([-123.], [44.], [100.]);
+/// assert!(helmert.transform(&mut x, &mut y, &mut z).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Helmert {
+    /// Translation along x, in metres.
+    pub tx: f64,
+
+    /// Translation along y, in metres.
+    pub ty: f64,
+
+    /// Translation along z, in metres.
+    pub tz: f64,
+
+    /// Rotation about x, in arc-seconds.
+    pub rx: f64,
+
+    /// Rotation about y, in arc-seconds.
+    pub ry: f64,
+
+    /// Rotation about z, in arc-seconds.
+    pub rz: f64,
+
+    /// Scale difference, in parts per million.
+    pub scale: f64,
+
+    /// How to interpret the rotations.
+    pub convention: RotationConvention,
+}
+
+/// A conversion between geographic and geocentric cartesian coordinates, in either direction.
+///
+/// Geographic heights are above the ellipsoid, which defaults to WGS 84.
+///
+/// # Examples
+///
+/// ```
+/// use las::reproject::{CoordinateTransform, Geocentric};
+/// let geocentric = Geocentric::from_geographic();
+/// let (mut x, mut y, mut z) = ([0.], [0.], [0.]);
+/// geocentric.transform(&mut x, &mut y, &mut z).unwrap();
+/// assert_eq!(([6_378_137.], [0.], [0.]), (x, y, z));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geocentric {
+    inverse: bool,
+    semi_major_axis: f64,
+    inverse_flattening: f64,
+}
+
+/// A sequence of transforms, applied in order.
+///
+/// # Examples
+///
+/// ```
+/// use las::reproject::{CoordinateTransform, Geocentric, Helmert, Pipeline};
+/// // A datum shift between geographic coordinates
+/// let pipeline = Pipeline::new()
+///     .then(Geocentric::from_geographic())
+///     .then(Helmert { tz: 1., ..Default::default() })
+///     .then(Geocentric::to_geographic());
+/// let (mut x, mut y, mut z) = ([-123.], [90.], [0.]);
+/// pipeline.transform(&mut x, &mut y, &mut z).unwrap();
+/// assert!((z[0] - 1.).abs() < 1e-6);
+/// ```
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn CoordinateTransform>>,
+}
+
+/// A Universal Transverse Mercator projection, in either direction.
+///
+/// Uses Krüger's series to fourth order, which is accurate to well under a
+/// millimetre within a zone. The ellipsoid defaults to WGS 84.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Utm {
+    zone: u8,
+    south: bool,
+    inverse: bool,
+    semi_major_axis: f64,
+    inverse_flattening: f64,
+}
+
+impl Affine {
+    /// Creates an affine transform from its matrix rows.
+    pub fn new(matrix: [[f64; 4]; 3]) -> Affine {
+        Affine { matrix }
+    }
+
+    /// Creates an affine transform that only translates.
+    pub fn translation(x: f64, y: f64, z: f64) -> Affine {
+        Affine::new([[1., 0., 0., x], [0., 1., 0., y], [0., 0., 1., z]])
+    }
+
+    /// Creates an affine transform that only scales.
+    pub fn scale(x: f64, y: f64, z: f64) -> Affine {
+        Affine::new([[x, 0., 0., 0.], [0., y, 0., 0.], [0., 0., z, 0.]])
+    }
+}
+
+impl CoordinateTransform for Affine {
+    fn transform(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) -> Result<()> {
+        let m = &self.matrix;
+        for ((x, y), z) in x.iter_mut().zip(y.iter_mut()).zip(z.iter_mut()) {
+            let (x0, y0, z0) = (*x, *y, *z);
+            *x = m[0][0] * x0 + m[0][1] * y0 + m[0][2] * z0 + m[0][3];
+            *y = m[1][0] * x0 + m[1][1] * y0 + m[1][2] * z0 + m[1][3];
+            *z = m[2][0] * x0 + m[2][1] * y0 + m[2][2] * z0 + m[2][3];
+        }
+        Ok(())
+    }
+}
+
+impl Helmert {
+    /// Returns the approximate inverse, with every parameter negated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Helmert;
+    /// let helmert = Helmert { tx: 1., ..Default::default() };
+    /// assert_eq!(-1., helmert.inverse().tx);
+    /// ```
+    pub fn inverse(&self) -> Helmert {
+        Helmert {
+            tx: -self.tx,
+            ty: -self.ty,
+            tz: -self.tz,
+            rx: -self.rx,
+            ry: -self.ry,
+            rz: -self.rz,
+            scale: -self.scale,
+            convention: self.convention,
+        }
+    }
+
+    fn affine(&self) -> Affine {
+        let sign = match self.convention {
+            RotationConvention::PositionVector => 1.,
+            RotationConvention::CoordinateFrame => -1.,
+        };
+        let rx = sign * self.rx * ARC_SECONDS_TO_RADIANS;
+        let ry = sign * self.ry * ARC_SECONDS_TO_RADIANS;
+        let rz = sign * self.rz * ARC_SECONDS_TO_RADIANS;
+        let s = 1. + self.scale * 1e-6;
+        Affine::new([
+            [s, -s * rz, s * ry, self.tx],
+            [s * rz, s, -s * rx, self.ty],
+            [-s * ry, s * rx, s, self.tz],
+        ])
+    }
+}
+
+impl CoordinateTransform for Helmert {
+    fn transform(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) -> Result<()> {
+        for ((&x, &y), &z) in x.iter().zip(y.iter()).zip(z.iter()) {
+            let radius = (x * x + y * y + z * z).sqrt();
+            if !(MIN_GEOCENTRIC_RADIUS..=MAX_GEOCENTRIC_RADIUS).contains(&radius) {
+                return Err(Error::NotGeocentric(x, y, z));
+            }
+        }
+        self.affine().transform(x, y, z)
+    }
+}
+
+impl Geocentric {
+    /// Creates a transform from geographic to geocentric coordinates.
+    pub fn from_geographic() -> Geocentric {
+        Geocentric {
+            inverse: false,
+            semi_major_axis: WGS84_SEMI_MAJOR_AXIS,
+            inverse_flattening: WGS84_INVERSE_FLATTENING,
+        }
+    }
+
+    /// Creates a transform from geocentric to geographic coordinates.
+    pub fn to_geographic() -> Geocentric {
+        Geocentric::from_geographic().inverse()
+    }
+
+    /// Uses another ellipsoid, e.g. GRS 80.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Geocentric;
+    /// let geocentric = Geocentric::from_geographic().with_ellipsoid(6378137., 298.257222101);
+    /// ```
+    pub fn with_ellipsoid(mut self, semi_major_axis: f64, inverse_flattening: f64) -> Geocentric {
+        self.semi_major_axis = semi_major_axis;
+        self.inverse_flattening = inverse_flattening;
+        self
+    }
+
+    /// Returns the transform in the other direction.
+    pub fn inverse(mut self) -> Geocentric {
+        self.inverse = !self.inverse;
+        self
+    }
+}
+
+impl CoordinateTransform for Geocentric {
+    fn transform(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) -> Result<()> {
+        let a = self.semi_major_axis;
+        let f = 1. / self.inverse_flattening;
+        let e2 = f * (2. - f);
+        let prime_vertical = |latitude: f64| a / (1. - e2 * latitude.sin().powi(2)).sqrt();
+        for ((x, y), z) in x.iter_mut().zip(y.iter_mut()).zip(z.iter_mut()) {
+            if self.inverse {
+                let p = x.hypot(*y);
+                let longitude = y.atan2(*x);
+                let mut latitude = z.atan2(p * (1. - e2));
+                for _ in 0..10 {
+                    let n = prime_vertical(latitude);
+                    let next = (*z + e2 * n * latitude.sin()).atan2(p);
+                    let delta = next - latitude;
+                    latitude = next;
+                    if delta.abs() < 1e-14 {
+                        break;
+                    }
+                }
+                let n = prime_vertical(latitude);
+                let height = p * latitude.cos() + *z * latitude.sin() - a * a / n;
+                *x = longitude.to_degrees();
+                *y = latitude.to_degrees();
+                *z = height;
+            } else {
+                let (longitude, latitude) = (x.to_radians(), y.to_radians());
+                let n = prime_vertical(latitude);
+                let r = (n + *z) * latitude.cos();
+                *x = r * longitude.cos();
+                *y = r * longitude.sin();
+                *z = (n * (1. - e2) + *z) * latitude.sin();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline, which leaves coordinates as they are.
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Adds a transform to the end of the pipeline.
+    pub fn then<T: CoordinateTransform + 'static>(mut self, transform: T) -> Pipeline {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+}
+
+impl CoordinateTransform for Pipeline {
+    fn transform(&self, x: &mut [f64], y: &mut [f64], z: &mut [f64]) -> Result<()> {
+        for transform in &self.transforms {
+            transform.transform(x, y, z)?;
+        }
+        Ok(())
+    }
+}
+
+impl Utm {
+    /// Creates a transform from geographic coordinates to this UTM zone.
+    ///
+    /// Returns an error if the zone isn't between 1 and 60.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Utm;
+    /// let utm = Utm::from_geographic(10, false).unwrap();
+    /// assert!(Utm::from_geographic(61, false).is_err());
+    /// ```
+    pub fn from_geographic(zone: u8, south: bool) -> Result<Utm> {
+        if !(1..=60).contains(&zone) {
+            return Err(Error::InvalidUtmZone(zone));
+        }
+        Ok(Utm {
+            zone,
+            south,
+            inverse: false,
+            semi_major_axis: WGS84_SEMI_MAJOR_AXIS,
+            inverse_flattening: WGS84_INVERSE_FLATTENING,
+        })
+    }
+
+    /// Creates a transform from this UTM zone to geographic coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Utm;
+    /// let utm = Utm::to_geographic(32, false).unwrap();
+    /// ```
+    pub fn to_geographic(zone: u8, south: bool) -> Result<Utm> {
+        Utm::from_geographic(zone, south).map(|utm| utm.inverse())
+    }
+
+    /// Returns the zone whose central meridian is closest to this longitude, in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Utm;
+    /// assert_eq!(10, Utm::zone(-123.));
+    /// assert_eq!(60, Utm::zone(177.));
+    /// ```
+    pub fn zone(longitude: f64) -> u8 {
+        let zone = ((longitude + 180.) / 6.).floor() as i64 % 60 + 1;
+        zone.clamp(1, 60) as u8
+    }
+
+    /// Uses another ellipsoid, e.g. Clarke 1866 for NAD27.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::Utm;
+    /// let utm = Utm::from_geographic(10, false)
+    ///     .unwrap()
+    ///     .with_ellipsoid(6378206.4, 294.978698213898);
+    /// ```
+    pub fn with_ellipsoid(mut self, semi_major_axis: f64, inverse_flattening: f64) -> Utm {
+        self.semi_major_axis = semi_major_axis;
+        self.inverse_flattening = inverse_flattening;
+        self
+    }
+
+    /// Returns the transform in the other direction.
+    pub fn inverse(mut self) -> Utm {
+        self.inverse = !self.inverse;
+        self
+    }
+
+    fn central_meridian(&self) -> f64 {
+        (f64::from(self.zone) * 6. - 183.).to_radians()
+    }
+
+    fn false_northing(&self) -> f64 {
+        if self.south {
+            UTM_FALSE_NORTHING_SOUTH
+        } else {
+            0.
+        }
+    }
+}
+
+impl CoordinateTransform for Utm {
+    fn transform(&self, x: &mut [f64], y: &mut [f64], _: &mut [f64]) -> Result<()> {
+        let series = Series::new(self.semi_major_axis, self.inverse_flattening);
+        let scale = UTM_SCALE_FACTOR * series.rectifying_radius;
+        let central_meridian = self.central_meridian();
+        let false_northing = self.false_northing();
+        for (x, y) in x.iter_mut().zip(y.iter_mut()) {
+            if self.inverse {
+                let (longitude, latitude) = series.inverse(
+                    (*x - UTM_FALSE_EASTING) / scale,
+                    (*y - false_northing) / scale,
+                );
+                *x = (longitude + central_meridian).to_degrees();
+                *y = latitude.to_degrees();
+            } else {
+                let (eta, xi) = series.forward(x.to_radians() - central_meridian, y.to_radians());
+                *x = UTM_FALSE_EASTING + scale * eta;
+                *y = false_northing + scale * xi;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Krüger's series coefficients for one ellipsoid.
+struct Series {
+    eccentricity: f64,
+    rectifying_radius: f64,
+    alpha: [f64; 4],
+    beta: [f64; 4],
+}
+
+impl Series {
+    fn new(semi_major_axis: f64, inverse_flattening: f64) -> Series {
+        let f = 1. / inverse_flattening;
+        let n = f / (2. - f);
+        let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+        Series {
+            eccentricity: (f * (2. - f)).sqrt(),
+            rectifying_radius: semi_major_axis / (1. + n) * (1. + n2 / 4. + n4 / 64.),
+            alpha: [
+                n / 2. - 2. * n2 / 3. + 5. * n3 / 16. + 41. * n4 / 180.,
+                13. * n2 / 48. - 3. * n3 / 5. + 557. * n4 / 1440.,
+                61. * n3 / 240. - 103. * n4 / 140.,
+                49561. * n4 / 161280.,
+            ],
+            beta: [
+                n / 2. - 2. * n2 / 3. + 37. * n3 / 96. - n4 / 360.,
+                n2 / 48. + n3 / 15. - 437. * n4 / 1440.,
+                17. * n3 / 480. - 37. * n4 / 840.,
+                4397. * n4 / 161280.,
+            ],
+        }
+    }
+
+    /// Returns the normalized easting and northing for a longitude relative to the central meridian and a latitude, in radians.
+    fn forward(&self, longitude: f64, latitude: f64) -> (f64, f64) {
+        let e = self.eccentricity;
+        let t = (latitude.sin().atanh() - e * (e * latitude.sin()).atanh()).sinh();
+        let xi_prime = t.atan2(longitude.cos());
+        let eta_prime = (longitude.sin() / (1. + t * t).sqrt()).atanh();
+        let mut xi = xi_prime;
+        let mut eta = eta_prime;
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2. * (j + 1) as f64;
+            xi += alpha * (k * xi_prime).sin() * (k * eta_prime).cosh();
+            eta += alpha * (k * xi_prime).cos() * (k * eta_prime).sinh();
+        }
+        (eta, xi)
+    }
+
+    /// Returns the longitude relative to the central meridian and the latitude, in radians, for a normalized easting and northing.
+    fn inverse(&self, eta: f64, xi: f64) -> (f64, f64) {
+        let mut xi_prime = xi;
+        let mut eta_prime = eta;
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2. * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let longitude = eta_prime.sinh().atan2(xi_prime.cos());
+        let conformal_tau =
+            xi_prime.sin() / (eta_prime.sinh().powi(2) + xi_prime.cos().powi(2)).sqrt();
+        (longitude, self.latitude(conformal_tau))
+    }
+
+    /// Converts the tangent of the conformal latitude to the geodetic latitude with Newton's method.
+    fn latitude(&self, conformal_tau: f64) -> f64 {
+        let e = self.eccentricity;
+        let e2m = 1. - e * e;
+        let mut tau = conformal_tau;
+        for _ in 0..10 {
+            let tau1 = (1. + tau * tau).sqrt();
+            let sigma = (e * (e * tau / tau1).atanh()).sinh();
+            let tau_i = tau * (1. + sigma * sigma).sqrt() - sigma * tau1;
+            let delta = (conformal_tau - tau_i) / (1. + tau_i * tau_i).sqrt()
+                * (1. + e2m * tau * tau)
+                / (e2m * tau1);
+            tau += delta;
+            if delta.abs() < 1e-14 {
+                break;
+            }
+        }
+        tau.atan()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(transform: &dyn CoordinateTransform, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let (mut xs, mut ys, mut zs) = ([x], [y], [z]);
+        transform.transform(&mut xs, &mut ys, &mut zs).unwrap();
+        (xs[0], ys[0], zs[0])
+    }
+
+    #[test]
+    fn utm_forward() {
+        let utm = Utm::from_geographic(31, false).unwrap();
+        let (x, y, z) = transform(&utm, 0., 0., 42.);
+        assert!((x - 166021.443).abs() < 1e-3, "{x}");
+        assert!(y.abs() < 1e-9);
+        assert_eq!(42., z);
+
+        let utm = Utm::from_geographic(31, true).unwrap();
+        let (x, y, _) = transform(&utm, 3., -45., 0.);
+        assert!((x - 500000.).abs() < 1e-6);
+        assert!((y - 5017049.6).abs() < 0.1, "{y}");
+    }
+
+    #[test]
+    fn utm_roundtrip() {
+        let forward = Utm::from_geographic(32, false).unwrap();
+        let inverse = Utm::to_geographic(32, false).unwrap();
+        assert_eq!(inverse, forward.inverse());
+        for (longitude, latitude) in [(9., 60.), (5.5, 58.9), (12., 71.), (8.1, 0.1)] {
+            let (x, y, _) = transform(&forward, longitude, latitude, 0.);
+            let (lon, lat, _) = transform(&inverse, x, y, 0.);
+            assert!((lon - longitude).abs() < 1e-10, "{lon} {longitude}");
+            assert!((lat - latitude).abs() < 1e-10, "{lat} {latitude}");
+        }
+    }
+
+    #[test]
+    fn utm_zone() {
+        assert_eq!(1, Utm::zone(-180.));
+        assert_eq!(31, Utm::zone(0.));
+        assert_eq!(32, Utm::zone(9.));
+        assert!(matches!(
+            Utm::to_geographic(0, false),
+            Err(Error::InvalidUtmZone(0))
+        ));
+    }
+
+    #[test]
+    fn helmert() {
+        let helmert = Helmert {
+            rz: 1.,
+            ..Default::default()
+        };
+        let (x, y, _) = transform(&helmert, 6378137., 0., 0.);
+        assert!((y - 6378137. * ARC_SECONDS_TO_RADIANS).abs() < 1e-9);
+        assert_eq!(6378137., x);
+
+        let helmert = Helmert {
+            convention: RotationConvention::CoordinateFrame,
+            ..helmert
+        };
+        let (_, y, _) = transform(&helmert, 6378137., 0., 0.);
+        assert!(y < 0.);
+
+        let helmert = Helmert {
+            tx: 10.,
+            ty: -5.,
+            rx: 0.1,
+            scale: 2.,
+            ..Default::default()
+        };
+        let (x, y, z) = transform(&helmert, 4000000., 500000., 4900000.);
+        let (x, y, z) = transform(&helmert.inverse(), x, y, z);
+        assert!((x - 4000000.).abs() < 1e-3);
+        assert!((y - 500000.).abs() < 1e-3);
+        assert!((z - 4900000.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn helmert_rejects_non_geocentric() {
+        let helmert = Helmert::default();
+        let (mut x, mut y, mut z) = ([6378137., 500000.], [0., 5000000.], [0., 100.]);
+        assert!(matches!(
+            helmert.transform(&mut x, &mut y, &mut z),
+            Err(Error::NotGeocentric(500000., 5000000., 100.))
+        ));
+        // Nothing is transformed if any point is rejected
+        assert_eq!([6378137., 500000.], x);
+    }
+
+    #[test]
+    fn geocentric() {
+        let forward = Geocentric::from_geographic();
+        let (x, y, z) = transform(&forward, 90., 0., 10.);
+        assert!(x.abs() < 1e-6);
+        assert!((y - 6378147.).abs() < 1e-6);
+        assert_eq!(0., z);
+        let (_, _, z) = transform(&forward, 0., 90., 0.);
+        assert!((z - 6356752.314245).abs() < 1e-6, "{z}");
+    }
+
+    #[test]
+    fn geocentric_roundtrip() {
+        let forward = Geocentric::from_geographic();
+        let inverse = Geocentric::to_geographic();
+        assert_eq!(inverse, forward.inverse());
+        for (longitude, latitude, height) in [
+            (-123.07, 44.05, 130.),
+            (151.2, -33.9, -20.),
+            (0., 89.9999, 3000.),
+            (179.9, -89.5, 0.),
+        ] {
+            let (x, y, z) = transform(&forward, longitude, latitude, height);
+            let (lon, lat, h) = transform(&inverse, x, y, z);
+            assert!((lon - longitude).abs() < 1e-10, "{lon} {longitude}");
+            assert!((lat - latitude).abs() < 1e-10, "{lat} {latitude}");
+            assert!((h - height).abs() < 1e-6, "{h} {height}");
+        }
+    }
+
+    #[test]
+    fn pipeline() {
+        let helmert = Helmert {
+            tx: 10.,
+            ty: -5.,
+            rz: 0.1,
+            scale: 2.,
+            ..Default::default()
+        };
+        let shift = Pipeline::new()
+            .then(Geocentric::from_geographic())
+            .then(helmert)
+            .then(Geocentric::to_geographic());
+        let (longitude, latitude, height) = (-123.07, 44.05, 130.);
+        let (x, y, z) = transform(&shift, longitude, latitude, height);
+        assert!((x - longitude).abs() > 1e-7);
+        let back = Pipeline::new()
+            .then(Geocentric::from_geographic())
+            .then(helmert.inverse())
+            .then(Geocentric::to_geographic());
+        let (x, y, z) = transform(&back, x, y, z);
+        assert!((x - longitude).abs() < 1e-9, "{x}");
+        assert!((y - latitude).abs() < 1e-9, "{y}");
+        assert!((z - height).abs() < 1e-3, "{z}");
+        assert_eq!((1., 2., 3.), transform(&Pipeline::new(), 1., 2., 3.));
+    }
+
+    #[test]
+    fn affine() {
+        let affine = Affine::new([[0., -1., 0., 0.], [1., 0., 0., 0.], [0., 0., 2., 1.]]);
+        assert_eq!((-2., 1., 7.), transform(&affine, 1., 2., 3.));
+        assert_eq!(
+            (2., 4., 6.),
+            transform(&Affine::scale(2., 2., 2.), 1., 2., 3.)
+        );
+    }
+}
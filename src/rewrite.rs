@@ -0,0 +1,367 @@
+//! Rewrite a file's metadata without rewriting its points.
+//!
+//! [rewrite_metadata] replaces the header, VLRs and EVLRs of a file with those
+//! of a [Header], e.g. one read from the file and then edited with
+//! [Header::vlrs_mut] and the header setters.
+
+use crate::{raw, Error, Header, Result, Vlr};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const COPY_BUFFER_LEN: usize = 1 << 20;
+const LASZIP_USER_ID: &str = "laszip encoded";
+const LASZIP_RECORD_ID: u16 = 22204;
+const COPC_USER_ID: &str = "copc";
+const COPC_INFO_RECORD_ID: u16 = 1;
+
+/// Rewrites a file's header, VLRs and EVLRs in place.
+///
+/// The point data isn't decoded, so the fields that describe it — the point
+/// format, record length, point counts, scales, offsets and bounds — are kept
+/// from the file. Everything else comes from `header`, whose version must
+/// have the same header layout as the file's.
+///
+/// Point data can't be decoded without the laszip VLR of a compressed file or
+/// the extra bytes VLR of a file with extra bytes, so if `header` doesn't
+/// have them they're carried over from the file.
+///
+/// If the new header and VLRs fit before the point data they're padded to
+/// the existing offset to point data. Otherwise the point data is shifted
+/// towards the end of the file, and the laz chunk table offset and internal
+/// waveform data offset are updated to match. EVLRs are written after the point data and the file is
+/// truncated after them.
+///
+/// COPC files are rejected, because their info VLR and hierarchy hold
+/// absolute offsets to the point data and hierarchy pages.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Reader, Vlr};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("autzen.las");
+/// std::fs::copy("tests/data/autzen.las", &path).unwrap();
+/// let mut header = Reader::from_path(&path).unwrap().header().clone();
+/// header.set_system_identifier("MODIFICATION").unwrap();
+/// header.vlrs_mut().push(Vlr::default());
+/// las::rewrite_metadata(&path, &header).unwrap();
+///
+/// let mut reader = Reader::from_path(&path).unwrap();
+/// assert_eq!("MODIFICATION", reader.header().system_identifier());
+/// assert_eq!(header.vlrs().len(), reader.header().vlrs().len());
+/// assert_eq!(106, reader.read_all().unwrap().len());
+/// ```
+pub fn rewrite_metadata<P: AsRef<Path>>(path: P, header: &Header) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+    let old = raw::Header::read_from(&mut file)?;
+    if old.version.header_size() != header.version().header_size() {
+        return Err(Error::VersionMismatch {
+            file: old.version,
+            header: header.version(),
+        });
+    }
+    let old_offset = u64::from(old.offset_to_point_data);
+    let end_of_point_data = match old.evlr {
+        Some(evlr) if evlr.number_of_evlrs > 0 => evlr.start_of_first_evlr,
+        _ => file_len,
+    };
+    if end_of_point_data < old_offset || end_of_point_data > file_len {
+        return Err(Error::OffsetToEvlrsTooSmall(end_of_point_data));
+    }
+
+    let old_vlrs = read_vlrs(&mut file, &old)?;
+    if old_vlrs
+        .iter()
+        .any(|vlr| vlr.user_id == COPC_USER_ID && vlr.record_id == COPC_INFO_RECORD_ID)
+    {
+        return Err(Error::CopcMetadataRewrite);
+    }
+    let header = &with_required_vlrs(old_vlrs, header);
+    let mut new = header.clone().into_raw()?;
+    new.point_data_record_format = old.point_data_record_format;
+    new.point_data_record_length = old.point_data_record_length;
+    new.number_of_point_records = old.number_of_point_records;
+    new.number_of_points_by_return = old.number_of_points_by_return;
+    new.large_file = old.large_file;
+    new.x_scale_factor = old.x_scale_factor;
+    new.y_scale_factor = old.y_scale_factor;
+    new.z_scale_factor = old.z_scale_factor;
+    new.x_offset = old.x_offset;
+    new.y_offset = old.y_offset;
+    new.z_offset = old.z_offset;
+    new.min_x = old.min_x;
+    new.min_y = old.min_y;
+    new.min_z = old.min_z;
+    new.max_x = old.max_x;
+    new.max_y = old.max_y;
+    new.max_z = old.max_z;
+    new.start_of_waveform_data_packet_record = old.start_of_waveform_data_packet_record;
+
+    let new_offset = u64::from(new.offset_to_point_data);
+    let shift = new_offset.saturating_sub(old_offset);
+    new.offset_to_point_data = old.offset_to_point_data.max(new.offset_to_point_data);
+    if let Some(evlr) = new.evlr.as_mut() {
+        evlr.start_of_first_evlr = end_of_point_data + shift;
+    }
+    if let Some(start) = new.start_of_waveform_data_packet_record.as_mut()
+        && *start >= old_offset
+    {
+        *start += shift;
+    }
+
+    if shift > 0 {
+        shift_forward(&mut file, old_offset, end_of_point_data, shift)?;
+        if old.point_data_record_format & 0x80 != 0 {
+            update_chunk_table_offset(&mut file, old_offset + shift, shift)?;
+        }
+    }
+
+    let _ = file.seek(SeekFrom::Start(0))?;
+    let mut write = BufWriter::new(&mut file);
+    new.write_to(&mut write)?;
+    let mut position = u64::from(new.header_size);
+    for vlr in header.vlrs() {
+        position += vlr.len(false) as u64;
+        vlr.clone().into_raw(false)?.write_to(&mut write)?;
+    }
+    let padding = u64::from(new.offset_to_point_data) - position;
+    if padding > 0 {
+        let mut vlr_padding = header.vlr_padding().clone();
+        vlr_padding.resize(padding as usize, 0);
+        write.write_all(&vlr_padding)?;
+    }
+    let _ = write.seek(SeekFrom::Start(end_of_point_data + shift))?;
+    for evlr in header.evlrs() {
+        evlr.clone().into_raw(true)?.write_to(&mut write)?;
+    }
+    let end = write.stream_position()?;
+    let file = write.into_inner().map_err(|err| err.into_error())?;
+    file.set_len(end)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Reads the file's VLRs.
+fn read_vlrs(file: &mut File, old: &raw::Header) -> Result<Vec<Vlr>> {
+    let _ = file.seek(SeekFrom::Start(u64::from(old.header_size)))?;
+    let mut read = BufReader::new(file);
+    (0..old.number_of_variable_length_records)
+        .map(|_| raw::Vlr::read_from(&mut read, false).map(Vlr::new))
+        .collect()
+}
+
+/// Returns `header` with the file's laszip and extra bytes VLRs added, if it doesn't have its own.
+fn with_required_vlrs(old_vlrs: Vec<Vlr>, header: &Header) -> Header {
+    let mut header = header.clone();
+    for vlr in old_vlrs {
+        let is_laszip = vlr.user_id == LASZIP_USER_ID && vlr.record_id == LASZIP_RECORD_ID;
+        if (is_laszip || vlr.is_extra_bytes())
+            && !header
+                .vlrs()
+                .iter()
+                .any(|other| other.user_id == vlr.user_id && other.record_id == vlr.record_id)
+        {
+            header.vlrs_mut().push(vlr);
+        }
+    }
+    header
+}
+
+/// Moves the bytes in `start..end` to `start + shift..end + shift`, starting from the end.
+fn shift_forward(file: &mut File, start: u64, end: u64, shift: u64) -> Result<()> {
+    let mut buffer = vec![0; COPY_BUFFER_LEN];
+    let mut remaining = end - start;
+    while remaining > 0 {
+        let len = remaining.min(COPY_BUFFER_LEN as u64);
+        let position = start + remaining - len;
+        let buffer = &mut buffer[..len as usize];
+        let _ = file.seek(SeekFrom::Start(position))?;
+        file.read_exact(buffer)?;
+        let _ = file.seek(SeekFrom::Start(position + shift))?;
+        file.write_all(buffer)?;
+        remaining -= len;
+    }
+    Ok(())
+}
+
+/// Laz point data starts with the absolute offset of the chunk table, or -1 if there isn't one.
+fn update_chunk_table_offset(file: &mut File, offset_to_point_data: u64, shift: u64) -> Result<()> {
+    let mut bytes = [0; 8];
+    let _ = file.seek(SeekFrom::Start(offset_to_point_data))?;
+    file.read_exact(&mut bytes)?;
+    let chunk_table_offset = i64::from_le_bytes(bytes);
+    if chunk_table_offset >= 0 {
+        let _ = file.seek(SeekFrom::Start(offset_to_point_data))?;
+        file.write_all(&(chunk_table_offset + shift as i64).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::copy_to_temp_dir, Builder, Point, Reader, Writer};
+
+    fn large_vlr() -> Vlr {
+        Vlr {
+            user_id: "las-rs".to_string(),
+            record_id: 1,
+            description: "test".to_string(),
+            data: vec![42; 10_000],
+        }
+    }
+
+    #[test]
+    fn in_place() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        let len = std::fs::metadata(&path).unwrap().len();
+        let before = Reader::from_path(&path).unwrap().read_all().unwrap();
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().clear();
+        header.set_file_source_id(7);
+        rewrite_metadata(&path, &header).unwrap();
+        assert_eq!(len, std::fs::metadata(&path).unwrap().len());
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(7, reader.header().file_source_id());
+        assert!(reader.header().vlrs().is_empty());
+        assert_eq!(before.raw_bytes(), reader.read_all().unwrap().raw_bytes());
+    }
+
+    #[test]
+    fn shift() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        let before = Reader::from_path(&path).unwrap().read_all().unwrap();
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().push(large_vlr());
+        rewrite_metadata(&path, &header).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(Some(&large_vlr()), reader.header().vlrs().last());
+        assert_eq!(before.raw_bytes(), reader.read_all().unwrap().raw_bytes());
+    }
+
+    #[test]
+    fn evlrs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evlrs.las");
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr::default());
+        let mut writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+        for i in 0..10 {
+            writer
+                .write_point(Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+        drop(writer);
+
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().push(large_vlr());
+        header.evlrs_mut().push(large_vlr());
+        rewrite_metadata(&path, &header).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(2, reader.header().evlrs().len());
+        assert_eq!(Some(&large_vlr()), reader.header().evlrs().last());
+        let points = reader.read_all().unwrap();
+        assert_eq!(10, points.len());
+        assert_eq!(9., points.x().last().unwrap());
+
+        header.evlrs_mut().clear();
+        rewrite_metadata(&path, &header).unwrap();
+        let reader = Reader::from_path(&path).unwrap();
+        assert!(reader.header().evlrs().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn shift_laz() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/32-1-472-150-76.laz");
+        let before = Reader::from_path(&path).unwrap().read_all().unwrap();
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().push(large_vlr());
+        rewrite_metadata(&path, &header).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(before.raw_bytes(), reader.read_all().unwrap().raw_bytes());
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.las");
+        assert!(matches!(
+            rewrite_metadata(&path, &Header::from((1, 4))),
+            Err(Error::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn rejects_copc() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/autzen.copc.laz");
+        let before = std::fs::read(&path).unwrap();
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.set_system_identifier("MODIFICATION").unwrap();
+        assert!(matches!(
+            rewrite_metadata(&path, &header),
+            Err(Error::CopcMetadataRewrite)
+        ));
+        assert_eq!(before, std::fs::read(&path).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn keeps_laszip_vlr() {
+        let (_dir, path) = copy_to_temp_dir("tests/data/32-1-472-150-76.laz");
+        let before = Reader::from_path(&path).unwrap().read_all().unwrap();
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().clear();
+        header.vlrs_mut().push(large_vlr());
+        rewrite_metadata(&path, &header).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert!(reader
+            .header()
+            .vlrs()
+            .iter()
+            .any(|vlr| vlr.user_id == LASZIP_USER_ID && vlr.record_id == LASZIP_RECORD_ID));
+        assert_eq!(before.raw_bytes(), reader.read_all().unwrap().raw_bytes());
+    }
+
+    #[test]
+    fn shifts_internal_waveform_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("waveform.las");
+        let mut writer =
+            Writer::from_path(&path, Builder::from((1, 3)).into_header().unwrap()).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        writer.close().unwrap();
+        drop(writer);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        let mut raw_header = raw::Header::read_from(&mut file).unwrap();
+        let old_offset = u64::from(raw_header.offset_to_point_data);
+        raw_header.start_of_waveform_data_packet_record = Some(old_offset + 20);
+        let _ = file.seek(SeekFrom::Start(0)).unwrap();
+        raw_header.write_to(&mut file).unwrap();
+        drop(file);
+
+        let mut header = Reader::from_path(&path).unwrap().header().clone();
+        header.vlrs_mut().push(large_vlr());
+        rewrite_metadata(&path, &header).unwrap();
+        let raw_header = raw::Header::read_from(File::open(&path).unwrap()).unwrap();
+        let shift = u64::from(raw_header.offset_to_point_data) - old_offset;
+        assert!(shift > 0);
+        assert_eq!(
+            Some(old_offset + 20 + shift),
+            raw_header.start_of_waveform_data_packet_record
+        );
+    }
+}
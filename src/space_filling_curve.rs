@@ -0,0 +1,119 @@
+/// A curve that orders points so that points close together in space are close together in the order.
+///
+/// Sorting points along a curve before writing them makes laz compress
+/// better, and means each laz chunk covers a small area, so queries by
+/// bounding box only have to decompress a few chunks.
+///
+/// Both curves are computed on the raw x and y values, so the order is
+/// the same for every [PointData](crate::PointData) with the same
+/// transforms.
+///
+/// # Examples
+///
+/// ```
+/// use las::SpaceFillingCurve;
+/// assert!(SpaceFillingCurve::Hilbert.index(0, 0) < SpaceFillingCurve::Hilbert.index(0, 1));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpaceFillingCurve {
+    /// The Hilbert curve, which has better locality than the Morton curve.
+    #[default]
+    Hilbert,
+
+    /// The Morton curve, a.k.a. Z-order, which is a bit faster to compute.
+    Morton,
+}
+
+impl SpaceFillingCurve {
+    /// Returns the position of raw x and y values along this curve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::SpaceFillingCurve;
+    /// let curve = SpaceFillingCurve::Morton;
+    /// assert_eq!(curve.index(0, 0) + 1, curve.index(1, 0));
+    /// ```
+    pub fn index(self, x: i32, y: i32) -> u64 {
+        // Flipping the sign bit keeps the order of negative and positive values
+        let x = (x as u32) ^ 0x8000_0000;
+        let y = (y as u32) ^ 0x8000_0000;
+        match self {
+            SpaceFillingCurve::Hilbert => hilbert(x, y),
+            SpaceFillingCurve::Morton => spread(x) | (spread(y) << 1),
+        }
+    }
+
+    /// Returns the position of a point record along this curve.
+    pub(crate) fn record_index(self, record: &[u8]) -> u64 {
+        let x = i32::from_le_bytes([record[0], record[1], record[2], record[3]]);
+        let y = i32::from_le_bytes([record[4], record[5], record[6], record[7]]);
+        self.index(x, y)
+    }
+}
+
+fn spread(n: u32) -> u64 {
+    let mut n = u64::from(n);
+    n = (n | (n << 16)) & 0x0000_ffff_0000_ffff;
+    n = (n | (n << 8)) & 0x00ff_00ff_00ff_00ff;
+    n = (n | (n << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    n = (n | (n << 2)) & 0x3333_3333_3333_3333;
+    (n | (n << 1)) & 0x5555_5555_5555_5555
+}
+
+fn hilbert(x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (u64::from(x), u64::from(y));
+    let max = u64::from(u32::MAX);
+    let mut d = 0;
+    let mut s = 1u64 << 31;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = max - x;
+                y = max - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn morton() {
+        let curve = SpaceFillingCurve::Morton;
+        assert_eq!(0, curve.index(i32::MIN, i32::MIN));
+        assert_eq!(u64::MAX, curve.index(i32::MAX, i32::MAX));
+        assert!(curve.index(-1, -1) < curve.index(0, 0));
+        assert_eq!(curve.index(0, 0) + 3, curve.index(1, 1));
+    }
+
+    #[test]
+    fn hilbert_visits_neighbors() {
+        let curve = SpaceFillingCurve::Hilbert;
+        let mut cells: Vec<(i32, i32)> = (0..8).flat_map(|x| (0..8).map(move |y| (x, y))).collect();
+        cells.sort_by_key(|&(x, y)| curve.index(x, y));
+        for pair in cells.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(1, (a.0 - b.0).abs() + (a.1 - b.1).abs(), "{a:?} -> {b:?}");
+        }
+    }
+
+    #[test]
+    fn hilbert_is_a_bijection_on_a_grid() {
+        let curve = SpaceFillingCurve::Hilbert;
+        let mut indices: Vec<u64> = (-4..4)
+            .flat_map(|x| (-4..4).map(move |y| curve.index(x, y)))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(64, indices.len());
+    }
+}
@@ -0,0 +1,592 @@
+//! Per-dimension statistics and histograms.
+//!
+//! A [Stats] accumulates [PointData] batches and reports, for every dimension
+//! the point format has, the count, minimum, maximum, mean and standard
+//! deviation. Classification, return number, number of returns, scanner
+//! channel and point source id also get exact histograms. Stats from
+//! different batches or threads can be combined with [Stats::merge].
+//!
+//! ```
+//! use las::{point::Dimension, stats::Stats, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut stats = Stats::default();
+//! stats.add_point_data(&reader.read_all().unwrap());
+//! assert_eq!(106, stats.count());
+//! let z = stats.summary(Dimension::Z).unwrap();
+//! assert!(z.min() <= z.mean() && z.mean() <= z.max());
+//! ```
+
+use crate::{
+    point::{ClassificationScheme, Dimension},
+    PointData, PointRef,
+};
+use std::collections::BTreeMap;
+
+/// Dimensions that get an exact histogram.
+pub const HISTOGRAM_DIMENSIONS: [Dimension; 5] = [
+    Dimension::Classification,
+    Dimension::ReturnNumber,
+    Dimension::NumberOfReturns,
+    Dimension::ScannerChannel,
+    Dimension::PointSourceId,
+];
+
+const SKETCH_CAPACITY: usize = 1024;
+const DIMENSIONS: usize = Dimension::ALL.len();
+
+/// Accumulated statistics for point data.
+///
+/// Everything is indexed by the dimension's position in [Dimension::ALL], and
+/// histograms count by value, so adding a point doesn't touch any maps.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    count: u64,
+    summaries: [Summary; DIMENSIONS],
+    histograms: [Vec<u64>; HISTOGRAM_DIMENSIONS.len()],
+    sketches: Option<Vec<Sketch>>,
+}
+
+/// The count, range, mean and standard deviation of one dimension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    m2: f64,
+}
+
+/// A mergeable quantile sketch.
+///
+/// Values in level `i` stand for `2^i` points. When a level holds more than
+/// [SKETCH_CAPACITY] values it's sorted and every other value is promoted to
+/// the next level.
+#[derive(Clone, Debug, Default)]
+struct Sketch {
+    levels: Vec<Vec<f64>>,
+    compactions: u64,
+}
+
+impl Stats {
+    /// Creates empty stats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Stats;
+    /// let stats = Stats::new();
+    /// assert_eq!(0, stats.count());
+    /// ```
+    pub fn new() -> Stats {
+        Stats::default()
+    }
+
+    /// Also tracks approximate medians.
+    ///
+    /// Medians are estimated from a fixed-size sketch per dimension, so they
+    /// cost memory but not much time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Stats;
+    /// let stats = Stats::new().with_median(true);
+    /// ```
+    pub fn with_median(mut self, median: bool) -> Stats {
+        self.sketches = median.then(|| vec![Sketch::default(); DIMENSIONS]);
+        self
+    }
+
+    /// Adds every point in a [PointData].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{stats::Stats, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut stats = Stats::new();
+    /// stats.add_point_data(&reader.read_points(10).unwrap());
+    /// assert_eq!(10, stats.count());
+    /// ```
+    pub fn add_point_data(&mut self, points: &PointData) {
+        let dimensions: Vec<(usize, Dimension)> = Dimension::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, dimension)| points.format().has_dimension(dimension))
+            .collect();
+        for point in points.views() {
+            self.add(point, dimensions.iter().copied());
+        }
+    }
+
+    /// Adds one point.
+    pub fn add_point(&mut self, point: PointRef<'_>) {
+        self.add(point, Dimension::ALL.into_iter().enumerate());
+    }
+
+    fn add(&mut self, point: PointRef<'_>, dimensions: impl Iterator<Item = (usize, Dimension)>) {
+        self.count += 1;
+        for (i, dimension) in dimensions {
+            let Some(value) = point.value(dimension) else {
+                continue;
+            };
+            self.summaries[i].add(value);
+            if let Some(j) = histogram_index(dimension) {
+                let histogram = &mut self.histograms[j];
+                let value = usize::from(value as u16);
+                if histogram.len() <= value {
+                    histogram.resize(value + 1, 0);
+                }
+                histogram[value] += 1;
+            }
+            if let Some(sketches) = &mut self.sketches {
+                sketches[i].add(value);
+            }
+        }
+    }
+
+    /// Merges another set of stats into these.
+    ///
+    /// Medians are only kept if both sides track them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{stats::Stats, Reader};
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap();
+    /// let (a, b) = points.split_at(50);
+    /// let mut stats = Stats::new();
+    /// stats.add_point_data(&a);
+    /// let mut other = Stats::new();
+    /// other.add_point_data(&b);
+    /// stats.merge(other);
+    /// assert_eq!(106, stats.count());
+    /// ```
+    pub fn merge(&mut self, other: Stats) {
+        self.count += other.count;
+        for (summary, other) in self.summaries.iter_mut().zip(&other.summaries) {
+            summary.merge(other);
+        }
+        for (histogram, other) in self.histograms.iter_mut().zip(other.histograms) {
+            if histogram.len() < other.len() {
+                histogram.resize(other.len(), 0);
+            }
+            for (count, other) in histogram.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+        match (&mut self.sketches, other.sketches) {
+            (Some(sketches), Some(other)) => {
+                for (sketch, other) in sketches.iter_mut().zip(other) {
+                    sketch.merge(other);
+                }
+            }
+            _ => self.sketches = None,
+        }
+    }
+
+    /// Returns the number of points.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the summary of a dimension, or `None` if no point had it.
+    pub fn summary(&self, dimension: Dimension) -> Option<&Summary> {
+        let summary = &self.summaries[index(dimension)];
+        (summary.count > 0).then_some(summary)
+    }
+
+    /// Returns the summaries of every dimension the points had, in point record order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, stats::Stats, Reader};
+    /// let mut stats = Stats::new();
+    /// stats.add_point_data(&Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap());
+    /// let (dimension, _) = stats.summaries().next().unwrap();
+    /// assert_eq!(Dimension::X, dimension);
+    /// ```
+    pub fn summaries(&self) -> impl Iterator<Item = (Dimension, &Summary)> {
+        Dimension::ALL
+            .into_iter()
+            .filter_map(|dimension| self.summary(dimension).map(|summary| (dimension, summary)))
+    }
+
+    /// Returns the exact histogram of a dimension, as value to count.
+    ///
+    /// Only [HISTOGRAM_DIMENSIONS] have histograms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, stats::Stats, Reader};
+    /// let mut stats = Stats::new();
+    /// stats.add_point_data(&Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap());
+    /// let classes = stats.histogram(Dimension::Classification).unwrap();
+    /// assert_eq!(106, classes.values().sum::<u64>());
+    /// assert!(stats.histogram(Dimension::Z).is_none());
+    /// ```
+    pub fn histogram(&self, dimension: Dimension) -> Option<BTreeMap<u16, u64>> {
+        let histogram = &self.histograms[histogram_index(dimension)?];
+        if histogram.is_empty() {
+            return None;
+        }
+        Some(
+            (0..=u16::MAX)
+                .zip(histogram)
+                .filter(|&(_, &count)| count > 0)
+                .map(|(value, &count)| (value, count))
+                .collect(),
+        )
+    }
+
+    /// Returns the classification histogram as code, name and count.
+    ///
+    /// Names come from the scheme, e.g. from [Header::classification_scheme](crate::Header::classification_scheme).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{stats::Stats, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let scheme = reader.header().classification_scheme().unwrap();
+    /// let mut stats = Stats::new();
+    /// stats.add_point_data(&reader.read_all().unwrap());
+    /// for (code, name, count) in stats.classification_histogram(&scheme) {
+    ///     println!("{code} {name}: {count}");
+    /// }
+    /// ```
+    pub fn classification_histogram(
+        &self,
+        scheme: &ClassificationScheme,
+    ) -> Vec<(u8, String, u64)> {
+        self.histogram(Dimension::Classification)
+            .into_iter()
+            .flatten()
+            .filter_map(|(code, count)| {
+                let code = u8::try_from(code).ok()?;
+                Some((code, scheme.label(code), count))
+            })
+            .collect()
+    }
+
+    /// Returns the minimum and maximum GPS time, if the points have GPS time.
+    pub fn gps_time_range(&self) -> Option<(f64, f64)> {
+        self.summary(Dimension::GpsTime)
+            .map(|summary| (summary.min, summary.max))
+    }
+
+    /// Returns the approximate median of a dimension.
+    ///
+    /// Returns `None` if medians aren't tracked (see [Stats::with_median]) or
+    /// no point had the dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, stats::Stats, Reader};
+    /// let mut stats = Stats::new().with_median(true);
+    /// stats.add_point_data(&Reader::from_path("tests/data/autzen.las").unwrap().read_all().unwrap());
+    /// let z = stats.summary(Dimension::Z).unwrap();
+    /// let median = stats.median(Dimension::Z).unwrap();
+    /// assert!(z.min() <= median && median <= z.max());
+    /// ```
+    pub fn median(&self, dimension: Dimension) -> Option<f64> {
+        self.sketches
+            .as_ref()
+            .and_then(|sketches| sketches[index(dimension)].quantile(0.5))
+    }
+}
+
+fn index(dimension: Dimension) -> usize {
+    Dimension::ALL
+        .iter()
+        .position(|&d| d == dimension)
+        .expect("every dimension is in ALL")
+}
+
+fn histogram_index(dimension: Dimension) -> Option<usize> {
+    HISTOGRAM_DIMENSIONS.iter().position(|&d| d == dimension)
+}
+
+impl Summary {
+    /// Returns the number of values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the minimum value.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the maximum value.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns the mean.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the population standard deviation.
+    pub fn stddev(&self) -> f64 {
+        if self.count == 0 {
+            0.
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn merge(&mut self, other: &Summary) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 +=
+            other.m2 + delta * delta * (self.count as f64 * other.count as f64) / count as f64;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+}
+
+impl Default for Summary {
+    fn default() -> Summary {
+        Summary {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.,
+            m2: 0.,
+        }
+    }
+}
+
+impl Sketch {
+    fn add(&mut self, value: f64) {
+        if self.levels.is_empty() {
+            self.levels.push(Vec::new());
+        }
+        self.levels[0].push(value);
+        self.compact();
+    }
+
+    fn merge(&mut self, other: Sketch) {
+        if self.levels.len() < other.levels.len() {
+            self.levels.resize_with(other.levels.len(), Vec::new);
+        }
+        for (level, values) in self.levels.iter_mut().zip(other.levels) {
+            level.extend(values);
+        }
+        self.compactions += other.compactions;
+        self.compact();
+    }
+
+    fn compact(&mut self) {
+        let mut i = 0;
+        while i < self.levels.len() {
+            if self.levels[i].len() > SKETCH_CAPACITY {
+                let mut values = std::mem::take(&mut self.levels[i]);
+                values.sort_by(f64::total_cmp);
+                // Alternate which half survives so the sketch isn't biased low or high
+                let offset = (self.compactions % 2) as usize;
+                self.compactions += 1;
+                if values.len() % 2 == 1 {
+                    self.levels[i].push(values.pop().expect("the level is over capacity"));
+                }
+                if i + 1 == self.levels.len() {
+                    self.levels.push(Vec::new());
+                }
+                self.levels[i + 1].extend(values.into_iter().skip(offset).step_by(2));
+            }
+            i += 1;
+        }
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let mut items: Vec<(f64, u64)> = self
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(i, values)| values.iter().map(move |&value| (value, 1 << i)))
+            .collect();
+        items.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let total: u64 = items.iter().map(|item| item.1).sum();
+        let target = q * total as f64;
+        let mut cumulative = 0;
+        for (value, weight) in items {
+            cumulative += weight;
+            if cumulative as f64 >= target {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::{Classification, Format},
+        Point, PointDataBuilder, Reader,
+    };
+
+    fn autzen() -> PointData {
+        Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .read_all()
+            .unwrap()
+    }
+
+    #[test]
+    fn summary() {
+        let mut summary = Summary::default();
+        for value in [2., 4., 4., 4., 5., 5., 7., 9.] {
+            summary.add(value);
+        }
+        assert_eq!(8, summary.count());
+        assert_eq!(2., summary.min());
+        assert_eq!(9., summary.max());
+        assert_eq!(5., summary.mean());
+        assert_eq!(2., summary.stddev());
+    }
+
+    #[test]
+    fn merge_matches_single_pass() {
+        let points = autzen();
+        let mut expected = Stats::new().with_median(true);
+        expected.add_point_data(&points);
+
+        let (a, b) = points.split_at(37);
+        let mut stats = Stats::new().with_median(true);
+        stats.add_point_data(&a);
+        let mut other = Stats::new().with_median(true);
+        other.add_point_data(&b);
+        stats.merge(other);
+
+        assert_eq!(expected.count(), stats.count());
+        for (dimension, summary) in expected.summaries() {
+            let merged = stats.summary(dimension).unwrap();
+            assert_eq!(summary.count(), merged.count());
+            assert_eq!(summary.min(), merged.min());
+            assert_eq!(summary.max(), merged.max());
+            assert!((summary.mean() - merged.mean()).abs() < 1e-6);
+            assert!((summary.stddev() - merged.stddev()).abs() < 1e-6);
+        }
+        for dimension in HISTOGRAM_DIMENSIONS {
+            assert_eq!(expected.histogram(dimension), stats.histogram(dimension));
+        }
+        assert_eq!(expected.median(Dimension::Z), stats.median(Dimension::Z));
+    }
+
+    #[test]
+    fn add_point_matches_add_point_data() {
+        let points = autzen();
+        let mut expected = Stats::new();
+        expected.add_point_data(&points);
+        let mut stats = Stats::new();
+        for point in points.views() {
+            stats.add_point(point);
+        }
+        assert_eq!(
+            expected.summaries().collect::<Vec<_>>(),
+            stats.summaries().collect::<Vec<_>>()
+        );
+        for dimension in HISTOGRAM_DIMENSIONS {
+            assert_eq!(expected.histogram(dimension), stats.histogram(dimension));
+        }
+    }
+
+    #[test]
+    fn merge_without_median() {
+        let mut stats = Stats::new().with_median(true);
+        stats.add_point_data(&autzen());
+        stats.merge(Stats::new());
+        assert!(stats.median(Dimension::Z).is_none());
+    }
+
+    #[test]
+    fn histograms() {
+        let points = PointDataBuilder::new()
+            .with_format(Format::new(6).unwrap())
+            .build_from_points([(2, 1, 1), (2, 1, 2), (6, 2, 2)].map(
+                |(class, return_number, channel)| Point {
+                    classification: Classification::new(class).unwrap(),
+                    return_number,
+                    number_of_returns: 2,
+                    scanner_channel: channel,
+                    point_source_id: 7,
+                    gps_time: Some(f64::from(channel)),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+        let mut stats = Stats::new();
+        stats.add_point_data(&points);
+        assert_eq!(
+            BTreeMap::from([(2, 2), (6, 1)]),
+            stats.histogram(Dimension::Classification).unwrap()
+        );
+        assert_eq!(
+            BTreeMap::from([(1, 2), (2, 1)]),
+            stats.histogram(Dimension::ReturnNumber).unwrap()
+        );
+        assert_eq!(
+            BTreeMap::from([(2, 3)]),
+            stats.histogram(Dimension::NumberOfReturns).unwrap()
+        );
+        assert_eq!(
+            BTreeMap::from([(1, 1), (2, 2)]),
+            stats.histogram(Dimension::ScannerChannel).unwrap()
+        );
+        assert_eq!(
+            BTreeMap::from([(7, 3)]),
+            stats.histogram(Dimension::PointSourceId).unwrap()
+        );
+        assert_eq!(Some((1., 2.)), stats.gps_time_range());
+        assert!(stats.summary(Dimension::Red).is_none());
+        assert_eq!(
+            vec![(2, "Ground".to_string(), 2), (6, "Roof".to_string(), 1)],
+            stats.classification_histogram(&ClassificationScheme::default().with_name(6, "Roof"))
+        );
+    }
+
+    #[test]
+    fn approximate_median() {
+        let mut sketch = Sketch::default();
+        for i in 0..100_000 {
+            sketch.add(f64::from(i));
+        }
+        assert!(sketch
+            .levels
+            .iter()
+            .all(|level| level.len() <= SKETCH_CAPACITY));
+        let median = sketch.quantile(0.5).unwrap();
+        assert!((median - 50_000.).abs() < 1_000., "{median}");
+    }
+
+    #[test]
+    fn exact_median_when_small() {
+        let mut stats = Stats::new().with_median(true);
+        stats.add_point_data(&autzen());
+        let mut z: Vec<f64> = autzen().z().collect();
+        z.sort_by(f64::total_cmp);
+        assert_eq!(Some(z[52]), stats.median(Dimension::Z));
+    }
+}
@@ -64,6 +64,17 @@ impl FromLasStr for &'_ mut [u8] {
     }
 }
 
+/// Copies a file into a new temporary directory, for tests that modify it.
+#[cfg(test)]
+pub(crate) fn copy_to_temp_dir(from: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir
+        .path()
+        .join(std::path::Path::new(from).file_name().unwrap());
+    let _ = std::fs::copy(from, &path).unwrap();
+    (dir, path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,903 @@
+//! Check a file against the las specification.
+//!
+//! Reading is permissive and most spec checks only happen on write (e.g.
+//! [Error::WktRequired](crate::Error::WktRequired)), so a file can read
+//! cleanly and still be invalid. [validate] reads a whole file and reports
+//! everything it finds instead of stopping at the first problem.
+//!
+//! ```
+//! let report = las::validate::validate("tests/data/autzen.las").unwrap();
+//! for finding in report.findings() {
+//!     println!("{finding}");
+//! }
+//! assert!(report.is_valid());
+//! ```
+
+use crate::{
+    extra_bytes::Descriptor, point::Format, raw, GpsTimeType, PointDataBuilder, Reader, Result,
+    Version, Vlr,
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// The number of seconds in a GPS week.
+const SECONDS_PER_WEEK: f64 = 604_800.;
+
+/// How bad a finding is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The file breaks a recommendation, or looks suspicious, but is readable.
+    Warning,
+
+    /// The file breaks the specification.
+    Error,
+}
+
+/// What was checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// The file signature is `LASF`.
+    FileSignature,
+
+    /// The version is a known las version.
+    Version,
+
+    /// The header size matches the version.
+    HeaderSize,
+
+    /// The point format is valid and allowed by the version.
+    PointFormat,
+
+    /// The point record length fits the point format and extra bytes.
+    PointRecordLength,
+
+    /// The VLR reserved fields are zero.
+    VlrReserved,
+
+    /// The VLRs can be read.
+    VlrTruncated,
+
+    /// The CRS is stored the way the global encoding says.
+    Crs,
+
+    /// The legacy (32-bit) point counts follow the las 1.4 rules.
+    LegacyPointCounts,
+
+    /// The point counts match the points.
+    PointCounts,
+
+    /// The points can be read.
+    PointData,
+
+    /// Every return number is between one and the number of returns.
+    ReturnNumber,
+
+    /// Classifications are valid for the point format.
+    Classification,
+
+    /// The header bounds contain every point.
+    Bounds,
+
+    /// The GPS time type bit matches the GPS time values.
+    GpsTimeType,
+}
+
+/// One problem with a file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// How bad it is.
+    pub severity: Severity,
+
+    /// What was checked.
+    pub check: Check,
+
+    /// A human-readable description.
+    pub message: String,
+
+    /// The index of the first point with this problem, for point checks.
+    pub point_index: Option<u64>,
+}
+
+/// The findings from validating a file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    findings: Vec<Finding>,
+}
+
+/// Validates a file.
+///
+/// Only fails if the file can't be opened, measured or seeked; every problem
+/// with its contents becomes a [Finding]. Point checks report the first offending point and how
+/// many points failed, rather than one finding per point.
+///
+/// # Examples
+///
+/// ```
+/// use las::validate::{validate, Severity};
+/// let report = validate("tests/data/autzen.las").unwrap();
+/// assert!(report.findings().iter().all(|f| f.severity == Severity::Warning));
+/// ```
+pub fn validate<P: AsRef<Path>>(path: P) -> Result<Report> {
+    let path = path.as_ref();
+    let mut report = Report::default();
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut read = BufReader::new(file);
+
+    let mut signature = [0; 4];
+    if read.read_exact(&mut signature).is_err() || signature != raw::LASF {
+        report.push(
+            Severity::Error,
+            Check::FileSignature,
+            format!("file signature is {signature:?}, not \"LASF\""),
+        );
+        return Ok(report);
+    }
+    let _ = read.seek(SeekFrom::Start(0))?;
+    let raw_header = match raw::Header::read_from(&mut read) {
+        Ok(raw_header) => raw_header,
+        Err(err) => {
+            report.push(
+                Severity::Error,
+                Check::HeaderSize,
+                format!("could not read the header: {err}"),
+            );
+            return Ok(report);
+        }
+    };
+    let version = raw_header.version;
+    if version.major != 1 || version.minor > 4 {
+        report.push(
+            Severity::Error,
+            Check::Version,
+            format!("unknown version {version}"),
+        );
+        return Ok(report);
+    }
+    check_header_size(&mut report, &raw_header);
+    let Some(format) = check_point_format(&mut report, &raw_header) else {
+        return Ok(report);
+    };
+    let vlrs = read_vlrs(&mut report, &mut read, &raw_header);
+    check_point_record_length(&mut report, &raw_header, format, &vlrs);
+    check_crs(&mut report, &raw_header, format, &vlrs);
+    check_legacy_point_counts(&mut report, &raw_header, format);
+    check_number_of_points(&mut report, &raw_header, format, file_len);
+    check_points(&mut report, path, &raw_header);
+    Ok(report)
+}
+
+impl Report {
+    /// Returns every finding.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Returns the findings with [Severity::Error].
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.with_severity(Severity::Error)
+    }
+
+    /// Returns the findings with [Severity::Warning].
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.with_severity(Severity::Warning)
+    }
+
+    /// Returns true if there are no errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = las::validate::validate("tests/data/autzen.las").unwrap();
+    /// assert!(report.is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity == severity)
+    }
+
+    fn push(&mut self, severity: Severity, check: Check, message: String) {
+        self.findings.push(Finding {
+            severity,
+            check,
+            message,
+            point_index: None,
+        });
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        if let Some(point_index) = self.point_index {
+            write!(f, "point {point_index}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+fn check_header_size(report: &mut Report, raw_header: &raw::Header) {
+    let expected = raw_header.version.header_size();
+    let actual = raw_header.header_size;
+    if actual < expected {
+        report.push(
+            Severity::Error,
+            Check::HeaderSize,
+            format!(
+                "header size {actual} is smaller than {expected} for version {}",
+                raw_header.version
+            ),
+        );
+    } else if actual > expected {
+        // Only las 1.4 forbids extending the header
+        let severity = if raw_header.version >= Version::new(1, 4) {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        report.push(
+            severity,
+            Check::HeaderSize,
+            format!(
+                "header size {actual} is larger than {expected} for version {}",
+                raw_header.version
+            ),
+        );
+    }
+}
+
+fn check_point_format(report: &mut Report, raw_header: &raw::Header) -> Option<Format> {
+    let format = match Format::new(raw_header.point_data_record_format) {
+        Ok(format) => format,
+        Err(err) => {
+            report.push(Severity::Error, Check::PointFormat, err.to_string());
+            return None;
+        }
+    };
+    if !raw_header.version.supports_point_format(format) {
+        report.push(
+            Severity::Error,
+            Check::PointFormat,
+            format!(
+                "point format {format} is not supported by version {}",
+                raw_header.version
+            ),
+        );
+    }
+    Some(format)
+}
+
+fn read_vlrs<R: Read + Seek>(
+    report: &mut Report,
+    mut read: R,
+    raw_header: &raw::Header,
+) -> Vec<raw::Vlr> {
+    let mut vlrs = Vec::new();
+    if let Err(err) = read.seek(SeekFrom::Start(u64::from(raw_header.header_size))) {
+        report.push(
+            Severity::Error,
+            Check::VlrTruncated,
+            format!("could not seek to the vlrs: {err}"),
+        );
+        return vlrs;
+    }
+    for i in 0..raw_header.number_of_variable_length_records {
+        match raw::Vlr::read_from(&mut read, false) {
+            Ok(vlr) => vlrs.push(vlr),
+            Err(err) => {
+                report.push(
+                    Severity::Error,
+                    Check::VlrTruncated,
+                    format!("could not read vlr {i}: {err}"),
+                );
+                break;
+            }
+        }
+    }
+    for (i, vlr) in vlrs.iter().enumerate() {
+        // las 1.0 used 0xAABB as the record signature
+        if vlr.reserved != 0 && !(raw_header.version.minor == 0 && vlr.reserved == 0xAABB) {
+            report.push(
+                Severity::Warning,
+                Check::VlrReserved,
+                format!("vlr {i} has reserved value {:#06x}, not zero", vlr.reserved),
+            );
+        }
+    }
+    vlrs
+}
+
+fn check_point_record_length(
+    report: &mut Report,
+    raw_header: &raw::Header,
+    format: Format,
+    vlrs: &[raw::Vlr],
+) {
+    let len = raw_header.point_data_record_length;
+    if len < format.len() {
+        report.push(
+            Severity::Error,
+            Check::PointRecordLength,
+            format!(
+                "point record length {len} is smaller than {} for point format {format}",
+                format.len()
+            ),
+        );
+        return;
+    }
+    let extra_bytes = usize::from(len - format.len());
+    let mut described = 0;
+    for vlr in vlrs.iter().filter(|vlr| is(vlr, "LASF_Spec", 4)) {
+        match Descriptor::from_vlr(&Vlr::new(vlr.clone())) {
+            Ok(descriptors) => {
+                described += descriptors
+                    .iter()
+                    .map(|descriptor| usize::from(descriptor.data_type.len()))
+                    .sum::<usize>()
+            }
+            Err(err) => report.push(
+                Severity::Error,
+                Check::PointRecordLength,
+                format!("could not read the extra bytes vlr: {err}"),
+            ),
+        }
+    }
+    if described > extra_bytes {
+        report.push(
+            Severity::Error,
+            Check::PointRecordLength,
+            format!("the extra bytes vlr describes {described} bytes, but points only have {extra_bytes} extra bytes"),
+        );
+    }
+}
+
+fn check_crs(report: &mut Report, raw_header: &raw::Header, format: Format, vlrs: &[raw::Vlr]) {
+    let has_wkt_bit = raw_header.global_encoding & 0b1_0000 != 0;
+    let has_wkt_vlr = vlrs.iter().any(|vlr| is(vlr, "LASF_Projection", 2112));
+    let has_geotiff_vlr = vlrs.iter().any(|vlr| {
+        (34735..=34737).contains(&vlr.record_id) && is(vlr, "LASF_Projection", vlr.record_id)
+    });
+    if has_wkt_bit && !has_wkt_vlr {
+        // The WKT may be in an EVLR, which we don't read here
+        if raw_header.evlr.is_none_or(|evlr| evlr.number_of_evlrs == 0) {
+            report.push(
+                Severity::Error,
+                Check::Crs,
+                "the wkt bit is set, but there is no wkt vlr".to_string(),
+            );
+        }
+    }
+    if !has_wkt_bit && has_wkt_vlr {
+        report.push(
+            Severity::Warning,
+            Check::Crs,
+            "there is a wkt vlr, but the wkt bit is not set".to_string(),
+        );
+    }
+    if has_wkt_bit && has_geotiff_vlr {
+        report.push(
+            Severity::Warning,
+            Check::Crs,
+            "the wkt bit is set, but there are geotiff vlrs".to_string(),
+        );
+    }
+    if format.is_extended && !has_wkt_bit {
+        report.push(
+            Severity::Error,
+            Check::Crs,
+            format!("point format {format} requires the wkt bit"),
+        );
+    }
+}
+
+fn check_legacy_point_counts(report: &mut Report, raw_header: &raw::Header, format: Format) {
+    let Some(large_file) = raw_header.large_file else {
+        return;
+    };
+    let legacy = raw_header.number_of_point_records;
+    let count = large_file.number_of_point_records;
+    if format.is_extended || count > u64::from(u32::MAX) {
+        if legacy != 0 || raw_header.number_of_points_by_return.iter().any(|&n| n > 0) {
+            report.push(
+                Severity::Warning,
+                Check::LegacyPointCounts,
+                format!(
+                    "the legacy point counts should be zero for point format {format} with {count} points"
+                ),
+            );
+        }
+    } else {
+        if u64::from(legacy) != count {
+            report.push(
+                Severity::Error,
+                Check::LegacyPointCounts,
+                format!("the legacy number of points {legacy} does not match {count}"),
+            );
+        }
+        for (i, (&legacy, &n)) in raw_header
+            .number_of_points_by_return
+            .iter()
+            .zip(&large_file.number_of_points_by_return)
+            .enumerate()
+        {
+            if u64::from(legacy) != n {
+                report.push(
+                    Severity::Error,
+                    Check::LegacyPointCounts,
+                    format!(
+                        "the legacy number of points for return {} is {legacy}, not {n}",
+                        i + 1
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Counts the points that fail one check and remembers the first.
+#[derive(Debug, Default)]
+struct Failures {
+    count: u64,
+    first: Option<(u64, String)>,
+}
+
+impl Failures {
+    fn add(&mut self, index: u64, message: impl FnOnce() -> String) {
+        self.count += 1;
+        if self.first.is_none() {
+            self.first = Some((index, message()));
+        }
+    }
+
+    fn report(self, report: &mut Report, severity: Severity, check: Check) {
+        if let Some((index, message)) = self.first {
+            let message = if self.count > 1 {
+                format!("{message} ({} points in total)", self.count)
+            } else {
+                message
+            };
+            report.findings.push(Finding {
+                severity,
+                check,
+                message,
+                point_index: Some(index),
+            });
+        }
+    }
+}
+
+fn check_number_of_points(
+    report: &mut Report,
+    raw_header: &raw::Header,
+    format: Format,
+    file_len: u64,
+) {
+    let record_len = u64::from(raw_header.point_data_record_length);
+    if format.is_compressed || record_len == 0 {
+        return;
+    }
+    let number_of_points = raw_header
+        .large_file
+        .map(|large_file| large_file.number_of_point_records)
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| u64::from(raw_header.number_of_point_records));
+    let end_of_point_data = match raw_header.evlr {
+        Some(evlr) if evlr.number_of_evlrs > 0 => evlr.start_of_first_evlr.min(file_len),
+        _ => file_len,
+    };
+    let available =
+        end_of_point_data.saturating_sub(u64::from(raw_header.offset_to_point_data)) / record_len;
+    if number_of_points > available {
+        report.push(
+            Severity::Error,
+            Check::PointCounts,
+            format!("the header says there are {number_of_points} points, but the point data only holds {available}"),
+        );
+    } else if number_of_points < available {
+        report.push(
+            Severity::Warning,
+            Check::PointCounts,
+            format!("the header says there are {number_of_points} points, but the point data holds {available}"),
+        );
+    }
+}
+
+fn check_points(report: &mut Report, path: &Path, raw_header: &raw::Header) {
+    let mut reader = match Reader::from_path(path) {
+        Ok(reader) => reader,
+        Err(err) => {
+            report.push(
+                Severity::Error,
+                Check::PointData,
+                format!("could not read the file: {err}"),
+            );
+            return;
+        }
+    };
+    let header = reader.header().clone();
+    let format = *header.point_format();
+    let bounds = header.bounds();
+    let transforms = *header.transforms();
+    let tolerance = |scale: f64| scale.abs() / 2.;
+
+    let mut return_number = Failures::default();
+    let mut classification = Failures::default();
+    let mut outside = Failures::default();
+    let mut number_of_points_by_return = [0u64; 15];
+    let mut gps_time_range = (f64::INFINITY, f64::NEG_INFINITY);
+    let mut index = 0;
+    let mut points = PointDataBuilder::new().for_header(&header).build();
+    loop {
+        match reader.fill_points(1_000_000, &mut points) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    check: Check::PointData,
+                    message: format!("could not read points: {err}"),
+                    point_index: Some(index),
+                });
+                return;
+            }
+        }
+        for point in points.views() {
+            let (r, n) = (point.return_number(), point.number_of_returns());
+            if r == 0 || r > n {
+                return_number.add(index, || {
+                    format!("return number {r} is not between 1 and the number of returns {n}")
+                });
+            }
+            if r > 0
+                && let Some(count) = number_of_points_by_return.get_mut(usize::from(r) - 1)
+            {
+                *count += 1;
+            }
+            let class = point.classification();
+            if class == 12 {
+                classification.add(index, || {
+                    if format.is_extended {
+                        "classification 12 is reserved, use the overlap flag instead".to_string()
+                    } else {
+                        "classification 12 (overlap) is deprecated, use point format 6 or higher and the overlap flag".to_string()
+                    }
+                });
+            }
+            let (x, y, z) = (point.x(), point.y(), point.z());
+            if x < bounds.min.x - tolerance(transforms.x.scale)
+                || x > bounds.max.x + tolerance(transforms.x.scale)
+                || y < bounds.min.y - tolerance(transforms.y.scale)
+                || y > bounds.max.y + tolerance(transforms.y.scale)
+                || z < bounds.min.z - tolerance(transforms.z.scale)
+                || z > bounds.max.z + tolerance(transforms.z.scale)
+            {
+                outside.add(index, || {
+                    format!("({x}, {y}, {z}) is outside the header bounds")
+                });
+            }
+            if let Some(gps_time) = point.gps_time() {
+                gps_time_range.0 = gps_time_range.0.min(gps_time);
+                gps_time_range.1 = gps_time_range.1.max(gps_time);
+            }
+            index += 1;
+        }
+    }
+    if index != header.number_of_points() {
+        report.push(
+            Severity::Error,
+            Check::PointCounts,
+            format!(
+                "the header says there are {} points, but {index} were read",
+                header.number_of_points()
+            ),
+        );
+    }
+    return_number.report(report, Severity::Warning, Check::ReturnNumber);
+    classification.report(report, Severity::Warning, Check::Classification);
+    outside.report(report, Severity::Error, Check::Bounds);
+
+    for (i, &n) in number_of_points_by_return.iter().enumerate() {
+        let expected = header.number_of_points_by_return(i as u8 + 1).unwrap_or(0);
+        if n != expected && (i < 5 || raw_header.large_file.is_some()) {
+            report.push(
+                Severity::Error,
+                Check::PointCounts,
+                format!(
+                    "the header says {expected} points have return number {}, but {n} do",
+                    i + 1
+                ),
+            );
+        }
+    }
+
+    let (min, max) = gps_time_range;
+    if min <= max {
+        match header.gps_time_type() {
+            GpsTimeType::Week if min < 0. || max > SECONDS_PER_WEEK => report.push(
+                Severity::Warning,
+                Check::GpsTimeType,
+                format!("gps times range from {min} to {max}, which is not gps week time"),
+            ),
+            GpsTimeType::Standard if min >= 0. && max <= SECONDS_PER_WEEK => report.push(
+                Severity::Warning,
+                Check::GpsTimeType,
+                format!(
+                    "gps times range from {min} to {max}, which looks like gps week time, not adjusted standard gps time"
+                ),
+            ),
+            _ => {}
+        }
+    }
+}
+
+fn is(vlr: &raw::Vlr, user_id: &str, record_id: u16) -> bool {
+    let len = vlr
+        .user_id
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(vlr.user_id.len());
+    vlr.record_id == record_id && vlr.user_id[..len].eq_ignore_ascii_case(user_id.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Point, Vlr, Writer};
+    use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+    fn write(
+        builder: Builder,
+        points: impl IntoIterator<Item = Point>,
+    ) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.las");
+        let mut writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+        for point in points {
+            writer.write_point(point).unwrap();
+        }
+        writer.close().unwrap();
+        (dir, path)
+    }
+
+    fn patch(path: &Path, f: impl FnOnce(&mut raw::Header)) {
+        let mut raw_header = raw::Header::read_from(File::open(path).unwrap()).unwrap();
+        f(&mut raw_header);
+        let mut file = OpenOptions::new().write(true).open(path).unwrap();
+        raw_header.write_to(&mut file).unwrap();
+        file.flush().unwrap();
+    }
+
+    fn point(return_number: u8, number_of_returns: u8) -> Point {
+        Point {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+            return_number,
+            number_of_returns,
+            ..Default::default()
+        }
+    }
+
+    fn find(report: &Report, check: Check) -> &Finding {
+        report
+            .findings()
+            .iter()
+            .find(|finding| finding.check == check)
+            .unwrap_or_else(|| panic!("no {check:?} finding in {report:?}"))
+    }
+
+    #[test]
+    fn autzen() {
+        let report = validate("tests/data/autzen.las").unwrap();
+        assert!(report.is_valid(), "{report:?}");
+    }
+
+    #[test]
+    fn signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-las.las");
+        std::fs::write(&path, b"not a las file").unwrap();
+        let report = validate(path).unwrap();
+        assert_eq!(Check::FileSignature, report.findings()[0].check);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn return_number() {
+        let (_dir, path) = write(
+            Builder::from((1, 2)),
+            [point(1, 1), point(3, 2), point(2, 1)],
+        );
+        let report = validate(path).unwrap();
+        let finding = find(&report, Check::ReturnNumber);
+        assert_eq!(Some(1), finding.point_index);
+        assert!(finding.message.contains("2 points in total"));
+        assert_eq!(Severity::Warning, finding.severity);
+    }
+
+    #[test]
+    fn classification() {
+        let (_dir, path) = write(
+            Builder::from((1, 2)),
+            [
+                point(1, 1),
+                Point {
+                    is_overlap: true,
+                    ..point(1, 1)
+                },
+            ],
+        );
+        let report = validate(path).unwrap();
+        assert_eq!(Some(1), find(&report, Check::Classification).point_index);
+    }
+
+    #[test]
+    fn bounds() {
+        let (_dir, path) = write(Builder::from((1, 2)), [point(1, 1)]);
+        patch(&path, |raw_header| raw_header.max_x = 0.5);
+        let report = validate(path).unwrap();
+        let finding = find(&report, Check::Bounds);
+        assert_eq!(Severity::Error, finding.severity);
+        assert_eq!(Some(0), finding.point_index);
+    }
+
+    #[test]
+    fn point_counts() {
+        let (_dir, path) = write(Builder::from((1, 2)), [point(1, 1), point(1, 1)]);
+        patch(&path, |raw_header| {
+            raw_header.number_of_points_by_return[0] = 1
+        });
+        let report = validate(path).unwrap();
+        assert_eq!(Severity::Error, find(&report, Check::PointCounts).severity);
+    }
+
+    #[test]
+    fn legacy_point_counts() {
+        let (_dir, path) = write(Builder::from((1, 4)), [point(1, 1)]);
+        assert!(validate(&path).unwrap().is_valid());
+        patch(&path, |raw_header| raw_header.number_of_point_records = 2);
+        let report = validate(path).unwrap();
+        assert_eq!(
+            Severity::Error,
+            find(&report, Check::LegacyPointCounts).severity
+        );
+    }
+
+    #[test]
+    fn header_size() {
+        let (_dir, path) = write(Builder::from((1, 2)), [point(1, 1)]);
+        patch(&path, |raw_header| raw_header.header_size = 200);
+        let report = validate(path).unwrap();
+        assert_eq!(Severity::Error, find(&report, Check::HeaderSize).severity);
+    }
+
+    #[test]
+    fn wkt_bit_without_vlr() {
+        let (_dir, path) = write(Builder::from((1, 4)), [point(1, 1)]);
+        patch(&path, |raw_header| raw_header.global_encoding |= 0b1_0000);
+        let report = validate(path).unwrap();
+        assert_eq!(Severity::Error, find(&report, Check::Crs).severity);
+    }
+
+    #[test]
+    fn extended_format_without_wkt() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let (_dir, path) = write(
+            builder,
+            [Point {
+                gps_time: Some(0.),
+                ..point(1, 1)
+            }],
+        );
+        patch(&path, |raw_header| raw_header.global_encoding &= !0b1_0000);
+        let report = validate(path).unwrap();
+        assert!(report
+            .errors()
+            .any(|finding| finding.check == Check::Crs && finding.message.contains("requires")));
+    }
+
+    #[test]
+    fn vlr_reserved() {
+        let mut builder = Builder::from((1, 2));
+        builder.vlrs.push(Vlr::default());
+        let (_dir, path) = write(builder, [point(1, 1)]);
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[227] = 0xbb;
+        std::fs::write(&path, bytes).unwrap();
+        let report = validate(path).unwrap();
+        assert_eq!(
+            Severity::Warning,
+            find(&report, Check::VlrReserved).severity
+        );
+    }
+
+    #[test]
+    fn vlr_truncated() {
+        let (_dir, path) = write(Builder::from((1, 2)), []);
+        patch(&path, |raw_header| {
+            raw_header.number_of_variable_length_records = 1
+        });
+        let report = validate(path).unwrap();
+        assert_eq!(Severity::Error, find(&report, Check::VlrTruncated).severity);
+        assert!(report
+            .findings()
+            .iter()
+            .all(|finding| finding.check != Check::VlrReserved));
+    }
+
+    #[test]
+    fn number_of_points() {
+        let (_dir, path) = write(Builder::from((1, 2)), [point(1, 1), point(1, 1)]);
+        patch(&path, |raw_header| raw_header.number_of_point_records = 3);
+        let report = validate(path).unwrap();
+        assert!(report
+            .errors()
+            .any(|finding| finding.check == Check::PointCounts
+                && finding.message.contains("only holds 2")));
+    }
+
+    #[test]
+    fn invalid_extra_bytes_vlr() {
+        let mut builder = Builder::from((1, 2));
+        builder.vlrs.push(Vlr {
+            user_id: "LASF_Spec".to_string(),
+            record_id: 4,
+            data: vec![0; 10],
+            ..Default::default()
+        });
+        let (_dir, path) = write(builder, [point(1, 1)]);
+        let report = validate(path).unwrap();
+        assert_eq!(
+            Severity::Error,
+            find(&report, Check::PointRecordLength).severity
+        );
+    }
+
+    #[test]
+    fn gps_time_type() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let (_dir, path) = write(
+            builder,
+            [Point {
+                gps_time: Some(1e9),
+                ..point(1, 1)
+            }],
+        );
+        let report = validate(path).unwrap();
+        assert_eq!(
+            Severity::Warning,
+            find(&report, Check::GpsTimeType).severity
+        );
+    }
+
+    #[test]
+    fn display() {
+        let finding = Finding {
+            severity: Severity::Error,
+            check: Check::Bounds,
+            message: "outside".to_string(),
+            point_index: Some(3),
+        };
+        assert_eq!("error: point 3: outside", finding.to_string());
+    }
+}
@@ -0,0 +1,766 @@
+use super::Vlr;
+use crate::{crs::wkt::Wkt, extra_bytes::Descriptor, Error, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{any::Any, fmt::Debug};
+
+const LASF_SPEC: &str = "LASF_Spec";
+const LASF_PROJECTION: &str = "LASF_Projection";
+const CLASSIFICATION_LOOKUP_ENTRY_LEN: usize = 16;
+const WAVEFORM_PACKET_DESCRIPTOR_LEN: usize = 26;
+
+/// A vlr that can be decoded into, and encoded from, a typed value.
+///
+/// Implement this trait and [register](Registry::register) the type to have a
+/// [Registry] decode your own vlrs.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::{KnownVlr, Registry}, Result, Vlr};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Answer(u8);
+///
+/// impl KnownVlr for Answer {
+///     fn matches(user_id: &str, record_id: u16) -> bool {
+///         user_id == "deep thought" && record_id == 42
+///     }
+///
+///     fn decode(vlr: &Vlr) -> Result<Answer> {
+///         Ok(Answer(vlr.data[0]))
+///     }
+///
+///     fn encode(&self) -> Result<Vlr> {
+///         Ok(Vlr {
+///             user_id: "deep thought".to_string(),
+///             record_id: 42,
+///             description: String::new(),
+///             data: vec![self.0],
+///         })
+///     }
+/// }
+///
+/// let mut registry = Registry::default();
+/// registry.register::<Answer>();
+/// let vlr = Answer(42).encode().unwrap();
+/// let known = registry.decode(&vlr).unwrap().unwrap();
+/// assert_eq!(Some(&Answer(42)), known.downcast_ref::<Answer>());
+/// ```
+pub trait KnownVlr: Debug + Any + Send + Sync {
+    /// Returns true if vlrs with this user id and record id decode as this type.
+    fn matches(user_id: &str, record_id: u16) -> bool
+    where
+        Self: Sized;
+
+    /// Decodes a vlr.
+    fn decode(vlr: &Vlr) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Encodes this value as a vlr.
+    fn encode(&self) -> Result<Vlr>;
+}
+
+impl dyn KnownVlr {
+    /// Returns true if this value is a `T`.
+    pub fn is<T: KnownVlr>(&self) -> bool {
+        let any: &dyn Any = self;
+        any.is::<T>()
+    }
+
+    /// Returns a reference to this value as a `T`, if it is one.
+    pub fn downcast_ref<T: KnownVlr>(&self) -> Option<&T> {
+        let any: &dyn Any = self;
+        any.downcast_ref()
+    }
+}
+
+/// Decodes vlrs into [KnownVlr] values.
+///
+/// The default registry knows every type in this module, plus the laszip and
+/// COPC info vlrs with the `laz` feature. [Registry::new] creates an empty one.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::{Registry, WktCrs}, Reader};
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let registry = Registry::default();
+/// for vlr in reader.header().vlrs() {
+///     if let Some(Ok(known)) = registry.decode(vlr) {
+///         println!("{:?}", known);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Registry {
+    decoders: Vec<Decoder>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Decoder {
+    matches: fn(&str, u16) -> bool,
+    decode: fn(&Vlr) -> Result<Box<dyn KnownVlr>>,
+}
+
+impl Registry {
+    /// Creates a registry that doesn't know any vlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::{Registry, TextAreaDescription, KnownVlr};
+    /// let vlr = TextAreaDescription::new("hello").encode().unwrap();
+    /// assert!(Registry::new().decode(&vlr).is_none());
+    /// ```
+    pub fn new() -> Registry {
+        Registry {
+            decoders: Vec::new(),
+        }
+    }
+
+    /// Registers a type.
+    ///
+    /// Types registered later take precedence over those registered earlier,
+    /// so this can replace the decoding of a built-in type.
+    pub fn register<T: KnownVlr>(&mut self) {
+        self.decoders.push(Decoder {
+            matches: T::matches,
+            decode: |vlr| Ok(Box::new(T::decode(vlr)?)),
+        });
+    }
+
+    /// Returns true if a registered type decodes this vlr.
+    pub fn is_known(&self, vlr: &Vlr) -> bool {
+        self.decoder(vlr).is_some()
+    }
+
+    /// Decodes this vlr with the registered type that matches it.
+    ///
+    /// Returns `None` if no registered type matches.
+    pub fn decode(&self, vlr: &Vlr) -> Option<Result<Box<dyn KnownVlr>>> {
+        self.decoder(vlr).map(|decoder| (decoder.decode)(vlr))
+    }
+
+    fn decoder(&self, vlr: &Vlr) -> Option<&Decoder> {
+        self.decoders
+            .iter()
+            .rev()
+            .find(|decoder| (decoder.matches)(&vlr.user_id, vlr.record_id))
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        let mut registry = Registry::new();
+        registry.register::<ClassificationLookup>();
+        registry.register::<TextAreaDescription>();
+        registry.register::<ExtraBytes>();
+        registry.register::<Superseded>();
+        registry.register::<WaveformPacketDescriptor>();
+        registry.register::<GeoKeyDirectory>();
+        registry.register::<GeoDoubleParams>();
+        registry.register::<GeoAsciiParams>();
+        registry.register::<WktCrs>();
+        #[cfg(feature = "laz")]
+        {
+            registry.register::<laz::LazVlr>();
+            registry.register::<crate::copc::CopcInfoVlr>();
+        }
+        registry
+    }
+}
+
+impl Vlr {
+    /// Decodes this vlr as a `T`.
+    ///
+    /// Returns an error if `T` doesn't match this vlr's user id and record id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::{KnownVlr, TextAreaDescription};
+    /// let vlr = TextAreaDescription::new("hello").encode().unwrap();
+    /// let text_area_description: TextAreaDescription = vlr.decode_as().unwrap();
+    /// assert_eq!("hello", text_area_description.text);
+    /// ```
+    pub fn decode_as<T: KnownVlr>(&self) -> Result<T> {
+        if T::matches(&self.user_id, self.record_id) {
+            T::decode(self)
+        } else {
+            Err(Error::UnexpectedVlr {
+                user_id: self.user_id.clone(),
+                record_id: self.record_id,
+            })
+        }
+    }
+}
+
+/// The classification lookup vlr, which describes classification values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClassificationLookup {
+    /// The entries, in the order they're stored.
+    ///
+    /// The spec has 256 entries, but they're encoded as-is.
+    pub entries: Vec<ClassificationLookupEntry>,
+}
+
+/// One entry of a [ClassificationLookup].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClassificationLookupEntry {
+    /// The classification value.
+    pub class: u8,
+
+    /// The description of the class, at most 15 bytes.
+    pub description: String,
+}
+
+impl KnownVlr for ClassificationLookup {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LASF_SPEC && record_id == 0
+    }
+
+    fn decode(vlr: &Vlr) -> Result<ClassificationLookup> {
+        use crate::utils::AsLasStr;
+        check_len(
+            vlr,
+            vlr.data
+                .len()
+                .is_multiple_of(CLASSIFICATION_LOOKUP_ENTRY_LEN),
+        )?;
+        let entries = vlr
+            .data
+            .chunks_exact(CLASSIFICATION_LOOKUP_ENTRY_LEN)
+            .map(|chunk| ClassificationLookupEntry {
+                class: chunk[0],
+                description: (&chunk[1..]).as_las_string_lossy(),
+            })
+            .collect();
+        Ok(ClassificationLookup { entries })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        use crate::utils::FromLasStr;
+        let mut data = vec![0; self.entries.len() * CLASSIFICATION_LOOKUP_ENTRY_LEN];
+        for (chunk, entry) in data
+            .chunks_exact_mut(CLASSIFICATION_LOOKUP_ENTRY_LEN)
+            .zip(&self.entries)
+        {
+            chunk[0] = entry.class;
+            (&mut chunk[1..]).from_las_str(&entry.description)?;
+        }
+        Ok(spec_vlr(0, "Classification lookup", data))
+    }
+}
+
+/// The text area description vlr, a free-form description of the file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextAreaDescription {
+    /// The text.
+    pub text: String,
+}
+
+impl TextAreaDescription {
+    /// Creates a new text area description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::TextAreaDescription;
+    /// let text_area_description = TextAreaDescription::new("Acquired in 2024");
+    /// ```
+    pub fn new(text: impl Into<String>) -> TextAreaDescription {
+        TextAreaDescription { text: text.into() }
+    }
+}
+
+impl KnownVlr for TextAreaDescription {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LASF_SPEC && record_id == 3
+    }
+
+    fn decode(vlr: &Vlr) -> Result<TextAreaDescription> {
+        Ok(TextAreaDescription::new(nul_terminated(&vlr.data)))
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        Ok(spec_vlr(
+            3,
+            "Text area description",
+            self.text.as_bytes().to_vec(),
+        ))
+    }
+}
+
+/// The extra bytes vlr, which describes the extra bytes of each point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtraBytes {
+    /// The descriptors, in the order of the extra bytes.
+    pub descriptors: Vec<Descriptor>,
+}
+
+impl KnownVlr for ExtraBytes {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LASF_SPEC && record_id == 4
+    }
+
+    fn decode(vlr: &Vlr) -> Result<ExtraBytes> {
+        Ok(ExtraBytes {
+            descriptors: Descriptor::from_vlr(vlr)?,
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        Descriptor::to_vlr(&self.descriptors)
+    }
+}
+
+/// A superseded vlr, which readers should ignore.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Superseded {
+    /// The description of the superseded vlr.
+    pub description: String,
+
+    /// The data of the superseded vlr.
+    pub data: Vec<u8>,
+}
+
+impl KnownVlr for Superseded {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LASF_SPEC && record_id == 7
+    }
+
+    fn decode(vlr: &Vlr) -> Result<Superseded> {
+        Ok(Superseded {
+            description: vlr.description.clone(),
+            data: vlr.data.clone(),
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        Ok(spec_vlr(7, &self.description, self.data.clone()))
+    }
+}
+
+/// A waveform packet descriptor, which describes how to read waveform packets.
+///
+/// Its record id is 99 plus its index, which is what points refer to in their
+/// wave packet descriptor index.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WaveformPacketDescriptor {
+    /// The index of this descriptor, 1 to 255.
+    pub index: u8,
+
+    /// The number of bits per sample, 2 to 32.
+    pub bits_per_sample: u8,
+
+    /// The compression type, zero for none.
+    pub compression_type: u8,
+
+    /// The number of samples in each packet.
+    pub number_of_samples: u32,
+
+    /// The temporal spacing between samples, in picoseconds.
+    pub temporal_sample_spacing: u32,
+
+    /// The digitizer gain, used to convert samples to volts.
+    pub digitizer_gain: f64,
+
+    /// The digitizer offset, used to convert samples to volts.
+    pub digitizer_offset: f64,
+}
+
+impl KnownVlr for WaveformPacketDescriptor {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        user_id == LASF_SPEC && (100..=354).contains(&record_id)
+    }
+
+    fn decode(vlr: &Vlr) -> Result<WaveformPacketDescriptor> {
+        check_len(vlr, vlr.data.len() == WAVEFORM_PACKET_DESCRIPTOR_LEN)?;
+        let mut data = vlr.data.as_slice();
+        Ok(WaveformPacketDescriptor {
+            index: u8::try_from(vlr.record_id.saturating_sub(99)).unwrap_or(u8::MAX),
+            bits_per_sample: data.read_u8()?,
+            compression_type: data.read_u8()?,
+            number_of_samples: data.read_u32::<LittleEndian>()?,
+            temporal_sample_spacing: data.read_u32::<LittleEndian>()?,
+            digitizer_gain: data.read_f64::<LittleEndian>()?,
+            digitizer_offset: data.read_f64::<LittleEndian>()?,
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        if self.index == 0 {
+            return Err(Error::InvalidWaveformPacketDescriptorIndex(self.index));
+        }
+        let mut data = Vec::with_capacity(WAVEFORM_PACKET_DESCRIPTOR_LEN);
+        data.write_u8(self.bits_per_sample)?;
+        data.write_u8(self.compression_type)?;
+        data.write_u32::<LittleEndian>(self.number_of_samples)?;
+        data.write_u32::<LittleEndian>(self.temporal_sample_spacing)?;
+        data.write_f64::<LittleEndian>(self.digitizer_gain)?;
+        data.write_f64::<LittleEndian>(self.digitizer_offset)?;
+        Ok(spec_vlr(
+            99 + u16::from(self.index),
+            "Waveform packet descriptor",
+            data,
+        ))
+    }
+}
+
+/// The GeoTiff GeoKeyDirectoryTag vlr.
+///
+/// This is the raw directory. Use [Header::get_geotiff_crs](crate::Header::get_geotiff_crs)
+/// to resolve its keys against the double and ascii params.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoKeyDirectory {
+    /// The key directory version, always 1.
+    pub key_directory_version: u16,
+
+    /// The key revision, always 1.
+    pub key_revision: u16,
+
+    /// The minor revision, 0 or 1.
+    pub minor_revision: u16,
+
+    /// The keys.
+    pub keys: Vec<GeoKeyEntry>,
+}
+
+/// One key of a [GeoKeyDirectory].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GeoKeyEntry {
+    /// The key id.
+    pub id: u16,
+
+    /// Where the value is: zero for `value_offset` itself, or the record id of the params vlr.
+    pub location: u16,
+
+    /// The number of values.
+    pub count: u16,
+
+    /// The value, or the index of the first value in the params vlr.
+    pub value_offset: u16,
+}
+
+impl KnownVlr for GeoKeyDirectory {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        is_projection(user_id) && record_id == 34735
+    }
+
+    fn decode(vlr: &Vlr) -> Result<GeoKeyDirectory> {
+        let mut data = vlr.data.as_slice();
+        let key_directory_version = data.read_u16::<LittleEndian>()?;
+        let key_revision = data.read_u16::<LittleEndian>()?;
+        let minor_revision = data.read_u16::<LittleEndian>()?;
+        let number_of_keys = data.read_u16::<LittleEndian>()?;
+        let mut keys = Vec::with_capacity(usize::from(number_of_keys));
+        for _ in 0..number_of_keys {
+            keys.push(GeoKeyEntry {
+                id: data.read_u16::<LittleEndian>()?,
+                location: data.read_u16::<LittleEndian>()?,
+                count: data.read_u16::<LittleEndian>()?,
+                value_offset: data.read_u16::<LittleEndian>()?,
+            });
+        }
+        Ok(GeoKeyDirectory {
+            key_directory_version,
+            key_revision,
+            minor_revision,
+            keys,
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(8 * (self.keys.len() + 1));
+        data.write_u16::<LittleEndian>(self.key_directory_version)?;
+        data.write_u16::<LittleEndian>(self.key_revision)?;
+        data.write_u16::<LittleEndian>(self.minor_revision)?;
+        data.write_u16::<LittleEndian>(
+            u16::try_from(self.keys.len()).map_err(|_| Error::VlrTooLong(self.keys.len()))?,
+        )?;
+        for key in &self.keys {
+            data.write_u16::<LittleEndian>(key.id)?;
+            data.write_u16::<LittleEndian>(key.location)?;
+            data.write_u16::<LittleEndian>(key.count)?;
+            data.write_u16::<LittleEndian>(key.value_offset)?;
+        }
+        Ok(projection_vlr(34735, "GeoTiff GeoKeyDirectoryTag", data))
+    }
+}
+
+/// The GeoTiff GeoDoubleParamsTag vlr.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoDoubleParams {
+    /// The values.
+    pub values: Vec<f64>,
+}
+
+impl KnownVlr for GeoDoubleParams {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        is_projection(user_id) && record_id == 34736
+    }
+
+    fn decode(vlr: &Vlr) -> Result<GeoDoubleParams> {
+        check_len(vlr, vlr.data.len().is_multiple_of(8))?;
+        let mut data = vlr.data.as_slice();
+        let mut values = Vec::with_capacity(vlr.data.len() / 8);
+        while !data.is_empty() {
+            values.push(data.read_f64::<LittleEndian>()?);
+        }
+        Ok(GeoDoubleParams { values })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(self.values.len() * 8);
+        for &value in &self.values {
+            data.write_f64::<LittleEndian>(value)?;
+        }
+        Ok(projection_vlr(34736, "GeoTiff GeoDoubleParamsTag", data))
+    }
+}
+
+/// The GeoTiff GeoAsciiParamsTag vlr.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoAsciiParams {
+    /// The text, with each value terminated by `|`.
+    ///
+    /// Bytes are read as Latin-1.
+    pub text: String,
+}
+
+impl KnownVlr for GeoAsciiParams {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        is_projection(user_id) && record_id == 34737
+    }
+
+    fn decode(vlr: &Vlr) -> Result<GeoAsciiParams> {
+        Ok(GeoAsciiParams {
+            text: vlr.data.iter().map(|&b| b as char).collect(),
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let data = self
+            .text
+            .chars()
+            .map(|c| u8::try_from(c).map_err(|_| Error::NotAscii(self.text.clone())))
+            .collect::<Result<Vec<u8>>>()?;
+        Ok(projection_vlr(34737, "GeoTiff GeoAsciiParamsTag", data))
+    }
+}
+
+/// The OGC coordinate system WKT vlr.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WktCrs {
+    /// The WKT, without its nul terminator.
+    pub wkt: String,
+}
+
+impl WktCrs {
+    /// Parses the WKT.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::WktCrs;
+    /// let wkt_crs = WktCrs { wkt: r#"VERT_CS["NAVD88",VERT_DATUM["North American Vertical Datum 1988",2005]]"#.to_string() };
+    /// assert!(wkt_crs.parse().unwrap().vertical().is_some());
+    /// ```
+    pub fn parse(&self) -> Result<Wkt> {
+        self.wkt.parse()
+    }
+}
+
+impl KnownVlr for WktCrs {
+    fn matches(user_id: &str, record_id: u16) -> bool {
+        is_projection(user_id) && record_id == 2112
+    }
+
+    fn decode(vlr: &Vlr) -> Result<WktCrs> {
+        Ok(WktCrs {
+            wkt: nul_terminated(&vlr.data),
+        })
+    }
+
+    fn encode(&self) -> Result<Vlr> {
+        let mut data = self.wkt.as_bytes().to_vec();
+        data.push(0);
+        Ok(projection_vlr(2112, "OGC Coordinate System WKT", data))
+    }
+}
+
+fn is_projection(user_id: &str) -> bool {
+    user_id.eq_ignore_ascii_case(LASF_PROJECTION)
+}
+
+fn nul_terminated(data: &[u8]) -> String {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..len]).into_owned()
+}
+
+fn check_len(vlr: &Vlr, is_valid: bool) -> Result<()> {
+    if is_valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidVlrLength {
+            user_id: vlr.user_id.clone(),
+            record_id: vlr.record_id,
+            len: vlr.data.len(),
+        })
+    }
+}
+
+fn spec_vlr(record_id: u16, description: &str, data: Vec<u8>) -> Vlr {
+    Vlr {
+        user_id: LASF_SPEC.to_string(),
+        record_id,
+        description: description.to_string(),
+        data,
+    }
+}
+
+fn projection_vlr(record_id: u16, description: &str, data: Vec<u8>) -> Vlr {
+    Vlr {
+        user_id: LASF_PROJECTION.to_string(),
+        record_id,
+        description: description.to_string(),
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extra_bytes::DataType, Reader};
+
+    fn roundtrip<T: KnownVlr + PartialEq>(value: T) {
+        let vlr = value.encode().unwrap();
+        assert!(T::matches(&vlr.user_id, vlr.record_id));
+        assert_eq!(value, T::decode(&vlr).unwrap());
+        let known = Registry::default().decode(&vlr).unwrap().unwrap();
+        assert_eq!(Some(&value), known.downcast_ref::<T>());
+    }
+
+    #[test]
+    fn roundtrips() {
+        roundtrip(ClassificationLookup {
+            entries: vec![ClassificationLookupEntry {
+                class: 2,
+                description: "Bare earth".to_string(),
+            }],
+        });
+        roundtrip(TextAreaDescription::new("Survey of the moon"));
+        roundtrip(ExtraBytes {
+            descriptors: vec![Descriptor::new("amplitude", DataType::U16)],
+        });
+        roundtrip(Superseded {
+            description: "Old extra bytes".to_string(),
+            data: vec![1, 2, 3],
+        });
+        roundtrip(WaveformPacketDescriptor {
+            index: 255,
+            bits_per_sample: 8,
+            compression_type: 0,
+            number_of_samples: 256,
+            temporal_sample_spacing: 1000,
+            digitizer_gain: 0.5,
+            digitizer_offset: -1.,
+        });
+        roundtrip(GeoKeyDirectory {
+            key_directory_version: 1,
+            key_revision: 1,
+            minor_revision: 0,
+            keys: vec![GeoKeyEntry {
+                id: 3072,
+                location: 0,
+                count: 1,
+                value_offset: 2994,
+            }],
+        });
+        roundtrip(GeoDoubleParams {
+            values: vec![1., 2.5],
+        });
+        roundtrip(GeoAsciiParams {
+            text: "NAD83 / Oregon GIC Lambert (ft)|".to_string(),
+        });
+        roundtrip(WktCrs {
+            wkt: "GEOGCS[\"WGS 84\"]".to_string(),
+        });
+    }
+
+    #[test]
+    fn geotiff_vlrs_are_byte_for_byte() {
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let registry = Registry::default();
+        let mut n = 0;
+        for vlr in reader
+            .header()
+            .vlrs()
+            .iter()
+            .filter(|vlr| vlr.is_geotiff_crs())
+        {
+            let known = registry.decode(vlr).unwrap().unwrap();
+            assert_eq!(vlr.data, known.encode().unwrap().data);
+            n += 1;
+        }
+        assert!(n > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn laszip() {
+        let mut header = crate::Header::default();
+        header.add_laz_vlr().unwrap();
+        let vlr = &header.vlrs()[0];
+        let known = Registry::default().decode(vlr).unwrap().unwrap();
+        assert!(known.is::<laz::LazVlr>());
+        assert_eq!(*vlr, known.encode().unwrap());
+    }
+
+    #[test]
+    fn invalid_length() {
+        let vlr = spec_vlr(100, "", vec![0; 25]);
+        assert!(matches!(
+            WaveformPacketDescriptor::decode(&vlr),
+            Err(Error::InvalidVlrLength { len: 25, .. })
+        ));
+    }
+
+    #[test]
+    fn waveform_packet_descriptor_index_zero() {
+        assert!(matches!(
+            WaveformPacketDescriptor::default().encode(),
+            Err(Error::InvalidWaveformPacketDescriptorIndex(0))
+        ));
+    }
+
+    #[test]
+    fn unknown() {
+        let vlr = Vlr::default();
+        assert!(!Registry::default().is_known(&vlr));
+        assert!(vlr.decode_as::<TextAreaDescription>().is_err());
+    }
+
+    #[test]
+    fn later_registrations_win() {
+        #[derive(Debug)]
+        struct Text;
+        impl KnownVlr for Text {
+            fn matches(user_id: &str, record_id: u16) -> bool {
+                TextAreaDescription::matches(user_id, record_id)
+            }
+            fn decode(_: &Vlr) -> Result<Text> {
+                Ok(Text)
+            }
+            fn encode(&self) -> Result<Vlr> {
+                TextAreaDescription::default().encode()
+            }
+        }
+        let mut registry = Registry::default();
+        registry.register::<Text>();
+        let vlr = TextAreaDescription::new("hello").encode().unwrap();
+        assert!(registry.decode(&vlr).unwrap().unwrap().is::<Text>());
+    }
+}
@@ -28,7 +28,29 @@
 //! assert_eq!(0, header.evlrs().len());
 //! assert_eq!(1, header.vlrs().len());
 //! ```
+//!
+//! Well-known vlrs can be decoded into typed values with a [Registry], and
+//! encoded back with [KnownVlr::encode]:
+//!
+//! ```
+//! use las::{vlr::{KnownVlr, Registry, WktCrs}, Reader};
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let registry = Registry::default();
+//! for vlr in reader.header().all_vlrs() {
+//!     if let Some(Ok(known)) = registry.decode(vlr) {
+//!         assert_eq!(vlr.record_id, known.encode().unwrap().record_id);
+//!     }
+//! }
+//! ```
+
+mod known;
 
+pub use self::known::{
+    ClassificationLookup, ClassificationLookupEntry, ExtraBytes, GeoAsciiParams, GeoDoubleParams,
+    GeoKeyDirectory, GeoKeyEntry, KnownVlr, Registry, Superseded, TextAreaDescription,
+    WaveformPacketDescriptor, WktCrs,
+};
 use crate::{raw, Error, Result};
 
 const REGULAR_HEADER_SIZE: usize = 54;
@@ -36,6 +58,7 @@ const EXTENDED_HEADER_SIZE: usize = 60;
 
 /// A variable length record.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vlr {
     /// The user that created this record.
     ///
@@ -1,5 +1,5 @@
 use super::WritePoint;
-use crate::{Header, Point, Result};
+use crate::{Error, Header, Point, Result};
 use std::io::{Seek, Write};
 
 pub(crate) struct PointWriter<W: Write + Seek> {
@@ -45,6 +45,10 @@ impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<W> {
         &mut self.header
     }
 
+    fn end_chunk(&mut self) -> Result<()> {
+        Err(Error::NoVariableLazChunks)
+    }
+
     fn done(&mut self) -> Result<()> {
         Ok(())
     }
@@ -10,19 +10,71 @@ pub(crate) struct PointWriter<C> {
     // Buffer with raw bytes of the points to be compressed
     buffer: Cursor<Vec<u8>>,
     header: Header,
+    variable_chunks: bool,
+    // Points in the current variable-size chunk, for compressors that need
+    // a whole chunk at once
+    chunk: Vec<u8>,
+    points_in_chunk: u64,
+    chunk_ended: bool,
+}
+
+/// Compresses variable-size chunks.
+pub(crate) trait CompressChunks {
+    /// Whether the points of a chunk must be collected before they can be compressed.
+    const COLLECTS_CHUNKS: bool;
+
+    /// Compresses a whole chunk, when chunks are collected.
+    fn compress_chunk(&mut self, points: &[u8]) -> std::io::Result<()>;
+
+    /// Ends the current chunk, when chunks are not collected.
+    fn finish_current_chunk(&mut self) -> std::io::Result<()>;
+}
+
+impl<W: Write + Seek + Send + Sync> CompressChunks for LasZipCompressor<'_, W> {
+    const COLLECTS_CHUNKS: bool = false;
+
+    fn compress_chunk(&mut self, points: &[u8]) -> std::io::Result<()> {
+        self.compress_chunks([points])
+    }
+
+    fn finish_current_chunk(&mut self) -> std::io::Result<()> {
+        LasZipCompressor::finish_current_chunk(self)
+    }
+}
+
+#[cfg(feature = "laz-parallel")]
+impl<W: Write + Seek + Send + Sync> CompressChunks for ParLasZipCompressor<W> {
+    const COLLECTS_CHUNKS: bool = true;
+
+    fn compress_chunk(&mut self, points: &[u8]) -> std::io::Result<()> {
+        self.compress_chunks(vec![points])
+    }
+
+    fn finish_current_chunk(&mut self) -> std::io::Result<()> {
+        unreachable!("chunks are collected for the parallel compressor")
+    }
+}
+
+impl<C> PointWriter<C> {
+    fn with_compressor(compressor: C, header: Header, variable_chunks: bool) -> PointWriter<C> {
+        PointWriter {
+            buffer: Cursor::new(vec![0u8; header.point_format().len() as usize]),
+            header,
+            compressor,
+            variable_chunks,
+            chunk: Vec::new(),
+            points_in_chunk: 0,
+            chunk_ended: false,
+        }
+    }
 }
 
 impl<'a, W: Write + Seek + Send + Sync> PointWriter<LasZipCompressor<'a, W>> {
     pub(crate) fn new(write: W, header: Header) -> Result<PointWriter<LasZipCompressor<'a, W>>> {
-        let buffer = Cursor::new(vec![0u8; header.point_format().len() as usize]);
         let vlr = header.laz_vlr()?;
+        let variable_chunks = vlr.uses_variable_size_chunks();
         let compressor = LasZipCompressor::new(write, vlr)?;
-
-        Ok(Self {
-            header,
-            buffer,
-            compressor,
-        })
+        Ok(Self::with_compressor(compressor, header, variable_chunks))
     }
 }
 
@@ -32,21 +84,37 @@ impl<W: Write + Seek + Send + Sync> PointWriter<ParLasZipCompressor<W>> {
         write: W,
         header: Header,
     ) -> Result<PointWriter<ParLasZipCompressor<W>>> {
-        let buffer = Cursor::new(vec![0u8; header.point_format().len() as usize]);
         let vlr = header.laz_vlr()?;
+        let variable_chunks = vlr.uses_variable_size_chunks();
         let compressor = ParLasZipCompressor::new(write, vlr)?;
+        Ok(Self::with_compressor(compressor, header, variable_chunks))
+    }
+}
 
-        Ok(Self {
-            header,
-            buffer,
-            compressor,
-        })
+impl<C> PointWriter<C>
+where
+    C: LazCompressor + CompressChunks,
+{
+    fn compress(&mut self, bytes: &[u8], point_count: u64) -> Result<()> {
+        if self.variable_chunks {
+            self.points_in_chunk += point_count;
+            if C::COLLECTS_CHUNKS {
+                self.chunk.extend_from_slice(bytes);
+                return Ok(());
+            } else if self.chunk_ended {
+                // Ending a chunk is deferred until the next point, because
+                // the compressor always ends the last chunk when it's done.
+                self.compressor.finish_current_chunk()?;
+                self.chunk_ended = false;
+            }
+        }
+        self.compressor.compress_many(bytes).map_err(Error::from)
     }
 }
 
 impl<W, C> WritePoint<W> for PointWriter<C>
 where
-    C: LazCompressor + LazCompressorWithInner<W> + Send + Sync,
+    C: LazCompressor + LazCompressorWithInner<W> + CompressChunks + Send + Sync,
     W: Write + Seek + Send + Sync,
 {
     fn write_point(&mut self, point: Point) -> Result<()> {
@@ -57,17 +125,17 @@ where
             .and_then(|raw_point| {
                 raw_point.write_to(&mut self.buffer, self.header.point_format())
             })?;
-        self.compressor
-            .compress_one(self.buffer.get_ref())
-            .map_err(Error::from)
+        let buffer = std::mem::take(self.buffer.get_mut());
+        let result = self.compress(&buffer, 1);
+        *self.buffer.get_mut() = buffer;
+        result
     }
 
-    fn write_bytes(&mut self, bytes: &[u8], _point_count: u64) -> Result<()> {
+    fn write_bytes(&mut self, bytes: &[u8], point_count: u64) -> Result<()> {
         // Header stats were updated by the caller. Hand the raw
         // decompressed point bytes to the LAZ compressor in one batch —
         // this is where parallel compression pays off for large slabs.
-        self.compressor.compress_many(bytes)?;
-        Ok(())
+        self.compress(bytes, point_count)
     }
 
     fn into_inner(self: Box<Self>) -> W {
@@ -86,7 +154,28 @@ where
         &mut self.header
     }
 
+    fn end_chunk(&mut self) -> Result<()> {
+        if !self.variable_chunks {
+            return Err(Error::NoVariableLazChunks);
+        }
+        if self.points_in_chunk == 0 {
+            return Ok(());
+        }
+        if C::COLLECTS_CHUNKS {
+            self.compressor.compress_chunk(&self.chunk)?;
+            self.chunk.clear();
+        } else {
+            self.chunk_ended = true;
+        }
+        self.points_in_chunk = 0;
+        Ok(())
+    }
+
     fn done(&mut self) -> Result<()> {
+        if C::COLLECTS_CHUNKS && !self.chunk.is_empty() {
+            self.compressor.compress_chunk(&self.chunk)?;
+            self.chunk.clear();
+        }
         self.compressor.done()?;
         Ok(())
     }
@@ -34,17 +34,29 @@
 mod las;
 #[cfg(feature = "laz")]
 mod laz;
+mod sort;
+mod split;
+mod tile;
+
+pub use split::{Part, SplitBy, SplitWriter};
+pub use tile::{Tile, TileWriter};
 
 #[cfg(feature = "laz")]
 use crate::LazParallelism;
-use crate::{Error, Header, Point, PointData, Result};
+use crate::{
+    crs::Crs, CoordinateTransform, Error, Header, Point, PointData, PointDataBuilder, Result,
+    SpaceFillingCurve,
+};
 use std::{
     fmt::Debug,
     fs::File,
     io::{BufWriter, Cursor, Seek, SeekFrom},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
+const DEFAULT_SORT_BUFFER_LEN: usize = 5_000_000;
+const REPROJECTION_CHUNK_LEN: usize = 65_536;
+
 trait WritePoint<W: std::io::Write>: Send {
     fn write_point(&mut self, point: Point) -> Result<()>;
     /// Writes a pre-encoded byte slab of `point_count` records directly
@@ -56,6 +68,7 @@ trait WritePoint<W: std::io::Write>: Send {
     fn get_mut(&mut self) -> &mut W;
     fn header(&self) -> &Header;
     fn header_mut(&mut self) -> &mut Header;
+    fn end_chunk(&mut self) -> Result<()>;
     fn done(&mut self) -> Result<()>;
 }
 
@@ -80,40 +93,153 @@ impl<W: std::io::Write> WritePoint<W> for ClosedPointWriter {
     fn header_mut(&mut self) -> &mut Header {
         unreachable!()
     }
+    fn end_chunk(&mut self) -> Result<()> {
+        unreachable!()
+    }
     fn done(&mut self) -> Result<()> {
         unreachable!()
     }
 }
 
+/// The number of points in each chunk of laz data.
+///
+/// Readers can only seek to the start of a chunk, so smaller chunks make
+/// seeking faster, at the cost of slightly worse compression.
+#[cfg(feature = "laz")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LazChunkSize {
+    /// Every chunk has this many points, except the last one.
+    ///
+    /// Zero is treated as one.
+    Fixed(u32),
+
+    /// Chunks are ended explicitly with [Writer::end_chunk].
+    ///
+    /// Use this to group points that are close together into the same
+    /// chunk, e.g. one chunk per tile or per octree node.
+    Variable,
+}
+
+#[cfg(feature = "laz")]
+impl Default for LazChunkSize {
+    fn default() -> LazChunkSize {
+        LazChunkSize::Fixed(50_000)
+    }
+}
+
 /// Options for Writer
 ///
 #[cfg_attr(
     feature = "laz",
-    doc = "Currently, the only option is the selection of LAZ parallelism via [`LazParallelism`]."
+    doc = "The laz options are the selection of LAZ parallelism via [`LazParallelism`] and the chunk size via [`LazChunkSize`]."
 )]
 #[cfg_attr(
     not(feature = "laz"),
-    doc = "Currently, the only option is the selection of LAZ parallelism via `LazParallelism`."
+    doc = "The laz options are the selection of LAZ parallelism via `LazParallelism` and the chunk size via `LazChunkSize`."
 )]
-/// This option requires the `laz` feature to be enabled (and to use parallelism, the `laz-parallel`
+/// These options require the `laz` feature to be enabled (and to use parallelism, the `laz-parallel`
 /// feature must also be enabled).
 /// Using parallel writing will speedup compression when writing points in batch, at the cost
 /// of slightly more memory consumption.
 ///
 /// By default, if the `laz-parallel` feature is enabled, parallelism will be the default choice
-#[derive(Debug, Clone, Copy)]
+///
+/// Points can also be sorted along a [`SpaceFillingCurve`] before they're
+/// written, see [`WriterOptions::with_space_filling_curve`].
+#[derive(Debug, Clone)]
 pub struct WriterOptions {
     #[cfg(feature = "laz")]
     laz_parallelism: LazParallelism,
+    #[cfg(feature = "laz")]
+    laz_chunk_size: LazChunkSize,
+    space_filling_curve: Option<SpaceFillingCurve>,
+    sort_buffer_len: usize,
+    sort_directory: Option<PathBuf>,
 }
 
 impl WriterOptions {
+    /// Sort the points along a space-filling curve before writing them.
+    ///
+    /// Points are buffered until the writer is closed. If more than
+    /// [`sort_buffer_len`](WriterOptions::with_sort_buffer_len) points are
+    /// written, sorted runs are spilled to temporary files in the
+    /// [`sort_directory`](WriterOptions::with_sort_directory), which are
+    /// merged when the writer is closed, so files larger than memory can be
+    /// sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{SpaceFillingCurve, Writer, WriterOptions};
+    ///
+    /// let options = WriterOptions::default().with_space_filling_curve(SpaceFillingCurve::Hilbert);
+    /// let writer = Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// ```
+    pub fn with_space_filling_curve(mut self, space_filling_curve: SpaceFillingCurve) -> Self {
+        self.space_filling_curve = Some(space_filling_curve);
+        self
+    }
+
+    /// Change the number of points kept in memory when sorting.
+    ///
+    /// Values less than one are treated as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{SpaceFillingCurve, WriterOptions};
+    /// let options = WriterOptions::default()
+    ///     .with_space_filling_curve(SpaceFillingCurve::Morton)
+    ///     .with_sort_buffer_len(1_000_000);
+    /// ```
+    pub fn with_sort_buffer_len(mut self, sort_buffer_len: usize) -> Self {
+        self.sort_buffer_len = sort_buffer_len.max(1);
+        self
+    }
+
+    /// Change the directory that sorted runs are spilled to.
+    ///
+    /// Defaults to [`std::env::temp_dir`], which is often a small in-memory
+    /// file system, so use a directory with room for a copy of the points
+    /// when sorting large files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{SpaceFillingCurve, WriterOptions};
+    /// let options = WriterOptions::default()
+    ///     .with_space_filling_curve(SpaceFillingCurve::Morton)
+    ///     .with_sort_directory("/var/tmp");
+    /// ```
+    pub fn with_sort_directory(mut self, sort_directory: impl Into<PathBuf>) -> Self {
+        self.sort_directory = Some(sort_directory.into());
+        self
+    }
+
     /// Change the laz parallelism option
     #[cfg(feature = "laz")]
     pub fn with_laz_parallelism(mut self, laz_parallelism: LazParallelism) -> Self {
         self.laz_parallelism = laz_parallelism;
         self
     }
+
+    /// Change the laz chunk size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "laz")]
+    /// # {
+    /// use las::{writer::LazChunkSize, WriterOptions};
+    /// let options = WriterOptions::default().with_laz_chunk_size(LazChunkSize::Variable);
+    /// # }
+    /// ```
+    #[cfg(feature = "laz")]
+    pub fn with_laz_chunk_size(mut self, laz_chunk_size: LazChunkSize) -> Self {
+        self.laz_chunk_size = laz_chunk_size;
+        self
+    }
 }
 
 impl Default for WriterOptions {
@@ -122,18 +248,30 @@ impl Default for WriterOptions {
         {
             Self {
                 laz_parallelism: LazParallelism::Yes,
+                laz_chunk_size: LazChunkSize::default(),
+                space_filling_curve: None,
+                sort_buffer_len: DEFAULT_SORT_BUFFER_LEN,
+                sort_directory: None,
             }
         }
         #[cfg(all(feature = "laz", not(feature = "laz-parallel")))]
         {
             Self {
                 laz_parallelism: LazParallelism::No,
+                laz_chunk_size: LazChunkSize::default(),
+                space_filling_curve: None,
+                sort_buffer_len: DEFAULT_SORT_BUFFER_LEN,
+                sort_directory: None,
             }
         }
 
         #[cfg(not(feature = "laz"))]
         {
-            Self {}
+            Self {
+                space_filling_curve: None,
+                sort_buffer_len: DEFAULT_SORT_BUFFER_LEN,
+                sort_directory: None,
+            }
         }
     }
 }
@@ -158,6 +296,15 @@ pub struct Writer<W: 'static + std::io::Write + Seek + Send + Sync> {
     closed: bool,
     start: u64,
     point_writer: Box<dyn WritePoint<W> + Send>,
+    reprojection: Option<Reprojection>,
+}
+
+/// A coordinate transform applied as points are written.
+#[derive(Debug)]
+struct Reprojection {
+    coordinate_transform: Box<dyn CoordinateTransform>,
+    /// Reused between calls to [Writer::write_points].
+    buffer: Vec<u8>,
 }
 
 impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
@@ -202,10 +349,10 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
         if header.point_format().is_compressed {
             #[cfg(feature = "laz")]
             {
-                header.add_laz_vlr()?;
+                header.add_laz_vlr_with_chunk_size(options.laz_chunk_size)?;
                 header.write_to(&mut write)?;
 
-                let point_writer: Box<dyn WritePoint<W>> = match options.laz_parallelism {
+                let point_writer: Box<dyn WritePoint<W> + Send> = match options.laz_parallelism {
                     #[cfg(feature = "laz-parallel")]
                     LazParallelism::Yes => {
                         laz::PointWriter::new_parallel(write, header).map(Box::new)?
@@ -216,7 +363,8 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
                 Ok(Writer {
                     closed: false,
                     start,
-                    point_writer,
+                    point_writer: sorted(point_writer, options),
+                    reprojection: None,
                 })
             }
             #[cfg(not(feature = "laz"))]
@@ -224,17 +372,81 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
                 Err(Error::LaszipNotEnabled)
             }
         } else {
-            // Silence unused variable warning as the only option is related to laz
-            let _ = options;
             header.write_to(&mut write)?;
             Ok(Writer {
                 closed: false,
                 start,
-                point_writer: Box::new(las::PointWriter::new(write, header)),
+                point_writer: sorted(Box::new(las::PointWriter::new(write, header)), options),
+                reprojection: None,
             })
         }
     }
 
+    /// Creates a new writer that transforms the coordinates of every point as it's written.
+    ///
+    /// If `crs` is provided, it replaces the header's CRS (E)VLRs, converted
+    /// with [`Header::set_crs`]. Otherwise the header's CRS is kept. The
+    /// header's scales and offsets must suit the transformed coordinates.
+    ///
+    /// [`Writer::write_points`] takes points in their source transforms and
+    /// re-quantizes them into the header's. [`TileWriter`] and [`SplitWriter`]
+    /// bin points by their coordinates as given, so reproject before handing
+    /// points to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{crs::{Crs, GeoTiffCrs}, reproject::Utm, Builder, Point, Writer};
+    ///
+    /// let utm = Utm::from_geographic(10, false).unwrap();
+    /// let crs = Crs::GeoTiff(GeoTiffCrs::default().with_projected_crs(32610));
+    /// let header = Builder::from((1, 2)).into_header().unwrap();
+    /// let mut writer = Writer::with_coordinate_transform(
+    ///     Cursor::new(Vec::new()),
+    ///     header,
+    ///     Default::default(),
+    ///     utm,
+    ///     Some(crs),
+    /// )
+    /// .unwrap();
+    /// writer.write_point(Point { x: -123., y: 0.5, ..Default::default() }).unwrap();
+    /// assert_eq!(500000., writer.header().bounds().min.x);
+    /// ```
+    pub fn with_coordinate_transform<T: CoordinateTransform + 'static>(
+        write: W,
+        mut header: Header,
+        options: WriterOptions,
+        coordinate_transform: T,
+        crs: Option<Crs>,
+    ) -> Result<Self> {
+        if let Some(crs) = &crs {
+            header.set_crs(crs)?;
+        }
+        let mut writer = Self::with_options(write, header, options)?;
+        writer.reprojection = Some(Reprojection {
+            coordinate_transform: Box::new(coordinate_transform),
+            buffer: Vec::new(),
+        });
+        Ok(writer)
+    }
+
+    /// Creates an uncompressed writer that appends to the points already in `write`.
+    ///
+    /// `write` must be positioned at the end of the existing point records, and
+    /// `header` must describe them, since its stats are not cleared.
+    ///
+    /// The points aren't sorted, since appending can't order them among the
+    /// points already written.
+    fn resume(write: W, header: Header) -> Writer<W> {
+        Writer {
+            closed: false,
+            start: 0,
+            point_writer: Box::new(las::PointWriter::new(write, header)),
+            reprojection: None,
+        }
+    }
+
     /// Close this writer.
     ///
     /// # Examples
@@ -313,7 +525,7 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
     /// let mut writer = Writer::default();
     /// writer.write_point(Default::default()).unwrap();
     /// ```
-    pub fn write_point(&mut self, point: Point) -> Result<()> {
+    pub fn write_point(&mut self, mut point: Point) -> Result<()> {
         if self.closed {
             return Err(Error::ClosedWriter);
         }
@@ -322,6 +534,13 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
                 *self.header().point_format(),
             ));
         }
+        if let Some(reprojection) = &self.reprojection {
+            let (mut x, mut y, mut z) = ([point.x], [point.y], [point.z]);
+            reprojection
+                .coordinate_transform
+                .transform(&mut x, &mut y, &mut z)?;
+            (point.x, point.y, point.z) = (x[0], y[0], z[0]);
+        }
         self.point_writer.write_point(point)
     }
 
@@ -339,7 +558,9 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
     ///
     /// Returns an error if the slab's point format or coordinate
     /// transforms don't match this writer's header — slab bytes are only
-    /// valid for the exact schema they were decoded against.
+    /// valid for the exact schema they were decoded against. A writer created
+    /// with [`Writer::with_coordinate_transform`] accepts any transforms and
+    /// re-quantizes the transformed points into the header's.
     pub fn write_points(&mut self, points: &PointData) -> Result<()> {
         if self.closed {
             return Err(Error::ClosedWriter);
@@ -355,7 +576,7 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
                 *self.header().point_format(),
             ));
         }
-        if points.transforms() != self.header().transforms() {
+        if self.reprojection.is_none() && points.transforms() != self.header().transforms() {
             return Err(Error::PointAttributesDoNotMatch(
                 *self.header().point_format(),
             ));
@@ -363,6 +584,42 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
         if points.is_empty() {
             return Ok(());
         }
+        if let Some(mut reprojection) = self.reprojection.take() {
+            let result = self.write_reprojected(points, &mut reprojection);
+            self.reprojection = Some(reprojection);
+            return result;
+        }
+        self.write_points_unchecked(points)
+    }
+
+    /// Transforms and writes `points` a chunk at a time, so the copy that's
+    /// re-quantized into the header's transforms stays small.
+    fn write_reprojected(
+        &mut self,
+        points: &PointData,
+        reprojection: &mut Reprojection,
+    ) -> Result<()> {
+        let transforms = *self.header().transforms();
+        for bytes in points
+            .raw_bytes()
+            .chunks(REPROJECTION_CHUNK_LEN * points.record_len())
+        {
+            let mut buffer = std::mem::take(&mut reprojection.buffer);
+            buffer.clear();
+            buffer.extend_from_slice(bytes);
+            let mut chunk = PointDataBuilder::new()
+                .with_format(*points.format())
+                .with_transforms(*points.transforms())
+                .build_from_bytes(buffer)?;
+            let _ = chunk
+                .transform_coordinates(reprojection.coordinate_transform.as_ref(), transforms)?;
+            self.write_points_unchecked(&chunk)?;
+            reprojection.buffer = std::mem::take(chunk.take_bytes_mut());
+        }
+        Ok(())
+    }
+
+    fn write_points_unchecked(&mut self, points: &PointData) -> Result<()> {
         // Update header stats once over the whole slab — no per-Point
         // materialization.
         self.point_writer.header_mut().add_point_data(points);
@@ -370,6 +627,43 @@ impl<W: 'static + std::io::Write + Seek + Send + Sync> Writer<W> {
             .write_bytes(points.raw_bytes(), points.len() as u64)
     }
 
+    /// Ends the current chunk of laz data.
+    ///
+    /// The points written after this call go into a new chunk. Does nothing
+    /// if no points have been written since the last chunk ended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this writer doesn't write laz data with
+    #[cfg_attr(feature = "laz", doc = "[LazChunkSize::Variable].")]
+    #[cfg_attr(not(feature = "laz"), doc = "`LazChunkSize::Variable`.")]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "laz")]
+    /// # {
+    /// use std::io::Cursor;
+    /// use las::{writer::LazChunkSize, Builder, Writer, WriterOptions};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format.is_compressed = true;
+    /// let options = WriterOptions::default().with_laz_chunk_size(LazChunkSize::Variable);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// writer.end_chunk().unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// # }
+    /// ```
+    pub fn end_chunk(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.point_writer.end_chunk()
+    }
+
     /// Closes this writer and returns its inner `Write`, seeked to the beginning of the las data.
     ///
     /// # Examples
@@ -447,10 +741,34 @@ impl<W: 'static + Seek + std::io::Write + Send + Sync> Drop for Writer<W> {
     }
 }
 
+fn sorted<W: 'static + std::io::Write + Seek + Send>(
+    point_writer: Box<dyn WritePoint<W> + Send>,
+    options: WriterOptions,
+) -> Box<dyn WritePoint<W> + Send> {
+    if let Some(space_filling_curve) = options.space_filling_curve {
+        Box::new(sort::PointWriter::new(
+            point_writer,
+            space_filling_curve,
+            options.sort_buffer_len,
+            options.sort_directory.unwrap_or_else(std::env::temp_dir),
+        ))
+    } else {
+        point_writer
+    }
+}
+
+fn is_laz(path: &Path) -> bool {
+    cfg!(feature = "laz")
+        && path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("laz"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{header::Builder, point::Format, Version};
+    use crate::{header::Builder, point::Format, PointDataBuilder, Version};
     use std::io::Cursor;
 
     fn writer(format: Format, version: Version) -> Writer<Cursor<Vec<u8>>> {
@@ -526,4 +844,104 @@ mod tests {
         let got = pd.points().next().unwrap().unwrap();
         assert_eq!(point, got);
     }
+
+    #[test]
+    fn coordinate_transform() {
+        use crate::{crs::GeoTiffCrs, reproject::Affine, Reader};
+
+        let crs = Crs::GeoTiff(GeoTiffCrs::default().with_projected_crs(32610));
+        let header = Builder::from((1, 2)).into_header().unwrap();
+        let mut writer = Writer::with_coordinate_transform(
+            Cursor::new(Vec::new()),
+            header,
+            WriterOptions::default(),
+            Affine::translation(1., 2., 3.),
+            Some(crs),
+        )
+        .unwrap();
+        writer.write_point(Point::default()).unwrap();
+        let points = PointDataBuilder::new()
+            .for_header(writer.header())
+            .build_from_points([Point {
+                x: 10.,
+                ..Default::default()
+            }])
+            .unwrap();
+        writer.write_points(&points).unwrap();
+
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let crs = reader.header().get_geotiff_crs().unwrap().unwrap();
+        assert_eq!(Some(32610), crs.get_projected_crs_geo_key_value());
+        assert_eq!(11., reader.header().bounds().max.x);
+        let points = reader.read_all().unwrap();
+        assert_eq!(vec![1., 11.], points.x().collect::<Vec<_>>());
+        assert_eq!(vec![2., 2.], points.y().collect::<Vec<_>>());
+        assert_eq!(vec![3., 3.], points.z().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn coordinate_transform_requantizes_into_header_transforms() {
+        use crate::{reproject::Utm, Reader, Transform, Vector};
+
+        let geographic = Vector {
+            x: Transform {
+                scale: 1e-7,
+                offset: 0.,
+            },
+            y: Transform {
+                scale: 1e-7,
+                offset: 0.,
+            },
+            z: Transform {
+                scale: 0.01,
+                offset: 0.,
+            },
+        };
+        let points = PointDataBuilder::new()
+            .with_format(Format::new(0).unwrap())
+            .with_transforms(geographic)
+            .build_from_points((0..3).map(|i| Point {
+                x: -123.0123456 + f64::from(i) * 1e-6,
+                y: 45.0123456,
+                ..Default::default()
+            }))
+            .unwrap();
+        let (mut x, mut y, mut z): (Vec<f64>, Vec<f64>, Vec<f64>) = (
+            points.x().collect(),
+            points.y().collect(),
+            points.z().collect(),
+        );
+        let utm = Utm::from_geographic(10, false).unwrap();
+        utm.transform(&mut x, &mut y, &mut z).unwrap();
+
+        let mut builder = Builder::from((1, 2));
+        builder.transforms = Vector {
+            x: Transform {
+                scale: 0.01,
+                offset: 0.,
+            },
+            ..geographic
+        };
+        builder.transforms.y = builder.transforms.x;
+        let header = builder.into_header().unwrap();
+        let mut writer = Writer::with_coordinate_transform(
+            Cursor::new(Vec::new()),
+            header,
+            WriterOptions::default(),
+            utm,
+            None,
+        )
+        .unwrap();
+        writer.write_points(&points).unwrap();
+        let points = Reader::new(writer.into_inner().unwrap())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        for (expected, actual) in x.iter().zip(points.x()) {
+            assert!((expected - actual).abs() <= 0.005);
+        }
+        for (expected, actual) in y.iter().zip(points.y()) {
+            assert!((expected - actual).abs() <= 0.005);
+        }
+    }
 }
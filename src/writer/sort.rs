@@ -0,0 +1,331 @@
+//! Sorts points along a space-filling curve before handing them to another point writer.
+
+use super::WritePoint;
+use crate::{Error, Header, Point, PointDataBuilder, Result, SpaceFillingCurve};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Number of records handed to the inner writer at once when merging runs.
+const MERGE_BATCH_LEN: usize = 10_000;
+
+/// The most runs merged at once, to stay well under open file limits.
+const MAX_MERGE_FAN_IN: usize = 64;
+
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) struct PointWriter<W: Write> {
+    inner: Box<dyn WritePoint<W> + Send>,
+    curve: SpaceFillingCurve,
+    buffer: Vec<u8>,
+    buffer_len: usize,
+    runs: Vec<PathBuf>,
+    directory: PathBuf,
+}
+
+impl<W: Write> PointWriter<W> {
+    pub(crate) fn new(
+        inner: Box<dyn WritePoint<W> + Send>,
+        curve: SpaceFillingCurve,
+        buffer_len: usize,
+        directory: PathBuf,
+    ) -> PointWriter<W> {
+        PointWriter {
+            inner,
+            curve,
+            buffer: Vec::new(),
+            buffer_len: buffer_len.max(1),
+            runs: Vec::new(),
+            directory,
+        }
+    }
+
+    fn record_len(&self) -> usize {
+        usize::from(self.inner.header().point_format().len())
+    }
+
+    fn buffered(&self) -> usize {
+        self.buffer.len() / self.record_len()
+    }
+
+    fn sorted_buffer(&mut self) -> Result<Vec<u8>> {
+        let bytes = std::mem::take(&mut self.buffer);
+        let mut points = PointDataBuilder::new()
+            .for_header(self.inner.header())
+            .build_from_bytes(bytes)?;
+        points.sort_by_space(self.curve);
+        Ok(std::mem::take(points.take_bytes_mut()))
+    }
+
+    fn new_run(&mut self) -> Result<BufWriter<File>> {
+        let path = self.directory.join(format!(
+            "las-sort-{}-{}.run",
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.runs.push(path.clone());
+        Ok(BufWriter::new(File::create(path)?))
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        let bytes = self.sorted_buffer()?;
+        let mut write = self.new_run()?;
+        write.write_all(&bytes)?;
+        write.flush()?;
+        Ok(())
+    }
+
+    fn spill_if_full(&mut self) -> Result<()> {
+        if self.buffered() >= self.buffer_len {
+            self.spill()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Merges the runs into the inner writer.
+    ///
+    /// If there are more than [MAX_MERGE_FAN_IN] runs, consecutive groups of
+    /// runs are first merged into longer runs, pass by pass, so points that
+    /// sort equal keep the order they were written in.
+    fn merge(&mut self) -> Result<()> {
+        let record_len = self.record_len();
+        while self.runs.len() > MAX_MERGE_FAN_IN {
+            let n = self.runs.len();
+            for start in (0..n).step_by(MAX_MERGE_FAN_IN) {
+                let end = (start + MAX_MERGE_FAN_IN).min(n);
+                let mut write = self.new_run()?;
+                merge_runs(
+                    &self.runs[start..end],
+                    record_len,
+                    self.curve,
+                    |bytes, _| write.write_all(bytes).map_err(Error::from),
+                )?;
+                write.flush()?;
+                for path in &self.runs[start..end] {
+                    remove_run(path);
+                }
+            }
+            let _ = self.runs.drain(..n);
+        }
+        merge_runs(&self.runs, record_len, self.curve, |bytes, point_count| {
+            self.inner.write_bytes(bytes, point_count)
+        })
+    }
+
+    fn remove_runs(&mut self) {
+        for path in self.runs.drain(..) {
+            remove_run(&path);
+        }
+    }
+}
+
+/// Merges sorted runs, handing the merged records to `write` in batches.
+fn merge_runs(
+    paths: &[PathBuf],
+    record_len: usize,
+    curve: SpaceFillingCurve,
+    mut write: impl FnMut(&[u8], u64) -> Result<()>,
+) -> Result<()> {
+    let mut runs = Vec::with_capacity(paths.len());
+    let mut records = Vec::with_capacity(paths.len());
+    let mut heap = BinaryHeap::with_capacity(paths.len());
+    for (i, path) in paths.iter().enumerate() {
+        let mut read = BufReader::new(File::open(path)?);
+        let mut record = vec![0; record_len];
+        read.read_exact(&mut record)?;
+        heap.push(Reverse((curve.record_index(&record), i)));
+        runs.push(read);
+        records.push(record);
+    }
+    let mut batch = Vec::with_capacity(MERGE_BATCH_LEN * record_len);
+    while let Some(Reverse((_, i))) = heap.pop() {
+        batch.extend_from_slice(&records[i]);
+        if batch.len() == MERGE_BATCH_LEN * record_len {
+            write(&batch, MERGE_BATCH_LEN as u64)?;
+            batch.clear();
+        }
+        match runs[i].read_exact(&mut records[i]) {
+            Ok(()) => heap.push(Reverse((curve.record_index(&records[i]), i))),
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if !batch.is_empty() {
+        write(&batch, (batch.len() / record_len) as u64)?;
+    }
+    Ok(())
+}
+
+/// Removes a run, which may already be gone if it was merged into a longer one.
+fn remove_run(path: &Path) {
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => log::warn!("could not remove sort run {}: {}", path.display(), err),
+    }
+}
+
+impl<W: Write> WritePoint<W> for PointWriter<W> {
+    fn write_point(&mut self, point: Point) -> Result<()> {
+        let header = self.inner.header_mut();
+        header.add_point(&point);
+        point
+            .into_raw(header.transforms())
+            .and_then(|raw_point| raw_point.write_to(&mut self.buffer, header.point_format()))?;
+        self.spill_if_full()
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8], _point_count: u64) -> Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        self.spill_if_full()
+    }
+
+    fn into_inner(self: Box<Self>) -> W {
+        let mut sorter = *self;
+        sorter.remove_runs();
+        let inner = std::mem::replace(&mut sorter.inner, Box::new(super::ClosedPointWriter));
+        inner.into_inner()
+    }
+
+    fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    fn header(&self) -> &Header {
+        self.inner.header()
+    }
+
+    fn header_mut(&mut self) -> &mut Header {
+        self.inner.header_mut()
+    }
+
+    fn end_chunk(&mut self) -> Result<()> {
+        Err(Error::EndChunkWhileSorting)
+    }
+
+    fn done(&mut self) -> Result<()> {
+        let result = if self.runs.is_empty() {
+            let bytes = self.sorted_buffer()?;
+            let point_count = (bytes.len() / self.record_len()) as u64;
+            self.inner.write_bytes(&bytes, point_count)
+        } else {
+            if !self.buffer.is_empty() {
+                self.spill()?;
+            }
+            self.merge()
+        };
+        self.remove_runs();
+        result?;
+        self.inner.done()
+    }
+}
+
+impl<W: Write> Drop for PointWriter<W> {
+    fn drop(&mut self) {
+        self.remove_runs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builder, Point, Reader, SpaceFillingCurve, Writer, WriterOptions};
+    use std::io::Cursor;
+
+    fn write(buffer_len: usize, points: &[Point]) -> Vec<Point> {
+        let options = WriterOptions::default()
+            .with_space_filling_curve(SpaceFillingCurve::Hilbert)
+            .with_sort_buffer_len(buffer_len);
+        write_with_options(options, points)
+    }
+
+    fn write_with_options(options: WriterOptions, points: &[Point]) -> Vec<Point> {
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            Builder::default().into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        for point in points {
+            writer.write_point(point.clone()).unwrap();
+        }
+        let cursor = writer.into_inner().unwrap();
+        let mut reader = Reader::new(Cursor::new(cursor.into_inner())).unwrap();
+        assert_eq!(points.len() as u64, reader.header().number_of_points());
+        reader
+            .read_all()
+            .unwrap()
+            .points()
+            .map(|point| point.unwrap())
+            .collect()
+    }
+
+    fn points() -> Vec<Point> {
+        (0..100)
+            .map(|i| Point {
+                x: f64::from((i * 37) % 10) / 100.,
+                y: f64::from((i * 53) % 10) / 100.,
+                z: f64::from(i),
+                return_number: 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn is_sorted(points: &[Point]) -> bool {
+        let curve = SpaceFillingCurve::Hilbert;
+        let key = |point: &Point| {
+            curve.index(
+                (point.x * 1000.).round() as i32,
+                (point.y * 1000.).round() as i32,
+            )
+        };
+        points.windows(2).all(|pair| key(&pair[0]) <= key(&pair[1]))
+    }
+
+    #[test]
+    fn in_memory() {
+        let points = write(1_000, &points());
+        assert!(is_sorted(&points));
+    }
+
+    #[test]
+    fn merge_runs() {
+        let expected = write(1_000, &points());
+        let points = write(7, &points());
+        assert!(is_sorted(&points));
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn merge_runs_in_passes() {
+        let expected = write(1_000, &points());
+        let points = write(1, &points());
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn sort_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = WriterOptions::default()
+            .with_space_filling_curve(SpaceFillingCurve::Hilbert)
+            .with_sort_buffer_len(7)
+            .with_sort_directory(dir.path());
+        let expected = write(1_000, &points());
+        assert_eq!(expected, write_with_options(options, &points()));
+        assert_eq!(0, std::fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn end_chunk() {
+        let options = WriterOptions::default().with_space_filling_curve(SpaceFillingCurve::Morton);
+        let mut writer =
+            Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+        assert!(writer.end_chunk().is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Write points into a sequence of files.
+
+use super::{is_laz, Writer, WriterOptions};
+use crate::{Error, Header, PointData, PointDataBuilder, Result};
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::PathBuf,
+};
+
+/// When a [SplitWriter] starts a new file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitBy {
+    /// Start a new file after this many points.
+    Points(u64),
+
+    /// Start a new file before the file would grow past this many bytes.
+    ///
+    /// The size is computed from the uncompressed point records, so laz
+    /// files will be smaller than the limit. Every file gets at least one
+    /// point, even if that point doesn't fit.
+    Bytes(u64),
+
+    /// Start a new file when the gps time enters a new window of this many
+    /// seconds.
+    ///
+    /// Windows are aligned to multiples of their length, e.g. a window of
+    /// `10.` puts times `20.` through `29.999...` into one file.
+    GpsTime(f64),
+
+    /// Start a new file when the point source id changes.
+    PointSourceId,
+}
+
+/// Writes points into a sequence of las or laz files.
+///
+/// A new file is started whenever the [SplitBy] criterion says so. Points
+/// are never reordered, so e.g. if a flight line shows up again after
+/// another one, it gets a new file.
+///
+/// The output path for each file comes from a template, in which these
+/// placeholders are replaced:
+///
+/// - `{index}`: the index of the file in the sequence, starting at zero
+/// - `{point_source_id}`: the point source id of the file's points, when
+///   splitting by [SplitBy::PointSourceId]
+///
+/// As with [Writer::from_path], files whose path ends with `.laz` are
+/// compressed. Every file gets a copy of the header, including its vlrs,
+/// evlrs and crs. The points passed to [SplitWriter::write_points] must
+/// have the header's point format and transforms.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::SplitBy, Reader, SplitWriter};
+///
+/// let directory = tempfile::tempdir().unwrap();
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut split_writer = SplitWriter::new(
+///     directory.path().join("{index}.las").to_str().unwrap(),
+///     reader.header().clone(),
+///     SplitBy::Points(50),
+/// )
+/// .unwrap();
+/// split_writer.write_points(&reader.read_all().unwrap()).unwrap();
+/// let parts = split_writer.close().unwrap();
+/// assert_eq!(3, parts.len());
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct SplitWriter {
+    closed: bool,
+    header: Header,
+    template: String,
+    split_by: SplitBy,
+    max_points: u64,
+    options: WriterOptions,
+    current: Option<Current>,
+    parts: Vec<Part>,
+}
+
+/// A file written by a [SplitWriter].
+#[derive(Clone, Debug)]
+pub struct Part {
+    /// The index of this file in the sequence.
+    pub index: usize,
+
+    /// The path this file was written to.
+    pub path: PathBuf,
+
+    /// The header of the file, with its final point counts and bounds.
+    pub header: Header,
+}
+
+struct Current {
+    key: Option<i64>,
+    path: PathBuf,
+    writer: Writer<BufWriter<File>>,
+}
+
+impl SplitWriter {
+    /// Creates a new split writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::SplitBy, Header, SplitWriter};
+    /// let split_writer = SplitWriter::new("{index}.las", Header::default(), SplitBy::Points(100)).unwrap();
+    ///
+    /// // The default point format doesn't have gps time
+    /// assert!(SplitWriter::new("{index}.las", Header::default(), SplitBy::GpsTime(1.)).is_err());
+    /// ```
+    pub fn new(
+        template: impl Into<String>,
+        header: Header,
+        split_by: SplitBy,
+    ) -> Result<SplitWriter> {
+        let max_points = match split_by {
+            SplitBy::Points(n) => n.max(1),
+            SplitBy::Bytes(n) => {
+                let mut overhead = Vec::new();
+                header.write_to(&mut overhead)?;
+                let overhead = overhead.len() as u64
+                    + header.point_padding().len() as u64
+                    + header
+                        .evlrs()
+                        .iter()
+                        .map(|evlr| evlr.len(true) as u64)
+                        .sum::<u64>();
+                let record_len = u64::from(header.point_format().len());
+                (n.saturating_sub(overhead) / record_len).max(1)
+            }
+            SplitBy::GpsTime(window) => {
+                if !window.is_finite() || window <= 0. {
+                    return Err(Error::InvalidGpsTimeWindow(window));
+                }
+                if !header.point_format().has_gps_time {
+                    return Err(Error::NoGpsTime(*header.point_format()));
+                }
+                u64::MAX
+            }
+            SplitBy::PointSourceId => u64::MAX,
+        };
+        Ok(SplitWriter {
+            closed: false,
+            header,
+            template: template.into(),
+            split_by,
+            max_points,
+            options: WriterOptions::default(),
+            current: None,
+            parts: Vec::new(),
+        })
+    }
+
+    /// Sets the options used for each file's [Writer].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::SplitBy, Header, SplitWriter, WriterOptions};
+    /// let split_writer = SplitWriter::new("{index}.las", Header::default(), SplitBy::Points(100))
+    ///     .unwrap()
+    ///     .with_writer_options(WriterOptions::default());
+    /// ```
+    pub fn with_writer_options(mut self, options: WriterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Writes points, starting new files as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::SplitBy, Reader, SplitWriter};
+    ///
+    /// let directory = tempfile::tempdir().unwrap();
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut split_writer = SplitWriter::new(
+    ///     directory.path().join("{point_source_id}-{index}.las").to_str().unwrap(),
+    ///     reader.header().clone(),
+    ///     SplitBy::PointSourceId,
+    /// )
+    /// .unwrap();
+    /// split_writer.write_points(&reader.read_points(100).unwrap()).unwrap();
+    /// split_writer.close().unwrap();
+    /// ```
+    pub fn write_points(&mut self, points: &PointData) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        let keys: Vec<i64> = match self.split_by {
+            SplitBy::Points(_) | SplitBy::Bytes(_) => Vec::new(),
+            SplitBy::GpsTime(window) => points
+                .gps_time()
+                .ok_or(Error::NoGpsTime(*points.format()))?
+                .map(|gps_time| (gps_time / window).floor() as i64)
+                .collect(),
+            SplitBy::PointSourceId => points.point_source_id().map(i64::from).collect(),
+        };
+        let record_len = points.record_len();
+        let mut start = 0;
+        while start < points.len() {
+            let key = keys.get(start).copied();
+            let end = if let Some(key) = key {
+                keys[start..]
+                    .iter()
+                    .position(|&k| k != key)
+                    .map_or(points.len(), |n| start + n)
+            } else {
+                points.len()
+            };
+            let max_points = self.max_points;
+            let writer = self.writer(key)?;
+            let room = usize::try_from(max_points - writer.header().number_of_points())
+                .unwrap_or(usize::MAX);
+            let end = end.min(start.saturating_add(room));
+            let run = PointDataBuilder::new()
+                .with_format(*points.format())
+                .with_transforms(*points.transforms())
+                .build_from_bytes(
+                    points.raw_bytes()[start * record_len..end * record_len].to_vec(),
+                )?;
+            writer.write_points(&run)?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Closes the last file and returns all of them, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::SplitBy, Header, SplitWriter};
+    /// let mut split_writer = SplitWriter::new("{index}.las", Header::default(), SplitBy::Points(100)).unwrap();
+    /// assert!(split_writer.close().unwrap().is_empty());
+    /// assert!(split_writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<Vec<Part>> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.closed = true;
+        self.finish_current()?;
+        Ok(std::mem::take(&mut self.parts))
+    }
+
+    fn writer(&mut self, key: Option<i64>) -> Result<&mut Writer<BufWriter<File>>> {
+        let rotate = self.current.as_ref().is_some_and(|current| {
+            current.key != key || current.writer.header().number_of_points() >= self.max_points
+        });
+        if rotate {
+            self.finish_current()?;
+        }
+        if self.current.is_none() {
+            let mut path = self
+                .template
+                .replace("{index}", &self.parts.len().to_string());
+            if let (SplitBy::PointSourceId, Some(key)) = (self.split_by, key) {
+                path = path.replace("{point_source_id}", &key.to_string());
+            }
+            let path = PathBuf::from(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut header = self.header.clone();
+            header.point_format_mut().is_compressed = is_laz(&path);
+            let writer = Writer::with_options(
+                BufWriter::new(File::create(&path)?),
+                header,
+                self.options.clone(),
+            )?;
+            self.current = Some(Current { key, path, writer });
+        }
+        Ok(&mut self
+            .current
+            .as_mut()
+            .expect("the writer was just opened")
+            .writer)
+    }
+
+    fn finish_current(&mut self) -> Result<()> {
+        if let Some(mut current) = self.current.take() {
+            current.writer.close()?;
+            self.parts.push(Part {
+                index: self.parts.len(),
+                path: current.path,
+                header: current.writer.header().clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SplitWriter {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close().expect("Error when dropping the split writer");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Point, Reader, Transform, Vector, Vlr};
+    use tempfile::TempDir;
+
+    fn header(format: u8) -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.transforms = Vector {
+            x: Transform::default(),
+            y: Transform::default(),
+            z: Transform::default(),
+        };
+        builder.vlrs.push(Vlr {
+            user_id: "las-rs".to_string(),
+            record_id: 42,
+            description: "a test vlr".to_string(),
+            data: vec![1, 2, 3],
+        });
+        builder.into_header().unwrap()
+    }
+
+    fn points(header: &Header, points: impl IntoIterator<Item = Point>) -> PointData {
+        PointDataBuilder::new()
+            .for_header(header)
+            .build_from_points(points)
+            .unwrap()
+    }
+
+    fn template(directory: &TempDir, name: &str) -> String {
+        directory.path().join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn points_across_batches() {
+        let directory = TempDir::new().unwrap();
+        let header = header(0);
+        let mut split_writer = SplitWriter::new(
+            template(&directory, "{index}.las"),
+            header.clone(),
+            SplitBy::Points(3),
+        )
+        .unwrap();
+        for _ in 0..2 {
+            split_writer
+                .write_points(&points(&header, (0..4).map(|_| Point::default())))
+                .unwrap();
+        }
+        let parts = split_writer.close().unwrap();
+        let counts: Vec<_> = parts.iter().map(|p| p.header.number_of_points()).collect();
+        assert_eq!(vec![3, 3, 2], counts);
+        assert_eq!(directory.path().join("2.las"), parts[2].path);
+        for part in parts {
+            let reader = Reader::from_path(&part.path).unwrap();
+            assert_eq!(
+                part.header.number_of_points(),
+                reader.header().number_of_points()
+            );
+            assert_eq!(header.vlrs(), reader.header().vlrs());
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let directory = TempDir::new().unwrap();
+        let header = header(0);
+        let mut overhead = Vec::new();
+        header.write_to(&mut overhead).unwrap();
+        let limit = overhead.len() as u64 + 2 * u64::from(header.point_format().len());
+        let mut split_writer = SplitWriter::new(
+            template(&directory, "{index}.las"),
+            header.clone(),
+            SplitBy::Bytes(limit),
+        )
+        .unwrap();
+        split_writer
+            .write_points(&points(&header, (0..5).map(|_| Point::default())))
+            .unwrap();
+        let parts = split_writer.close().unwrap();
+        assert_eq!(3, parts.len());
+        for part in parts {
+            assert!(fs::metadata(&part.path).unwrap().len() <= limit);
+        }
+    }
+
+    #[test]
+    fn gps_time() {
+        let directory = TempDir::new().unwrap();
+        let header = header(1);
+        let mut split_writer = SplitWriter::new(
+            template(&directory, "{index}.las"),
+            header.clone(),
+            SplitBy::GpsTime(10.),
+        )
+        .unwrap();
+        let gps_times = [1., 9.5, 10., 25., 29.];
+        split_writer
+            .write_points(&points(
+                &header,
+                gps_times.iter().map(|&gps_time| Point {
+                    gps_time: Some(gps_time),
+                    ..Default::default()
+                }),
+            ))
+            .unwrap();
+        let parts = split_writer.close().unwrap();
+        let counts: Vec<_> = parts.iter().map(|p| p.header.number_of_points()).collect();
+        assert_eq!(vec![2, 1, 2], counts);
+    }
+
+    #[test]
+    fn point_source_id() {
+        let directory = TempDir::new().unwrap();
+        let header = header(0);
+        let mut split_writer = SplitWriter::new(
+            template(&directory, "{point_source_id}-{index}.las"),
+            header.clone(),
+            SplitBy::PointSourceId,
+        )
+        .unwrap();
+        for ids in [[1, 1], [1, 2]] {
+            split_writer
+                .write_points(&points(
+                    &header,
+                    ids.iter().map(|&point_source_id| Point {
+                        point_source_id,
+                        ..Default::default()
+                    }),
+                ))
+                .unwrap();
+        }
+        let parts = split_writer.close().unwrap();
+        assert_eq!(2, parts.len());
+        assert_eq!(directory.path().join("1-0.las"), parts[0].path);
+        assert_eq!(3, parts[0].header.number_of_points());
+        assert_eq!(directory.path().join("2-1.las"), parts[1].path);
+    }
+
+    #[test]
+    fn copies_crs() {
+        let directory = TempDir::new().unwrap();
+        let mut header = header(6);
+        header.set_wkt_crs(b"a wkt".to_vec()).unwrap();
+        let mut split_writer = SplitWriter::new(
+            template(&directory, "{index}.las"),
+            header.clone(),
+            SplitBy::Points(1),
+        )
+        .unwrap();
+        split_writer
+            .write_points(&points(
+                &header,
+                (0..2).map(|_| Point {
+                    gps_time: Some(0.),
+                    ..Default::default()
+                }),
+            ))
+            .unwrap();
+        for part in split_writer.close().unwrap() {
+            let reader = Reader::from_path(&part.path).unwrap();
+            assert_eq!(Some(&b"a wkt"[..]), reader.header().get_wkt_crs_bytes());
+        }
+    }
+}
@@ -0,0 +1,592 @@
+//! Write points into a regular grid of files.
+
+use super::{is_laz, Writer, WriterOptions};
+use crate::{Error, Header, PointData, PointDataBuilder, Reader, Result};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_MAX_OPEN_FILES: usize = 64;
+const COMPRESSION_CHUNK_SIZE: u64 = 1_000_000;
+
+/// Writes points into a regular grid of las or laz files.
+///
+/// Each point goes to the tile that contains it, and — if a buffer is set —
+/// to every other tile whose extent, grown by the buffer, contains it. Tile
+/// writers are opened the first time a point lands in them. When more than
+/// `max_open_files` tiles are open, the least recently used tile is closed,
+/// and it's re-opened and appended to if it gets more points later.
+///
+/// The output path for each tile comes from a template, in which these
+/// placeholders are replaced:
+///
+/// - `{column}` and `{row}`: the tile's index in the grid
+/// - `{x}` and `{y}`: the coordinates of the tile's lower left corner
+///
+/// As with [Writer::from_path], tiles whose path ends with `.laz` are
+/// compressed. Since laz data can't be appended to, those tiles are staged
+/// as uncompressed las files next to their final path, and compressed
+/// when the tile writer is closed.
+///
+/// If the [WriterOptions] sort points along a space-filling curve, every
+/// tile is staged, and each is sorted as a whole when the tile writer is
+/// closed, even if it was closed and re-opened along the way.
+///
+/// The header is used as the template for every tile. The points passed to
+/// [TileWriter::write_points] must have the header's point format and
+/// transforms.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Reader, TileWriter};
+///
+/// let directory = tempfile::tempdir().unwrap();
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut tile_writer = TileWriter::new(
+///     directory.path().join("{column}_{row}.las").to_str().unwrap(),
+///     reader.header().clone(),
+///     500.,
+/// )
+/// .unwrap()
+/// .with_max_open_files(8);
+/// tile_writer.write_points(&reader.read_all().unwrap()).unwrap();
+/// let tiles = tile_writer.close().unwrap();
+/// let number_of_points: u64 = tiles.iter().map(|t| t.header.number_of_points()).sum();
+/// assert_eq!(reader.header().number_of_points(), number_of_points);
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct TileWriter {
+    closed: bool,
+    header: Header,
+    template: String,
+    tile_size: f64,
+    origin: (f64, f64),
+    buffer: f64,
+    max_open_files: usize,
+    options: WriterOptions,
+    tick: u64,
+    tiles: BTreeMap<(i64, i64), TileState>,
+}
+
+/// A tile written by a [TileWriter].
+#[derive(Clone, Debug)]
+pub struct Tile {
+    /// The column of this tile in the grid.
+    pub column: i64,
+
+    /// The row of this tile in the grid.
+    pub row: i64,
+
+    /// The path this tile was written to.
+    pub path: PathBuf,
+
+    /// The header of the tile, with its final point counts and bounds.
+    pub header: Header,
+}
+
+struct TileState {
+    path: PathBuf,
+    staged_path: Option<PathBuf>,
+    writer: Option<Writer<BufWriter<File>>>,
+    suspended: Option<(Header, u64)>,
+    last_used: u64,
+}
+
+impl TileWriter {
+    /// Creates a new tile writer.
+    ///
+    /// The grid's origin defaults to `(0, 0)`, and there is no buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter};
+    /// let tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.).unwrap();
+    /// assert!(TileWriter::new("{x}_{y}.las", Header::default(), 0.).is_err());
+    /// ```
+    pub fn new(template: impl Into<String>, header: Header, tile_size: f64) -> Result<TileWriter> {
+        if !tile_size.is_finite() || tile_size <= 0. {
+            return Err(Error::InvalidTileSize(tile_size));
+        }
+        Ok(TileWriter {
+            closed: false,
+            header,
+            template: template.into(),
+            tile_size,
+            origin: (0., 0.),
+            buffer: 0.,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            options: WriterOptions::default(),
+            tick: 0,
+            tiles: BTreeMap::new(),
+        })
+    }
+
+    /// Sets the origin of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter};
+    /// let tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.)
+    ///     .unwrap()
+    ///     .with_origin(500., 500.);
+    /// ```
+    pub fn with_origin(mut self, x: f64, y: f64) -> Self {
+        self.origin = (x, y);
+        self
+    }
+
+    /// Sets the buffer distance.
+    ///
+    /// Points within this distance of a tile are also written to that tile.
+    /// Negative distances are treated as zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter};
+    /// let tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.)
+    ///     .unwrap()
+    ///     .with_buffer(20.);
+    /// ```
+    pub fn with_buffer(mut self, buffer: f64) -> Self {
+        self.buffer = buffer.max(0.);
+        self
+    }
+
+    /// Sets the maximum number of tile files that are open at once.
+    ///
+    /// Values less than one are treated as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter};
+    /// let tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.)
+    ///     .unwrap()
+    ///     .with_max_open_files(16);
+    /// ```
+    pub fn with_max_open_files(mut self, max_open_files: usize) -> Self {
+        self.max_open_files = max_open_files.max(1);
+        self
+    }
+
+    /// Sets the options used for each tile's [Writer].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter, WriterOptions};
+    /// let tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.)
+    ///     .unwrap()
+    ///     .with_writer_options(WriterOptions::default());
+    /// ```
+    pub fn with_writer_options(mut self, options: WriterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Writes points to their tiles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, TileWriter};
+    ///
+    /// let directory = tempfile::tempdir().unwrap();
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut tile_writer = TileWriter::new(
+    ///     directory.path().join("{x}_{y}.las").to_str().unwrap(),
+    ///     reader.header().clone(),
+    ///     1000.,
+    /// )
+    /// .unwrap();
+    /// tile_writer.write_points(&reader.read_points(100).unwrap()).unwrap();
+    /// tile_writer.close().unwrap();
+    /// ```
+    pub fn write_points(&mut self, points: &PointData) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        let mut tiles: BTreeMap<(i64, i64), Vec<u8>> = BTreeMap::new();
+        let records = points.raw_bytes().chunks_exact(points.record_len());
+        for ((x, y), record) in points.x().zip(points.y()).zip(records) {
+            let (columns, rows) = self.indices(x, y);
+            for column in columns {
+                for row in rows.clone() {
+                    tiles
+                        .entry((column, row))
+                        .or_default()
+                        .extend_from_slice(record);
+                }
+            }
+        }
+        for (key, bytes) in tiles {
+            let points = PointDataBuilder::new()
+                .with_format(*points.format())
+                .with_transforms(*points.transforms())
+                .build_from_bytes(bytes)?;
+            self.writer(key)?.write_points(&points)?;
+        }
+        Ok(())
+    }
+
+    /// Closes every tile and returns them, ordered by column and row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TileWriter};
+    /// let mut tile_writer = TileWriter::new("{x}_{y}.las", Header::default(), 1000.).unwrap();
+    /// assert!(tile_writer.close().unwrap().is_empty());
+    /// assert!(tile_writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<Vec<Tile>> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.closed = true;
+        let mut tiles = Vec::with_capacity(self.tiles.len());
+        for (&(column, row), state) in &mut self.tiles {
+            let header = if let Some(mut writer) = state.writer.take() {
+                writer.close()?;
+                writer.header().clone()
+            } else if let Some((header, _)) = state.suspended.take() {
+                header
+            } else {
+                continue;
+            };
+            let header = if let Some(staged_path) = state.staged_path.take() {
+                let header = finish(&staged_path, &state.path, self.options.clone())?;
+                fs::remove_file(staged_path)?;
+                header
+            } else {
+                header
+            };
+            tiles.push(Tile {
+                column,
+                row,
+                path: state.path.clone(),
+                header,
+            });
+        }
+        Ok(tiles)
+    }
+
+    fn indices(
+        &self,
+        x: f64,
+        y: f64,
+    ) -> (std::ops::RangeInclusive<i64>, std::ops::RangeInclusive<i64>) {
+        let index = |n: f64, origin: f64| ((n - origin) / self.tile_size).floor() as i64;
+        (
+            index(x - self.buffer, self.origin.0)..=index(x + self.buffer, self.origin.0),
+            index(y - self.buffer, self.origin.1)..=index(y + self.buffer, self.origin.1),
+        )
+    }
+
+    fn path(&self, (column, row): (i64, i64)) -> PathBuf {
+        let x = self.origin.0 + column as f64 * self.tile_size;
+        let y = self.origin.1 + row as f64 * self.tile_size;
+        self.template
+            .replace("{column}", &column.to_string())
+            .replace("{row}", &row.to_string())
+            .replace("{x}", &x.to_string())
+            .replace("{y}", &y.to_string())
+            .into()
+    }
+
+    fn writer(&mut self, key: (i64, i64)) -> Result<&mut Writer<BufWriter<File>>> {
+        self.tick += 1;
+        let is_open = self
+            .tiles
+            .get(&key)
+            .is_some_and(|state| state.writer.is_some());
+        if !is_open {
+            let open = self
+                .tiles
+                .values()
+                .filter(|state| state.writer.is_some())
+                .count();
+            if open >= self.max_open_files {
+                self.suspend_least_recently_used()?;
+            }
+        }
+        if !self.tiles.contains_key(&key) {
+            let path = self.path(key);
+            let is_staged = is_laz(&path) || self.options.space_filling_curve.is_some();
+            let staged_path = is_staged.then(|| {
+                let mut staged_path = path.clone().into_os_string();
+                staged_path.push(".part.las");
+                PathBuf::from(staged_path)
+            });
+            let _ = self.tiles.insert(
+                key,
+                TileState {
+                    path,
+                    staged_path,
+                    writer: None,
+                    suspended: None,
+                    last_used: 0,
+                },
+            );
+        }
+        let state = self
+            .tiles
+            .get_mut(&key)
+            .expect("the tile was just inserted");
+        state.last_used = self.tick;
+        if state.writer.is_none() {
+            let path = state.staged_path.as_ref().unwrap_or(&state.path);
+            let writer = if let Some((header, end_of_points)) = state.suspended.take() {
+                let mut file = OpenOptions::new().write(true).open(path)?;
+                let _ = file.seek(SeekFrom::Start(end_of_points))?;
+                Writer::resume(BufWriter::new(file), header)
+            } else {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut header = self.header.clone();
+                header.point_format_mut().is_compressed = false;
+                // Staged tiles are sorted when they're finished
+                let options = WriterOptions {
+                    space_filling_curve: None,
+                    ..self.options.clone()
+                };
+                Writer::with_options(BufWriter::new(File::create(path)?), header, options)?
+            };
+            state.writer = Some(writer);
+        }
+        Ok(state.writer.as_mut().expect("the writer was just opened"))
+    }
+
+    fn suspend_least_recently_used(&mut self) -> Result<()> {
+        if let Some(state) = self
+            .tiles
+            .values_mut()
+            .filter(|state| state.writer.is_some())
+            .min_by_key(|state| state.last_used)
+        {
+            let mut writer = state.writer.take().expect("only open tiles are considered");
+            writer.close()?;
+            let header = writer.header().clone();
+            let end_of_points = header.end_of_point_data()?;
+            state.suspended = Some((header, end_of_points));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TileWriter {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close().expect("Error when dropping the tile writer");
+        }
+    }
+}
+
+fn finish(staged_path: &Path, path: &Path, options: WriterOptions) -> Result<Header> {
+    let mut reader = Reader::from_path(staged_path)?;
+    let mut header = reader.header().clone();
+    header.point_format_mut().is_compressed = is_laz(path);
+    let mut writer = Writer::with_options(BufWriter::new(File::create(path)?), header, options)?;
+    loop {
+        let points = reader.read_points(COMPRESSION_CHUNK_SIZE)?;
+        if points.is_empty() {
+            break;
+        }
+        writer.write_points(&points)?;
+    }
+    writer.close()?;
+    Ok(writer.header().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Point, SpaceFillingCurve, Transform, Vector};
+    use tempfile::TempDir;
+
+    fn header() -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(0).unwrap();
+        builder.transforms = Vector {
+            x: Transform::default(),
+            y: Transform::default(),
+            z: Transform::default(),
+        };
+        builder.into_header().unwrap()
+    }
+
+    fn points(coordinates: &[(f64, f64)]) -> PointData {
+        let header = header();
+        PointDataBuilder::new()
+            .for_header(&header)
+            .build_from_points(coordinates.iter().map(|&(x, y)| Point {
+                x,
+                y,
+                return_number: 1,
+                ..Default::default()
+            }))
+            .unwrap()
+    }
+
+    fn read(path: &Path) -> (Header, Vec<(f64, f64)>) {
+        let mut reader = Reader::from_path(path).unwrap();
+        let points = reader.read_all().unwrap();
+        (
+            reader.header().clone(),
+            points.x().zip(points.y()).collect(),
+        )
+    }
+
+    fn template(directory: &TempDir, extension: &str) -> String {
+        directory
+            .path()
+            .join(format!("{{column}}_{{row}}.{extension}"))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn grid() {
+        let directory = TempDir::new().unwrap();
+        let mut tile_writer = TileWriter::new(template(&directory, "las"), header(), 10.).unwrap();
+        tile_writer
+            .write_points(&points(&[
+                (1., 1.),
+                (11., 2.),
+                (3., 15.),
+                (-1., -1.),
+                (4., 4.),
+            ]))
+            .unwrap();
+        let tiles = tile_writer.close().unwrap();
+        let keys: Vec<_> = tiles.iter().map(|t| (t.column, t.row)).collect();
+        assert_eq!(vec![(-1, -1), (0, 0), (0, 1), (1, 0)], keys);
+
+        let tile = &tiles[1];
+        assert_eq!(directory.path().join("0_0.las"), tile.path);
+        let (header, coordinates) = read(&tile.path);
+        assert_eq!(2, header.number_of_points());
+        assert_eq!(1., header.bounds().min.x);
+        assert_eq!(4., header.bounds().max.y);
+        assert_eq!(vec![(1., 1.), (4., 4.)], coordinates);
+    }
+
+    #[test]
+    fn origin_and_coordinates_in_path() {
+        let directory = TempDir::new().unwrap();
+        let template = directory.path().join("{x}_{y}.las");
+        let mut tile_writer = TileWriter::new(template.to_str().unwrap(), header(), 10.)
+            .unwrap()
+            .with_origin(5., 5.);
+        tile_writer.write_points(&points(&[(1., 16.)])).unwrap();
+        let tiles = tile_writer.close().unwrap();
+        assert_eq!(directory.path().join("-5_15.las"), tiles[0].path);
+    }
+
+    #[test]
+    fn buffer() {
+        let directory = TempDir::new().unwrap();
+        let mut tile_writer = TileWriter::new(template(&directory, "las"), header(), 10.)
+            .unwrap()
+            .with_buffer(2.);
+        tile_writer
+            .write_points(&points(&[(5., 5.), (9., 5.), (9., 9.)]))
+            .unwrap();
+        let tiles = tile_writer.close().unwrap();
+        let counts: Vec<_> = tiles
+            .iter()
+            .map(|t| ((t.column, t.row), t.header.number_of_points()))
+            .collect();
+        assert_eq!(
+            vec![((0, 0), 3), ((0, 1), 1), ((1, 0), 2), ((1, 1), 1)],
+            counts
+        );
+    }
+
+    #[test]
+    fn bounded_open_files() {
+        let directory = TempDir::new().unwrap();
+        let mut tile_writer = TileWriter::new(template(&directory, "las"), header(), 10.)
+            .unwrap()
+            .with_max_open_files(1);
+        for i in 0..3 {
+            let i = f64::from(i);
+            tile_writer
+                .write_points(&points(&[(1. + i, 1.), (11. + i, 1.), (21. + i, 1.)]))
+                .unwrap();
+        }
+        let tiles = tile_writer.close().unwrap();
+        assert_eq!(3, tiles.len());
+        for tile in tiles {
+            let (header, coordinates) = read(&tile.path);
+            let x = tile.column as f64 * 10.;
+            assert_eq!(3, header.number_of_points());
+            assert_eq!(Some(3), header.number_of_points_by_return(1));
+            assert_eq!(x + 1., header.bounds().min.x);
+            assert_eq!(x + 3., header.bounds().max.x);
+            assert_eq!(vec![(x + 1., 1.), (x + 2., 1.), (x + 3., 1.)], coordinates);
+        }
+    }
+
+    #[test]
+    fn sorted_across_reopens() {
+        let directory = TempDir::new().unwrap();
+        let options = WriterOptions::default().with_space_filling_curve(SpaceFillingCurve::Morton);
+        let mut tile_writer = TileWriter::new(template(&directory, "las"), header(), 10.)
+            .unwrap()
+            .with_max_open_files(1)
+            .with_writer_options(options);
+        for (x, y) in [(9., 9.), (11., 1.), (1., 1.), (12., 1.), (5., 5.)] {
+            tile_writer.write_points(&points(&[(x, y)])).unwrap();
+        }
+        let tiles = tile_writer.close().unwrap();
+        assert_eq!(2, tiles.len());
+        let (header, coordinates) = read(&tiles[0].path);
+        assert_eq!(3, header.number_of_points());
+        assert_eq!(vec![(1., 1.), (5., 5.), (9., 9.)], coordinates);
+        assert_eq!(2, fs::read_dir(directory.path()).unwrap().count());
+    }
+
+    #[test]
+    fn mismatched_format() {
+        let directory = TempDir::new().unwrap();
+        let mut tile_writer = TileWriter::new(template(&directory, "las"), header(), 10.).unwrap();
+        let points = PointDataBuilder::new()
+            .with_format(Format::new(1).unwrap())
+            .with_transforms(*header().transforms())
+            .build_from_points(vec![Point {
+                gps_time: Some(0.),
+                ..Default::default()
+            }])
+            .unwrap();
+        assert!(tile_writer.write_points(&points).is_err());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz() {
+        let directory = TempDir::new().unwrap();
+        let mut tile_writer = TileWriter::new(template(&directory, "laz"), header(), 10.)
+            .unwrap()
+            .with_max_open_files(1);
+        tile_writer
+            .write_points(&points(&[(1., 1.), (11., 1.), (2., 2.)]))
+            .unwrap();
+        let tiles = tile_writer.close().unwrap();
+        assert_eq!(2, tiles.len());
+        assert!(tiles[0].header.point_format().is_compressed);
+        let (header, coordinates) = read(&tiles[0].path);
+        assert!(header.point_format().is_compressed);
+        assert_eq!(vec![(1., 1.), (2., 2.)], coordinates);
+        assert_eq!(2, fs::read_dir(directory.path()).unwrap().count());
+    }
+}
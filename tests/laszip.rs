@@ -229,4 +229,150 @@ mod laz_compression_test {
     fn test_extra_bytes_laz() {
         test_compression_does_not_corrupt("tests/data/extrabytes.laz");
     }
+
+    /// Writes autzen compressed in the given point format, ending a chunk
+    /// after each of the given point counts, and returns the chunk table's
+    /// point counts.
+    fn write_chunked(options: las::WriterOptions, point_format: u8, chunks: &[usize]) -> Vec<u64> {
+        use std::io::{Seek, SeekFrom};
+
+        let mut reader = las::Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut builder = las::Builder::from(reader.header().clone());
+        builder.version = las::Version::new(1, 4);
+        builder.point_format = las::point::Format::new(point_format).unwrap();
+        builder.point_format.is_compressed = true;
+        let header = builder.into_header().unwrap();
+        let format = *header.point_format();
+        // Go through the point format, which can change the scan angles
+        let original = las::PointDataBuilder::new()
+            .with_format(format)
+            .with_transforms(*header.transforms())
+            .build_from_points(reader.read_all().unwrap().points().map(|p| p.unwrap()))
+            .unwrap();
+        let original_points: Vec<_> = original.points().map(|p| p.unwrap()).collect();
+
+        let mut writer =
+            las::Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        let mut start = 0;
+        for &n in chunks {
+            let points = las::PointDataBuilder::new()
+                .with_format(format)
+                .with_transforms(*original.transforms())
+                .build_from_points(original_points[start..start + n].iter().cloned())
+                .unwrap();
+            writer.write_points(&points).unwrap();
+            writer.end_chunk().unwrap();
+            writer.end_chunk().unwrap();
+            start += n;
+        }
+        for point in &original_points[start..] {
+            writer.write_point(point.clone()).unwrap();
+        }
+        let mut cursor = writer.into_inner().unwrap();
+
+        let mut reader = las::Reader::new(cursor.clone()).unwrap();
+        let points: Vec<_> = reader
+            .read_all()
+            .unwrap()
+            .points()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(original_points, points);
+        for index in [42, 0, 29, 30, 69, 70, 105] {
+            reader.seek(index).unwrap();
+            let point = reader
+                .read_points(1)
+                .unwrap()
+                .points()
+                .next()
+                .unwrap()
+                .unwrap();
+            assert_eq!(original_points[index as usize], point);
+        }
+        // Reads that start after a seek carry on into the next chunks
+        reader.seek(25).unwrap();
+        let points: Vec<_> = reader
+            .read_points(50)
+            .unwrap()
+            .points()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(original_points[25..75], points);
+
+        let laz_vlr = reader.header().laz_vlr().unwrap();
+        let raw_header = las::raw::Header::read_from(&mut cursor).unwrap();
+        let _ = cursor
+            .seek(SeekFrom::Start(raw_header.offset_to_point_data.into()))
+            .unwrap();
+        let chunk_table = laz::laszip::ChunkTable::read_from(&mut cursor, &laz_vlr).unwrap();
+        let entries: &[laz::laszip::ChunkTableEntry] = chunk_table.as_ref();
+        entries.iter().map(|entry| entry.point_count).collect()
+    }
+
+    #[test]
+    fn test_fixed_chunk_size() {
+        use las::{LazChunkSize, WriterOptions};
+
+        let options = WriterOptions::default()
+            .with_laz_parallelism(las::LazParallelism::No)
+            .with_laz_chunk_size(LazChunkSize::Fixed(25));
+        let mut builder = las::Builder::from((1, 2));
+        builder.point_format.is_compressed = true;
+        let writer = las::Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        assert_eq!(25, writer.header().laz_vlr().unwrap().chunk_size());
+        // Fixed chunk tables only record the chunk size
+        let counts = write_chunked(
+            WriterOptions::default().with_laz_chunk_size(LazChunkSize::Fixed(25)),
+            1,
+            &[],
+        );
+        assert_eq!(vec![25; 5], counts);
+    }
+
+    #[test]
+    fn test_end_chunk_requires_variable_chunks() {
+        let mut builder = las::Builder::from((1, 2));
+        builder.point_format.is_compressed = true;
+        let mut writer =
+            las::Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        assert!(writer.end_chunk().is_err());
+        let mut writer = las::Writer::default();
+        assert!(writer.end_chunk().is_err());
+    }
+
+    #[test]
+    fn test_variable_chunks() {
+        use las::{LazChunkSize, LazParallelism, WriterOptions};
+
+        let options = WriterOptions::default()
+            .with_laz_parallelism(LazParallelism::No)
+            .with_laz_chunk_size(LazChunkSize::Variable);
+        assert_eq!(
+            vec![30, 40, 36],
+            write_chunked(options.clone(), 1, &[30, 40])
+        );
+        assert_eq!(vec![30, 76], write_chunked(options.clone(), 1, &[30, 76]));
+        assert_eq!(vec![30, 40, 36], write_chunked(options, 6, &[30, 40]));
+    }
+
+    #[cfg(feature = "laz-parallel")]
+    #[test]
+    fn test_variable_chunks_parallel() {
+        use las::{LazChunkSize, LazParallelism, WriterOptions};
+
+        let options = WriterOptions::default()
+            .with_laz_parallelism(LazParallelism::Yes)
+            .with_laz_chunk_size(LazChunkSize::Variable);
+        assert_eq!(
+            vec![30, 40, 36],
+            write_chunked(options.clone(), 1, &[30, 40])
+        );
+        assert_eq!(vec![30, 76], write_chunked(options.clone(), 1, &[30, 76]));
+        assert_eq!(vec![30, 40, 36], write_chunked(options, 6, &[30, 40]));
+    }
 }